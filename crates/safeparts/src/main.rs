@@ -1,10 +1,23 @@
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow, bail};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use safeparts_core::age::{self, Recipient, WrappedShare};
+use safeparts_core::codex32;
+use safeparts_core::compression::Compression;
+use safeparts_core::crypto::{self, AeadAlgorithm, CipherSuite, KdfParams};
 use safeparts_core::encoding::{self, Encoding};
+use safeparts_core::metadata::SecretMetadata;
+use safeparts_core::padding::Padding;
+use safeparts_core::policy::Policy;
+use safeparts_core::refresh::{self, RefreshRound};
+use safeparts_core::secrets_js;
+use safeparts_core::slip39;
+use safeparts_core::ssss;
+use safeparts_core::vault;
+use safeparts_core::{CoreError, KeySlotChange, SplitOptions};
 use zeroize::Zeroizing;
 
 #[derive(Debug, Parser)]
@@ -22,12 +35,16 @@ enum Commands {
     /// Split a secret into N shares.
     Split {
         /// Threshold shares required to recover.
-        #[arg(short = 'k', long = "threshold")]
-        k: u8,
+        #[arg(short = 'k', long = "threshold", required_unless_present = "policy")]
+        k: Option<u8>,
 
         /// Total shares to create.
-        #[arg(short = 'n', long = "shares")]
-        n: u8,
+        #[arg(short = 'n', long = "shares", required_unless_present = "policy")]
+        n: Option<u8>,
+
+        /// Read an access policy from file instead of -k/-n (see the CLI automation manual).
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["k", "n", "verifiable"])]
+        policy: Option<PathBuf>,
 
         /// Output encoding for shares.
         #[arg(short = 'e', long, value_enum, default_value_t = CliEncoding::Base64url)]
@@ -48,6 +65,110 @@ enum Commands {
         /// Write shares to file (use '-' for stdout).
         #[arg(short = 'o', long, value_name = "FILE")]
         out: Option<PathBuf>,
+
+        /// Create shares each custodian can check alone with `verify-share`.
+        #[arg(long)]
+        verifiable: bool,
+
+        /// Write the published share commitments to file (requires --verifiable).
+        #[arg(long, value_name = "FILE", requires = "verifiable")]
+        commitments_out: Option<PathBuf>,
+
+        /// Large-secret mode: share a random key and disperse the ciphertext, so each share is about 1/K of the secret.
+        #[arg(long, conflicts_with_all = ["policy", "verifiable"])]
+        large: bool,
+
+        /// Stream binary share files into DIR in constant memory (for inputs that don't fit in RAM).
+        #[arg(
+            long,
+            value_name = "DIR",
+            conflicts_with_all = ["out", "policy", "verifiable", "large"]
+        )]
+        out_dir: Option<PathBuf>,
+
+        /// Wrap share i for the i-th age recipient (age1...); repeat once per share.
+        #[arg(
+            long = "recipient",
+            value_name = "RECIPIENT",
+            conflicts_with = "out_dir"
+        )]
+        recipients: Vec<Recipient>,
+
+        /// Add a key slot: any slot's passphrase decrypts, as LABEL=FILE (repeatable).
+        #[arg(
+            long = "key-slot",
+            value_name = "LABEL=FILE",
+            value_parser = parse_key_slot_arg,
+            conflicts_with_all = ["passphrase", "passphrase_file", "policy", "verifiable", "large", "out_dir"]
+        )]
+        key_slots: Vec<(String, PathBuf)>,
+
+        /// Store the input filename, content type, and creation time inside the shares.
+        #[arg(
+            long,
+            conflicts_with_all = ["policy", "verifiable", "large", "out_dir", "key_slots"]
+        )]
+        metadata: bool,
+
+        /// Store a free-text label inside the shares (implies --metadata).
+        #[arg(
+            long,
+            value_name = "TEXT",
+            conflicts_with_all = ["policy", "verifiable", "large", "out_dir", "key_slots"]
+        )]
+        label: Option<String>,
+
+        /// Store this MIME type instead of a guessed one (implies --metadata).
+        #[arg(
+            long,
+            value_name = "MIME",
+            conflicts_with_all = ["policy", "verifiable", "large", "out_dir", "key_slots"]
+        )]
+        content_type: Option<String>,
+
+        /// Pad the secret before splitting so shares hide its length: none, pow2, fixed:BYTES, or random:MAX_BYTES.
+        #[arg(
+            long,
+            value_name = "MODE",
+            default_value_t = Padding::None,
+            conflicts_with_all = ["policy", "verifiable", "large", "out_dir", "key_slots"]
+        )]
+        padding: Padding,
+
+        /// Compress the secret with DEFLATE before splitting, when that makes it smaller.
+        #[arg(
+            long,
+            conflicts_with_all = ["policy", "verifiable", "large", "out_dir", "key_slots"]
+        )]
+        compress: bool,
+
+        /// Write shares in the compact packet format, which needs fewer mnemonic words.
+        #[arg(
+            long,
+            conflicts_with_all = ["policy", "verifiable", "large", "out_dir", "key_slots"]
+        )]
+        compact: bool,
+
+        /// Hide the threshold, share count, and set id, and use random share indexes (implies --compact).
+        ///
+        /// Stealth sets all share one set id, so combine cannot tell shares of two sets apart; keep
+        /// each set separate.
+        #[arg(
+            long,
+            conflicts_with_all = ["policy", "verifiable", "large", "out_dir", "key_slots"]
+        )]
+        stealth: bool,
+
+        /// With `-e ssss`, skip the diffusion layer, like `ssss-split -D`.
+        #[arg(long)]
+        no_diffusion: bool,
+
+        /// With `-e secrets-js`, the field size in bits, like `secrets.init(bits)` (3 to 20; default 8).
+        #[arg(long, value_name = "BITS")]
+        field_bits: Option<u8>,
+
+        #[command(flatten)]
+        suite: SuiteArgs,
     },
 
     /// Combine shares to recover the original secret.
@@ -71,13 +192,435 @@ enum Commands {
         /// Write recovered secret to file (use '-' for stdout).
         #[arg(short = 'o', long, value_name = "FILE")]
         out: Option<PathBuf>,
+
+        /// Combine the binary share files written by `split --out-dir` in constant memory.
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["in", "encoding"])]
+        in_dir: Option<PathBuf>,
+
+        /// Unwrap age-wrapped shares with the identities in FILE (repeatable).
+        #[arg(long = "identity", value_name = "FILE", conflicts_with = "in_dir")]
+        identities: Vec<PathBuf>,
+
+        /// Passphrase file for one locked share, as INDEX=FILE (repeatable); other locked shares prompt.
+        #[arg(
+            long = "share-passphrase-file",
+            value_name = "INDEX=FILE",
+            value_parser = parse_share_passphrase_arg,
+            conflicts_with = "in_dir"
+        )]
+        share_passphrase_files: Vec<(u8, PathBuf)>,
+
+        /// List the key slots of the shares instead of recovering the secret.
+        #[arg(long, conflicts_with_all = ["in_dir", "passphrase", "passphrase_file"])]
+        list_key_slots: bool,
+
+        /// Write the secret into DIR under its stored filename; never overwrites.
+        #[arg(
+            long,
+            value_name = "DIR",
+            conflicts_with_all = ["out", "in_dir", "list_key_slots"]
+        )]
+        out_dir: Option<PathBuf>,
+
+        /// With ssss shares, skip the diffusion layer, like `ssss-combine -D`.
+        #[arg(long)]
+        no_diffusion: bool,
+    },
+
+    /// Lock one share with its custodian's own passphrase or PIN.
+    Lock {
+        /// Share encoding (if omitted, auto-detect); the locked share uses the same encoding.
+        #[arg(short = 'e', long, value_enum, alias = "from")]
+        encoding: Option<CliEncoding>,
+
+        /// Read the share passphrase from file (if omitted, prompt on the terminal).
+        #[arg(short = 'P', long, value_name = "FILE")]
+        passphrase_file: Option<PathBuf>,
+
+        /// Read the share from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Write the locked share to file (use '-' for stdout).
+        #[arg(short = 'o', long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        #[command(flatten)]
+        suite: SuiteArgs,
+    },
+
+    /// Check one share from a verifiable split without any other share.
+    VerifyShare {
+        /// Share encoding (if omitted, auto-detect).
+        #[arg(short = 'e', long, value_enum, alias = "from")]
+        encoding: Option<CliEncoding>,
+
+        /// Published commitments file written by `split --commitments-out`.
+        #[arg(short = 'c', long, value_name = "FILE")]
+        commitments: Option<PathBuf>,
+
+        /// Read the share from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+    },
+
+    /// Describe shares without combining them.
+    Inspect {
+        /// Share encoding (if omitted, auto-detect).
+        #[arg(short = 'e', long, value_enum, alias = "from")]
+        encoding: Option<CliEncoding>,
+
+        /// Read shares from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+    },
+
+    /// Issue a new or replacement share from at least K existing shares.
+    Extend {
+        /// Index of the share to issue (1-255); reuse a lost share's index to replace it.
+        #[arg(short = 'x', long = "index")]
+        x: u8,
+
+        /// Share encoding (if omitted, auto-detect); the new share uses the same encoding.
+        #[arg(short = 'e', long, value_enum, alias = "from")]
+        encoding: Option<CliEncoding>,
+
+        /// Read shares from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Write the new share to file (use '-' for stdout).
+        #[arg(short = 'o', long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Unwrap age-wrapped shares with the identities in FILE (repeatable).
+        #[arg(long = "identity", value_name = "FILE")]
+        identities: Vec<PathBuf>,
+    },
+
+    /// Move a secret to a new set with a new threshold and share count.
+    Reshare {
+        /// Threshold shares required to recover the new set.
+        #[arg(short = 'k', long = "threshold")]
+        k: u8,
+
+        /// Total shares to create in the new set.
+        #[arg(short = 'n', long = "shares")]
+        n: u8,
+
+        /// Output encoding for new shares (if omitted, same as the old shares).
+        #[arg(short = 'e', long, value_enum)]
+        encoding: Option<CliEncoding>,
+
+        /// Passphrase of the old set (prefer --passphrase-file to avoid shell history).
+        #[arg(short = 'p', long, conflicts_with = "passphrase_file")]
+        passphrase: Option<String>,
+
+        /// Read the old set's passphrase from file.
+        #[arg(short = 'P', long, value_name = "FILE", conflicts_with = "passphrase")]
+        passphrase_file: Option<PathBuf>,
+
+        /// Passphrase for the new set (prefer --new-passphrase-file to avoid shell history).
+        #[arg(long, conflicts_with = "new_passphrase_file")]
+        new_passphrase: Option<String>,
+
+        /// Read the new set's passphrase from file.
+        #[arg(long, value_name = "FILE", conflicts_with = "new_passphrase")]
+        new_passphrase_file: Option<PathBuf>,
+
+        /// Write the new set without a passphrase or key slots, even if the old set had them.
+        #[arg(
+            long,
+            conflicts_with_all = ["new_passphrase", "new_passphrase_file", "add_key_slots", "remove_key_slots"]
+        )]
+        drop_passphrase: bool,
+
+        /// Read old shares from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Write new shares to file (use '-' for stdout).
+        #[arg(short = 'o', long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Write the new set's commitments to file (verifiable sets only).
+        #[arg(long, value_name = "FILE")]
+        commitments_out: Option<PathBuf>,
+
+        /// Add a key slot to the new set, as LABEL=FILE (repeatable).
+        #[arg(
+            long = "add-key-slot",
+            value_name = "LABEL=FILE",
+            value_parser = parse_key_slot_arg,
+            conflicts_with_all = ["new_passphrase", "new_passphrase_file"]
+        )]
+        add_key_slots: Vec<(String, PathBuf)>,
+
+        /// Remove the key slot LABEL from the new set (repeatable).
+        #[arg(
+            long = "remove-key-slot",
+            value_name = "LABEL",
+            conflicts_with_all = ["new_passphrase", "new_passphrase_file"]
+        )]
+        remove_key_slots: Vec<String>,
+
+        /// Unwrap age-wrapped shares with the identities in FILE (repeatable).
+        #[arg(long = "identity", value_name = "FILE")]
+        identities: Vec<PathBuf>,
+    },
+
+    /// Re-randomise shares in place without reconstructing the secret.
+    Refresh {
+        #[command(subcommand)]
+        command: RefreshCommand,
+    },
+
+    /// Convert between SLIP-39 mnemonic shares and a master secret.
+    Slip39 {
+        #[command(subcommand)]
+        command: Slip39Command,
     },
 
     /// Launch the interactive terminal UI.
     Tui,
 }
 
+#[derive(Debug, Subcommand)]
+enum RefreshCommand {
+    /// Start a refresh round for the set a share belongs to.
+    Init {
+        /// Indexes of every share taking part, comma-separated (at least K).
+        #[arg(long, value_delimiter = ',', required = true)]
+        participants: Vec<u8>,
+
+        /// Read one share of the set from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Write the public round file (use '-' for stdout).
+        #[arg(short = 'o', long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+
+    /// Generate this custodian's sub-shares, one file per participant.
+    Contribute {
+        /// Round file written by `refresh init`.
+        #[arg(short = 'r', long, value_name = "FILE")]
+        round: PathBuf,
+
+        /// Read your own share from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Directory to write `subshare-<from>-to-<to>.txt` files into.
+        #[arg(long, value_name = "DIR")]
+        out_dir: PathBuf,
+    },
+
+    /// Add every sub-share addressed to you and move your share to the next epoch.
+    Apply {
+        /// Round file written by `refresh init`.
+        #[arg(short = 'r', long, value_name = "FILE")]
+        round: PathBuf,
+
+        /// Read your own share from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Sub-share files addressed to you, one from every participant.
+        #[arg(short = 's', long = "sub-share", value_name = "FILE", required = true)]
+        sub_shares: Vec<PathBuf>,
+
+        /// Write the refreshed share to file (use '-' for stdout).
+        #[arg(short = 'o', long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum Slip39Command {
+    /// Split a master secret (16 bytes or more, even length) into SLIP-39 mnemonics.
+    Split {
+        /// Member threshold of a single group.
+        #[arg(short = 'k', long = "threshold", required_unless_present = "groups")]
+        k: Option<u8>,
+
+        /// Member count of a single group.
+        #[arg(short = 'n', long = "shares", required_unless_present = "groups")]
+        n: Option<u8>,
+
+        /// One group as THRESHOLD-of-COUNT, e.g. 2-of-3 (repeatable, instead of -k/-n).
+        #[arg(
+            long = "group",
+            value_name = "T-of-N",
+            value_parser = parse_slip39_group_arg,
+            conflicts_with_all = ["k", "n"]
+        )]
+        groups: Vec<slip39::GroupSpec>,
+
+        /// Groups needed to recover [default: 1].
+        #[arg(long, requires = "groups")]
+        group_threshold: Option<u8>,
+
+        /// SLIP-39 passphrase (prefer --passphrase-file to avoid shell history).
+        #[arg(short = 'p', long, conflicts_with = "passphrase_file")]
+        passphrase: Option<String>,
+
+        /// Read the SLIP-39 passphrase from file.
+        #[arg(short = 'P', long, value_name = "FILE", conflicts_with = "passphrase")]
+        passphrase_file: Option<PathBuf>,
+
+        /// PBKDF2 cost exponent (0-15); each step doubles the work.
+        #[arg(long, default_value_t = 1)]
+        iteration_exponent: u8,
+
+        /// Read the master secret from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Write mnemonics to file, one per line and a blank line between groups (use '-' for stdout).
+        #[arg(short = 'o', long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+
+    /// Recover the master secret from SLIP-39 mnemonics, one per line.
+    Combine {
+        /// SLIP-39 passphrase (prefer --passphrase-file to avoid shell history).
+        #[arg(short = 'p', long, conflicts_with = "passphrase_file")]
+        passphrase: Option<String>,
+
+        /// Read the SLIP-39 passphrase from file.
+        #[arg(short = 'P', long, value_name = "FILE", conflicts_with = "passphrase")]
+        passphrase_file: Option<PathBuf>,
+
+        /// Read mnemonics from file (use '-' for stdin).
+        #[arg(short = 'i', long = "in", value_name = "FILE")]
+        r#in: Option<PathBuf>,
+
+        /// Write the master secret to file (use '-' for stdout).
+        #[arg(short = 'o', long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Cipher and Argon2id cost options for passphrase-protected splits.
+#[derive(Debug, Args)]
+struct SuiteArgs {
+    /// AEAD for the passphrase encryption [default: chacha20-poly1305].
+    #[arg(long, value_enum, value_name = "CIPHER")]
+    cipher: Option<CliCipher>,
+
+    /// Argon2id cost profile for the passphrase [default: moderate].
+    #[arg(long = "kdf", value_enum, value_name = "PROFILE")]
+    profile: Option<CliKdfProfile>,
+
+    /// Argon2id memory in KiB; caps memory when calibrating.
+    #[arg(long = "kdf-memory", value_name = "KIB")]
+    memory: Option<u32>,
+
+    /// Argon2id passes.
+    #[arg(
+        long = "kdf-iterations",
+        value_name = "N",
+        conflicts_with = "target_ms"
+    )]
+    iterations: Option<u32>,
+
+    /// Argon2id lanes.
+    #[arg(
+        long = "kdf-parallelism",
+        value_name = "N",
+        conflicts_with = "target_ms"
+    )]
+    parallelism: Option<u32>,
+
+    /// Calibrate Argon2id so unlocking takes about MS milliseconds on this machine.
+    #[arg(long = "kdf-target-ms", value_name = "MS", conflicts_with = "profile")]
+    target_ms: Option<u64>,
+}
+
+impl SuiteArgs {
+    fn is_set(&self) -> bool {
+        self.cipher.is_some()
+            || self.profile.is_some()
+            || self.memory.is_some()
+            || self.iterations.is_some()
+            || self.parallelism.is_some()
+            || self.target_ms.is_some()
+    }
+
+    fn suite(&self) -> Result<CipherSuite> {
+        Ok(CipherSuite {
+            kdf: self.kdf()?,
+            aead: self.cipher.map(AeadAlgorithm::from).unwrap_or_default(),
+        })
+    }
+
+    /// Resolve the Argon2id options: a calibration, or a profile with explicit
+    /// overrides.
+    fn kdf(&self) -> Result<KdfParams> {
+        if let Some(ms) = self.target_ms {
+            let max_mem = self.memory.unwrap_or(KdfParams::PARANOID.mem_cost_kib);
+            eprintln!("calibrating Argon2id for about {ms} ms...");
+            let kdf = crypto::calibrate(std::time::Duration::from_millis(ms), max_mem)
+                .map_err(|e| anyhow!(e))?;
+            eprintln!(
+                "calibrated Argon2id: {} KiB, {} passes, {} lane(s)",
+                kdf.mem_cost_kib, kdf.time_cost, kdf.parallelism
+            );
+            return Ok(kdf);
+        }
+
+        let base = self.profile.map(KdfParams::from).unwrap_or_default();
+        KdfParams::new(
+            self.memory.unwrap_or(base.mem_cost_kib),
+            self.iterations.unwrap_or(base.time_cost),
+            self.parallelism.unwrap_or(base.parallelism),
+        )
+        .map_err(|e| anyhow!(e))
+    }
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum CliKdfProfile {
+    Interactive,
+    Moderate,
+    Paranoid,
+}
+
+impl From<CliKdfProfile> for KdfParams {
+    fn from(value: CliKdfProfile) -> Self {
+        match value {
+            CliKdfProfile::Interactive => KdfParams::INTERACTIVE,
+            CliKdfProfile::Moderate => KdfParams::MODERATE,
+            CliKdfProfile::Paranoid => KdfParams::PARANOID,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum CliCipher {
+    #[value(name = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+
+    #[value(name = "xchacha20-poly1305")]
+    XChaCha20Poly1305,
+
+    #[value(name = "aes-256-gcm-siv")]
+    Aes256GcmSiv,
+}
+
+impl From<CliCipher> for AeadAlgorithm {
+    fn from(value: CliCipher) -> Self {
+        match value {
+            CliCipher::ChaCha20Poly1305 => AeadAlgorithm::ChaCha20Poly1305,
+            CliCipher::XChaCha20Poly1305 => AeadAlgorithm::XChaCha20Poly1305,
+            CliCipher::Aes256GcmSiv => AeadAlgorithm::Aes256GcmSiv,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
 enum CliEncoding {
     #[value(name = "base64url", alias = "base64")]
     Base64url,
@@ -90,15 +633,38 @@ enum CliEncoding {
 
     #[value(name = "mnemo-bip39")]
     MnemoBip39,
+
+    /// HashiCorp Vault or OpenBao unseal keys.
+    #[value(name = "vault")]
+    Vault,
+
+    /// Shares of B. Poettering's `ssss-split`.
+    #[value(name = "ssss")]
+    Ssss,
+
+    /// BIP-93 codex32 strings.
+    #[value(name = "codex32")]
+    Codex32,
+
+    /// Shares of the secrets.js JavaScript library.
+    #[value(name = "secrets-js")]
+    SecretsJs,
 }
 
-impl From<CliEncoding> for Encoding {
-    fn from(value: CliEncoding) -> Self {
-        match value {
-            CliEncoding::Base64url => Encoding::Base64url,
-            CliEncoding::Base58check => Encoding::Base58check,
-            CliEncoding::MnemoWords => Encoding::MnemoWords,
-            CliEncoding::MnemoBip39 => Encoding::MnemoBip39,
+impl CliEncoding {
+    /// The share packet encoding; `vault` keys, `ssss` shares, codex32
+    /// strings, and secrets.js shares are not packets, and only `split` and
+    /// `combine` handle them.
+    fn packet_encoding(self) -> Result<Encoding> {
+        match self {
+            CliEncoding::Base64url => Ok(Encoding::Base64url),
+            CliEncoding::Base58check => Ok(Encoding::Base58check),
+            CliEncoding::MnemoWords => Ok(Encoding::MnemoWords),
+            CliEncoding::MnemoBip39 => Ok(Encoding::MnemoBip39),
+            CliEncoding::Vault => bail!("vault unseal keys work only with split and combine"),
+            CliEncoding::Ssss => bail!("ssss shares work only with split and combine"),
+            CliEncoding::Codex32 => bail!("codex32 strings work only with split and combine"),
+            CliEncoding::SecretsJs => bail!("secrets.js shares work only with split and combine"),
         }
     }
 }
@@ -110,18 +676,258 @@ fn main() -> Result<()> {
         Commands::Split {
             k,
             n,
+            policy,
             encoding,
             passphrase,
             passphrase_file,
             r#in,
             out,
+            verifiable,
+            commitments_out,
+            large,
+            out_dir,
+            recipients,
+            key_slots,
+            metadata,
+            label,
+            content_type,
+            padding,
+            compress,
+            compact,
+            stealth,
+            no_diffusion,
+            field_bits,
+            suite,
         } => {
-            let input = Zeroizing::new(read_input(r#in)?);
             let passphrase = read_passphrase(passphrase, passphrase_file)?;
             let passphrase_bytes = passphrase.as_ref().map(|p| p.as_slice());
+            let suite = match passphrase_bytes {
+                Some(_) => suite.suite()?,
+                None if !key_slots.is_empty() => suite.suite()?,
+                None if suite.is_set() => {
+                    bail!(
+                        "--cipher and --kdf options need --passphrase, --passphrase-file, or --key-slot"
+                    )
+                }
+                None => CipherSuite::default(),
+            };
+            let key_slots = read_key_slots(&key_slots)?;
+
+            if matches!(
+                encoding,
+                CliEncoding::Vault
+                    | CliEncoding::Ssss
+                    | CliEncoding::Codex32
+                    | CliEncoding::SecretsJs
+            ) {
+                let packet_options = passphrase_bytes.is_some()
+                    || policy.is_some()
+                    || verifiable
+                    || large
+                    || out_dir.is_some()
+                    || !recipients.is_empty()
+                    || !key_slots.is_empty()
+                    || metadata
+                    || label.is_some()
+                    || content_type.is_some()
+                    || !padding.is_none()
+                    || compress
+                    || compact
+                    || stealth;
+                if encoding == CliEncoding::SecretsJs {
+                    if packet_options || no_diffusion {
+                        bail!(
+                            "-e secrets-js writes plain secrets.js shares and takes only -k, -n, -i, -o, and --field-bits"
+                        );
+                    }
+                    let (Some(k), Some(n)) = (k, n) else {
+                        bail!("split -e secrets-js needs -k and -n");
+                    };
+                    let input = Zeroizing::new(read_input(r#in)?);
+                    let bits = field_bits.unwrap_or(secrets_js::DEFAULT_BITS);
+                    let shares = secrets_js::split(&input, k, n, bits)
+                        .map_err(|e| anyhow!(e))
+                        .with_context(|| format!("split failed (k={k}, n={n})"))?;
+                    let lines: Vec<String> =
+                        shares.iter().map(|share| share.to_text() + "\n").collect();
+                    return write_output_text(out, &lines.concat());
+                }
+                if field_bits.is_some() {
+                    bail!("--field-bits applies only to -e secrets-js");
+                }
+                if encoding == CliEncoding::Codex32 {
+                    if packet_options || no_diffusion {
+                        bail!(
+                            "-e codex32 writes plain codex32 strings and takes only -k, -n, -i, and -o"
+                        );
+                    }
+                    let (Some(k), Some(n)) = (k, n) else {
+                        bail!("split -e codex32 needs -k and -n");
+                    };
+                    let input = Zeroizing::new(read_input(r#in)?);
+                    let shares = codex32::split(&input, k, n, &codex32::random_identifier())
+                        .map_err(|e| anyhow!(e))
+                        .with_context(|| format!("split failed (k={k}, n={n})"))?;
+                    let lines: Vec<String> =
+                        shares.iter().map(|share| format!("{share}\n")).collect();
+                    return write_output_text(out, &lines.concat());
+                }
+                if encoding == CliEncoding::Ssss {
+                    if packet_options {
+                        bail!("-e ssss writes plain ssss shares and takes only -k, -n, -i, and -o");
+                    }
+                    let (Some(k), Some(n)) = (k, n) else {
+                        bail!("split -e ssss needs -k and -n");
+                    };
+                    let input = Zeroizing::new(read_input(r#in)?);
+                    let shares = ssss::split(&input, k, n, !no_diffusion)
+                        .map_err(|e| anyhow!(e))
+                        .with_context(|| format!("split failed (k={k}, n={n})"))?;
+                    let output = ssss::encode_shares(&shares).join("\n") + "\n";
+                    return write_output_text(out, &output);
+                }
+                if packet_options || no_diffusion {
+                    bail!("-e vault writes plain unseal keys and takes only -k, -n, -i, and -o");
+                }
+                let (Some(k), Some(n)) = (k, n) else {
+                    bail!("split -e vault needs -k and -n");
+                };
+                let input = Zeroizing::new(read_input(r#in)?);
+                let keys = vault::split(&input, k, n)
+                    .map_err(|e| anyhow!(e))
+                    .with_context(|| format!("split failed (k={k}, n={n})"))?;
+                let lines: Vec<String> = keys.iter().map(|key| key.to_base64() + "\n").collect();
+                return write_output_text(out, &lines.concat());
+            }
+            if no_diffusion {
+                bail!("--no-diffusion applies only to -e ssss");
+            }
+            if field_bits.is_some() {
+                bail!("--field-bits applies only to -e secrets-js");
+            }
+
+            if let Some(dir) = out_dir {
+                let (Some(k), Some(n)) = (k, n) else {
+                    bail!("split --out-dir needs -k and -n");
+                };
+                return split_to_dir(r#in, &dir, k, n, passphrase_bytes, suite);
+            }
+
+            let metadata = (metadata || label.is_some() || content_type.is_some())
+                .then(|| secret_metadata(r#in.as_deref(), label, content_type));
+            let input = Zeroizing::new(read_input(r#in)?);
+
+            let packets = if let Some(path) = policy {
+                let text = fs::read_to_string(&path)
+                    .with_context(|| format!("read policy {}", path.display()))?;
+                let policy = Policy::parse(&text).map_err(|e| anyhow!(e))?;
+                let packets = safeparts_core::split_secret_with_policy(
+                    input.as_slice(),
+                    &policy,
+                    passphrase_bytes,
+                    suite,
+                )
+                .map_err(|e| anyhow!(e))
+                .context("split failed")?;
+
+                let holders: Vec<&str> =
+                    policy.holders().into_iter().map(|(_, name)| name).collect();
+                eprintln!("shares are in holder order: {}", holders.join(", "));
+                packets
+            } else {
+                let (Some(k), Some(n)) = (k, n) else {
+                    bail!("split needs -k and -n, or --policy");
+                };
 
-            let packets = safeparts_core::split_secret(input.as_slice(), k, n, passphrase_bytes)
-                .with_context(|| format!("split failed (k={k}, n={n})"))?;
+                if verifiable {
+                    let (packets, commitments) = safeparts_core::split_secret_verifiable(
+                        input.as_slice(),
+                        k,
+                        n,
+                        passphrase_bytes,
+                        suite,
+                    )
+                    .with_context(|| format!("split failed (k={k}, n={n})"))?;
+
+                    if let Some(path) = commitments_out {
+                        let text = encoding::encode_commitments(&commitments) + "\n";
+                        write_output_text(Some(path), &text)?;
+                    }
+                    packets
+                } else if !key_slots.is_empty() {
+                    let slots: Vec<(&str, &[u8])> = key_slots
+                        .iter()
+                        .map(|(label, passphrase)| (label.as_str(), passphrase.as_slice()))
+                        .collect();
+                    safeparts_core::split_secret_with_key_slots(
+                        input.as_slice(),
+                        k,
+                        n,
+                        &slots,
+                        suite,
+                    )
+                    .with_context(|| format!("split failed (k={k}, n={n})"))?
+                } else if large {
+                    safeparts_core::split_secret_large(
+                        input.as_slice(),
+                        k,
+                        n,
+                        passphrase_bytes,
+                        suite,
+                    )
+                    .with_context(|| format!("split failed (k={k}, n={n})"))?
+                } else {
+                    let mut metadata = metadata;
+                    if let Some(metadata) = &mut metadata {
+                        metadata.content_type.get_or_insert_with(|| {
+                            match std::str::from_utf8(&input) {
+                                Ok(_) => "text/plain".to_string(),
+                                Err(_) => "application/octet-stream".to_string(),
+                            }
+                        });
+                    }
+                    let options = SplitOptions {
+                        suite,
+                        metadata,
+                        padding,
+                        compression: if compress {
+                            Compression::Deflate
+                        } else {
+                            Compression::None
+                        },
+                        compact,
+                        stealth,
+                    };
+                    safeparts_core::split_secret_with_options(
+                        input.as_slice(),
+                        k,
+                        n,
+                        passphrase_bytes,
+                        &options,
+                    )
+                    .with_context(|| format!("split failed (k={k}, n={n})"))?
+                }
+            };
+
+            if !recipients.is_empty() {
+                if recipients.len() != packets.len() {
+                    bail!(
+                        "got {} --recipient for {} shares; give one per share",
+                        recipients.len(),
+                        packets.len()
+                    );
+                }
+                let wrapped: Vec<String> = packets
+                    .iter()
+                    .zip(&recipients)
+                    .map(|(packet, recipient)| {
+                        WrappedShare::wrap(packet, recipient)
+                            .map(|wrapped| wrapped.to_armored())
+                            .map_err(|e| anyhow!(e))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                return write_output_text(out, &(wrapped.join("\n") + "\n"));
+            }
 
             let encoded: Vec<String> = packets
                 .iter()
@@ -138,27 +944,507 @@ fn main() -> Result<()> {
             passphrase_file,
             r#in,
             out,
+            in_dir,
+            identities,
+            share_passphrase_files,
+            list_key_slots,
+            out_dir,
+            no_diffusion,
         } => {
-            let input = read_input(r#in)?;
-            let input_str = String::from_utf8(input).context("shares input must be UTF-8")?;
             let passphrase = read_passphrase(passphrase, passphrase_file)?;
             let passphrase_bytes = passphrase.as_ref().map(|p| p.as_slice());
 
-            let packets = parse_share_packets(&input_str, encoding)?;
+            if encoding == Some(CliEncoding::Vault) {
+                if passphrase_bytes.is_some()
+                    || !identities.is_empty()
+                    || !share_passphrase_files.is_empty()
+                    || list_key_slots
+                    || out_dir.is_some()
+                    || no_diffusion
+                {
+                    bail!("-e vault reads plain unseal keys and takes only -i and -o");
+                }
+                let input = read_input(r#in)?;
+                let input = String::from_utf8(input).context("keys input must be UTF-8")?;
+                let keys = vault::parse_keys(&input).map_err(|e| anyhow!(e))?;
+                let secret = vault::combine(&keys)
+                    .map_err(|e| anyhow!(e))
+                    .context("combine failed")?;
+                return write_output_bytes(out, &secret);
+            }
+
+            if let Some(dir) = in_dir {
+                return combine_from_dir(&dir, out, passphrase_bytes);
+            }
 
-            let secret = safeparts_core::combine_shares(&packets, passphrase_bytes)
+            let input = read_input(r#in)?;
+            let input_str = String::from_utf8(input).context("shares input must be UTF-8")?;
+
+            let encoding = encoding.or_else(|| detect_foreign_encoding(&input_str));
+            if encoding == Some(CliEncoding::Codex32) {
+                if passphrase_bytes.is_some()
+                    || !identities.is_empty()
+                    || !share_passphrase_files.is_empty()
+                    || list_key_slots
+                    || out_dir.is_some()
+                    || no_diffusion
+                {
+                    bail!("codex32 strings take only -i and -o");
+                }
+                let shares = codex32::parse_shares(&input_str).map_err(|e| anyhow!(e))?;
+                let secret = codex32::combine(&shares)
+                    .map_err(|e| anyhow!(e))
+                    .context("combine failed")?;
+                return write_output_bytes(out, &secret);
+            }
+            if encoding == Some(CliEncoding::SecretsJs) {
+                if passphrase_bytes.is_some()
+                    || !identities.is_empty()
+                    || !share_passphrase_files.is_empty()
+                    || list_key_slots
+                    || out_dir.is_some()
+                    || no_diffusion
+                {
+                    bail!("secrets.js shares take only -i and -o");
+                }
+                let shares = secrets_js::parse_shares(&input_str).map_err(|e| anyhow!(e))?;
+                let secret = secrets_js::combine(&shares)
+                    .map_err(|e| anyhow!(e))
+                    .context("combine failed")?;
+                return write_output_bytes(out, &secret);
+            }
+            if encoding == Some(CliEncoding::Ssss) {
+                if passphrase_bytes.is_some()
+                    || !identities.is_empty()
+                    || !share_passphrase_files.is_empty()
+                    || list_key_slots
+                    || out_dir.is_some()
+                {
+                    bail!("ssss shares take only -i, -o, and --no-diffusion");
+                }
+                let shares = ssss::parse_shares(&input_str).map_err(|e| anyhow!(e))?;
+                let secret = ssss::combine(&shares, !no_diffusion)
+                    .map_err(|e| anyhow!(e))
+                    .context("combine failed")?;
+                // ssss-combine prints the secret without its leading zero bytes.
+                let start = secret.iter().position(|&b| b != 0).unwrap_or(secret.len());
+                return write_output_bytes(out, &secret[start..]);
+            }
+            if no_diffusion {
+                bail!("--no-diffusion applies only to ssss shares");
+            }
+
+            let packets = parse_share_packets(&input_str, encoding, &identities)?.packets;
+            let packets = unlock_locked_shares(packets, &share_passphrase_files)?;
+
+            if list_key_slots {
+                let slots = safeparts_core::list_key_slots(&packets)
+                    .map_err(|e| anyhow!(e))
+                    .context("list key slots failed")?;
+                let lines: Vec<String> = slots
+                    .iter()
+                    .map(|slot| {
+                        let kdf = slot.params.kdf();
+                        format!(
+                            "{}\t{}\targon2id m={} t={} p={}\n",
+                            slot.label,
+                            slot.params.aead.label(),
+                            kdf.mem_cost_kib,
+                            kdf.time_cost,
+                            kdf.parallelism
+                        )
+                    })
+                    .collect();
+                return write_output_text(out, &lines.concat());
+            }
+
+            let recovery = safeparts_core::combine_shares_robust(&packets, passphrase_bytes)
                 .map_err(|e| anyhow!(e))
                 .context("combine failed")?;
+            let secret = Zeroizing::new(recovery.secret);
 
-            write_output_bytes(out, &secret)?;
+            if !recovery.suspects.is_empty() {
+                let suspects: Vec<String> = recovery.suspects.iter().map(u8::to_string).collect();
+                eprintln!(
+                    "warning: ignored corrupted share(s) with index {}",
+                    suspects.join(", ")
+                );
+            }
+
+            match out_dir {
+                Some(dir) => write_output_to_dir(&dir, recovery.metadata.as_ref(), &secret)?,
+                None => write_output_bytes(out, &secret)?,
+            }
         }
 
+        Commands::Lock {
+            encoding: input_encoding,
+            passphrase_file,
+            r#in,
+            out,
+            suite,
+        } => {
+            let (packet, share_encoding) = read_one_share(r#in, input_encoding)?;
+            let passphrase = match passphrase_file {
+                Some(path) => read_passphrase_file(&path)?,
+                None => prompt_new_passphrase(packet.x)?,
+            };
+
+            let locked = safeparts_core::lock_share(&packet, &passphrase, suite.suite()?)
+                .map_err(|e| anyhow!(e))
+                .context("lock failed")?;
+            let encoded = Zeroizing::new(
+                encoding::encode_packet(&locked, share_encoding).map_err(|e| anyhow!(e))?,
+            );
+            write_output_text(out, &(encoded.to_string() + "\n"))?;
+            eprintln!(
+                "share {} is locked; combine asks for this passphrase",
+                locked.x
+            );
+        }
+
+        Commands::VerifyShare {
+            encoding,
+            commitments,
+            r#in,
+        } => {
+            let (packet, _) = read_one_share(r#in, encoding)?;
+
+            let commitments = match commitments {
+                Some(path) => {
+                    let text = fs::read_to_string(&path)
+                        .with_context(|| format!("read commitments {}", path.display()))?;
+                    encoding::decode_commitments(&text).map_err(|e| anyhow!(e))?
+                }
+                None => {
+                    eprintln!(
+                        "warning: no --commitments given; checking against the share's own copy"
+                    );
+                    packet
+                        .commitments
+                        .clone()
+                        .ok_or_else(|| anyhow!(safeparts_core::CoreError::NotVerifiable))?
+                }
+            };
+
+            safeparts_core::verify_share(&packet, &commitments)
+                .map_err(|e| anyhow!(e))
+                .context("verify failed")?;
+
+            println!(
+                "share {} of {} is valid (commitments {})",
+                packet.x,
+                packet.n,
+                hex(&commitments.fingerprint())
+            );
+        }
+
+        Commands::Inspect { encoding, r#in } => {
+            let input = read_input(r#in)?;
+            let input_str = String::from_utf8(input).context("shares input must be UTF-8")?;
+            let parsed = encoding::parse_share_packets(
+                &input_str,
+                encoding.map_or(Ok(Encoding::Auto), CliEncoding::packet_encoding)?,
+            )
+            .map_err(|e| anyhow!(e))?;
+
+            let lines: Vec<String> = parsed
+                .packets
+                .iter()
+                .map(|packet| format!("share {}: {}\n", packet.x, describe_packet(packet)))
+                .collect();
+            print!("{}", lines.concat());
+            if !parsed.wrapped.is_empty() {
+                eprintln!(
+                    "{} age-wrapped share(s) not shown; combine --identity unwraps them",
+                    parsed.wrapped.len()
+                );
+            }
+        }
+
+        Commands::Extend {
+            x,
+            encoding: input_encoding,
+            r#in,
+            out,
+            identities,
+        } => {
+            let input = read_input(r#in)?;
+            let input_str = String::from_utf8(input).context("shares input must be UTF-8")?;
+            let parsed = parse_share_packets(&input_str, input_encoding, &identities)?;
+
+            let packet = safeparts_core::derive_share(&parsed.packets, x)
+                .map_err(|e| anyhow!(e))
+                .with_context(|| format!("extend failed (index={x})"))?;
+
+            let encoded =
+                encoding::encode_packet(&packet, parsed.encoding).map_err(|e| anyhow!(e))?;
+            write_output_text(out, &(encoded + "\n"))?;
+        }
+
+        Commands::Reshare {
+            k,
+            n,
+            encoding: output_encoding,
+            passphrase,
+            passphrase_file,
+            new_passphrase,
+            new_passphrase_file,
+            drop_passphrase,
+            r#in,
+            out,
+            commitments_out,
+            add_key_slots,
+            remove_key_slots,
+            identities,
+        } => {
+            let input = read_input(r#in)?;
+            let input_str = String::from_utf8(input).context("shares input must be UTF-8")?;
+            let old_passphrase = read_passphrase(passphrase, passphrase_file)?;
+            let new_passphrase = read_passphrase(new_passphrase, new_passphrase_file)?;
+            let add_key_slots = read_key_slots(&add_key_slots)?;
+
+            let parsed = parse_share_packets(&input_str, None, &identities)?;
+            if commitments_out.is_some() && !parsed.packets[0].is_verifiable() {
+                bail!("--commitments-out requires shares from a verifiable split");
+            }
+
+            // Key-slot sets keep their slots unless a new passphrase replaces them.
+            let edit_slots = !add_key_slots.is_empty()
+                || !remove_key_slots.is_empty()
+                || (parsed.packets[0].key_slots && new_passphrase.is_none() && !drop_passphrase);
+            let packets = if edit_slots {
+                let Some(old_passphrase) = &old_passphrase else {
+                    bail!("key slot changes need the old set's passphrase (--passphrase-file)");
+                };
+                let changes: Vec<KeySlotChange> = remove_key_slots
+                    .iter()
+                    .map(|label| KeySlotChange::Remove(label))
+                    .chain(
+                        add_key_slots
+                            .iter()
+                            .map(|(label, passphrase)| KeySlotChange::Add { label, passphrase }),
+                    )
+                    .collect();
+                safeparts_core::reshare_with_key_slots(
+                    &parsed.packets,
+                    k,
+                    n,
+                    old_passphrase,
+                    &changes,
+                )
+            } else if drop_passphrase {
+                safeparts_core::reshare_unprotected(
+                    &parsed.packets,
+                    k,
+                    n,
+                    old_passphrase.as_ref().map(|p| p.as_slice()),
+                )
+            } else {
+                safeparts_core::reshare(
+                    &parsed.packets,
+                    k,
+                    n,
+                    old_passphrase.as_ref().map(|p| p.as_slice()),
+                    new_passphrase.as_ref().map(|p| p.as_slice()),
+                )
+            }
+            .map_err(|e| anyhow!(e))
+            .with_context(|| format!("reshare failed (k={k}, n={n})"))?;
+
+            if let (Some(commitments), Some(path)) = (&packets[0].commitments, commitments_out) {
+                let text = encoding::encode_commitments(commitments) + "\n";
+                write_output_text(Some(path), &text)?;
+            }
+
+            let output_encoding =
+                output_encoding.map_or(Ok(parsed.encoding), CliEncoding::packet_encoding)?;
+            let encoded: Vec<String> = packets
+                .iter()
+                .map(|p| encoding::encode_packet(p, output_encoding).map_err(|e| anyhow!(e)))
+                .collect::<Result<Vec<_>>>()?;
+            write_output_text(out, &(encoded.join("\n") + "\n"))?;
+
+            eprintln!(
+                "warning: set {} is superseded by set {}; destroy the old shares",
+                hex(&parsed.packets[0].set_id.0),
+                hex(&packets[0].set_id.0)
+            );
+        }
+
+        Commands::Refresh { command } => run_refresh(command)?,
+
+        Commands::Slip39 { command } => run_slip39(command)?,
+
         Commands::Tui => launch_tui()?,
     }
 
     Ok(())
 }
 
+fn run_refresh(command: RefreshCommand) -> Result<()> {
+    match command {
+        RefreshCommand::Init {
+            participants,
+            r#in,
+            out,
+        } => {
+            let (packet, _) = read_one_share(r#in, None)?;
+            let round = RefreshRound::new(&packet, &participants)
+                .map_err(|e| anyhow!(e))
+                .context("refresh init failed")?;
+            write_output_text(out, &(encoding::encode_refresh_round(&round) + "\n"))?;
+        }
+
+        RefreshCommand::Contribute {
+            round,
+            r#in,
+            out_dir,
+        } => {
+            let round = read_refresh_round(&round)?;
+            let (packet, _) = read_one_share(r#in, None)?;
+            let sub_shares = refresh::contribute(&round, &packet)
+                .map_err(|e| anyhow!(e))
+                .context("refresh contribute failed")?;
+
+            fs::create_dir_all(&out_dir)
+                .with_context(|| format!("create directory {}", out_dir.display()))?;
+            for sub_share in &sub_shares {
+                let path = out_dir.join(format!(
+                    "subshare-{}-to-{}.txt",
+                    sub_share.from, sub_share.to
+                ));
+                let text = Zeroizing::new(encoding::encode_sub_share(sub_share) + "\n");
+                write_output_text(Some(path), &text)?;
+            }
+            eprintln!(
+                "wrote {} sub-shares; deliver each file privately to its recipient",
+                sub_shares.len()
+            );
+        }
+
+        RefreshCommand::Apply {
+            round,
+            r#in,
+            sub_shares,
+            out,
+        } => {
+            let round = read_refresh_round(&round)?;
+            let (packet, share_encoding) = read_one_share(r#in, None)?;
+            let sub_shares = sub_shares
+                .iter()
+                .map(|path| {
+                    let text = Zeroizing::new(
+                        fs::read_to_string(path)
+                            .with_context(|| format!("read sub-share {}", path.display()))?,
+                    );
+                    encoding::decode_sub_share(&text).map_err(|e| anyhow!(e))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let refreshed = refresh::apply(&round, &packet, &sub_shares)
+                .map_err(|e| anyhow!(e))
+                .context("refresh apply failed")?;
+            let encoded = Zeroizing::new(
+                encoding::encode_packet(&refreshed, share_encoding).map_err(|e| anyhow!(e))?,
+            );
+            write_output_text(out, &(encoded.to_string() + "\n"))?;
+            eprintln!(
+                "share {} is now in epoch {}; destroy the old share",
+                refreshed.x, refreshed.epoch
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn run_slip39(command: Slip39Command) -> Result<()> {
+    match command {
+        Slip39Command::Split {
+            k,
+            n,
+            groups,
+            group_threshold,
+            passphrase,
+            passphrase_file,
+            iteration_exponent,
+            r#in,
+            out,
+        } => {
+            let passphrase = read_passphrase(passphrase, passphrase_file)?.unwrap_or_default();
+            let groups = match (k, n) {
+                (Some(threshold), Some(count)) => vec![slip39::GroupSpec { threshold, count }],
+                _ => groups,
+            };
+            let options = slip39::Slip39Options {
+                iteration_exponent,
+                ..slip39::Slip39Options::default()
+            };
+            let secret = Zeroizing::new(read_input(r#in)?);
+            let mnemonics = slip39::split(
+                &secret,
+                &passphrase,
+                group_threshold.unwrap_or(1),
+                &groups,
+                &options,
+            )
+            .map_err(|e| anyhow!(e))
+            .context("slip39 split failed")?;
+
+            let text = mnemonics
+                .iter()
+                .map(|group| group.join("\n") + "\n")
+                .collect::<Vec<_>>()
+                .join("\n");
+            write_output_text(out, &text)?;
+        }
+
+        Slip39Command::Combine {
+            passphrase,
+            passphrase_file,
+            r#in,
+            out,
+        } => {
+            let passphrase = read_passphrase(passphrase, passphrase_file)?.unwrap_or_default();
+            let input = read_input(r#in)?;
+            let input = String::from_utf8(input).context("mnemonics input must be UTF-8")?;
+            let mnemonics: Vec<&str> = input
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect();
+            let secret = slip39::combine(&mnemonics, &passphrase)
+                .map_err(|e| anyhow!(e))
+                .context("slip39 combine failed")?;
+            write_output_bytes(out, &secret)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse a `--group` value such as `2-of-3`.
+fn parse_slip39_group_arg(value: &str) -> Result<slip39::GroupSpec, String> {
+    let (threshold, count) = value
+        .split_once("-of-")
+        .ok_or_else(|| format!("expected THRESHOLD-of-COUNT, got {value:?}"))?;
+    let parse = |part: &str| {
+        part.trim()
+            .parse::<u8>()
+            .map_err(|_| format!("not a number in {value:?}"))
+    };
+    Ok(slip39::GroupSpec {
+        threshold: parse(threshold)?,
+        count: parse(count)?,
+    })
+}
+
+fn read_refresh_round(path: &Path) -> Result<RefreshRound> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("read refresh round {}", path.display()))?;
+    encoding::decode_refresh_round(&text).map_err(|e| anyhow!(e))
+}
+
 fn launch_tui() -> Result<()> {
     let exe_suffix = std::env::consts::EXE_SUFFIX;
     let current = std::env::current_exe().context("resolve current executable")?;
@@ -183,16 +1469,222 @@ fn encode_packet_cli(
     packet: &safeparts_core::packet::SharePacket,
     encoding: CliEncoding,
 ) -> Result<String> {
-    encoding::encode_packet(packet, encoding.into()).map_err(|e| anyhow!(e))
+    encoding::encode_packet(packet, encoding.packet_encoding()?).map_err(|e| anyhow!(e))
+}
+
+/// The foreign share format of input that no share packet encoding reads.
+fn detect_foreign_encoding(input: &str) -> Option<CliEncoding> {
+    if encoding::detect_encoding(input).is_ok_and(|detected| detected.is_some()) {
+        return None;
+    }
+    if ssss::looks_like(input) {
+        Some(CliEncoding::Ssss)
+    } else if codex32::looks_like(input) {
+        Some(CliEncoding::Codex32)
+    } else if secrets_js::looks_like(input) {
+        Some(CliEncoding::SecretsJs)
+    } else {
+        None
+    }
 }
 
+/// Parse pasted shares, unwrapping age-wrapped ones with the identity files.
+///
+/// The result holds at least one share. Input of only wrapped shares has no
+/// text encoding, so it reports the default split encoding.
 fn parse_share_packets(
     input: &str,
     encoding: Option<CliEncoding>,
+    identity_files: &[PathBuf],
+) -> Result<encoding::ParsedSharePackets> {
+    let encoding = encoding.map_or(Ok(Encoding::Auto), CliEncoding::packet_encoding)?;
+    let mut parsed = encoding::parse_share_packets(input, encoding).map_err(|e| anyhow!(e))?;
+    if parsed.wrapped.is_empty() {
+        return Ok(parsed);
+    }
+    if identity_files.is_empty() {
+        bail!(
+            "input has {} age-wrapped share(s); pass --identity to unwrap them",
+            parsed.wrapped.len()
+        );
+    }
+
+    let mut identities = Vec::new();
+    for path in identity_files {
+        let text = Zeroizing::new(
+            fs::read_to_string(path)
+                .with_context(|| format!("read identity {}", path.display()))?,
+        );
+        identities.extend(
+            age::parse_identities(&text)
+                .map_err(|e| anyhow!(e))
+                .with_context(|| format!("parse identity {}", path.display()))?,
+        );
+    }
+    parsed
+        .unwrap_with(&identities)
+        .map_err(|e| anyhow!(e))
+        .context("unwrap failed")?;
+    if parsed.packets.is_empty() {
+        bail!("none of the age-wrapped shares is for the given identities");
+    }
+    if !parsed.wrapped.is_empty() {
+        eprintln!(
+            "warning: skipped {} age-wrapped share(s) for other recipients",
+            parsed.wrapped.len()
+        );
+    }
+    if parsed.encoding.is_auto() {
+        parsed.encoding = Encoding::Base64url;
+    }
+    Ok(parsed)
+}
+
+fn parse_key_slot_arg(value: &str) -> Result<(String, PathBuf), String> {
+    let (label, path) = value
+        .split_once('=')
+        .ok_or_else(|| "expected LABEL=FILE".to_string())?;
+    if label.is_empty() {
+        return Err("key slot label must not be empty".to_string());
+    }
+    Ok((label.to_string(), PathBuf::from(path)))
+}
+
+/// Read each `LABEL=FILE` key slot's passphrase file.
+fn read_key_slots(slots: &[(String, PathBuf)]) -> Result<Vec<(String, Zeroizing<Vec<u8>>)>> {
+    slots
+        .iter()
+        .map(|(label, path)| Ok((label.clone(), read_passphrase_file(path)?)))
+        .collect()
+}
+
+fn parse_share_passphrase_arg(value: &str) -> Result<(u8, PathBuf), String> {
+    let (x, path) = value
+        .split_once('=')
+        .ok_or_else(|| "expected INDEX=FILE".to_string())?;
+    let x = x
+        .parse::<u8>()
+        .ok()
+        .filter(|&x| x != 0)
+        .ok_or_else(|| format!("invalid share index {x:?}"))?;
+    Ok((x, PathBuf::from(path)))
+}
+
+/// Unlock per-share locks with the `INDEX=FILE` passphrase files, prompting
+/// on the terminal for the rest. An empty answer skips the share.
+fn unlock_locked_shares(
+    packets: Vec<safeparts_core::packet::SharePacket>,
+    files: &[(u8, PathBuf)],
 ) -> Result<Vec<safeparts_core::packet::SharePacket>> {
-    let encoding = encoding.map_or(Encoding::Auto, Into::into);
-    let parsed = encoding::parse_share_packets(input, encoding).map_err(|e| anyhow!(e))?;
-    Ok(parsed.packets)
+    let mut out = Vec::with_capacity(packets.len());
+    for packet in packets {
+        if !packet.is_locked() {
+            out.push(packet);
+            continue;
+        }
+
+        if let Some((_, path)) = files.iter().find(|(x, _)| *x == packet.x) {
+            let passphrase = read_passphrase_file(path)?;
+            out.push(safeparts_core::unlock_share(&packet, &passphrase).map_err(|e| anyhow!(e))?);
+            continue;
+        }
+
+        if !io::stderr().is_terminal() {
+            bail!(
+                "share {x} is locked; pass --share-passphrase-file {x}=FILE",
+                x = packet.x
+            );
+        }
+        loop {
+            let passphrase = Zeroizing::new(
+                rpassword::prompt_password(format!(
+                    "passphrase for share {} (empty to skip): ",
+                    packet.x
+                ))
+                .context("read share passphrase")?,
+            );
+            if passphrase.is_empty() {
+                eprintln!("skipped locked share {}", packet.x);
+                break;
+            }
+            match safeparts_core::unlock_share(&packet, passphrase.as_bytes()) {
+                Ok(unlocked) => {
+                    out.push(unlocked);
+                    break;
+                }
+                Err(err @ CoreError::ShareUnlockFailed { .. }) => eprintln!("{err}; try again"),
+                Err(err) => return Err(anyhow!(err)),
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Ask twice on the terminal for a new share passphrase.
+fn prompt_new_passphrase(x: u8) -> Result<Zeroizing<Vec<u8>>> {
+    if !io::stderr().is_terminal() {
+        bail!("lock needs --passphrase-file when not run in a terminal");
+    }
+    let first = Zeroizing::new(
+        rpassword::prompt_password(format!("new passphrase for share {x}: "))
+            .context("read share passphrase")?,
+    );
+    if first.is_empty() {
+        bail!("share passphrase must not be empty");
+    }
+    let second = Zeroizing::new(
+        rpassword::prompt_password("repeat passphrase: ").context("read share passphrase")?,
+    );
+    if first != second {
+        bail!("passphrases do not match");
+    }
+    Ok(Zeroizing::new(first.as_bytes().to_vec()))
+}
+
+/// One-line summary of what a share reveals about its set.
+fn describe_packet(packet: &safeparts_core::packet::SharePacket) -> String {
+    if packet.stealth {
+        return "stealth share, metadata hidden".to_string();
+    }
+    let needed = match &packet.policy {
+        Some(placement) => format!("policy {}", placement.policy),
+        None => format!("{} of {} needed", packet.k, packet.n),
+    };
+    let mut parts = vec![needed, format!("set {}", hex(&packet.set_id.0))];
+    if packet.is_encrypted() {
+        parts.push("passphrase".to_string());
+    }
+    if packet.is_locked() {
+        parts.push("locked".to_string());
+    }
+    if packet.is_verifiable() {
+        parts.push("verifiable".to_string());
+    }
+    if packet.epoch != 0 {
+        parts.push(format!("epoch {}", packet.epoch));
+    }
+    parts.join(", ")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn read_one_share(
+    path: Option<PathBuf>,
+    encoding: Option<CliEncoding>,
+) -> Result<(safeparts_core::packet::SharePacket, Encoding)> {
+    let input = read_input(path)?;
+    let input_str = String::from_utf8(input).context("share input must be UTF-8")?;
+    let parsed = encoding::parse_share_packets(
+        &input_str,
+        encoding.map_or(Ok(Encoding::Auto), CliEncoding::packet_encoding)?,
+    )
+    .map_err(|e| anyhow!(e))?;
+    match parsed.packets.as_slice() {
+        [packet] => Ok((packet.clone(), parsed.encoding)),
+        packets => bail!("expected one share, got {}", packets.len()),
+    }
 }
 
 fn is_dash_path(path: &Path) -> bool {
@@ -212,6 +1704,96 @@ fn read_input(path: Option<PathBuf>) -> Result<Vec<u8>> {
     }
 }
 
+/// Stream `n` binary share files into `dir` without loading the input.
+fn split_to_dir(
+    input: Option<PathBuf>,
+    dir: &Path,
+    k: u8,
+    n: u8,
+    passphrase: Option<&[u8]>,
+    suite: CipherSuite,
+) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let outputs = (1..=n)
+        .map(|x| {
+            let path = dir.join(format!("share-{x}-of-{n}.bin"));
+            let file =
+                fs::File::create(&path).with_context(|| format!("create {}", path.display()))?;
+            Ok(io::BufWriter::new(file))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let result = match input.as_deref() {
+        Some(path) if !is_dash_path(path) => {
+            let file =
+                fs::File::open(path).with_context(|| format!("read input {}", path.display()))?;
+            safeparts_core::stream::split(io::BufReader::new(file), outputs, k, passphrase, suite)
+        }
+        _ => safeparts_core::stream::split(io::stdin().lock(), outputs, k, passphrase, suite),
+    };
+    result
+        .map_err(|e| anyhow!(e))
+        .with_context(|| format!("split failed (k={k}, n={n})"))?;
+
+    eprintln!("wrote {n} share files to {}", dir.display());
+    Ok(())
+}
+
+/// Stream the secret back from the share files in `dir`.
+///
+/// A partially written output file is removed when combine fails.
+fn combine_from_dir(dir: &Path, out: Option<PathBuf>, passphrase: Option<&[u8]>) -> Result<()> {
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("read {}", dir.display()))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<io::Result<Vec<_>>>()
+        .with_context(|| format!("read {}", dir.display()))?;
+    paths.retain(|path| path.is_file() && is_share_file_name(path));
+    paths.sort();
+    if paths.is_empty() {
+        bail!("no share-X-of-N.bin files in {}", dir.display());
+    }
+
+    let inputs = paths
+        .iter()
+        .map(|path| {
+            let file = fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+            Ok(io::BufReader::new(file))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    match out.as_deref() {
+        Some(path) if !is_dash_path(path) => {
+            let file = fs::File::create(path)
+                .with_context(|| format!("write output {}", path.display()))?;
+            let result =
+                safeparts_core::stream::combine(inputs, io::BufWriter::new(file), passphrase);
+            if let Err(e) = result {
+                fs::remove_file(path).ok();
+                return Err(anyhow!(e)).context("combine failed");
+            }
+        }
+        _ => {
+            safeparts_core::stream::combine(inputs, io::stdout().lock(), passphrase)
+                .map_err(|e| anyhow!(e))
+                .context("combine failed")?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` is named like the files [`split_to_dir`] writes.
+fn is_share_file_name(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    name.strip_prefix("share-")
+        .and_then(|rest| rest.strip_suffix(".bin"))
+        .and_then(|rest| rest.split_once("-of-"))
+        .is_some_and(|(x, n)| is_number(x) && is_number(n))
+}
+
 fn write_output_text(path: Option<PathBuf>, text: &str) -> Result<()> {
     match path.as_deref() {
         Some(path) if !is_dash_path(path) => {
@@ -238,21 +1820,71 @@ fn write_output_bytes(path: Option<PathBuf>, bytes: &[u8]) -> Result<()> {
     }
 }
 
+/// Write a recovered secret into `dir` under its stored filename, or
+/// `recovered-secret` when the shares carry no usable one.
+fn write_output_to_dir(dir: &Path, metadata: Option<&SecretMetadata>, secret: &[u8]) -> Result<()> {
+    let stored = metadata.and_then(|metadata| metadata.filename.as_deref());
+    let name = match metadata.and_then(SecretMetadata::safe_filename) {
+        Some(name) => name,
+        None => {
+            if let Some(stored) = stored {
+                eprintln!("warning: ignored unsafe stored filename {stored:?}");
+            }
+            "recovered-secret"
+        }
+    };
+
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let path = dir.join(name);
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .with_context(|| format!("write output {} (will not overwrite)", path.display()))?;
+    file.write_all(secret)
+        .with_context(|| format!("write output {}", path.display()))?;
+
+    eprintln!("wrote {}", path.display());
+    Ok(())
+}
+
+/// Metadata for `split --metadata`: the input's filename, the time, and any
+/// label or content type given on the command line.
+fn secret_metadata(
+    input: Option<&Path>,
+    label: Option<String>,
+    content_type: Option<String>,
+) -> SecretMetadata {
+    let filename = input
+        .filter(|path| !is_dash_path(path))
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned());
+    SecretMetadata {
+        filename,
+        content_type,
+        label,
+        ..SecretMetadata::now()
+    }
+}
+
+/// Read a passphrase file, trimming trailing newlines.
+fn read_passphrase_file(path: &Path) -> Result<Zeroizing<Vec<u8>>> {
+    let mut bytes = Zeroizing::new(
+        fs::read(path).with_context(|| format!("read passphrase {}", path.display()))?,
+    );
+    while matches!(bytes.last(), Some(b'\n' | b'\r')) {
+        bytes.pop();
+    }
+    Ok(bytes)
+}
+
 fn read_passphrase(
     passphrase: Option<String>,
     passphrase_file: Option<PathBuf>,
 ) -> Result<Option<Zeroizing<Vec<u8>>>> {
     match (passphrase, passphrase_file) {
         (Some(p), None) => Ok(Some(Zeroizing::new(p.into_bytes()))),
-        (None, Some(path)) => {
-            let mut bytes = Zeroizing::new(
-                fs::read(&path).with_context(|| format!("read passphrase {}", path.display()))?,
-            );
-            while matches!(bytes.last(), Some(b'\n' | b'\r')) {
-                bytes.pop();
-            }
-            Ok(Some(bytes))
-        }
+        (None, Some(path)) => read_passphrase_file(&path).map(Some),
         (None, None) => Ok(None),
         (Some(_), Some(_)) => Err(anyhow!("use either --passphrase or --passphrase-file")),
     }
@@ -56,6 +56,13 @@ fn run_combine(encoding: Option<&str>, shares: &[String], passphrase: Option<&st
     assert.get_output().stdout.clone()
 }
 
+fn hex_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
 #[test]
 fn explicit_dash_paths_use_stdin_and_stdout() {
     let input = b"explicit stdio paths";
@@ -161,3 +168,1224 @@ fn combine_with_insufficient_shares_fails() {
         .failure()
         .stderr(predicate::str::contains("need at least k shares"));
 }
+
+#[test]
+fn verifiable_split_shares_pass_verify_share_and_combine() {
+    let commitments = std::env::temp_dir().join(format!(
+        "safeparts-e2e-{}-commitments.txt",
+        std::process::id()
+    ));
+
+    let mut split = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"));
+    let output = split
+        .args([
+            "split",
+            "-k",
+            "2",
+            "-n",
+            "3",
+            "--verifiable",
+            "--commitments-out",
+        ])
+        .arg(&commitments)
+        .write_stdin(b"hello verifiable".as_slice())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shares: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    for share in &shares {
+        Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["verify-share", "-c"])
+            .arg(&commitments)
+            .write_stdin(share.clone())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("is valid"));
+    }
+
+    let recovered = run_combine(None, &shares[1..], None);
+    assert_eq!(recovered, b"hello verifiable");
+
+    let other = run_split("base64", 2, 3, b"plain", None);
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["verify-share", "-c"])
+        .arg(&commitments)
+        .write_stdin(other[0].clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not verifiable"));
+
+    std::fs::remove_file(commitments).ok();
+}
+
+#[test]
+fn combine_ignores_and_reports_corrupted_surplus_share() {
+    let input = b"hello robust combine";
+    let mut shares = run_split("base64", 2, 4, input, None);
+
+    let mut chars: Vec<char> = shares[2].chars().collect();
+    let pos = chars.len() - 4;
+    chars[pos] = if chars[pos] == 'A' { 'B' } else { 'A' };
+    shares[2] = chars.into_iter().collect();
+
+    let stdin = shares.join("\n") + "\n";
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .arg("combine")
+        .write_stdin(stdin)
+        .assert()
+        .success()
+        .stdout(input.as_slice())
+        .stderr(predicate::str::contains("index 3"));
+}
+
+#[test]
+fn extend_issues_share_that_combines_with_existing_shares() {
+    let input = b"hello extend";
+    let shares = run_split("mnemo-words", 2, 3, input, Some("pw"));
+
+    let stdin = shares[..2].join("\n") + "\n";
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["extend", "-x", "7"])
+        .write_stdin(stdin)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let extended = String::from_utf8(output).unwrap().trim().to_string();
+
+    let recovered = run_combine(
+        Some("mnemo-words"),
+        &[shares[2].clone(), extended],
+        Some("pw"),
+    );
+    assert_eq!(recovered, input);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["extend", "-x", "1"])
+        .write_stdin(shares[..2].join("\n") + "\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("duplicate"));
+}
+
+#[test]
+fn reshare_moves_secret_to_new_policy_and_warns_old_set_superseded() {
+    let input = b"hello reshare";
+    let old = run_split("base58", 2, 3, input, Some("old"));
+
+    let stdin = old[..2].join("\n") + "\n";
+    let assert = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args([
+            "reshare",
+            "-k",
+            "3",
+            "-n",
+            "5",
+            "-p",
+            "old",
+            "--new-passphrase",
+            "new",
+        ])
+        .write_stdin(stdin)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("superseded"));
+    let new: Vec<String> = String::from_utf8(assert.get_output().stdout.clone())
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(new.len(), 5);
+
+    let recovered = run_combine(Some("base58"), &new[2..], Some("new"));
+    assert_eq!(recovered, input);
+
+    let stdin = format!("{}\n{}\n", old[2], new[0]);
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-p", "new"])
+        .write_stdin(stdin)
+        .assert()
+        .failure();
+
+    let stdin = old[..2].join("\n") + "\n";
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["reshare", "-k", "2", "-n", "3", "-p", "old"])
+        .write_stdin(stdin.clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("new passphrase required"));
+    let assert = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args([
+            "reshare",
+            "-k",
+            "2",
+            "-n",
+            "3",
+            "-p",
+            "old",
+            "--drop-passphrase",
+        ])
+        .write_stdin(stdin)
+        .assert()
+        .success();
+    let plain: Vec<String> = String::from_utf8(assert.get_output().stdout.clone())
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(run_combine(None, &plain[..2], None), input);
+}
+
+#[test]
+fn refresh_round_rerandomises_shares_and_rejects_mixed_epochs() {
+    let dir = std::env::temp_dir().join(format!("safeparts-e2e-{}-refresh", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = b"hello refresh";
+    let old = run_split("base64", 2, 3, input, None);
+    let round = dir.join("round.txt");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["refresh", "init", "--participants", "1,2,3", "-o"])
+        .arg(&round)
+        .write_stdin(old[0].clone())
+        .assert()
+        .success();
+
+    for share in &old {
+        Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["refresh", "contribute", "-r"])
+            .arg(&round)
+            .arg("--out-dir")
+            .arg(&dir)
+            .write_stdin(share.clone())
+            .assert()
+            .success();
+    }
+
+    let new: Vec<String> = old
+        .iter()
+        .enumerate()
+        .map(|(index, share)| {
+            let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"));
+            cmd.args(["refresh", "apply", "-r"]).arg(&round);
+            for from in 1..=3 {
+                cmd.arg("-s")
+                    .arg(dir.join(format!("subshare-{from}-to-{}.txt", index + 1)));
+            }
+            let output = cmd
+                .write_stdin(share.clone())
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone();
+            String::from_utf8(output).unwrap().trim().to_string()
+        })
+        .collect();
+
+    assert_ne!(new[0], old[0]);
+    assert_eq!(run_combine(None, &new[1..], None), input);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .arg("combine")
+        .write_stdin(format!("{}\n{}\n", new[0], old[1]))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("different refresh epochs"));
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn policy_split_combines_when_satisfied_and_names_missing_groups() {
+    let policy =
+        std::env::temp_dir().join(format!("safeparts-e2e-{}-policy.txt", std::process::id()));
+    std::fs::write(
+        &policy,
+        "all of (eng: 2 of (e1, e2, e3), legal: any of (l1, l2))\n",
+    )
+    .unwrap();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "--policy"])
+        .arg(&policy)
+        .write_stdin(b"hello policy".as_slice())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("e1, e2, e3, l1, l2"))
+        .get_output()
+        .stdout
+        .clone();
+    let shares: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(shares.len(), 5);
+
+    let recovered = run_combine(
+        None,
+        &[shares[1].clone(), shares[2].clone(), shares[4].clone()],
+        None,
+    );
+    assert_eq!(recovered, b"hello policy");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .arg("combine")
+        .write_stdin(shares[..2].join("\n") + "\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("legal: 1 more of (l1, l2)"));
+
+    std::fs::remove_file(policy).ok();
+}
+
+#[test]
+fn large_split_shares_are_about_one_kth_of_the_secret() {
+    let input: Vec<u8> = (0..=255u8).cycle().take(60_000).collect();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "3", "-n", "5", "--large"])
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shares: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(shares.len(), 5);
+    assert!(shares.iter().all(|share| share.len() < 30_000));
+
+    let recovered = run_combine(None, &shares[2..], None);
+    assert_eq!(recovered, input);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--large", "--verifiable"])
+        .write_stdin(b"conflict".as_slice())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn streaming_split_and_combine_use_share_directories() {
+    let dir = std::env::temp_dir().join(format!("safeparts-e2e-{}-stream", std::process::id()));
+    let shares = dir.join("shares");
+    let input: Vec<u8> = (0..=255u8).cycle().take(200_000).collect();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "-p", "pw", "--out-dir"])
+        .arg(&shares)
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("wrote 3 share files"));
+
+    std::fs::remove_file(shares.join("share-2-of-3.bin")).unwrap();
+    std::fs::write(shares.join(".DS_Store"), b"not a share").unwrap();
+    std::fs::write(shares.join("README"), b"not a share either").unwrap();
+    let recovered = dir.join("recovered.bin");
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-p", "pw", "--in-dir"])
+        .arg(&shares)
+        .arg("-o")
+        .arg(&recovered)
+        .assert()
+        .success();
+    assert_eq!(std::fs::read(&recovered).unwrap(), input);
+
+    let share = shares.join("share-3-of-3.bin");
+    let mut bytes = std::fs::read(&share).unwrap();
+    bytes.truncate(bytes.len() - 10);
+    std::fs::write(&share, bytes).unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-p", "pw", "--in-dir"])
+        .arg(&shares)
+        .arg("-o")
+        .arg(&recovered)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("truncated stream share"));
+    assert!(!recovered.exists());
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn kdf_profiles_custom_costs_and_calibration_round_trip() {
+    let split = |args: &[&str]| -> (Vec<String>, String) {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["split", "-k", "2", "-n", "3", "-p", "pw"])
+            .args(args)
+            .write_stdin(b"kdf secret".as_slice())
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        let shares = String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        (shares, String::from_utf8(output.stderr).unwrap())
+    };
+
+    for args in [
+        &["--kdf", "interactive"][..],
+        &[
+            "--kdf",
+            "interactive",
+            "--kdf-iterations",
+            "1",
+            "--kdf-parallelism",
+            "2",
+        ],
+        &["--kdf-target-ms", "20", "--kdf-memory", "16384"],
+    ] {
+        let (shares, stderr) = split(args);
+        assert_eq!(run_combine(None, &shares[1..], Some("pw")), b"kdf secret");
+        assert_eq!(
+            stderr.contains("calibrated Argon2id"),
+            args[0] == "--kdf-target-ms"
+        );
+    }
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--kdf", "paranoid"])
+        .write_stdin(b"no passphrase".as_slice())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--kdf options need"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args([
+            "split",
+            "-k",
+            "2",
+            "-n",
+            "3",
+            "-p",
+            "pw",
+            "--kdf-memory",
+            "4",
+        ])
+        .write_stdin(b"too little memory".as_slice())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid key-derivation parameters",
+        ));
+}
+
+#[test]
+fn every_cipher_round_trips_through_text_and_stream_shares() {
+    for cipher in ["chacha20-poly1305", "xchacha20-poly1305", "aes-256-gcm-siv"] {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args([
+                "split",
+                "-k",
+                "2",
+                "-n",
+                "3",
+                "-p",
+                "pw",
+                "--kdf",
+                "interactive",
+            ])
+            .args(["--cipher", cipher])
+            .write_stdin(b"cipher secret".as_slice())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let shares: Vec<String> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            run_combine(None, &shares[..2], Some("pw")),
+            b"cipher secret"
+        );
+
+        let dir = std::env::temp_dir().join(format!(
+            "safeparts-e2e-{}-cipher-{cipher}",
+            std::process::id()
+        ));
+        Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args([
+                "split",
+                "-k",
+                "2",
+                "-n",
+                "2",
+                "-p",
+                "pw",
+                "--kdf",
+                "interactive",
+            ])
+            .args(["--cipher", cipher, "--out-dir"])
+            .arg(&dir)
+            .write_stdin(b"streamed cipher secret".as_slice())
+            .assert()
+            .success();
+        Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["combine", "-p", "pw", "--in-dir"])
+            .arg(&dir)
+            .assert()
+            .success()
+            .stdout(predicate::eq(b"streamed cipher secret".as_slice()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--cipher", "aes-256-gcm-siv"])
+        .write_stdin(b"no passphrase".as_slice())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--cipher and --kdf options need"));
+}
+
+#[test]
+fn recipient_wrapped_shares_combine_with_identity_files() {
+    const IDENTITIES: [(&str, &str); 2] = [
+        (
+            "AGE-SECRET-KEY-1GFPYYSJZGFPYYSJZGFPYYSJZGFPYYSJZGFPYYSJZGFPYYSJZGFPQ4EGAEX",
+            "age1zvkyg2lqzraa2lnjvqej32nkuu0ues2s82hzrye869xeexvn73equnujwj",
+        ),
+        (
+            "AGE-SECRET-KEY-1QYPQXPQ9QCRSSZG2PVXQ6RS0ZQG3YYC5Z5TPWXQERGD3C8G7RUSQGPQYEE",
+            "age1q73he0q5yzfu3d64msd3p6rvksnrwjk3d2598mgtmlqt9wrdr37q2vrn72",
+        ),
+    ];
+    let third = "age1tp56lazs2jtn9ja2a409m7dnpfk6x89su46zht266js6w6835eascutqdx";
+
+    let dir = std::env::temp_dir().join(format!("safeparts-e2e-{}-age", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let key_files: Vec<_> = IDENTITIES
+        .iter()
+        .enumerate()
+        .map(|(i, (identity, recipient))| {
+            let path = dir.join(format!("key{i}.txt"));
+            std::fs::write(&path, format!("# public key: {recipient}\n{identity}\n")).unwrap();
+            path
+        })
+        .collect();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3"])
+        .args([
+            "--recipient",
+            IDENTITIES[0].1,
+            "--recipient",
+            IDENTITIES[1].1,
+        ])
+        .args(["--recipient", third])
+        .write_stdin(b"wrapped secret".as_slice())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let wrapped = String::from_utf8(output).unwrap();
+    assert_eq!(
+        wrapped
+            .matches("-----BEGIN AGE ENCRYPTED FILE-----")
+            .count(),
+        3
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .arg("combine")
+        .write_stdin(wrapped.clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --identity"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--identity"])
+        .arg(&key_files[0])
+        .write_stdin(wrapped.clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("need at least k shares"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--identity"])
+        .arg(&key_files[0])
+        .arg("--identity")
+        .arg(&key_files[1])
+        .write_stdin(wrapped.clone())
+        .assert()
+        .success()
+        .stdout(predicate::eq(b"wrapped secret".as_slice()))
+        .stderr(predicate::str::contains("skipped 1 age-wrapped share"));
+
+    for args in [
+        &["reshare", "-k", "2", "-n", "2"][..],
+        &["extend", "-x", "4"],
+    ] {
+        Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(args)
+            .write_stdin(wrapped.clone())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("pass --identity"));
+    }
+
+    let reshared = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["reshare", "-k", "2", "-n", "2", "--identity"])
+        .arg(&key_files[0])
+        .arg("--identity")
+        .arg(&key_files[1])
+        .write_stdin(wrapped)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .arg("combine")
+        .write_stdin(reshared)
+        .assert()
+        .success()
+        .stdout(predicate::eq(b"wrapped secret".as_slice()));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--recipient", third])
+        .write_stdin(b"wrapped secret".as_slice())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("got 1 --recipient for 3 shares"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn locked_share_combines_only_with_its_own_passphrase() {
+    let shares = run_split("base64url", 2, 3, b"locked secret", None);
+    let dir = std::env::temp_dir().join(format!("safeparts-e2e-{}-lock", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let pin = dir.join("pin.txt");
+    let other_pin = dir.join("other.txt");
+    std::fs::write(&pin, "2468\n").unwrap();
+    std::fs::write(&other_pin, "1357\n").unwrap();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["lock", "--kdf", "interactive", "-P"])
+        .arg(&pin)
+        .write_stdin(shares[1].clone())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("share 2 is locked"))
+        .get_output()
+        .stdout
+        .clone();
+    let locked = String::from_utf8(output).unwrap().trim().to_string();
+    assert_ne!(locked, shares[1]);
+    let input = format!("{}\n{locked}\n", shares[0]);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .arg("combine")
+        .write_stdin(input.clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "share 2 is locked; pass --share-passphrase-file 2=FILE",
+        ));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--share-passphrase-file"])
+        .arg(format!("2={}", other_pin.display()))
+        .write_stdin(input.clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("wrong passphrase for share 2"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--share-passphrase-file"])
+        .arg(format!("2={}", pin.display()))
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(b"locked secret".as_slice()));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn key_slots_open_with_either_passphrase_and_change_on_reshare() {
+    let dir = std::env::temp_dir().join(format!("safeparts-e2e-{}-slots", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let ops = dir.join("ops.txt");
+    let bg = dir.join("bg.txt");
+    let sec = dir.join("sec.txt");
+    std::fs::write(&ops, "ops pass\n").unwrap();
+    std::fs::write(&bg, "break-glass pass\n").unwrap();
+    std::fs::write(&sec, "security pass\n").unwrap();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--kdf", "interactive"])
+        .arg("--key-slot")
+        .arg(format!("ops={}", ops.display()))
+        .arg("--key-slot")
+        .arg(format!("break-glass={}", bg.display()))
+        .write_stdin(b"slotted secret".as_slice())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shares: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(shares.len(), 3);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--list-key-slots"])
+        .write_stdin(shares[..2].join("\n"))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                "^ops\tchacha20-poly1305\targon2id m=19456 t=2 p=1\nbreak-glass\t",
+            )
+            .unwrap(),
+        );
+
+    assert_eq!(
+        run_combine(None, &shares[..2], Some("ops pass")),
+        b"slotted secret"
+    );
+    assert_eq!(
+        run_combine(None, &shares[1..], Some("break-glass pass")),
+        b"slotted secret"
+    );
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["reshare", "-k", "2", "-n", "3", "-P"])
+        .arg(&bg)
+        .args(["--remove-key-slot", "ops", "--add-key-slot"])
+        .arg(format!("security={}", sec.display()))
+        .write_stdin(shares[..2].join("\n"))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let new: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    assert_eq!(
+        run_combine(None, &new[..2], Some("security pass")),
+        b"slotted secret"
+    );
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-p", "ops pass"])
+        .write_stdin(new[..2].join("\n"))
+        .assert()
+        .failure();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn combine_out_dir_restores_the_stored_filename() {
+    let dir = std::env::temp_dir().join(format!("safeparts-e2e-{}-metadata", std::process::id()));
+    let out_dir = dir.join("restored");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("wallet.dat");
+    std::fs::write(&input, b"\x00\x01wallet").unwrap();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args([
+            "split",
+            "-k",
+            "2",
+            "-n",
+            "3",
+            "--label",
+            "cold wallet",
+            "-i",
+        ])
+        .arg(&input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shares: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    assert_eq!(run_combine(None, &shares[..2], None), b"\x00\x01wallet");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--out-dir"])
+        .arg(&out_dir)
+        .write_stdin(shares[1..].join("\n"))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("wallet.dat"));
+    assert_eq!(
+        std::fs::read(out_dir.join("wallet.dat")).unwrap(),
+        b"\x00\x01wallet"
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--out-dir"])
+        .arg(&out_dir)
+        .write_stdin(shares[..2].join("\n"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("will not overwrite"));
+
+    let plain = run_split("base64", 2, 3, b"no metadata", None);
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "--out-dir"])
+        .arg(&out_dir)
+        .write_stdin(plain[..2].join("\n"))
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read(out_dir.join("recovered-secret")).unwrap(),
+        b"no metadata"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn padded_split_shares_hide_the_secret_length() {
+    let split = |padding: &str, input: &[u8]| -> Vec<String> {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["split", "-k", "2", "-n", "3", "--padding", padding])
+            .write_stdin(input)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let short = split("pow2", b"pin");
+    let long = split("pow2", &[b'x'; 40]);
+    assert_eq!(short[0].len(), long[0].len());
+    assert_eq!(run_combine(None, &short[1..], None), b"pin");
+    assert_eq!(run_combine(None, &long[..2], None), [b'x'; 40]);
+
+    let fixed = split("fixed:128", b"pin");
+    assert_eq!(run_combine(None, &fixed[..2], None), b"pin");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--padding", "fixed:2"])
+        .write_stdin(b"too long".as_slice())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid padding"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--padding", "pow3"])
+        .write_stdin(b"pin".as_slice())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown mode"));
+}
+
+#[test]
+fn compressed_split_shortens_mnemonic_shares() {
+    let input = b"AWS_ACCESS_KEY_ID=AKIAEXAMPLE\nAWS_REGION=eu-west-1\n".repeat(6);
+    let split = |extra: &[&str]| -> Vec<String> {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["split", "-k", "2", "-n", "3", "-e", "mnemo-words"])
+            .args(extra)
+            .write_stdin(input.clone())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let plain = split(&["-p", "pw"]);
+    let compressed = split(&["--compress", "-p", "pw"]);
+    let words = |share: &str| share.split_whitespace().count();
+    assert!(words(&compressed[0]) * 2 < words(&plain[0]));
+    assert_eq!(run_combine(None, &compressed[1..], Some("pw")), input);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--compress", "--large"])
+        .write_stdin(input.clone())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn compact_split_needs_fewer_mnemonic_words() {
+    let input = b"0123456789abcdef0123456789abcdef".to_vec();
+    let split = |extra: &[&str]| -> Vec<String> {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["split", "-k", "2", "-n", "3", "-e", "mnemo-words"])
+            .args(extra)
+            .write_stdin(input.clone())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let full = split(&["-p", "pw"]);
+    let compact = split(&["--compact", "-p", "pw"]);
+    let words = |share: &str| share.split_whitespace().count();
+    assert!(words(&compact[0]) + 20 < words(&full[0]));
+    assert_eq!(run_combine(None, &compact[..2], Some("pw")), input);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--compact", "--verifiable"])
+        .write_stdin(input)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn stealth_shares_hide_metadata_and_combine_when_pasted_together() {
+    let input = b"stealthy secret".to_vec();
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "3", "-n", "4", "--stealth", "-p", "pw"])
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shares: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(shares.len(), 4);
+
+    let inspected = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .arg("inspect")
+        .write_stdin(shares[0].clone() + "\n")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let inspected = String::from_utf8(inspected).unwrap();
+    assert!(inspected.ends_with(": stealth share, metadata hidden\n"));
+
+    assert_eq!(run_combine(None, &shares, Some("pw")), input);
+    assert_eq!(run_combine(None, &shares[1..], Some("pw")), input);
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-p", "pw"])
+        .write_stdin(shares[..2].join("\n") + "\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("stealth share(s) did not recover"));
+}
+
+#[test]
+fn slip39_official_vector_combines_and_round_trips_through_groups() {
+    let vector = [
+        "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+        "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+    ];
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["slip39", "combine", "-p", "TREZOR"])
+        .write_stdin(vector.join("\n") + "\n")
+        .assert()
+        .success()
+        .stdout(hex_bytes("b43ceb7e57a0ea8766221624d01b0864"));
+
+    let secret = b"0123456789abcdef".to_vec();
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["slip39", "split", "--group", "1-of-1", "--group", "2-of-3"])
+        .args([
+            "--group-threshold",
+            "2",
+            "--iteration-exponent",
+            "0",
+            "-p",
+            "pw",
+        ])
+        .write_stdin(secret.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let groups: Vec<Vec<&str>> = output
+        .split("\n\n")
+        .map(|group| group.lines().collect())
+        .collect();
+    assert_eq!(groups.iter().map(Vec::len).collect::<Vec<_>>(), [1, 3]);
+
+    let picked = [groups[0][0], groups[1][2], groups[1][0]].join("\n");
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["slip39", "combine", "-p", "pw"])
+        .write_stdin(picked + "\n")
+        .assert()
+        .success()
+        .stdout(secret);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["slip39", "combine", "-p", "pw"])
+        .write_stdin(groups[1][1].to_string() + "\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("group 2 has 1 of 2 shares"));
+}
+
+#[test]
+fn vault_unseal_keys_combine_and_split_round_trips() {
+    let fixture = "Unseal Key 1: sMgqTjjPYVMZTH01o+UHiytEma2kAj8B2HML6Q5FVrfa\n\
+                   Unseal Key 3: tVJ4QxT/U7gVZ9Sr/F3FMtxZqjp1CRk3CTX6ZVx+vZZf\n\
+                   Unseal Key 5: FTo/g0VUgcysP+GD2GYqnNjR2Y1Eave4BSqYEKECIo93\n";
+    let root_key = hex_bytes("3ca33472d7fbe17a0129389332e605fba06bcb80b2b6c027ae2d9593ea489e0c");
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-e", "vault"])
+        .write_stdin(fixture)
+        .assert()
+        .success()
+        .stdout(root_key.clone());
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-e", "vault", "-k", "2", "-n", "3"])
+        .write_stdin(root_key.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let keys: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(keys.len(), 3);
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-e", "vault"])
+        .write_stdin(keys[1..].join("\n") + "\n")
+        .assert()
+        .success()
+        .stdout(root_key);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-e", "vault", "-k", "2", "-n", "3", "-p", "pw"])
+        .write_stdin("secret")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("takes only -k, -n"));
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["inspect", "-e", "vault"])
+        .write_stdin(keys[0].clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only with split and combine"));
+}
+
+#[test]
+fn ssss_shares_are_detected_by_combine_and_round_trip_through_split() {
+    let fixture = "1-1c41ef496eccfbeba439714085df8437236298da8dd824\n\
+                   3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309\n\
+                   4-468de7d6eb36674c9cf008c8e8fc8c566537ad6301eb9e\n";
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine"])
+        .write_stdin(fixture)
+        .assert()
+        .success()
+        .stdout("my secret root password");
+
+    for extra in [&[][..], &["--no-diffusion"][..]] {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["split", "-e", "ssss", "-k", "2", "-n", "3"])
+            .args(extra)
+            .write_stdin("hunter2 but longer")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let shares = String::from_utf8(output).unwrap();
+        assert_eq!(shares.lines().count(), 3);
+        Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+            .args(["combine", "-e", "ssss"])
+            .args(extra)
+            .write_stdin(shares.lines().skip(1).collect::<Vec<_>>().join("\n"))
+            .assert()
+            .success()
+            .stdout("hunter2 but longer");
+    }
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-p", "pw"])
+        .write_stdin(fixture)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ssss shares take only"));
+}
+
+#[test]
+fn codex32_strings_are_detected_by_combine_and_round_trip_through_split() {
+    let fixture = "ms13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0t\n\
+                   ms13cashcacdefghjklmnpqrstuvwxyz023949xq35my48dr\n\
+                   ms13cashd0wsedstcdcts64cd7wvy4m90lm28w4ffupqs7rm\n";
+    let master = hex_bytes("ffeeddccbbaa99887766554433221100");
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine"])
+        .write_stdin(fixture)
+        .assert()
+        .success()
+        .stdout(master.clone());
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-e", "codex32", "-k", "2", "-n", "3"])
+        .write_stdin(master.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shares = String::from_utf8(output).unwrap();
+    assert_eq!(shares.lines().count(), 3);
+    assert!(shares.lines().all(|line| line.starts_with("ms12")));
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-e", "codex32"])
+        .write_stdin(shares.lines().skip(1).collect::<Vec<_>>().join("\n"))
+        .assert()
+        .success()
+        .stdout(master);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-e", "codex32", "-k", "2", "-n", "3"])
+        .write_stdin("too short")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("16 to 64 bytes"));
+}
+
+#[test]
+fn secrets_js_shares_are_detected_by_combine_and_round_trip_through_split() {
+    // Three of five secrets.js shares, threshold 3, of the UTF-8 hex of
+    // "my secret" at the default 8 bits.
+    let fixture = "8012461f89c3f0044f2614d95c6dc2b21f6\n\
+                   8042679559ec9fd9c53acc9989b456d214f\n\
+                   8050218ad02f6fdd9ccb4a47e38fa3465cd\n";
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine"])
+        .write_stdin(fixture)
+        .assert()
+        .success()
+        .stdout("my secret");
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args([
+            "split",
+            "-e",
+            "secrets-js",
+            "-k",
+            "2",
+            "-n",
+            "3",
+            "--field-bits",
+            "12",
+        ])
+        .write_stdin("field sizes")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shares = String::from_utf8(output).unwrap();
+    assert_eq!(shares.lines().count(), 3);
+    assert!(shares.lines().all(|line| line.starts_with("C00")));
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-e", "secrets-js"])
+        .write_stdin(shares.lines().skip(1).collect::<Vec<_>>().join("\n"))
+        .assert()
+        .success()
+        .stdout("field sizes");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["split", "-k", "2", "-n", "3", "--field-bits", "12"])
+        .write_stdin("field sizes")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only to -e secrets-js"));
+}
+
+#[test]
+fn combine_suggests_corrections_for_a_misspelled_mnemonic_word() {
+    let shares = run_split("mnemo-words", 2, 3, b"typo secret", None);
+    let mut words = shares[1].split_whitespace().collect::<Vec<_>>();
+    let original = words[2];
+    // A trailing letter makes a word that is neither listed nor a prefix.
+    let typo = format!("{original}q");
+    words[2] = &typo;
+    let input = [shares[0].clone(), words.join(" ")].join("\n\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("safeparts"))
+        .args(["combine", "-e", "mnemo-words"])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "share 2: word 3 \"{typo}\" is not in the word list; did you mean {original}"
+        )))
+        .stderr(predicate::str::contains(format!(
+            "({original} passes the checksum)"
+        )));
+
+    let prefixes = shares[..2]
+        .iter()
+        .map(|share| {
+            share
+                .split_whitespace()
+                .map(|word| word.get(..4).unwrap_or(word).to_uppercase())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        run_combine(Some("mnemo-words"), &[prefixes.join("\n\n")], None),
+        b"typo secret"
+    );
+}
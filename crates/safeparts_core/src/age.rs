@@ -0,0 +1,816 @@
+//! Per-custodian share wrapping for age X25519 recipients.
+//!
+//! A [`WrappedShare`] is an [age v1](https://age-encryption.org/v1) file whose
+//! plaintext is the [`SharePacket::encode_binary`] bytes of one share,
+//! encrypted to a single X25519 [`Recipient`]. Only the custodian holding the
+//! matching [`Identity`] can open it. The tests check both directions against
+//! the `age` crate: its files unwrap here, and wrapped shares decrypt with it
+//! to the binary packet. Wrapped shares travel as ASCII-armored text, which
+//! [`crate::encoding::parse_share_packets`] picks out of pasted input next to
+//! plain shares.
+//!
+//! Keys use the age text forms: `age1...` recipients and
+//! `AGE-SECRET-KEY-1...` identities, as written by `age-keygen`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::Sha256;
+use x25519_dalek::{X25519_BASEPOINT_BYTES, x25519};
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+use crate::packet::SharePacket;
+
+const VERSION_LINE: &str = "age-encryption.org/v1";
+const X25519_STANZA: &str = "X25519";
+const X25519_INFO: &[u8] = b"age-encryption.org/v1/X25519";
+const RECIPIENT_HRP: &str = "age";
+const IDENTITY_HRP: &str = "age-secret-key-";
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+const ARMOR_END: &str = "-----END AGE ENCRYPTED FILE-----";
+
+const KEY_LEN: usize = 32;
+const FILE_KEY_LEN: usize = 16;
+const PAYLOAD_NONCE_LEN: usize = 16;
+const CHUNK_LEN: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+/// Column width of stanza bodies and armored lines.
+const COLUMNS: usize = 64;
+
+/// Public X25519 key a share is wrapped to, written as `age1...`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Recipient([u8; KEY_LEN]);
+
+impl Recipient {
+    /// Parse an `age1...` recipient.
+    pub fn parse(text: &str) -> CoreResult<Self> {
+        let text = text.trim();
+        if text.starts_with("AGE-SECRET-KEY-") {
+            return Err(CoreError::InvalidKey(
+                "expected an age1... recipient, got a secret key".into(),
+            ));
+        }
+        bech32_decode(text, RECIPIENT_HRP).map(Self)
+    }
+
+    pub fn to_bytes(&self) -> [u8; KEY_LEN] {
+        self.0
+    }
+}
+
+impl fmt::Display for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bech32_encode(RECIPIENT_HRP, &self.0))
+    }
+}
+
+impl FromStr for Recipient {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Secret X25519 key that unwraps shares, written as `AGE-SECRET-KEY-1...`.
+#[derive(Clone)]
+pub struct Identity(Zeroizing<[u8; KEY_LEN]>);
+
+impl Identity {
+    /// Generate a fresh identity from the operating system RNG.
+    pub fn generate() -> Self {
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        OsRng.fill_bytes(key.as_mut());
+        Self(key)
+    }
+
+    /// Parse one `AGE-SECRET-KEY-1...` line.
+    pub fn parse(text: &str) -> CoreResult<Self> {
+        let text = text.trim();
+        if !text.starts_with("AGE-SECRET-KEY-") {
+            return Err(CoreError::InvalidKey(
+                "expected an AGE-SECRET-KEY-1... identity".into(),
+            ));
+        }
+        bech32_decode(text, IDENTITY_HRP).map(|key| Self(Zeroizing::new(key)))
+    }
+
+    /// The recipient that shares for this identity are wrapped to.
+    pub fn recipient(&self) -> Recipient {
+        Recipient(x25519(*self.0, X25519_BASEPOINT_BYTES))
+    }
+
+    /// The identity in its `AGE-SECRET-KEY-1...` form.
+    pub fn to_secret_string(&self) -> Zeroizing<String> {
+        Zeroizing::new(bech32_encode(IDENTITY_HRP, self.0.as_ref()).to_uppercase())
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Identity").field(&"<redacted>").finish()
+    }
+}
+
+/// Parse an identity file in the `age-keygen` layout.
+///
+/// Blank lines and `#` comments are skipped; every other line must be an
+/// identity. A file without any identity is an error.
+pub fn parse_identities(text: &str) -> CoreResult<Vec<Identity>> {
+    let identities = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Identity::parse)
+        .collect::<CoreResult<Vec<_>>>()?;
+    if identities.is_empty() {
+        return Err(CoreError::InvalidKey("no identities found".into()));
+    }
+    Ok(identities)
+}
+
+/// One share packet encrypted to one recipient, as the bytes of an age file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WrappedShare(Vec<u8>);
+
+impl WrappedShare {
+    /// Encrypt `packet` so that only the holder of `recipient`'s identity can
+    /// read it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use safeparts_core::age::{Identity, WrappedShare};
+    /// use safeparts_core::{split_secret, CoreResult};
+    ///
+    /// fn main() -> CoreResult<()> {
+    ///     let shares = split_secret(b"example", 2, 3, None)?;
+    ///     let custodian = Identity::generate();
+    ///     let wrapped = WrappedShare::wrap(&shares[0], &custodian.recipient())?;
+    ///     let armored = wrapped.to_armored();
+    ///
+    ///     let unwrapped = WrappedShare::from_armored(&armored)?.unwrap(&[custodian])?;
+    ///     assert_eq!(unwrapped, Some(shares[0].clone()));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn wrap(packet: &SharePacket, recipient: &Recipient) -> CoreResult<Self> {
+        let mut file_key = Zeroizing::new([0u8; FILE_KEY_LEN]);
+        OsRng.fill_bytes(file_key.as_mut());
+
+        let mut ephemeral = Zeroizing::new([0u8; KEY_LEN]);
+        OsRng.fill_bytes(ephemeral.as_mut());
+        let ephemeral_share = x25519(*ephemeral, X25519_BASEPOINT_BYTES);
+        let shared = Zeroizing::new(x25519(*ephemeral, recipient.0));
+        if shared.iter().all(|&b| b == 0) {
+            return Err(CoreError::InvalidKey("low-order recipient".into()));
+        }
+
+        let wrap_key = x25519_wrap_key(&shared, &ephemeral_share, &recipient.0);
+        let body = ChaCha20Poly1305::new(Key::from_slice(wrap_key.as_ref()))
+            .encrypt(&Nonce::default(), file_key.as_slice())
+            .map_err(|_| CoreError::EncryptFailed)?;
+
+        let mut header = format!(
+            "{VERSION_LINE}\n-> {X25519_STANZA} {}\n{}\n---",
+            STANDARD_NO_PAD.encode(ephemeral_share),
+            stanza_body(&body)
+        );
+        let mut mac = header_hmac(&file_key);
+        mac.update(header.as_bytes());
+        header.push(' ');
+        header.push_str(&STANDARD_NO_PAD.encode(mac.finalize().into_bytes()));
+        header.push('\n');
+
+        let mut nonce = [0u8; PAYLOAD_NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let payload_key = hkdf_sha256(file_key.as_ref(), &nonce, b"payload");
+        let plaintext = Zeroizing::new(packet.encode_binary()?);
+
+        let mut out = header.into_bytes();
+        out.extend_from_slice(&nonce);
+        out.extend(stream_seal(&payload_key, &plaintext)?);
+        Ok(Self(out))
+    }
+
+    /// Decrypt with the first identity the share was wrapped to.
+    ///
+    /// Returns `Ok(None)` when none of `identities` matches, so callers can
+    /// skip shares meant for other custodians. A matching identity with a
+    /// damaged header or payload is an error.
+    pub fn unwrap(&self, identities: &[Identity]) -> CoreResult<Option<SharePacket>> {
+        let header = Header::parse(&self.0)?;
+
+        let Some(file_key) = header
+            .stanzas
+            .iter()
+            .filter(|stanza| stanza.kind == X25519_STANZA)
+            .find_map(|stanza| unwrap_x25519(stanza, identities).transpose())
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+
+        let mut mac = header_hmac(&file_key);
+        mac.update(&self.0[..header.mac_input_len]);
+        mac.verify_slice(&header.mac)
+            .map_err(|_| CoreError::DecryptFailed)?;
+
+        let payload = &self.0[header.payload_start..];
+        if payload.len() < PAYLOAD_NONCE_LEN + TAG_LEN {
+            return Err(wrapped_error("payload is truncated"));
+        }
+        let (nonce, ciphertext) = payload.split_at(PAYLOAD_NONCE_LEN);
+        let payload_key = hkdf_sha256(file_key.as_ref(), nonce, b"payload");
+        let plaintext = stream_open(&payload_key, ciphertext)?;
+        SharePacket::decode_binary(&plaintext).map(Some)
+    }
+
+    /// Accept the bytes of an age file, checking that its header is readable.
+    pub fn from_bytes(bytes: Vec<u8>) -> CoreResult<Self> {
+        Header::parse(&bytes)?;
+        Ok(Self(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The share as an `-----BEGIN AGE ENCRYPTED FILE-----` block.
+    pub fn to_armored(&self) -> String {
+        let encoded = STANDARD.encode(&self.0);
+        let mut out = String::with_capacity(encoded.len() + 100);
+        out.push_str(ARMOR_BEGIN);
+        out.push('\n');
+        for line in encoded.as_bytes().chunks(COLUMNS) {
+            out.push_str(std::str::from_utf8(line).unwrap_or_default());
+            out.push('\n');
+        }
+        out.push_str(ARMOR_END);
+        out.push('\n');
+        out
+    }
+
+    /// Parse one armored block written by [`WrappedShare::to_armored`] or
+    /// `age --armor`.
+    pub fn from_armored(text: &str) -> CoreResult<Self> {
+        let inner = text
+            .trim()
+            .strip_prefix(ARMOR_BEGIN)
+            .and_then(|rest| rest.strip_suffix(ARMOR_END))
+            .ok_or_else(|| wrapped_error("missing armor lines"))?;
+        let encoded: String = inner.split_whitespace().collect();
+        let bytes = STANDARD
+            .decode(encoded)
+            .map_err(|e| wrapped_error(&e.to_string()))?;
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Separate armored wrapped shares from the rest of pasted input.
+///
+/// Returns the wrapped shares in input order and the remaining text, in which
+/// each armored block is replaced by a blank line.
+pub fn extract_armored(input: &str) -> CoreResult<(Vec<WrappedShare>, String)> {
+    let mut wrapped = Vec::new();
+    let mut rest = String::with_capacity(input.len());
+    let mut remaining = input;
+    while let Some(start) = remaining.find(ARMOR_BEGIN) {
+        let end = remaining[start..]
+            .find(ARMOR_END)
+            .map(|offset| start + offset + ARMOR_END.len())
+            .ok_or_else(|| wrapped_error("armored block is not terminated"))?;
+        wrapped.push(WrappedShare::from_armored(&remaining[start..end])?);
+        rest.push_str(&remaining[..start]);
+        rest.push_str("\n\n");
+        remaining = &remaining[end..];
+    }
+    rest.push_str(remaining);
+    Ok((wrapped, rest))
+}
+
+struct Stanza {
+    kind: String,
+    args: Vec<String>,
+    body: Vec<u8>,
+}
+
+struct Header {
+    stanzas: Vec<Stanza>,
+    mac: Vec<u8>,
+    /// Header bytes covered by the MAC, through the `---` marker.
+    mac_input_len: usize,
+    payload_start: usize,
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> CoreResult<Self> {
+        let mut pos = 0;
+        let mut next_line = || -> CoreResult<(usize, &str)> {
+            let start = pos;
+            let len = bytes[start..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .ok_or_else(|| wrapped_error("header is truncated"))?;
+            pos = start + len + 1;
+            let line = std::str::from_utf8(&bytes[start..start + len])
+                .map_err(|_| wrapped_error("header is not text"))?;
+            Ok((start, line))
+        };
+
+        if next_line()?.1 != VERSION_LINE {
+            return Err(wrapped_error("not an age v1 file"));
+        }
+
+        let mut stanzas = Vec::new();
+        loop {
+            let (start, line) = next_line()?;
+            if let Some(mac) = line.strip_prefix("--- ") {
+                let mac = decode_b64(mac)?;
+                if mac.len() != 32 {
+                    return Err(wrapped_error("header MAC has the wrong length"));
+                }
+                if stanzas.is_empty() {
+                    return Err(wrapped_error("header has no recipients"));
+                }
+                return Ok(Self {
+                    stanzas,
+                    mac,
+                    mac_input_len: start + 3,
+                    payload_start: start + line.len() + 1,
+                });
+            }
+
+            let mut fields = line
+                .strip_prefix("-> ")
+                .ok_or_else(|| wrapped_error("malformed header line"))?
+                .split(' ');
+            let kind = fields
+                .next()
+                .filter(|kind| !kind.is_empty())
+                .ok_or_else(|| wrapped_error("stanza without a type"))?
+                .to_string();
+            let args = fields.map(str::to_string).collect();
+
+            let mut encoded = String::new();
+            loop {
+                let (_, body_line) = next_line()?;
+                if body_line.len() > COLUMNS {
+                    return Err(wrapped_error("stanza body line is too long"));
+                }
+                encoded.push_str(body_line);
+                if body_line.len() < COLUMNS {
+                    break;
+                }
+            }
+            stanzas.push(Stanza {
+                kind,
+                args,
+                body: decode_b64(&encoded)?,
+            });
+        }
+    }
+}
+
+fn unwrap_x25519(
+    stanza: &Stanza,
+    identities: &[Identity],
+) -> CoreResult<Option<Zeroizing<[u8; FILE_KEY_LEN]>>> {
+    let [ephemeral] = stanza.args.as_slice() else {
+        return Err(wrapped_error("X25519 stanza needs one argument"));
+    };
+    let ephemeral_share: [u8; KEY_LEN] = decode_b64(ephemeral)?
+        .try_into()
+        .map_err(|_| wrapped_error("X25519 share has the wrong length"))?;
+    if stanza.body.len() != FILE_KEY_LEN + TAG_LEN {
+        return Err(wrapped_error("X25519 body has the wrong length"));
+    }
+
+    for identity in identities {
+        let shared = Zeroizing::new(x25519(*identity.0, ephemeral_share));
+        if shared.iter().all(|&b| b == 0) {
+            continue;
+        }
+        let wrap_key = x25519_wrap_key(&shared, &ephemeral_share, &identity.recipient().0);
+        if let Ok(file_key) = ChaCha20Poly1305::new(Key::from_slice(wrap_key.as_ref()))
+            .decrypt(&Nonce::default(), stanza.body.as_slice())
+        {
+            let file_key = Zeroizing::new(file_key);
+            let mut out = Zeroizing::new([0u8; FILE_KEY_LEN]);
+            out.copy_from_slice(&file_key);
+            return Ok(Some(out));
+        }
+    }
+    Ok(None)
+}
+
+fn x25519_wrap_key(
+    shared: &[u8; KEY_LEN],
+    ephemeral_share: &[u8; KEY_LEN],
+    recipient: &[u8; KEY_LEN],
+) -> Zeroizing<[u8; KEY_LEN]> {
+    let mut salt = [0u8; 2 * KEY_LEN];
+    salt[..KEY_LEN].copy_from_slice(ephemeral_share);
+    salt[KEY_LEN..].copy_from_slice(recipient);
+    hkdf_sha256(shared, &salt, X25519_INFO)
+}
+
+fn header_hmac(file_key: &[u8; FILE_KEY_LEN]) -> Hmac<Sha256> {
+    let key = hkdf_sha256(file_key, &[], b"header");
+    <Hmac<Sha256> as Mac>::new_from_slice(key.as_ref()).expect("HMAC accepts keys of any length")
+}
+
+fn hkdf_sha256(ikm: &[u8], salt: &[u8], info: &[u8]) -> Zeroizing<[u8; KEY_LEN]> {
+    let mut out = Zeroizing::new([0u8; KEY_LEN]);
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(info, out.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA-256 output length");
+    out
+}
+
+/// Nonce of the age STREAM construction: an 11-byte big-endian chunk counter
+/// followed by a last-chunk flag.
+fn stream_nonce(counter: u64, last: bool) -> Nonce {
+    let mut nonce = Nonce::default();
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = u8::from(last);
+    nonce
+}
+
+fn stream_seal(key: &[u8; KEY_LEN], plaintext: &[u8]) -> CoreResult<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let chunks: Vec<&[u8]> = if plaintext.is_empty() {
+        vec![&[]]
+    } else {
+        plaintext.chunks(CHUNK_LEN).collect()
+    };
+    let mut out = Vec::with_capacity(plaintext.len() + chunks.len() * TAG_LEN);
+    for (counter, chunk) in (0u64..).zip(&chunks) {
+        let last = counter as usize + 1 == chunks.len();
+        let sealed = cipher
+            .encrypt(&stream_nonce(counter, last), *chunk)
+            .map_err(|_| CoreError::EncryptFailed)?;
+        out.extend(sealed);
+    }
+    Ok(out)
+}
+
+fn stream_open(key: &[u8; KEY_LEN], ciphertext: &[u8]) -> CoreResult<Zeroizing<Vec<u8>>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let mut out = Zeroizing::new(Vec::with_capacity(ciphertext.len()));
+    let mut remaining = ciphertext;
+    for counter in 0u64.. {
+        let last = remaining.len() <= CHUNK_LEN + TAG_LEN;
+        let (chunk, rest) = remaining.split_at(remaining.len().min(CHUNK_LEN + TAG_LEN));
+        let opened = Zeroizing::new(
+            cipher
+                .decrypt(&stream_nonce(counter, last), chunk)
+                .map_err(|_| CoreError::DecryptFailed)?,
+        );
+        if last && opened.is_empty() && counter > 0 {
+            return Err(wrapped_error("empty final chunk"));
+        }
+        out.extend_from_slice(&opened);
+        if last {
+            break;
+        }
+        remaining = rest;
+    }
+    Ok(out)
+}
+
+fn stanza_body(body: &[u8]) -> String {
+    let encoded = STANDARD_NO_PAD.encode(body);
+    let mut lines: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(COLUMNS)
+        .map(|line| std::str::from_utf8(line).unwrap_or_default())
+        .collect();
+    // A full final line would read as a continuation, so end with an empty one.
+    if encoded.len().is_multiple_of(COLUMNS) {
+        lines.push("");
+    }
+    lines.join("\n")
+}
+
+fn decode_b64(text: &str) -> CoreResult<Vec<u8>> {
+    STANDARD_NO_PAD
+        .decode(text)
+        .map_err(|e| wrapped_error(&e.to_string()))
+}
+
+fn wrapped_error(message: &str) -> CoreError {
+    CoreError::Encoding(format!("wrapped share: {message}"))
+}
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn bech32_polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    const GENERATORS: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut checksum = 1u32;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x01ff_ffff) << 5) ^ u32::from(value);
+        for (bit, generator) in GENERATORS.iter().enumerate() {
+            if (top >> bit) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    let mut out: Vec<u8> = bytes.iter().map(|b| b >> 5).collect();
+    out.push(0);
+    out.extend(bytes.iter().map(|b| b & 31));
+    out
+}
+
+/// Regroup bits, as bech32 does between bytes and 5-bit words.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &value in data {
+        acc = (acc << from) | u32::from(value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+fn bech32_encode(hrp: &str, data: &[u8]) -> String {
+    let words = convert_bits(data, 8, 5, true).unwrap_or_default();
+    let mut values = bech32_hrp_expand(hrp);
+    values.extend(&words);
+    values.extend([0; 6]);
+    let checksum = bech32_polymod(values) ^ 1;
+
+    let mut out = format!("{hrp}1");
+    let checksum_words = (0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8);
+    for word in words.iter().copied().chain(checksum_words) {
+        out.push(char::from(BECH32_CHARSET[usize::from(word)]));
+    }
+    out
+}
+
+/// Decode a bech32 key with the expected human-readable part. Like age, this
+/// has no 90-character limit.
+fn bech32_decode(text: &str, hrp: &str) -> CoreResult<[u8; KEY_LEN]> {
+    let invalid = |why: &str| CoreError::InvalidKey(why.into());
+    if text.chars().any(|c| c.is_ascii_lowercase()) && text.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return Err(invalid("mixed-case key"));
+    }
+    let lower = text.to_ascii_lowercase();
+    let (found_hrp, data) = lower
+        .rsplit_once('1')
+        .ok_or_else(|| invalid("missing bech32 separator"))?;
+    if found_hrp != hrp {
+        return Err(invalid("unexpected key type"));
+    }
+    if data.len() < 6 {
+        return Err(invalid("key is too short"));
+    }
+    let words = data
+        .bytes()
+        .map(|c| {
+            BECH32_CHARSET
+                .iter()
+                .position(|&d| d == c)
+                .map(|i| i as u8)
+                .ok_or_else(|| invalid("invalid bech32 character"))
+        })
+        .collect::<CoreResult<Vec<_>>>()?;
+
+    let mut values = bech32_hrp_expand(hrp);
+    values.extend(&words);
+    if bech32_polymod(values) != 1 {
+        return Err(invalid("bad checksum"));
+    }
+
+    let bytes = Zeroizing::new(
+        convert_bits(&words[..words.len() - 6], 5, 8, false)
+            .ok_or_else(|| invalid("invalid bech32 padding"))?,
+    );
+    bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| invalid("key has the wrong length"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::split_secret;
+
+    /// Recipient and identity pair from the age test suite.
+    const TEST_RECIPIENT: &str = "age1zvkyg2lqzraa2lnjvqej32nkuu0ues2s82hzrye869xeexvn73equnujwj";
+    const TEST_IDENTITY: &str =
+        "AGE-SECRET-KEY-1GFPYYSJZGFPYYSJZGFPYYSJZGFPYYSJZGFPYYSJZGFPYYSJZGFPQ4EGAEX";
+
+    /// The binary share packet both fixtures below carry.
+    const FIXTURE_PACKET: &str = "534d4e310300020301808be708faf15cfb9644f9a606fe62970000002b514e395980a3e5b45a1a7d3d82be46784a916149b85bb4408b1b5ea78d46ee0baed9dbd5c73adea2915dbe";
+
+    /// `FIXTURE_PACKET` encrypted to `TEST_RECIPIENT` by the `age` crate
+    /// 0.11.2, the library behind `rage`, and armored. Its header also has the
+    /// grease stanza `age` adds to every file.
+    const FROM_AGE: &str = concat!(
+        "-----BEGIN AGE ENCRYPTED FILE-----\n",
+        "YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgyNTUxOSA3MHN0UWt3VnlnQmZRL1c4\n",
+        "WVNpaHJkdTZMY1dQazIyMUpvaStJOFBhU3lNCkZCY0xVb2oraHIzQUFKZUw2ZjFj\n",
+        "cnRmQ05Oek02VXB3UXNvb2R5clI3bGsKLT4gVHgtZ3JlYXNlIFdXeGZAKXkwCnBn\n",
+        "aytyWHA2VzJ4WEpBK1pKZ0xvdFRZczZkY0Zvem4yM1hqSVRPWXR4REZBQnJWV2pq\n",
+        "RE5ONHpDSmp5QytEQTMKNXROSUx1Nmw0NXpINGVOSHpWRUlsV3FrCi0tLSBaeHU0\n",
+        "Z0N4bmRZbXBaZUVFbHNGVGJXT25DTkxOYzBYNUZYVG5LVUNRTWFBCkUufznh6eQi\n",
+        "ZRessggzpzdTQN8CtO3bb1Ve1VAaYErdR+YTq8F/cc+32/bDN66zIS7hzYU5ADVX\n",
+        "9gPMoKqHWnc09LOH25gGOpxZqbSjKDFRW42wtWanFzYX91dSqv4kOGgZOGTyQm/i\n",
+        "-----END AGE ENCRYPTED FILE-----\n",
+    );
+
+    /// `FIXTURE_PACKET` wrapped to `TEST_RECIPIENT` by [`WrappedShare::wrap`].
+    const TO_AGE: &str = concat!(
+        "-----BEGIN AGE ENCRYPTED FILE-----\n",
+        "YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgyNTUxOSBDV0s5MUNmdnMyNVIrRFZD\n",
+        "SG5BSTJ1U1pxVURkWmNWTzBMMGRFSmljNlQ0CjJwTHhGbXZLcGo5N3RPQmh4cCs1\n",
+        "ZUIzM3FibVM2WnUwNEV4RDVhRlh5RzAKLS0tIFg3TzdSK2VPejIxR1ZFcWtIRXB0\n",
+        "d0NsdWRwNTZ1QU5mMVlhRExSbDRtaVkKl5Tby0xHKb0mHhGrne5S0+7AQ9TDlU+/\n",
+        "VDJn+qyIO9e76XESLnKstomhYO4MWIJSNC6e/2GWR5EzsupkZIj9P+cDEo/Uh/qm\n",
+        "y/TJpPbD/2IJ4YUq+naXqNXJ9oTg1b4Y+y962EkUzuc=\n",
+        "-----END AGE ENCRYPTED FILE-----\n",
+    );
+
+    #[test]
+    fn keys_round_trip_and_match_age_keygen() {
+        let identity = Identity::parse(TEST_IDENTITY).unwrap();
+        assert_eq!(identity.to_secret_string().as_str(), TEST_IDENTITY);
+        assert_eq!(identity.recipient().to_string(), TEST_RECIPIENT);
+        assert_eq!(
+            Recipient::parse(TEST_RECIPIENT).unwrap(),
+            identity.recipient()
+        );
+
+        let fresh = Identity::generate();
+        let reparsed = Identity::parse(&fresh.to_secret_string()).unwrap();
+        assert_eq!(reparsed.recipient(), fresh.recipient());
+    }
+
+    #[test]
+    fn bad_keys_are_typed_errors() {
+        let mut typo = TEST_RECIPIENT.to_string();
+        typo.replace_range(10..11, "q");
+        for text in [typo.as_str(), TEST_IDENTITY, "age1", "AGE1ZVKYG2lqz"] {
+            assert!(matches!(
+                Recipient::parse(text),
+                Err(CoreError::InvalidKey(_))
+            ));
+        }
+        assert!(matches!(
+            Identity::parse(TEST_RECIPIENT),
+            Err(CoreError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn identity_files_skip_comments() {
+        let file = format!("# created: today\n# public key: {TEST_RECIPIENT}\n\n{TEST_IDENTITY}\n");
+        let identities = parse_identities(&file).unwrap();
+        assert_eq!(identities.len(), 1);
+        assert!(matches!(
+            parse_identities("# nothing here\n"),
+            Err(CoreError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn wrapped_share_opens_only_for_its_recipient() {
+        let shares = split_secret(b"wrapped secret", 2, 3, None).unwrap();
+        let alice = Identity::generate();
+        let bob = Identity::generate();
+
+        let wrapped = WrappedShare::wrap(&shares[1], &alice.recipient()).unwrap();
+        assert!(
+            wrapped
+                .as_bytes()
+                .starts_with(b"age-encryption.org/v1\n-> X25519 ")
+        );
+
+        let armored = wrapped.to_armored();
+        let parsed = WrappedShare::from_armored(&armored).unwrap();
+        assert_eq!(parsed, wrapped);
+
+        assert_eq!(parsed.unwrap(std::slice::from_ref(&bob)).unwrap(), None);
+        assert_eq!(
+            parsed.unwrap(&[bob, alice]).unwrap(),
+            Some(shares[1].clone())
+        );
+    }
+
+    #[test]
+    fn tampered_wrapped_share_fails() {
+        let shares = split_secret(b"wrapped secret", 2, 2, None).unwrap();
+        let identity = Identity::generate();
+        let wrapped = WrappedShare::wrap(&shares[0], &identity.recipient()).unwrap();
+
+        let mut payload = wrapped.as_bytes().to_vec();
+        let last = payload.len() - 1;
+        payload[last] ^= 1;
+        let result = WrappedShare::from_bytes(payload)
+            .unwrap()
+            .unwrap(std::slice::from_ref(&identity));
+        assert!(matches!(result, Err(CoreError::DecryptFailed)));
+
+        let header =
+            String::from_utf8_lossy(wrapped.as_bytes()).replacen("X25519", "X25519 extra", 1);
+        let result = WrappedShare::from_bytes(header.into_bytes());
+        assert!(result.is_err() || result.unwrap().unwrap(&[identity]).is_err());
+    }
+
+    #[test]
+    fn extract_armored_separates_plain_text() {
+        let shares = split_secret(b"mixed input", 2, 3, None).unwrap();
+        let identity = Identity::generate();
+        let wrapped = WrappedShare::wrap(&shares[0], &identity.recipient()).unwrap();
+        let input = format!("plain-a\n{}\nplain-b\n", wrapped.to_armored());
+
+        let (found, rest) = extract_armored(&input).unwrap();
+        assert_eq!(found, vec![wrapped]);
+        assert_eq!(
+            rest.split_whitespace().collect::<Vec<_>>(),
+            ["plain-a", "plain-b"]
+        );
+
+        let truncated = &input[..input.find(ARMOR_END).unwrap()];
+        assert!(extract_armored(truncated).is_err());
+    }
+
+    #[test]
+    fn file_from_the_age_crate_unwraps() {
+        let identity = Identity::parse(TEST_IDENTITY).unwrap();
+        let packet = WrappedShare::from_armored(FROM_AGE)
+            .unwrap()
+            .unwrap(&[identity])
+            .unwrap()
+            .unwrap();
+        assert_eq!(hex::encode(packet.encode_binary().unwrap()), FIXTURE_PACKET);
+    }
+
+    #[test]
+    fn wrapped_shares_decrypt_with_the_age_crate() {
+        let packet = SharePacket::decode_binary(&hex::decode(FIXTURE_PACKET).unwrap()).unwrap();
+        let fresh =
+            WrappedShare::wrap(&packet, &Recipient::parse(TEST_RECIPIENT).unwrap()).unwrap();
+        let identity = ::age::x25519::Identity::from_str(TEST_IDENTITY).unwrap();
+
+        for wrapped in [WrappedShare::from_armored(TO_AGE).unwrap(), fresh] {
+            let mut plaintext = Vec::new();
+            ::age::Decryptor::new(wrapped.as_bytes())
+                .unwrap()
+                .decrypt(std::iter::once(&identity as &dyn ::age::Identity))
+                .unwrap()
+                .read_to_end(&mut plaintext)
+                .unwrap();
+            assert_eq!(hex::encode(plaintext), FIXTURE_PACKET);
+        }
+    }
+
+    #[test]
+    fn stream_handles_chunk_boundaries() {
+        let key = [7u8; KEY_LEN];
+        for len in [0, 1, CHUNK_LEN, CHUNK_LEN + 1, 2 * CHUNK_LEN] {
+            let plaintext = vec![0xa5; len];
+            let sealed = stream_seal(&key, &plaintext).unwrap();
+            assert_eq!(stream_open(&key, &sealed).unwrap().as_slice(), plaintext);
+        }
+    }
+}
@@ -34,18 +34,17 @@ pub fn decode_packet(s: &str, encoding: Encoding) -> CoreResult<SharePacket> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sss::SetId;
+    use crate::sss::{RawShare, SetId};
 
     #[test]
     fn base64url_round_trip() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([7u8; 16]),
             k: 3,
             n: 5,
             x: 1,
-            payload: vec![1, 2, 3, 4],
-            crypto_params: None,
-        };
+            y: vec![1, 2, 3, 4],
+        });
 
         let enc = encode_packet(&pkt, Encoding::Base64url).unwrap();
         let dec = decode_packet(&enc, Encoding::Base64url).unwrap();
@@ -54,14 +53,13 @@ mod tests {
 
     #[test]
     fn base58check_round_trip() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([9u8; 16]),
             k: 2,
             n: 3,
             x: 3,
-            payload: vec![0, 255, 4, 9],
-            crypto_params: None,
-        };
+            y: vec![0, 255, 4, 9],
+        });
 
         let enc = encode_packet(&pkt, Encoding::Base58check).unwrap();
         let dec = decode_packet(&enc, Encoding::Base58check).unwrap();
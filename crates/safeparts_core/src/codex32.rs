@@ -0,0 +1,572 @@
+//! Codex32 (BIP-93) shares.
+//!
+//! A codex32 string is `ms1`, a threshold digit, a four-character identifier,
+//! a share index, the secret's bits in the bech32 alphabet, and a BCH
+//! checksum. Shares are split character by character over GF(32), so a set
+//! can be checked, split, and recovered by hand with paper volvelles. The
+//! share with index `s` is the secret itself; threshold `0` marks a secret
+//! that is not shared.
+//!
+//! The field is unrelated to [`crate::gf256`], so codex32 shares are not
+//! [`crate::packet::SharePacket`]s. Convert a set by passing [`combine`]'s
+//! secret to `split_secret`, or `combine_shares`' secret to [`split`].
+//!
+//! Secrets are 16 to 64 bytes. Strings up to 93 data characters use the
+//! 13-character checksum; longer ones use the 15-character long checksum.
+
+use std::fmt;
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+
+/// Human-readable part of every codex32 string.
+pub const HRP: &str = "ms";
+
+/// Smallest secret, in bytes.
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// Largest secret, in bytes.
+pub const MAX_SECRET_LEN: usize = 64;
+
+/// Largest threshold; it is written as one digit.
+pub const MAX_THRESHOLD: u8 = 9;
+
+/// Largest number of shares: every index except `s`.
+pub const MAX_SHARE_COUNT: u8 = 31;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Share indexes in the order BIP-93 hands them out, skipping `s`.
+const SHARE_INDEXES: &[u8; 31] = b"acdefghjklmnpqrtuvwxyz023456789";
+
+/// The index of the secret share, `s`.
+const SECRET_INDEX: u8 = 16;
+
+const HEADER_LEN: usize = 6;
+const MAX_SHORT_DATA_LEN: usize = 93;
+const MIN_LONG_DATA_LEN: usize = 96;
+const MAX_LONG_DATA_LEN: usize = 124;
+
+struct Checksum {
+    len: usize,
+    generator: [u128; 5],
+    target: u128,
+}
+
+const SHORT_CHECKSUM: Checksum = Checksum {
+    len: 13,
+    generator: [
+        0x19dc500ce73fde210,
+        0x1bfae00def77fe529,
+        0x1fbd920fffe7bee52,
+        0x1739640bdeee3fdad,
+        0x07729a039cfc75f5a,
+    ],
+    target: 0x10ce0795c2fd1e62a,
+};
+
+const LONG_CHECKSUM: Checksum = Checksum {
+    len: 15,
+    generator: [
+        0x3d59d273535ea62d897,
+        0x7a9becb6361c6c51507,
+        0x543f9b7e6c38d8a2a0e,
+        0x0c577eaeccf1990d13c,
+        0x1887f74f8dc71b10651,
+    ],
+    target: 0x43381e570bf4798ab26,
+};
+
+impl Checksum {
+    fn for_data_len(len: usize) -> Option<&'static Checksum> {
+        match len {
+            0..=MAX_SHORT_DATA_LEN => Some(&SHORT_CHECKSUM),
+            MIN_LONG_DATA_LEN..=MAX_LONG_DATA_LEN => Some(&LONG_CHECKSUM),
+            _ => None,
+        }
+    }
+
+    fn polymod(&self, values: impl IntoIterator<Item = u8>) -> u128 {
+        let shift = 5 * (self.len - 1);
+        let mut residue: u128 = 0x23181b3;
+        for value in values {
+            let top = residue >> shift;
+            residue = ((residue & ((1 << shift) - 1)) << 5) ^ u128::from(value);
+            for (i, generator) in self.generator.iter().enumerate() {
+                if (top >> i) & 1 == 1 {
+                    residue ^= generator;
+                }
+            }
+        }
+        residue
+    }
+
+    fn verify(&self, values: &[u8]) -> bool {
+        self.polymod(values.iter().copied()) == self.target
+    }
+
+    fn create(&self, values: &[u8]) -> Vec<u8> {
+        let residue = self.polymod(
+            values
+                .iter()
+                .copied()
+                .chain(std::iter::repeat_n(0, self.len)),
+        ) ^ self.target;
+        (0..self.len)
+            .map(|i| ((residue >> (5 * (self.len - 1 - i))) & 31) as u8)
+            .collect()
+    }
+}
+
+/// One codex32 string.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    /// The data part after `ms1` as 5-bit values, checksum included.
+    values: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    /// Parse a codex32 string in all lower or all upper case.
+    pub fn parse(text: &str) -> CoreResult<Self> {
+        let text = text.trim();
+        if text.bytes().any(|b| b.is_ascii_lowercase())
+            && text.bytes().any(|b| b.is_ascii_uppercase())
+        {
+            return Err(malformed("mixed case"));
+        }
+        let text = Zeroizing::new(text.to_ascii_lowercase());
+        let data = text
+            .strip_prefix(HRP)
+            .and_then(|rest| rest.strip_prefix('1'))
+            .ok_or_else(|| malformed("expected a string starting with ms1"))?;
+        let values = Zeroizing::new(
+            data.bytes()
+                .map(|c| char_value(c).ok_or_else(|| malformed("character outside bech32")))
+                .collect::<CoreResult<Vec<_>>>()?,
+        );
+        let checksum = Checksum::for_data_len(values.len())
+            .filter(|checksum| values.len() > HEADER_LEN + checksum.len)
+            .ok_or_else(|| malformed("invalid length"))?;
+        if !checksum.verify(&values) {
+            return Err(malformed("bad checksum"));
+        }
+
+        let share = Self { values };
+        let payload_len = share.payload_values().len();
+        if payload_len * 5 < MIN_SECRET_LEN * 8 || payload_len * 5 % 8 > 4 {
+            return Err(malformed("invalid secret length"));
+        }
+        match share.threshold() {
+            0 if !share.is_secret() => return Err(malformed("threshold 0 needs share index s")),
+            0 | 2..=MAX_THRESHOLD => {}
+            _ => return Err(malformed("threshold must be 0 or 2 to 9")),
+        }
+        Ok(share)
+    }
+
+    /// The threshold, or `0` for a secret that is not shared.
+    pub fn threshold(&self) -> u8 {
+        threshold_of(self.values[0])
+    }
+
+    /// The four-character identifier shared by every share of a set.
+    pub fn identifier(&self) -> String {
+        self.values[1..5]
+            .iter()
+            .map(|&v| CHARSET[v as usize] as char)
+            .collect()
+    }
+
+    /// The share index character; `s` is the secret.
+    pub fn index(&self) -> char {
+        CHARSET[self.values[5] as usize] as char
+    }
+
+    /// The share index as a GF(32) element.
+    pub fn index_value(&self) -> u8 {
+        self.values[5]
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.values[5] == SECRET_INDEX
+    }
+
+    /// The payload bytes: the secret itself for index `s`.
+    pub fn payload(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::new());
+        let mut acc: u32 = 0;
+        let mut bits = 0;
+        for &value in self.payload_values() {
+            acc = ((acc << 5) | u32::from(value)) & 0xfff;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                bytes.push((acc >> bits) as u8);
+            }
+        }
+        bytes
+    }
+
+    fn checksum_len(&self) -> usize {
+        Checksum::for_data_len(self.values.len()).map_or(0, |checksum| checksum.len)
+    }
+
+    fn payload_values(&self) -> &[u8] {
+        &self.values[HEADER_LEN..self.values.len() - self.checksum_len()]
+    }
+
+    /// Build a share from its header and payload values, adding the checksum.
+    fn with_checksum(mut values: Vec<u8>) -> Self {
+        let checksum =
+            Checksum::for_data_len(values.len() + SHORT_CHECKSUM.len).unwrap_or(&LONG_CHECKSUM);
+        let tail = checksum.create(&values);
+        values.extend_from_slice(&tail);
+        Self {
+            values: Zeroizing::new(values),
+        }
+    }
+}
+
+/// Lower-case codex32 text.
+impl fmt::Display for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(HRP)?;
+        f.write_str("1")?;
+        for &value in self.values.iter() {
+            write!(f, "{}", CHARSET[value as usize] as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shows the header only.
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("threshold", &self.threshold())
+            .field("identifier", &self.identifier())
+            .field("index", &self.index())
+            .finish_non_exhaustive()
+    }
+}
+
+/// True when every non-empty line of `input` is a codex32 string.
+pub fn looks_like(input: &str) -> bool {
+    let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+    lines.clone().next().is_some() && lines.all(|line| Share::parse(line).is_ok())
+}
+
+/// Parse every codex32 string in `input`, one per line.
+pub fn parse_shares(input: &str) -> CoreResult<Vec<Share>> {
+    let shares = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(Share::parse)
+        .collect::<CoreResult<Vec<_>>>()?;
+    if shares.is_empty() {
+        return Err(CoreError::EmptyShareInput);
+    }
+    Ok(shares)
+}
+
+/// A random four-character identifier.
+pub fn random_identifier() -> String {
+    let mut bytes = [0u8; 4];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+        .iter()
+        .map(|&b| CHARSET[(b & 31) as usize] as char)
+        .collect()
+}
+
+/// Write `secret` as one unshared codex32 string, with threshold `0` and
+/// index `s`.
+pub fn encode_secret(secret: &[u8], identifier: &str) -> CoreResult<Share> {
+    Ok(Share::with_checksum(header_and_payload(
+        0,
+        identifier,
+        SECRET_INDEX,
+        secret,
+    )?))
+}
+
+/// Split `secret` into `n` codex32 shares with a threshold of `k`, using
+/// the share indexes `a`, `c`, `d`, and so on.
+///
+/// `k = n = 1` writes the unshared secret, as [`encode_secret`] does.
+pub fn split(secret: &[u8], k: u8, n: u8, identifier: &str) -> CoreResult<Vec<Share>> {
+    if k == 1 && n == 1 {
+        return Ok(vec![encode_secret(secret, identifier)?]);
+    }
+    if !(2..=MAX_THRESHOLD).contains(&k) || k > n || n > MAX_SHARE_COUNT {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+
+    // The secret and k - 1 random shares fix the polynomial; the other
+    // shares are interpolated from them.
+    let header = header_and_payload(k, identifier, SECRET_INDEX, secret)?;
+    let mut points = vec![Share::with_checksum(header.clone())];
+    let (random, derived) = SHARE_INDEXES[..usize::from(n)].split_at(usize::from(k) - 1);
+    for &index in random {
+        let mut values = header.clone();
+        values[5] = char_value(index).unwrap_or_default();
+        for value in &mut values[HEADER_LEN..] {
+            let mut byte = [0u8; 1];
+            OsRng.fill_bytes(&mut byte);
+            *value = byte[0] & 31;
+        }
+        points.push(Share::with_checksum(values));
+    }
+
+    let mut shares = points[1..].to_vec();
+    for &index in derived {
+        shares.push(interpolate(&points, char_value(index).unwrap_or_default()));
+    }
+    Ok(shares)
+}
+
+/// Recover the secret from codex32 strings of one set.
+///
+/// A string with index `s` is returned as it is; otherwise exactly the
+/// threshold's worth of distinct shares is interpolated.
+pub fn combine(shares: &[Share]) -> CoreResult<Zeroizing<Vec<u8>>> {
+    let Some(first) = shares.first() else {
+        return Err(CoreError::EmptyShareInput);
+    };
+    let mut seen = [false; 32];
+    for share in shares {
+        if share.threshold() != first.threshold()
+            || share.values[1..5] != first.values[1..5]
+            || share.values.len() != first.values.len()
+        {
+            return Err(CoreError::InconsistentMetadata);
+        }
+        if seen[share.index_value() as usize] {
+            return Err(CoreError::DuplicateX {
+                x: share.index_value(),
+            });
+        }
+        seen[share.index_value() as usize] = true;
+    }
+
+    if let Some(secret) = shares.iter().find(|share| share.is_secret()) {
+        return Ok(secret.payload());
+    }
+    let k = first.threshold();
+    if shares.len() < usize::from(k) {
+        return Err(CoreError::NotEnoughShares { k, m: shares.len() });
+    }
+    Ok(interpolate(&shares[..usize::from(k)], SECRET_INDEX).payload())
+}
+
+fn malformed(reason: &str) -> CoreError {
+    CoreError::Codex32(reason.to_string())
+}
+
+fn char_value(c: u8) -> Option<u8> {
+    CHARSET.iter().position(|&x| x == c).map(|v| v as u8)
+}
+
+/// The threshold digit a header value stands for.
+fn threshold_of(value: u8) -> u8 {
+    (CHARSET[value as usize] as char)
+        .to_digit(10)
+        .map_or(u8::MAX, |digit| digit as u8)
+}
+
+fn header_and_payload(k: u8, identifier: &str, index: u8, secret: &[u8]) -> CoreResult<Vec<u8>> {
+    if !(MIN_SECRET_LEN..=MAX_SECRET_LEN).contains(&secret.len()) {
+        return Err(CoreError::Codex32(format!(
+            "secrets are {MIN_SECRET_LEN} to {MAX_SECRET_LEN} bytes, got {}",
+            secret.len()
+        )));
+    }
+    let identifier = identifier.to_ascii_lowercase();
+    if identifier.len() != 4 {
+        return Err(malformed("identifier must be 4 characters"));
+    }
+    let mut values = vec![char_value(b'0' + k).unwrap_or_default()];
+    for c in identifier.bytes() {
+        values.push(char_value(c).ok_or_else(|| malformed("identifier outside bech32"))?);
+    }
+    values.push(index);
+
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for &byte in secret {
+        acc = (acc << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            values.push(((acc >> bits) & 31) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        values.push(((acc << (5 - bits)) & 31) as u8);
+    }
+    Ok(values)
+}
+
+/// The share at `index` on the polynomial through `shares`, whose count is
+/// taken as the threshold.
+fn interpolate(shares: &[Share], index: u8) -> Share {
+    let weights: Vec<u8> = shares
+        .iter()
+        .enumerate()
+        .map(|(i, share)| {
+            let mut numerator = 1;
+            let mut denominator = 1;
+            for (j, other) in shares.iter().enumerate() {
+                if i != j {
+                    numerator = gf32_mul(numerator, index ^ other.index_value());
+                    denominator = gf32_mul(denominator, share.index_value() ^ other.index_value());
+                }
+            }
+            gf32_mul(numerator, gf32_inv(denominator))
+        })
+        .collect();
+    let values = (0..shares[0].values.len())
+        .map(|position| {
+            shares
+                .iter()
+                .zip(&weights)
+                .fold(0, |acc, (share, &weight)| {
+                    acc ^ gf32_mul(weight, share.values[position])
+                })
+        })
+        .collect();
+    Share {
+        values: Zeroizing::new(values),
+    }
+}
+
+/// Multiplication in GF(32) modulo `x^5 + x^3 + 1`, the bech32 field.
+fn gf32_mul(a: u8, b: u8) -> u8 {
+    let mut product: u16 = 0;
+    for i in 0..5 {
+        if (b >> i) & 1 == 1 {
+            product ^= u16::from(a) << i;
+        }
+    }
+    for i in (5..9).rev() {
+        if (product >> i) & 1 == 1 {
+            product ^= 0b101001 << (i - 5);
+        }
+    }
+    product as u8
+}
+
+fn gf32_inv(a: u8) -> u8 {
+    // a^30 = a^-1 in a field of 32 elements.
+    (0..29).fold(a, |acc, _| gf32_mul(acc, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bip93_vectors_recover_their_master_secrets() {
+        let vectors: &[(&[&str], &str)] = &[
+            (
+                &["ms10testsxxxxxxxxxxxxxxxxxxxxxxxxxx4nzvca9cmczlw"],
+                "318c6318c6318c6318c6318c6318c631",
+            ),
+            (
+                &[
+                    "MS12NAMEA320ZYXWVUTSRQPNMLKJHGFEDCAXRPP870HKKQRM",
+                    "MS12NAMECACDEFGHJKLMNPQRSTUVWXYZ023FTR2GDZMPY6PN",
+                ],
+                "d1808e096b35b209ca12132b264662a5",
+            ),
+            (
+                &[
+                    "ms13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0t",
+                    "ms13cashcacdefghjklmnpqrstuvwxyz023949xq35my48dr",
+                    "ms13cashd0wsedstcdcts64cd7wvy4m90lm28w4ffupqs7rm",
+                ],
+                "ffeeddccbbaa99887766554433221100",
+            ),
+            (
+                &["ms10leetsllhdmn9m42vcsamx24zrxgs3qrl7ahwvhw4fnzrhve25gvezzyqqtum9pgv99ycma"],
+                "ffeeddccbbaa99887766554433221100ffeeddccbbaa99887766554433221100",
+            ),
+            (
+                &[
+                    "MS100C8VSM32ZXFGUHPCHTLUPZRY9X8GF2TVDW0S3JN54KHCE6MUA7LQPZYGSFJD6AN074RXVCEMLH8WU3TK925ACDEFGHJKLMNPQRSTUVWXY06FHPV80UNDVARHRAK",
+                ],
+                "dc5423251cb87175ff8110c8531d0952d8d73e1194e95b5f19d6f9df7c01111104c9baecdfea8cccc677fb9ddc8aec5553b86e528bcadfdcc201c17c638c47e9",
+            ),
+        ];
+        for (strings, secret) in vectors {
+            let shares = parse_shares(&strings.join("\n")).unwrap();
+            assert_eq!(hex::encode(combine(&shares).unwrap().as_slice()), *secret);
+        }
+    }
+
+    #[test]
+    fn interpolation_derives_the_bip93_secret_and_extra_shares() {
+        let shares = parse_shares(
+            "MS12NAMEA320ZYXWVUTSRQPNMLKJHGFEDCAXRPP870HKKQRM\n\
+             MS12NAMECACDEFGHJKLMNPQRSTUVWXYZ023FTR2GDZMPY6PN",
+        )
+        .unwrap();
+        assert_eq!(
+            interpolate(&shares, SECRET_INDEX).to_string(),
+            "ms12names6xqguzttxkeqnjsjzv4jv3nz5k3kwgsphuh6evw"
+        );
+        assert_eq!(
+            interpolate(&shares, char_value(b'd').unwrap()).to_string(),
+            "ms12namedll4f8jlh4e5vdvuldlfxu2jhdnlsm97xvenrxeg"
+        );
+    }
+
+    #[test]
+    fn split_round_trips_through_text_at_short_and_long_lengths() {
+        for len in [16, 32, 46, 47, 64] {
+            let secret: Vec<u8> = (0..len as u8).map(|b| b.wrapping_mul(29) ^ 0xa5).collect();
+            let shares = split(&secret, 3, 5, "cash").unwrap();
+            assert_eq!(shares.iter().map(Share::index).collect::<String>(), "acdef");
+            let text: Vec<String> = shares.iter().map(Share::to_string).collect();
+            let parsed = parse_shares(&text[2..].join("\n").to_uppercase()).unwrap();
+            assert!(parsed.iter().all(|share| share.identifier() == "cash"));
+            assert_eq!(combine(&parsed).unwrap().as_slice(), secret);
+        }
+
+        let single = split(&[7; 16], 1, 1, "s0lv").unwrap();
+        assert_eq!(single[0].threshold(), 0);
+        assert_eq!(combine(&single).unwrap().as_slice(), [7; 16]);
+    }
+
+    #[test]
+    fn rejects_malformed_strings_and_share_sets() {
+        let a = "ms13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0t";
+        let c = "ms13cashcacdefghjklmnpqrstuvwxyz023949xq35my48dr";
+        assert!(looks_like(&format!("{a}\n{c}")));
+        for bad in [
+            "ms13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0q",
+            "ms13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0T",
+            "ms13cashbq",
+            "xs13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0t",
+        ] {
+            assert!(Share::parse(bad).is_err(), "{bad}");
+        }
+        let shares = parse_shares(&format!("{a}\n{c}")).unwrap();
+        assert!(matches!(
+            combine(&shares),
+            Err(CoreError::NotEnoughShares { k: 3, m: 2 })
+        ));
+        assert!(matches!(
+            combine(&[shares[0].clone(), shares[0].clone()]),
+            Err(CoreError::DuplicateX { .. })
+        ));
+        assert!(split(&[0; 15], 2, 3, "abcd").is_err());
+        assert!(split(&[0; 16], 10, 12, "test").is_err());
+        assert!(split(&[0; 16], 2, 3, "bad!").is_err());
+    }
+}
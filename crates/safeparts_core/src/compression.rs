@@ -0,0 +1,185 @@
+//! Optional compression of the secret before splitting.
+//!
+//! Text secrets such as `.env` files, JSON keys, and PEM bundles shrink well,
+//! and every byte saved is fewer words to transcribe in the mnemonic
+//! encodings. [`crate::split_secret_with_options`] compresses after the
+//! metadata is added and before padding and encryption, and only when it
+//! saves space.
+//!
+//! Compressed data is an algorithm byte, the decompressed length as a `u32`,
+//! then the compressed stream. The length is checked against
+//! [`MAX_DECOMPRESSED_LEN`] before anything is allocated, and the stream is
+//! inflated into a buffer of exactly that length, so a crafted set cannot
+//! expand into more memory than it declares.
+
+use std::fmt;
+
+use miniz_oxide::inflate::{self, TINFLStatus};
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+
+/// Largest secret that is compressed or decompressed.
+pub const MAX_DECOMPRESSED_LEN: usize = 64 << 20;
+
+const ALGORITHM_DEFLATE: u8 = 1;
+const HEADER_LEN: usize = 1 + 4;
+const DEFLATE_LEVEL: u8 = 9;
+
+/// Compression applied before splitting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    /// Raw DEFLATE (RFC 1951).
+    Deflate,
+}
+
+impl Compression {
+    pub const ALL: [Compression; 2] = [Compression::None, Compression::Deflate];
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, Compression::None)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Deflate => "deflate",
+        }
+    }
+
+    /// Compress `data`, or return `None` when this mode is off or the result
+    /// would not be smaller.
+    pub fn compress(&self, data: &[u8]) -> CoreResult<Option<Zeroizing<Vec<u8>>>> {
+        if self.is_none() {
+            return Ok(None);
+        }
+        if data.len() > MAX_DECOMPRESSED_LEN {
+            return Err(CoreError::InvalidCompression(format!(
+                "{} bytes is above the {MAX_DECOMPRESSED_LEN}-byte limit",
+                data.len()
+            )));
+        }
+
+        let stream = Zeroizing::new(miniz_oxide::deflate::compress_to_vec(data, DEFLATE_LEVEL));
+        if HEADER_LEN + stream.len() >= data.len() {
+            return Ok(None);
+        }
+        let mut out = Zeroizing::new(Vec::with_capacity(HEADER_LEN + stream.len()));
+        out.push(ALGORITHM_DEFLATE);
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(&stream);
+        Ok(Some(out))
+    }
+}
+
+/// Undo [`Compression::compress`].
+pub fn decompress(data: &[u8]) -> CoreResult<Zeroizing<Vec<u8>>> {
+    let malformed = |reason: &str| CoreError::InvalidCompression(reason.to_string());
+    if data.len() < HEADER_LEN {
+        return Err(malformed("truncated header"));
+    }
+    if data[0] != ALGORITHM_DEFLATE {
+        return Err(malformed("unknown algorithm"));
+    }
+    let len = u32::from_be_bytes([data[1], data[2], data[3], data[4]]) as usize;
+    if len > MAX_DECOMPRESSED_LEN {
+        return Err(CoreError::InvalidCompression(format!(
+            "declared size {len} is above the {MAX_DECOMPRESSED_LEN}-byte limit"
+        )));
+    }
+
+    let mut out = Zeroizing::new(vec![0u8; len]);
+    let written = inflate::decompress_slice_iter_to_slice(
+        &mut out,
+        std::iter::once(&data[HEADER_LEN..]),
+        false,
+        false,
+    )
+    .map_err(|status| match status {
+        TINFLStatus::HasMoreOutput => malformed("stream is longer than its declared size"),
+        _ => malformed("corrupt stream"),
+    })?;
+    if written != len {
+        return Err(malformed("stream is shorter than its declared size"));
+    }
+    Ok(out)
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Parses `none` or `deflate`.
+impl std::str::FromStr for Compression {
+    type Err = CoreError;
+
+    fn from_str(text: &str) -> CoreResult<Self> {
+        let text = text.trim().to_ascii_lowercase();
+        Compression::ALL
+            .into_iter()
+            .find(|compression| compression.label() == text)
+            .ok_or_else(|| {
+                CoreError::InvalidCompression(format!(
+                    "unknown mode {text:?}; expected none or deflate"
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deflate_round_trips_and_skips_incompressible_data() {
+        let text = b"API_KEY=abc\nAPI_SECRET=def\n".repeat(20);
+        let compressed = Compression::Deflate.compress(&text).unwrap().unwrap();
+        assert!(compressed.len() < text.len() / 4);
+        assert_eq!(decompress(&compressed).unwrap().as_slice(), text);
+
+        assert_eq!(Compression::Deflate.compress(b"k3y").unwrap(), None);
+        assert_eq!(Compression::None.compress(&text).unwrap(), None);
+    }
+
+    #[test]
+    fn decompress_rejects_bombs_and_corrupt_streams() {
+        let zeros = vec![0u8; 1 << 16];
+        let mut bomb = Compression::Deflate.compress(&zeros).unwrap().unwrap();
+        bomb[1..5].copy_from_slice(&1024u32.to_be_bytes());
+        assert!(matches!(
+            decompress(&bomb),
+            Err(CoreError::InvalidCompression(reason)) if reason.contains("longer")
+        ));
+
+        bomb[1..5].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            decompress(&bomb),
+            Err(CoreError::InvalidCompression(reason)) if reason.contains("limit")
+        ));
+
+        let mut short = Compression::Deflate.compress(&zeros).unwrap().unwrap();
+        short[1..5].copy_from_slice(&(1u32 << 20).to_be_bytes());
+        assert!(decompress(&short).is_err());
+        assert!(decompress(&[ALGORITHM_DEFLATE, 0, 0]).is_err());
+        assert!(decompress(&[9, 0, 0, 0, 1, 0]).is_err());
+    }
+
+    #[test]
+    fn parse_round_trips_display() {
+        for compression in Compression::ALL {
+            assert_eq!(
+                compression.to_string().parse::<Compression>().unwrap(),
+                compression
+            );
+        }
+        assert_eq!(
+            " DEFLATE ".parse::<Compression>().unwrap(),
+            Compression::Deflate
+        );
+        assert!("zip".parse::<Compression>().is_err());
+    }
+}
@@ -1,5 +1,9 @@
-use chacha20poly1305::aead::{Aead, KeyInit};
-use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::time::{Duration, Instant};
+
+use aes_gcm_siv::Aes256GcmSiv;
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce, XChaCha20Poly1305};
 use rand::RngCore;
 use rand::rngs::OsRng;
 use zeroize::Zeroizing;
@@ -8,59 +12,765 @@ use crate::error::{CoreError, CoreResult};
 
 pub const SALT_LEN: usize = 16;
 pub const NONCE_LEN: usize = 12;
+/// Longest nonce of any [`AeadAlgorithm`], the 192-bit XChaCha20 nonce.
+pub const MAX_NONCE_LEN: usize = 24;
+pub const KEY_LEN: usize = 32;
+
+/// Packet id of Argon2id, currently the only key-derivation function.
+pub const KDF_ARGON2ID: u8 = 1;
+
+const DERIVED_NONCE_CONTEXT: &str = "safeparts 2026-10 nonce derived from salt";
+
+/// Upper bound on Argon2 memory, 4 GiB, for splits and for decoded packets.
+pub const MAX_MEM_COST_KIB: u32 = 4 * 1024 * 1024;
+/// Upper bound on Argon2 passes.
+pub const MAX_TIME_COST: u32 = 64;
+/// Upper bound on Argon2 lanes.
+pub const MAX_PARALLELISM: u32 = 16;
 
+/// Memory used for the first calibration run, 8 MiB.
+const CALIBRATION_START_KIB: u32 = 8 * 1024;
+
+/// Argon2id cost parameters for passphrase protection.
+///
+/// Use one of the named profiles, [`KdfParams::new`] for explicit values, or
+/// [`calibrate`] to fit a target unlock time on the current machine. The
+/// parameters are stored in every packet, so combine always uses the costs
+/// the split chose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub mem_cost_kib: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// 19 MiB and 2 passes: quick unlocks on phones and in browsers.
+    pub const INTERACTIVE: Self = Self {
+        mem_cost_kib: 19 * 1024,
+        time_cost: 2,
+        parallelism: 1,
+    };
+
+    /// 64 MiB and 3 passes: the default.
+    pub const MODERATE: Self = Self {
+        mem_cost_kib: 64 * 1024,
+        time_cost: 3,
+        parallelism: 1,
+    };
+
+    /// 1 GiB and 4 passes: high-value keys that are rarely unlocked.
+    pub const PARANOID: Self = Self {
+        mem_cost_kib: 1024 * 1024,
+        time_cost: 4,
+        parallelism: 1,
+    };
+
+    /// Named profiles, in increasing cost.
+    pub const PROFILES: [(&'static str, Self); 3] = [
+        ("interactive", Self::INTERACTIVE),
+        ("moderate", Self::MODERATE),
+        ("paranoid", Self::PARANOID),
+    ];
+
+    /// Explicit parameters, checked against the Argon2 and Safeparts limits.
+    pub fn new(mem_cost_kib: u32, time_cost: u32, parallelism: u32) -> CoreResult<Self> {
+        let params = Self {
+            mem_cost_kib,
+            time_cost,
+            parallelism,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Look up a named profile, ignoring case.
+    pub fn profile(name: &str) -> CoreResult<Self> {
+        Self::PROFILES
+            .iter()
+            .find(|(profile, _)| profile.eq_ignore_ascii_case(name.trim()))
+            .map(|&(_, params)| params)
+            .ok_or_else(|| {
+                CoreError::InvalidKdfParams(format!(
+                    "unknown profile {name:?}; expected interactive, moderate, or paranoid"
+                ))
+            })
+    }
+
+    /// Name of the profile these parameters match, if any.
+    pub fn profile_name(&self) -> Option<&'static str> {
+        Self::PROFILES
+            .iter()
+            .find(|(_, params)| params == self)
+            .map(|&(name, _)| name)
+    }
+
+    pub fn validate(&self) -> CoreResult<()> {
+        if !(1..=MAX_PARALLELISM).contains(&self.parallelism) {
+            return Err(CoreError::InvalidKdfParams(format!(
+                "parallelism must be 1..={MAX_PARALLELISM}, got {}",
+                self.parallelism
+            )));
+        }
+        if !(1..=MAX_TIME_COST).contains(&self.time_cost) {
+            return Err(CoreError::InvalidKdfParams(format!(
+                "iterations must be 1..={MAX_TIME_COST}, got {}",
+                self.time_cost
+            )));
+        }
+        // Argon2 needs at least 8 KiB per lane.
+        let min_mem = 8 * self.parallelism;
+        if !(min_mem..=MAX_MEM_COST_KIB).contains(&self.mem_cost_kib) {
+            return Err(CoreError::InvalidKdfParams(format!(
+                "memory must be {min_mem}..={MAX_MEM_COST_KIB} KiB, got {}",
+                self.mem_cost_kib
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self::MODERATE
+    }
+}
+
+/// Parses a profile name or `MEMORY_KIB ITERATIONS [LANES]`, separated by
+/// spaces or commas.
+impl std::str::FromStr for KdfParams {
+    type Err = CoreError;
+
+    fn from_str(text: &str) -> CoreResult<Self> {
+        let text = text.trim();
+        if text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Self::profile(text);
+        }
+
+        let numbers = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                part.parse::<u32>()
+                    .map_err(|_| CoreError::InvalidKdfParams(format!("not a number: {part}")))
+            })
+            .collect::<CoreResult<Vec<_>>>()?;
+        match numbers[..] {
+            [mem, time] => Self::new(mem, time, 1),
+            [mem, time, lanes] => Self::new(mem, time, lanes),
+            _ => Err(CoreError::InvalidKdfParams(
+                "expected a profile or MEMORY_KIB ITERATIONS [LANES]".to_string(),
+            )),
+        }
+    }
+}
+
+/// Pick parameters whose derivation takes about `target` on this machine.
+///
+/// Memory is raised first, up to `max_mem_kib`, because it is what makes GPU
+/// cracking expensive; the remaining budget goes to extra passes. Takes a few
+/// times `target` to run.
+pub fn calibrate(target: Duration, max_mem_kib: u32) -> CoreResult<KdfParams> {
+    calibrate_with(target, max_mem_kib, |kdf| {
+        let params = CryptoParams::random((*kdf).into());
+        let start = Instant::now();
+        derive_key(b"safeparts calibration", &params)?;
+        Ok(start.elapsed())
+    })
+}
+
+/// [`calibrate`] with a caller-supplied timer.
+///
+/// `measure` runs one derivation with the given parameters and returns how
+/// long it took. Use it where `std::time::Instant` is unavailable, such as
+/// WASM, or to calibrate against another machine's timings.
+pub fn calibrate_with(
+    target: Duration,
+    max_mem_kib: u32,
+    mut measure: impl FnMut(&KdfParams) -> CoreResult<Duration>,
+) -> CoreResult<KdfParams> {
+    if target.is_zero() {
+        return Err(CoreError::InvalidKdfParams(
+            "calibration target must be above zero".to_string(),
+        ));
+    }
+    let max_mem_kib = max_mem_kib.min(MAX_MEM_COST_KIB);
+    let mut kdf = KdfParams::new(CALIBRATION_START_KIB.min(max_mem_kib), 1, 1)?;
+
+    let mut elapsed = measure(&kdf)?;
+    while elapsed * 2 <= target && kdf.mem_cost_kib * 2 <= max_mem_kib {
+        kdf.mem_cost_kib *= 2;
+        elapsed = measure(&kdf)?;
+    }
+
+    // Each pass costs about as much as the first.
+    let passes = target.as_secs_f64() / elapsed.as_secs_f64().max(f64::EPSILON);
+    kdf.time_cost = (passes as u32).clamp(1, MAX_TIME_COST);
+    Ok(kdf)
+}
+
+/// AEAD that encrypts passphrase-protected secrets.
+///
+/// The choice is recorded in every packet, so combine picks it automatically.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AeadAlgorithm {
+    /// ChaCha20-Poly1305 with a random 96-bit nonce: the original scheme and
+    /// the default.
+    #[default]
+    ChaCha20Poly1305,
+    /// XChaCha20-Poly1305 with a random 192-bit nonce.
+    XChaCha20Poly1305,
+    /// AES-256-GCM-SIV, for AES-only compliance regimes. A repeated nonce
+    /// reveals only whether two plaintexts are equal.
+    Aes256GcmSiv,
+}
+
+impl AeadAlgorithm {
+    pub const ALL: &'static [AeadAlgorithm] = &[
+        AeadAlgorithm::ChaCha20Poly1305,
+        AeadAlgorithm::XChaCha20Poly1305,
+        AeadAlgorithm::Aes256GcmSiv,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AeadAlgorithm::ChaCha20Poly1305 => "chacha20-poly1305",
+            AeadAlgorithm::XChaCha20Poly1305 => "xchacha20-poly1305",
+            AeadAlgorithm::Aes256GcmSiv => "aes-256-gcm-siv",
+        }
+    }
+
+    /// Parse a canonical label, ignoring case.
+    pub fn parse_name(name: &str) -> CoreResult<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|aead| aead.label().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| CoreError::UnknownCipher(name.to_string()))
+    }
+
+    /// Id recorded in the packet crypto section.
+    pub fn id(self) -> u8 {
+        match self {
+            AeadAlgorithm::ChaCha20Poly1305 => 1,
+            AeadAlgorithm::XChaCha20Poly1305 => 2,
+            AeadAlgorithm::Aes256GcmSiv => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|aead| aead.id() == id)
+    }
+
+    pub fn nonce_len(self) -> usize {
+        match self {
+            AeadAlgorithm::XChaCha20Poly1305 => MAX_NONCE_LEN,
+            AeadAlgorithm::ChaCha20Poly1305 | AeadAlgorithm::Aes256GcmSiv => NONCE_LEN,
+        }
+    }
+}
+
+/// What a passphrase-protected split chooses about its encryption: the
+/// Argon2id costs and the AEAD.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CipherSuite {
+    pub kdf: KdfParams,
+    pub aead: AeadAlgorithm,
+}
+
+impl From<KdfParams> for CipherSuite {
+    fn from(kdf: KdfParams) -> Self {
+        Self {
+            kdf,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<AeadAlgorithm> for CipherSuite {
+    fn from(aead: AeadAlgorithm) -> Self {
+        Self {
+            aead,
+            ..Self::default()
+        }
+    }
+}
+
+/// Per-split encryption parameters, stored in every packet of the split.
+///
+/// `nonce` holds [`AeadAlgorithm::nonce_len`] bytes followed by zeros; use
+/// [`CryptoParams::nonce`] for the nonce itself.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CryptoParams {
     pub salt: [u8; SALT_LEN],
-    pub nonce: [u8; NONCE_LEN],
+    pub nonce: [u8; MAX_NONCE_LEN],
     pub mem_cost_kib: u32,
     pub time_cost: u32,
     pub parallelism: u32,
+    pub aead: AeadAlgorithm,
 }
 
 impl CryptoParams {
     pub fn random_default() -> Self {
+        Self::random(CipherSuite::default())
+    }
+
+    /// Fresh salt and nonce for the given suite.
+    pub fn random(suite: CipherSuite) -> Self {
         let mut salt = [0u8; SALT_LEN];
-        let mut nonce = [0u8; NONCE_LEN];
+        let mut nonce = [0u8; MAX_NONCE_LEN];
         OsRng.fill_bytes(&mut salt);
-        OsRng.fill_bytes(&mut nonce);
+        OsRng.fill_bytes(&mut nonce[..suite.aead.nonce_len()]);
 
         Self {
             salt,
             nonce,
-            mem_cost_kib: 65_536,
-            time_cost: 3,
-            parallelism: 1,
+            mem_cost_kib: suite.kdf.mem_cost_kib,
+            time_cost: suite.kdf.time_cost,
+            parallelism: suite.kdf.parallelism,
+            aead: suite.aead,
         }
     }
+
+    /// Fresh salt, with the nonce derived from it so compact packets need not
+    /// store it.
+    ///
+    /// A fresh salt gives a fresh key, and split encrypts once per key, so the
+    /// derived nonce is never reused under one key.
+    pub fn random_derived(suite: CipherSuite) -> Self {
+        let mut params = Self::random(suite);
+        params.nonce = derived_nonce(&params.salt, params.aead);
+        params
+    }
+
+    /// True when the nonce is the one [`CryptoParams::random_derived`] uses.
+    pub fn has_derived_nonce(&self) -> bool {
+        self.nonce == derived_nonce(&self.salt, self.aead)
+    }
+
+    pub fn nonce(&self) -> &[u8] {
+        &self.nonce[..self.aead.nonce_len()]
+    }
+
+    pub fn suite(&self) -> CipherSuite {
+        CipherSuite {
+            kdf: self.kdf(),
+            aead: self.aead,
+        }
+    }
+
+    pub fn kdf(&self) -> KdfParams {
+        KdfParams {
+            mem_cost_kib: self.mem_cost_kib,
+            time_cost: self.time_cost,
+            parallelism: self.parallelism,
+        }
+    }
+}
+
+/// Nonce for `aead` derived from `salt`, zero-filled like [`CryptoParams::nonce`].
+pub(crate) fn derived_nonce(salt: &[u8; SALT_LEN], aead: AeadAlgorithm) -> [u8; MAX_NONCE_LEN] {
+    let hash = blake3::Hasher::new_derive_key(DERIVED_NONCE_CONTEXT)
+        .update(salt)
+        .finalize();
+    let mut nonce = [0u8; MAX_NONCE_LEN];
+    nonce[..aead.nonce_len()].copy_from_slice(&hash.as_bytes()[..aead.nonce_len()]);
+    nonce
 }
 
 pub fn encrypt(plaintext: &[u8], passphrase: &[u8]) -> CoreResult<(Vec<u8>, CryptoParams)> {
     let params = CryptoParams::random_default();
-    let key = derive_key(passphrase, &params)?;
+    let ciphertext = encrypt_with_aad(plaintext, passphrase, &params, &[])?;
+    Ok((ciphertext, params))
+}
 
-    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
-    let nonce = Nonce::from_slice(&params.nonce);
+pub fn decrypt(ciphertext: &[u8], passphrase: &[u8], params: CryptoParams) -> CoreResult<Vec<u8>> {
+    decrypt_with_aad(ciphertext, passphrase, &params, &[])
+}
 
-    let ciphertext = cipher
-        .encrypt(nonce, plaintext)
-        .map_err(|_| CoreError::EncryptFailed)?;
+/// Encrypt under a passphrase with associated data, such as a packet's set
+/// header, that must match on decryption.
+pub fn encrypt_with_aad(
+    plaintext: &[u8],
+    passphrase: &[u8],
+    params: &CryptoParams,
+    aad: &[u8],
+) -> CoreResult<Vec<u8>> {
+    let key = derive_key(passphrase, params)?;
 
-    Ok((ciphertext, params))
+    Cipher::new(params.aead, &key).encrypt(
+        params.nonce(),
+        Payload {
+            msg: plaintext,
+            aad,
+        },
+    )
 }
 
-pub fn decrypt(ciphertext: &[u8], passphrase: &[u8], params: CryptoParams) -> CoreResult<Vec<u8>> {
-    let key = derive_key(passphrase, &params)?;
+pub fn decrypt_with_aad(
+    ciphertext: &[u8],
+    passphrase: &[u8],
+    params: &CryptoParams,
+    aad: &[u8],
+) -> CoreResult<Vec<u8>> {
+    let key = derive_key(passphrase, params)?;
 
-    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
-    let nonce = Nonce::from_slice(&params.nonce);
+    Cipher::new(params.aead, &key).decrypt(
+        params.nonce(),
+        Payload {
+            msg: ciphertext,
+            aad,
+        },
+    )
+}
+
+/// Generate a fresh random key for [`encrypt_with_key`].
+pub fn random_key() -> Zeroizing<[u8; KEY_LEN]> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    OsRng.fill_bytes(key.as_mut());
+    key
+}
+
+/// Encrypt under a single-use key.
+///
+/// The nonce is fixed at zero, so `key` must never encrypt anything else.
+/// Used by [`crate::split_secret_large`], which shares the key instead of a
+/// passphrase.
+pub fn encrypt_with_key(plaintext: &[u8], key: &[u8; KEY_LEN]) -> CoreResult<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .encrypt(&Nonce::default(), plaintext)
+        .map_err(|_| CoreError::EncryptFailed)
+}
 
+pub fn decrypt_with_key(ciphertext: &[u8], key: &[u8; KEY_LEN]) -> CoreResult<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
     cipher
-        .decrypt(nonce, ciphertext)
+        .decrypt(&Nonce::default(), ciphertext)
         .map_err(|_| CoreError::DecryptFailed)
 }
 
+/// Longest key-slot label in bytes.
+pub const MAX_SLOT_LABEL_LEN: usize = 64;
+/// A data key wrapped by any [`AeadAlgorithm`]: the key plus a 16-byte tag.
+const WRAPPED_KEY_LEN: usize = KEY_LEN + 16;
+
+/// One passphrase's copy of a [`SlotEnvelope`] data key.
+///
+/// The key is wrapped under an Argon2id key derived with this slot's own
+/// salt and costs, with the label as associated data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeySlot {
+    pub label: String,
+    pub params: CryptoParams,
+    wrapped_key: Vec<u8>,
+}
+
+/// The data key of an unlocked [`SlotEnvelope`], needed to edit its slots.
+pub struct SlotKey(Zeroizing<[u8; KEY_LEN]>);
+
+/// Data encrypted once under a random data key, with that key wrapped once
+/// per passphrase in a [`KeySlot`], LUKS-style. Any slot's passphrase opens
+/// the data, and slots can be added or removed without the others'
+/// passphrases.
+///
+/// The data is encrypted with the AEAD the envelope was created with and a
+/// fresh random nonce on every [`SlotEnvelope::encrypt`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotEnvelope {
+    slots: Vec<KeySlot>,
+    data_aead: AeadAlgorithm,
+    data_nonce: [u8; MAX_NONCE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+impl SlotEnvelope {
+    /// Envelope with a fresh data key wrapped once per `(label, passphrase)`.
+    /// Call [`SlotEnvelope::encrypt`] to add the data.
+    pub fn new(slots: &[(&str, &[u8])], suite: CipherSuite) -> CoreResult<(Self, SlotKey)> {
+        if slots.is_empty() {
+            return Err(CoreError::KeySlot(
+                "at least one slot is required".to_string(),
+            ));
+        }
+        let key = SlotKey(random_key());
+        let mut envelope = Self {
+            slots: Vec::with_capacity(slots.len()),
+            data_aead: suite.aead,
+            data_nonce: [0u8; MAX_NONCE_LEN],
+            ciphertext: Vec::new(),
+        };
+        for (label, passphrase) in slots {
+            envelope.add_slot(&key, label, passphrase, suite)?;
+        }
+        Ok((envelope, key))
+    }
+
+    pub fn slots(&self) -> &[KeySlot] {
+        &self.slots
+    }
+
+    /// Unwrap the data key with the first slot `passphrase` opens, and return
+    /// that slot's position.
+    ///
+    /// Tries every slot in turn, so a wrong passphrase costs one Argon2id run
+    /// per slot before failing with [`CoreError::DecryptFailed`].
+    pub fn unlock(&self, passphrase: &[u8]) -> CoreResult<(usize, SlotKey)> {
+        for (index, slot) in self.slots.iter().enumerate() {
+            match decrypt_with_aad(
+                &slot.wrapped_key,
+                passphrase,
+                &slot.params,
+                slot.label.as_bytes(),
+            ) {
+                Ok(unwrapped) => {
+                    let unwrapped = Zeroizing::new(unwrapped);
+                    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+                    key.copy_from_slice(&unwrapped);
+                    return Ok((index, SlotKey(key)));
+                }
+                Err(CoreError::DecryptFailed) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Err(CoreError::DecryptFailed)
+    }
+
+    /// Wrap `key` under a new passphrase. Labels must be unique, non-empty,
+    /// and at most [`MAX_SLOT_LABEL_LEN`] bytes.
+    pub fn add_slot(
+        &mut self,
+        key: &SlotKey,
+        label: &str,
+        passphrase: &[u8],
+        suite: CipherSuite,
+    ) -> CoreResult<()> {
+        if label.is_empty() || label.len() > MAX_SLOT_LABEL_LEN {
+            return Err(CoreError::KeySlot(format!(
+                "labels must be 1 to {MAX_SLOT_LABEL_LEN} bytes"
+            )));
+        }
+        if self.slots.iter().any(|slot| slot.label == label) {
+            return Err(CoreError::KeySlot(format!("duplicate label {label:?}")));
+        }
+        if self.slots.len() == usize::from(u8::MAX) {
+            return Err(CoreError::KeySlot("too many slots".to_string()));
+        }
+        suite.kdf.validate()?;
+
+        let params = CryptoParams::random(suite);
+        let wrapped_key = encrypt_with_aad(key.0.as_ref(), passphrase, &params, label.as_bytes())?;
+        self.slots.push(KeySlot {
+            label: label.to_string(),
+            params,
+            wrapped_key,
+        });
+        Ok(())
+    }
+
+    /// Drop the slot named `label`. The last slot cannot be removed.
+    pub fn remove_slot(&mut self, label: &str) -> CoreResult<()> {
+        let index = self
+            .slots
+            .iter()
+            .position(|slot| slot.label == label)
+            .ok_or_else(|| CoreError::KeySlot(format!("no slot named {label:?}")))?;
+        if self.slots.len() == 1 {
+            return Err(CoreError::KeySlot(
+                "cannot remove the last slot".to_string(),
+            ));
+        }
+        self.slots.remove(index);
+        Ok(())
+    }
+
+    /// Replace the data with `plaintext` under a fresh nonce.
+    pub fn encrypt(&mut self, key: &SlotKey, plaintext: &[u8], aad: &[u8]) -> CoreResult<()> {
+        let nonce_len = self.data_aead.nonce_len();
+        self.data_nonce = [0u8; MAX_NONCE_LEN];
+        OsRng.fill_bytes(&mut self.data_nonce[..nonce_len]);
+        self.ciphertext = Cipher::new(self.data_aead, &key.0).encrypt(
+            &self.data_nonce[..nonce_len],
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn decrypt(&self, key: &SlotKey, aad: &[u8]) -> CoreResult<Vec<u8>> {
+        Cipher::new(self.data_aead, &key.0).decrypt(
+            &self.data_nonce[..self.data_aead.nonce_len()],
+            Payload {
+                msg: &self.ciphertext,
+                aad,
+            },
+        )
+    }
+
+    /// Serialize as the slot count, each slot's label, parameters, and
+    /// wrapped key, then the data AEAD id, nonce, and ciphertext.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.push(self.slots.len() as u8);
+        for slot in &self.slots {
+            out.push(slot.label.len() as u8);
+            out.extend_from_slice(slot.label.as_bytes());
+            crate::packet::encode_suite_params(&slot.params, &mut out);
+            out.extend_from_slice(&slot.wrapped_key);
+        }
+        out.push(self.data_aead.id());
+        out.extend_from_slice(&self.data_nonce[..self.data_aead.nonce_len()]);
+        out.extend_from_slice(&self.ciphertext);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> CoreResult<Self> {
+        let malformed = || CoreError::KeySlot("malformed slot table".to_string());
+        let take = |offset: &mut usize, len: usize| -> CoreResult<&[u8]> {
+            let field = bytes.get(*offset..*offset + len).ok_or_else(malformed)?;
+            *offset += len;
+            Ok(field)
+        };
+
+        let mut offset = 0;
+        let count = take(&mut offset, 1)?[0];
+        if count == 0 {
+            return Err(malformed());
+        }
+        let mut slots = Vec::with_capacity(count.into());
+        for _ in 0..count {
+            let label_len = take(&mut offset, 1)?[0];
+            let label = std::str::from_utf8(take(&mut offset, label_len.into())?)
+                .map_err(|_| malformed())?
+                .to_string();
+            let params_len =
+                crate::packet::suite_params_len(bytes, offset).map_err(|_| malformed())?;
+            let params = crate::packet::decode_suite_params(take(&mut offset, params_len)?)?;
+            let wrapped_key = take(&mut offset, WRAPPED_KEY_LEN)?.to_vec();
+            slots.push(KeySlot {
+                label,
+                params,
+                wrapped_key,
+            });
+        }
+
+        let data_aead = AeadAlgorithm::from_id(take(&mut offset, 1)?[0]).ok_or_else(malformed)?;
+        let mut data_nonce = [0u8; MAX_NONCE_LEN];
+        data_nonce[..data_aead.nonce_len()]
+            .copy_from_slice(take(&mut offset, data_aead.nonce_len())?);
+
+        Ok(Self {
+            slots,
+            data_aead,
+            data_nonce,
+            ciphertext: bytes[offset..].to_vec(),
+        })
+    }
+}
+
+/// Passphrase encryption for one stream, applied frame by frame.
+///
+/// Each frame uses the stream's nonce with the frame index folded into its
+/// last eight bytes, so frames cannot be swapped without failing decryption.
+/// `aad` is authenticated with every frame.
+pub struct FrameCipher {
+    cipher: Cipher,
+    nonce: [u8; MAX_NONCE_LEN],
+    nonce_len: usize,
+    aad: Vec<u8>,
+}
+
+impl FrameCipher {
+    pub fn new(passphrase: &[u8], params: &CryptoParams, aad: Vec<u8>) -> CoreResult<Self> {
+        let key = derive_key(passphrase, params)?;
+        Ok(Self {
+            cipher: Cipher::new(params.aead, &key),
+            nonce: params.nonce,
+            nonce_len: params.aead.nonce_len(),
+            aad,
+        })
+    }
+
+    pub fn encrypt(&self, index: u64, plaintext: &[u8]) -> CoreResult<Vec<u8>> {
+        let payload = Payload {
+            msg: plaintext,
+            aad: &self.aad,
+        };
+        let nonce = self.frame_nonce(index);
+        self.cipher.encrypt(&nonce[..self.nonce_len], payload)
+    }
+
+    pub fn decrypt(&self, index: u64, ciphertext: &[u8]) -> CoreResult<Vec<u8>> {
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &self.aad,
+        };
+        let nonce = self.frame_nonce(index);
+        self.cipher.decrypt(&nonce[..self.nonce_len], payload)
+    }
+
+    fn frame_nonce(&self, index: u64) -> [u8; MAX_NONCE_LEN] {
+        let mut nonce = self.nonce;
+        let counter = &mut nonce[self.nonce_len - 8..self.nonce_len];
+        for (byte, index_byte) in counter.iter_mut().zip(index.to_be_bytes()) {
+            *byte ^= index_byte;
+        }
+        nonce
+    }
+}
+
+/// A keyed instance of one [`AeadAlgorithm`].
+enum Cipher {
+    ChaCha20Poly1305(ChaCha20Poly1305),
+    XChaCha20Poly1305(XChaCha20Poly1305),
+    // Boxed: its expanded key schedule dwarfs the ChaCha keys.
+    Aes256GcmSiv(Box<Aes256GcmSiv>),
+}
+
+impl Cipher {
+    fn new(aead: AeadAlgorithm, key: &[u8; KEY_LEN]) -> Self {
+        let key = GenericArray::from_slice(key);
+        match aead {
+            AeadAlgorithm::ChaCha20Poly1305 => Self::ChaCha20Poly1305(ChaCha20Poly1305::new(key)),
+            AeadAlgorithm::XChaCha20Poly1305 => {
+                Self::XChaCha20Poly1305(XChaCha20Poly1305::new(key))
+            }
+            AeadAlgorithm::Aes256GcmSiv => Self::Aes256GcmSiv(Box::new(Aes256GcmSiv::new(key))),
+        }
+    }
+
+    /// `nonce` must be [`AeadAlgorithm::nonce_len`] bytes long.
+    fn encrypt(&self, nonce: &[u8], payload: Payload<'_, '_>) -> CoreResult<Vec<u8>> {
+        match self {
+            Self::ChaCha20Poly1305(cipher) => {
+                cipher.encrypt(GenericArray::from_slice(nonce), payload)
+            }
+            Self::XChaCha20Poly1305(cipher) => {
+                cipher.encrypt(GenericArray::from_slice(nonce), payload)
+            }
+            Self::Aes256GcmSiv(cipher) => cipher.encrypt(GenericArray::from_slice(nonce), payload),
+        }
+        .map_err(|_| CoreError::EncryptFailed)
+    }
+
+    fn decrypt(&self, nonce: &[u8], payload: Payload<'_, '_>) -> CoreResult<Vec<u8>> {
+        match self {
+            Self::ChaCha20Poly1305(cipher) => {
+                cipher.decrypt(GenericArray::from_slice(nonce), payload)
+            }
+            Self::XChaCha20Poly1305(cipher) => {
+                cipher.decrypt(GenericArray::from_slice(nonce), payload)
+            }
+            Self::Aes256GcmSiv(cipher) => cipher.decrypt(GenericArray::from_slice(nonce), payload),
+        }
+        .map_err(|_| CoreError::DecryptFailed)
+    }
+}
+
 fn derive_key(passphrase: &[u8], params: &CryptoParams) -> CoreResult<Zeroizing<[u8; 32]>> {
+    params.kdf().validate()?;
     let argon_params = argon2::Params::new(
         params.mem_cost_kib,
         params.time_cost,
@@ -87,6 +797,66 @@ fn derive_key(passphrase: &[u8], params: &CryptoParams) -> CoreResult<Zeroizing<
 mod tests {
     use super::*;
 
+    fn fast_suite(aead: AeadAlgorithm) -> CipherSuite {
+        CipherSuite {
+            kdf: KdfParams::new(1024, 1, 1).unwrap(),
+            aead,
+        }
+    }
+
+    #[test]
+    fn slot_envelope_opens_with_any_slot() {
+        let suite = fast_suite(AeadAlgorithm::XChaCha20Poly1305);
+        let (mut envelope, key) =
+            SlotEnvelope::new(&[("ops", b"ops pass"), ("break-glass", b"bg pass")], suite).unwrap();
+        envelope.encrypt(&key, b"data", b"aad").unwrap();
+
+        let envelope = SlotEnvelope::from_bytes(&envelope.to_bytes()).unwrap();
+        let labels: Vec<_> = envelope.slots().iter().map(|slot| &slot.label).collect();
+        assert_eq!(labels, ["ops", "break-glass"]);
+        for (index, passphrase) in [(0, &b"ops pass"[..]), (1, b"bg pass")] {
+            let (slot, key) = envelope.unlock(passphrase).unwrap();
+            assert_eq!(slot, index);
+            assert_eq!(envelope.decrypt(&key, b"aad").unwrap(), b"data");
+            assert!(matches!(
+                envelope.decrypt(&key, b"other"),
+                Err(CoreError::DecryptFailed)
+            ));
+        }
+        assert!(matches!(
+            envelope.unlock(b"wrong"),
+            Err(CoreError::DecryptFailed)
+        ));
+    }
+
+    #[test]
+    fn slot_envelope_edits_keep_the_data_key() {
+        let suite = fast_suite(AeadAlgorithm::ChaCha20Poly1305);
+        let (mut envelope, _) = SlotEnvelope::new(&[("ops", b"ops pass")], suite).unwrap();
+        let (_, key) = envelope.unlock(b"ops pass").unwrap();
+        envelope.encrypt(&key, b"data", &[]).unwrap();
+        let first = envelope.clone();
+
+        envelope
+            .add_slot(&key, "security", b"sec pass", suite)
+            .unwrap();
+        assert!(matches!(
+            envelope.add_slot(&key, "security", b"again", suite),
+            Err(CoreError::KeySlot(_))
+        ));
+        envelope.remove_slot("ops").unwrap();
+        assert!(matches!(
+            envelope.remove_slot("security"),
+            Err(CoreError::KeySlot(_))
+        ));
+        assert!(envelope.unlock(b"ops pass").is_err());
+
+        let (_, key) = envelope.unlock(b"sec pass").unwrap();
+        envelope.encrypt(&key, b"data", &[]).unwrap();
+        assert_ne!(envelope.data_nonce, first.data_nonce);
+        assert_eq!(first.decrypt(&key, &[]).unwrap(), b"data");
+    }
+
     #[test]
     fn encrypt_decrypt_round_trip() {
         let plaintext = b"secret";
@@ -109,4 +879,121 @@ mod tests {
             CoreError::DecryptFailed | CoreError::Crypto(_)
         ));
     }
+
+    #[test]
+    fn mismatched_associated_data_fails() {
+        let params = CryptoParams::random_default();
+        let ciphertext = encrypt_with_aad(b"secret", b"pass", &params, b"k=2").unwrap();
+
+        assert_eq!(
+            decrypt_with_aad(&ciphertext, b"pass", &params, b"k=2").unwrap(),
+            b"secret"
+        );
+        assert!(matches!(
+            decrypt_with_aad(&ciphertext, b"pass", &params, b"k=1"),
+            Err(CoreError::DecryptFailed)
+        ));
+    }
+
+    #[test]
+    fn profiles_parse_and_validate() {
+        assert_eq!(KdfParams::default(), KdfParams::MODERATE);
+        assert_eq!(KdfParams::profile("Paranoid").unwrap(), KdfParams::PARANOID);
+        assert_eq!(KdfParams::INTERACTIVE.profile_name(), Some("interactive"));
+        assert!(matches!(
+            KdfParams::profile("fast"),
+            Err(CoreError::InvalidKdfParams(_))
+        ));
+        assert_eq!(
+            "moderate".parse::<KdfParams>().unwrap(),
+            KdfParams::MODERATE
+        );
+        assert_eq!(
+            "32768, 2".parse::<KdfParams>().unwrap(),
+            KdfParams::new(32768, 2, 1).unwrap()
+        );
+        assert!("65536".parse::<KdfParams>().is_err());
+
+        for (_, params) in KdfParams::PROFILES {
+            params.validate().unwrap();
+        }
+        for (mem, time, lanes) in [
+            (15, 1, 2),
+            (8, 0, 1),
+            (8, 1, 0),
+            (MAX_MEM_COST_KIB + 1, 1, 1),
+        ] {
+            assert!(matches!(
+                KdfParams::new(mem, time, lanes),
+                Err(CoreError::InvalidKdfParams(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn custom_params_round_trip() {
+        let kdf = KdfParams::new(1024, 1, 2).unwrap();
+        let params = CryptoParams::random(kdf.into());
+        assert_eq!(params.kdf(), kdf);
+
+        let ciphertext = encrypt_with_aad(b"secret", b"pass", &params, &[]).unwrap();
+        assert_eq!(
+            decrypt_with_aad(&ciphertext, b"pass", &params, &[]).unwrap(),
+            b"secret"
+        );
+    }
+
+    #[test]
+    fn every_aead_round_trips_and_rejects_the_others() {
+        let kdf = KdfParams::new(1024, 1, 1).unwrap();
+        for &aead in AeadAlgorithm::ALL {
+            assert_eq!(AeadAlgorithm::parse_name(aead.label()).unwrap(), aead);
+            assert_eq!(AeadAlgorithm::from_id(aead.id()), Some(aead));
+
+            let params = CryptoParams::random(CipherSuite { kdf, aead });
+            assert_eq!(params.nonce().len(), aead.nonce_len());
+            assert!(params.nonce[aead.nonce_len()..].iter().all(|&b| b == 0));
+
+            let ciphertext = encrypt_with_aad(b"secret", b"pass", &params, b"ad").unwrap();
+            assert_eq!(
+                decrypt_with_aad(&ciphertext, b"pass", &params, b"ad").unwrap(),
+                b"secret"
+            );
+            for &other in AeadAlgorithm::ALL.iter().filter(|&&other| other != aead) {
+                let other = CryptoParams {
+                    aead: other,
+                    ..params
+                };
+                assert!(decrypt_with_aad(&ciphertext, b"pass", &other, b"ad").is_err());
+            }
+
+            let frames = FrameCipher::new(b"pass", &params, b"ad".to_vec()).unwrap();
+            let frame = frames.encrypt(7, b"frame").unwrap();
+            assert_eq!(frames.decrypt(7, &frame).unwrap(), b"frame");
+            assert!(frames.decrypt(8, &frame).is_err());
+        }
+        assert!(matches!(
+            AeadAlgorithm::parse_name("aes-128-gcm"),
+            Err(CoreError::UnknownCipher(_))
+        ));
+        assert_eq!(AeadAlgorithm::from_id(0), None);
+    }
+
+    #[test]
+    fn calibration_raises_memory_then_passes() {
+        // One microsecond per KiB per pass.
+        let timer = |kdf: &KdfParams| {
+            Ok(Duration::from_micros(
+                u64::from(kdf.mem_cost_kib) * u64::from(kdf.time_cost),
+            ))
+        };
+
+        let kdf = calibrate_with(Duration::from_millis(500), 1024 * 1024, timer).unwrap();
+        assert_eq!((kdf.mem_cost_kib, kdf.time_cost), (256 * 1024, 1));
+
+        let kdf = calibrate_with(Duration::from_millis(500), 64 * 1024, timer).unwrap();
+        assert_eq!((kdf.mem_cost_kib, kdf.time_cost), (64 * 1024, 7));
+
+        assert!(calibrate_with(Duration::ZERO, 64 * 1024, timer).is_err());
+    }
 }
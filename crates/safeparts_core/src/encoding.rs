@@ -5,9 +5,15 @@
 //! while the mnemonic formats are better for paper and manual transcription.
 //! `Encoding::Auto` is accepted only by parsing functions.
 
+use base64::Engine;
+use zeroize::Zeroizing;
+
+use crate::age::{self, Identity, WrappedShare};
 use crate::error::{CoreError, CoreResult};
 use crate::packet::SharePacket;
-use crate::{ascii, mnemo_bip39, mnemo_words};
+use crate::refresh::{RefreshRound, SubShare};
+use crate::vss::Commitments;
+use crate::{ascii, codex32, mnemo_bip39, mnemo_words, secrets_js, ssss};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
@@ -65,6 +71,31 @@ impl Encoding {
 pub struct ParsedSharePackets {
     pub packets: Vec<SharePacket>,
     pub encoding: Encoding,
+    /// Armored shares wrapped to an age recipient, still encrypted.
+    pub wrapped: Vec<WrappedShare>,
+}
+
+impl ParsedSharePackets {
+    /// Unwrap every wrapped share that one of `identities` opens and append
+    /// it to `packets`.
+    ///
+    /// Shares meant for other recipients stay in `wrapped`. Returns the number
+    /// of shares unwrapped.
+    pub fn unwrap_with(&mut self, identities: &[Identity]) -> CoreResult<usize> {
+        let mut remaining = Vec::new();
+        let mut unwrapped = 0;
+        for share in self.wrapped.drain(..) {
+            match share.unwrap(identities)? {
+                Some(packet) => {
+                    self.packets.push(packet);
+                    unwrapped += 1;
+                }
+                None => remaining.push(share),
+            }
+        }
+        self.wrapped = remaining;
+        Ok(unwrapped)
+    }
 }
 
 /// Encode one share packet as text.
@@ -119,11 +150,61 @@ pub fn decode_packet(s: &str, encoding: Encoding) -> CoreResult<SharePacket> {
     }
 }
 
+/// Encode the published commitments of a verifiable split as base64url text.
+///
+/// Commitments are not secret. Share them with every custodian so each one can
+/// check their own share with [`crate::verify_share`].
+pub fn encode_commitments(commitments: &Commitments) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(commitments.to_bytes())
+}
+
+/// Decode commitments written by [`encode_commitments`].
+pub fn decode_commitments(s: &str) -> CoreResult<Commitments> {
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(s.trim())
+        .map_err(|e| CoreError::Encoding(e.to_string()))?;
+    Commitments::from_bytes(&bytes)
+}
+
+/// Encode a public refresh round as base64url text.
+pub fn encode_refresh_round(round: &RefreshRound) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(round.to_bytes())
+}
+
+/// Decode a refresh round written by [`encode_refresh_round`].
+pub fn decode_refresh_round(s: &str) -> CoreResult<RefreshRound> {
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(s.trim())
+        .map_err(|e| CoreError::Encoding(e.to_string()))?;
+    RefreshRound::from_bytes(&bytes)
+}
+
+/// Encode one refresh sub-share as base64url text.
+///
+/// Sub-shares are secret. Deliver each one only to its recipient.
+pub fn encode_sub_share(sub_share: &SubShare) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(sub_share.to_bytes())
+}
+
+/// Decode a refresh sub-share written by [`encode_sub_share`].
+pub fn decode_sub_share(s: &str) -> CoreResult<SubShare> {
+    let bytes = Zeroizing::new(
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(s.trim())
+            .map_err(|e| CoreError::Encoding(e.to_string()))?,
+    );
+    SubShare::from_bytes(&bytes)
+}
+
 /// Parse one or more share packets from pasted text.
 ///
 /// Compact encodings may be separated by any whitespace. Mnemonic shares are
 /// normally separated by lines or blank lines. The returned value includes the
 /// packets and the concrete encoding that was used.
+///
+/// Armored age blocks are collected into [`ParsedSharePackets::wrapped`]
+/// instead of being decoded. Input holding only wrapped shares parses to no
+/// packets and `Encoding::Auto`; see [`ParsedSharePackets::unwrap_with`].
 pub fn parse_share_packets(input: &str, encoding: Encoding) -> CoreResult<ParsedSharePackets> {
     parse_share_packets_with_mnemonic_lines(input, encoding, MnemonicLineMode::Shares)
 }
@@ -140,21 +221,33 @@ fn parse_share_packets_with_mnemonic_lines(
     encoding: Encoding,
     mnemonic_line_mode: MnemonicLineMode,
 ) -> CoreResult<ParsedSharePackets> {
+    let (wrapped, rest) = age::extract_armored(input)?;
+    let input = rest.as_str();
     let nonempty_lines = nonempty_lines(input);
 
     if nonempty_lines.is_empty() {
-        return Err(CoreError::EmptyShareInput);
+        if wrapped.is_empty() {
+            return Err(CoreError::EmptyShareInput);
+        }
+        return Ok(ParsedSharePackets {
+            packets: Vec::new(),
+            encoding: Encoding::Auto,
+            wrapped,
+        });
     }
 
     let encoding = if encoding.is_auto() {
-        detect_encoding_from_lines(&nonempty_lines, input)?
-            .ok_or(CoreError::CouldNotDetectEncoding)?
+        detect_encoding_from_lines(&nonempty_lines, input)?.ok_or_else(|| undetected(input))?
     } else {
         encoding
     };
 
     let packets = decode_share_packets_known(input, encoding, mnemonic_line_mode)?;
-    Ok(ParsedSharePackets { packets, encoding })
+    Ok(ParsedSharePackets {
+        packets,
+        encoding,
+        wrapped,
+    })
 }
 
 /// Try to detect the share encoding without decoding the caller's intent.
@@ -217,13 +310,13 @@ fn decode_share_packets_known(
 ) -> CoreResult<Vec<SharePacket>> {
     match encoding {
         Encoding::Auto => Err(CoreError::CouldNotDetectEncoding),
-        Encoding::MnemoWords => split_mnemonic_input(input, mnemonic_line_mode)
-            .iter()
-            .map(|block| mnemo_words::decode_packet(block))
+        Encoding::MnemoWords => (1..)
+            .zip(split_mnemonic_input(input, mnemonic_line_mode))
+            .map(|(share, block)| mnemo_words::decode_packet(&block).map_err(in_share(share)))
             .collect::<CoreResult<Vec<_>>>(),
-        Encoding::MnemoBip39 => split_mnemonic_input(input, mnemonic_line_mode)
-            .iter()
-            .map(|block| mnemo_bip39::decode_packet(block))
+        Encoding::MnemoBip39 => (1..)
+            .zip(split_mnemonic_input(input, mnemonic_line_mode))
+            .map(|(share, block)| mnemo_bip39::decode_packet(&block).map_err(in_share(share)))
             .collect::<CoreResult<Vec<_>>>(),
         Encoding::Base64url => input
             .split_whitespace()
@@ -236,6 +329,34 @@ fn decode_share_packets_known(
     }
 }
 
+/// The error for input no packet encoding reads, naming the foreign share
+/// format it is in when there is one.
+fn undetected(input: &str) -> CoreError {
+    let module = if ssss::looks_like(input) {
+        "ssss"
+    } else if codex32::looks_like(input) {
+        "codex32"
+    } else if secrets_js::looks_like(input) {
+        "secrets_js"
+    } else {
+        return CoreError::CouldNotDetectEncoding;
+    };
+    CoreError::Encoding(format!(
+        "these are {module} shares, not Safeparts share packets; combine them with {module}::combine"
+    ))
+}
+
+/// Point a [`CoreError::MisspelledWords`] from one decoded share at its
+/// position in the whole input.
+fn in_share(share: usize) -> impl Fn(CoreError) -> CoreError {
+    move |error| match error {
+        CoreError::MisspelledWords { corrections, .. } => {
+            CoreError::MisspelledWords { share, corrections }
+        }
+        error => error,
+    }
+}
+
 #[derive(Clone, Copy)]
 enum MnemonicLineMode {
     Shares,
@@ -282,17 +403,16 @@ fn split_mnemonic_blocks(input: &str) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sss::SetId;
+    use crate::sss::{RawShare, SetId};
 
     fn packet() -> SharePacket {
-        SharePacket {
+        SharePacket::from_raw_share(RawShare {
             set_id: SetId([7u8; 16]),
             k: 2,
             n: 3,
             x: 1,
-            payload: vec![1, 2, 3, 4],
-            crypto_params: None,
-        }
+            y: vec![1, 2, 3, 4],
+        })
     }
 
     #[test]
@@ -312,6 +432,61 @@ mod tests {
         );
     }
 
+    fn assert_foreign(input: &str, module: &str) {
+        assert_eq!(detect_encoding(input).unwrap(), None);
+        assert!(matches!(
+            parse_share_packets(input, Encoding::Auto),
+            Err(CoreError::Encoding(message)) if message.contains(&format!("{module}::combine"))
+        ));
+    }
+
+    #[test]
+    fn ssss_shares_are_named_but_not_decoded_as_packets() {
+        assert_foreign(
+            "1-1c41ef496eccfbeba439714085df8437236298da8dd824\n\
+             2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665\n",
+            "ssss",
+        );
+        assert!(Encoding::parse_name("ssss").is_err());
+    }
+
+    #[test]
+    fn codex32_strings_are_named_but_not_decoded_as_packets() {
+        assert_foreign(
+            "ms13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0t\n\
+             MS13CASHCACDEFGHJKLMNPQRSTUVWXYZ023949XQ35MY48DR\n",
+            "codex32",
+        );
+        assert!(Encoding::parse_name("codex32").is_err());
+    }
+
+    #[test]
+    fn secrets_js_shares_are_named_but_not_decoded_as_packets() {
+        assert_foreign(
+            "8012461f89c3f0044f2614d95c6dc2b21f6\n\
+             8028a192103f950465a05114f22dbd18be0\n",
+            "secrets_js",
+        );
+        assert!(Encoding::parse_name("secrets-js").is_err());
+    }
+
+    #[test]
+    fn misspelled_words_name_the_share_they_are_in() {
+        let first = encode_packet(&packet(), Encoding::MnemoWords).unwrap();
+        let mut second: Vec<String> = first.split_whitespace().map(str::to_string).collect();
+        second[2] = "xylophone".to_string();
+        let input = format!("{first}\n{}", second.join(" "));
+
+        let Err(CoreError::MisspelledWords { share, corrections }) =
+            parse_share_packets(&input, Encoding::Auto)
+        else {
+            panic!("expected a misspelled word");
+        };
+        assert_eq!(share, 2);
+        assert_eq!(corrections[0].position, 3);
+        assert_eq!(corrections[0].typed, "xylophone");
+    }
+
     #[test]
     fn base64url_round_trip_reports_detected_encoding() {
         let encoded = encode_packet(&packet(), Encoding::Base64url).unwrap();
@@ -320,6 +495,45 @@ mod tests {
         assert_eq!(parsed.packets, vec![packet()]);
     }
 
+    #[test]
+    fn wrapped_shares_are_detected_next_to_plain_shares() {
+        let identity = Identity::generate();
+        let wrapped = WrappedShare::wrap(&packet(), &identity.recipient()).unwrap();
+        let mut second = packet();
+        second.x = 2;
+        let plain = encode_packet(&second, Encoding::Base64url).unwrap();
+
+        let only_wrapped = parse_share_packets(&wrapped.to_armored(), Encoding::Auto).unwrap();
+        assert!(only_wrapped.packets.is_empty());
+        assert_eq!(only_wrapped.encoding, Encoding::Auto);
+
+        let input = format!("{}\n{plain}\n", wrapped.to_armored());
+        let mut parsed = parse_share_packets(&input, Encoding::Auto).unwrap();
+        assert_eq!(parsed.encoding, Encoding::Base64url);
+        assert_eq!(parsed.wrapped, vec![wrapped]);
+
+        assert_eq!(parsed.unwrap_with(&[Identity::generate()]).unwrap(), 0);
+        assert_eq!(parsed.wrapped.len(), 1);
+        assert_eq!(parsed.unwrap_with(&[identity]).unwrap(), 1);
+        assert!(parsed.wrapped.is_empty());
+        assert_eq!(parsed.packets, vec![second, packet()]);
+    }
+
+    #[test]
+    fn verifiable_packets_round_trip_every_encoding() {
+        let (packets, commitments) =
+            crate::split_secret_verifiable(b"vss", 2, 3, None, Default::default()).unwrap();
+
+        for encoding in Encoding::CONCRETE {
+            let encoded = encode_packet(&packets[0], *encoding).unwrap();
+            let decoded = decode_packet(&encoded, *encoding).unwrap();
+            assert_eq!(decoded, packets[0]);
+        }
+
+        let text = encode_commitments(&commitments);
+        assert_eq!(decode_commitments(&text).unwrap(), commitments);
+    }
+
     #[test]
     fn empty_input_is_typed_error() {
         let err = parse_share_packets("  \n\t", Encoding::Auto).unwrap_err();
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::mnemo_fix::WordCorrection;
+
 pub type CoreResult<T> = Result<T, CoreError>;
 
 #[derive(Debug, Error)]
@@ -58,6 +60,81 @@ pub enum CoreError {
     #[error("passphrase required")]
     PassphraseRequired,
 
+    #[error("new passphrase required to reshare a passphrase-protected set")]
+    NewPassphraseRequired,
+
     #[error("crypto params mismatch")]
     CryptoParamsMismatch,
+
+    #[error("share {x} does not match the published commitments")]
+    ShareVerificationFailed { x: u8 },
+
+    #[error("share commitments do not match")]
+    CommitmentsMismatch,
+
+    #[error("share packet is not verifiable")]
+    NotVerifiable,
+
+    #[error("too many corrupted shares to correct")]
+    UncorrectableShares,
+
+    #[error("shares come from different refresh epochs: {expected} and {found}")]
+    EpochMismatch { expected: u32, found: u32 },
+
+    #[error("invalid refresh round: {0}")]
+    InvalidRefresh(String),
+
+    #[error("invalid policy: {0}")]
+    InvalidPolicy(String),
+
+    #[error("access policy not satisfied; still missing {}", .missing.join("; "))]
+    PolicyNotSatisfied { missing: Vec<String> },
+
+    #[error("i/o error: {0}")]
+    Io(String),
+
+    #[error("share headers of one set disagree on {0}")]
+    HeaderMismatch(&'static str),
+
+    #[error("invalid key-derivation parameters: {0}")]
+    InvalidKdfParams(String),
+
+    #[error("unknown cipher: {0}")]
+    UnknownCipher(String),
+
+    #[error("invalid age key: {0}")]
+    InvalidKey(String),
+
+    #[error("share {x} is locked; unlock it with its own passphrase")]
+    ShareLocked { x: u8 },
+
+    #[error("wrong passphrase for share {x}")]
+    ShareUnlockFailed { x: u8 },
+
+    #[error("key slot: {0}")]
+    KeySlot(String),
+
+    #[error("invalid padding: {0}")]
+    InvalidPadding(String),
+
+    #[error("invalid compression: {0}")]
+    InvalidCompression(String),
+
+    #[error(
+        "{m} stealth share(s) did not recover a secret; more may be needed, or some are from another set"
+    )]
+    StealthNotRecovered { m: usize },
+
+    #[error("SLIP-39: {0}")]
+    Slip39(String),
+
+    #[error("codex32: {0}")]
+    Codex32(String),
+
+    #[error("share {share}: {}", crate::mnemo_fix::describe(.corrections))]
+    MisspelledWords {
+        /// 1-based position of the share in the input.
+        share: usize,
+        corrections: Vec<WordCorrection>,
+    },
 }
@@ -0,0 +1,167 @@
+//! Rabin information dispersal over GF(256).
+//!
+//! Data is cut into columns of `k` bytes. Each column is read as the
+//! coefficients of a polynomial of degree below `k`, and the fragment for `x`
+//! holds that polynomial evaluated at `x`. Any `k` fragments recover the data,
+//! and each fragment is `ceil(len / k)` bytes.
+//!
+//! Dispersal adds redundancy, not secrecy: fewer than `k` fragments still leak
+//! information about the data. [`crate::split_secret_large`] encrypts first and
+//! shares only the key with [`crate::sss`].
+
+use crate::error::{CoreError, CoreResult};
+use crate::gf256::Gf256;
+use crate::sss::{self, RawShare, SetId};
+
+/// Length of each fragment when dispersing `data_len` bytes with threshold `k`.
+pub fn fragment_len(data_len: usize, k: u8) -> usize {
+    data_len.div_ceil(k.max(1) as usize)
+}
+
+/// Disperse `data` into `n` fragments with x = 1..=n, any `k` of which recover it.
+pub fn disperse(data: &[u8], k: u8, n: u8, set_id: SetId) -> CoreResult<Vec<RawShare>> {
+    if k == 0 || n == 0 || k > n {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+
+    let shares = (1..=n)
+        .map(|x| {
+            // One table per coefficient, multiplying by x^j.
+            let mut x_pow = Gf256(1);
+            let tables: Vec<[u8; 256]> = (0..k)
+                .map(|_| {
+                    let table = mul_table(x_pow);
+                    x_pow = x_pow * Gf256(x);
+                    table
+                })
+                .collect();
+
+            // A short last column is implicitly padded with zero coefficients.
+            let y = data
+                .chunks(k as usize)
+                .map(|coeffs| {
+                    coeffs
+                        .iter()
+                        .zip(&tables)
+                        .fold(0u8, |acc, (&coef, table)| acc ^ table[coef as usize])
+                })
+                .collect();
+
+            RawShare { set_id, k, n, x, y }
+        })
+        .collect();
+
+    Ok(shares)
+}
+
+/// Rebuild `data_len` bytes of dispersed data from at least `k` fragments.
+///
+/// Uses the first `k` fragments; surplus fragments are only checked for
+/// consistent metadata.
+pub fn recover(shares: &[RawShare], data_len: usize) -> CoreResult<Vec<u8>> {
+    sss::check_shares(shares)?;
+    let k = shares[0].k;
+    let base = &shares[..k as usize];
+    let len = fragment_len(data_len, k);
+    if base[0].y.len() != len {
+        return Err(CoreError::InconsistentMetadata);
+    }
+
+    let x_values: Vec<Gf256> = base.iter().map(|share| Gf256(share.x)).collect();
+    let tables: Vec<Vec<[u8; 256]>> = basis_coefficients(&x_values)?
+        .into_iter()
+        .map(|row| row.into_iter().map(mul_table).collect())
+        .collect();
+
+    let mut data = Vec::with_capacity(len * k as usize);
+    for column in 0..len {
+        for row in &tables {
+            data.push(row.iter().zip(base).fold(0u8, |acc, (table, share)| {
+                acc ^ table[share.y[column] as usize]
+            }));
+        }
+    }
+    data.truncate(data_len);
+    Ok(data)
+}
+
+/// Coefficients of the Lagrange basis polynomials for `x_values`.
+///
+/// Entry `[j][i]` is the coefficient of `x^j` in the basis polynomial that is
+/// one at `x_values[i]` and zero at the others, so row `j` maps the fragment
+/// values of one column to its `j`-th data byte.
+fn basis_coefficients(x_values: &[Gf256]) -> CoreResult<Vec<Vec<Gf256>>> {
+    let k = x_values.len();
+    let mut out = vec![vec![Gf256(0); k]; k];
+
+    for (i, &xi) in x_values.iter().enumerate() {
+        let mut poly = vec![Gf256(1)];
+        let mut denominator = Gf256(1);
+        for (m, &xm) in x_values.iter().enumerate() {
+            if m == i {
+                continue;
+            }
+            // Multiply by (x - xm); subtraction is addition in GF(256).
+            let mut next = vec![Gf256(0); poly.len() + 1];
+            for (degree, &coef) in poly.iter().enumerate() {
+                next[degree + 1] = next[degree + 1] + coef;
+                next[degree] = next[degree] + coef * xm;
+            }
+            poly = next;
+            denominator = denominator * (xi - xm);
+        }
+
+        let scale = denominator.inv()?;
+        for (row, &coef) in out.iter_mut().zip(&poly) {
+            row[i] = coef * scale;
+        }
+    }
+
+    Ok(out)
+}
+
+fn mul_table(factor: Gf256) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (byte, entry) in table.iter_mut().enumerate() {
+        *entry = (factor * Gf256(byte as u8)).0;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_k_fragments_recover_the_data() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let shares = disperse(&data, 3, 7, SetId([1u8; 16])).unwrap();
+        assert!(shares.iter().all(|share| share.y.len() == 334));
+
+        for picked in [[0usize, 1, 2], [6, 3, 0], [2, 4, 5]] {
+            let subset: Vec<RawShare> = picked.iter().map(|&i| shares[i].clone()).collect();
+            assert_eq!(recover(&subset, data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn threshold_one_fragments_are_copies() {
+        let shares = disperse(b"copy", 1, 3, SetId([2u8; 16])).unwrap();
+        assert!(shares.iter().all(|share| share.y == b"copy"));
+        assert_eq!(recover(&shares[2..], 4).unwrap(), b"copy");
+    }
+
+    #[test]
+    fn recover_rejects_wrong_lengths_and_too_few_fragments() {
+        let shares = disperse(b"dispersed data", 2, 3, SetId([3u8; 16])).unwrap();
+
+        assert!(matches!(
+            recover(&shares[..1], 14),
+            Err(CoreError::NotEnoughShares { k: 2, m: 1 })
+        ));
+        assert!(matches!(
+            recover(&shares[..2], 20),
+            Err(CoreError::InconsistentMetadata)
+        ));
+    }
+}
@@ -19,20 +19,39 @@
 //! }
 //! ```
 
+pub mod age;
 pub mod ascii;
+pub mod codex32;
+pub mod compression;
 pub mod crypto;
 pub mod encoding;
 pub mod error;
 pub mod gf256;
+pub mod ida;
+pub mod metadata;
 pub mod mnemo_bip39;
+pub mod mnemo_fix;
 pub mod mnemo_words;
 pub mod packet;
+pub mod padding;
+pub mod policy;
+pub mod refresh;
+pub mod secrets_js;
+pub mod slip39;
 pub mod sss;
+pub mod ssss;
+pub mod stream;
+pub mod vault;
+pub mod vss;
+
+use zeroize::Zeroizing;
 
 pub use crate::error::{CoreError, CoreResult};
 
 pub const INTEGRITY_TAG_LEN: usize = 32;
 
+const INTEGRITY_TAG_CONTEXT: &str = "safeparts 2026-10 v3 packet integrity tag";
+
 /// Split plaintext bytes into `n` share packets with a threshold of `k`.
 ///
 /// This is a compatibility wrapper around [`split_secret`] without passphrase
@@ -79,30 +98,669 @@ pub fn split_secret(
     n: u8,
     passphrase: Option<&[u8]>,
 ) -> CoreResult<Vec<packet::SharePacket>> {
-    let (mut data_to_split, crypto_params) = if let Some(passphrase) = passphrase {
-        let (ciphertext, params) = crypto::encrypt(secret, passphrase)?;
-        (ciphertext, Some(params))
+    split_secret_with_suite(secret, k, n, passphrase, crypto::CipherSuite::default())
+}
+
+/// Like [`split_secret`], with the Argon2id costs and AEAD used for the
+/// passphrase.
+///
+/// `suite` is ignored without a passphrase. For its costs pick a named
+/// profile such as [`crypto::KdfParams::INTERACTIVE`], explicit values through
+/// [`crypto::KdfParams::new`], or a result of [`crypto::calibrate`]; for its
+/// cipher any [`crypto::AeadAlgorithm`]. Combine reads both back from the
+/// packets.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::crypto::{AeadAlgorithm, CipherSuite, KdfParams};
+/// use safeparts_core::{combine_shares, split_secret_with_suite, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let suite = CipherSuite {
+///         kdf: KdfParams::INTERACTIVE,
+///         aead: AeadAlgorithm::Aes256GcmSiv,
+///     };
+///     let shares = split_secret_with_suite(b"example secret", 2, 3, Some(b"pw"), suite)?;
+///     let recovered = combine_shares(&shares[..2], Some(b"pw"))?;
+///
+///     assert_eq!(recovered, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn split_secret_with_suite(
+    secret: &[u8],
+    k: u8,
+    n: u8,
+    passphrase: Option<&[u8]>,
+    suite: crypto::CipherSuite,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    split_secret_with_options(secret, k, n, passphrase, &suite.into())
+}
+
+/// What [`split_secret_with_options`] does to the secret before sharing it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SplitOptions {
+    /// Argon2id costs and AEAD, as in [`split_secret_with_suite`].
+    pub suite: crypto::CipherSuite,
+    /// Stored inside the secret, as in [`split_secret_with_metadata`].
+    pub metadata: Option<metadata::SecretMetadata>,
+    /// Length-hiding padding, see [`padding::Padding`].
+    pub padding: padding::Padding,
+    /// Compression applied before padding, see [`compression::Compression`].
+    pub compression: compression::Compression,
+    /// Write the shares in the compact packet encoding, with a short set id
+    /// and a nonce derived from the salt; see [`packet::SharePacket::compact`].
+    pub compact: bool,
+    /// Write stealth packets, which hide `k`, `n`, and the set id and use
+    /// random indexes; see [`packet::SharePacket::stealth`]. Implies `compact`.
+    pub stealth: bool,
+}
+
+impl From<crypto::CipherSuite> for SplitOptions {
+    fn from(suite: crypto::CipherSuite) -> Self {
+        Self {
+            suite,
+            ..Self::default()
+        }
+    }
+}
+
+/// Like [`split_secret`], with every option of a flat split.
+///
+/// Metadata, compression, and padding are applied before encryption and the
+/// integrity tag, so they are only visible once `k` shares are combined.
+/// [`combine_shares`] undoes all three; [`reshare`] keeps them, padding to the
+/// same length. `compact` only changes how the packets are encoded, and
+/// [`reshare`] keeps it too. `stealth` packets hide `k`, `n`, and the set id;
+/// combine finds the threshold by trying each one against the integrity tag.
+///
+/// Every stealth set has the same all-zero set id, so shares of two
+/// unprotected stealth sets with secrets of one length are combined as a
+/// single set. That fails with [`CoreError::StealthNotRecovered`], or, when
+/// one set has enough shares on its own, recovers that set's secret and
+/// reports the other set's shares only as suspects in
+/// [`combine_shares_robust`]. Keep stealth sets apart.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::padding::Padding;
+/// use safeparts_core::{combine_shares, split_secret_with_options, CoreResult, SplitOptions};
+///
+/// fn main() -> CoreResult<()> {
+///     let options = SplitOptions {
+///         padding: Padding::PowerOfTwo,
+///         ..SplitOptions::default()
+///     };
+///     let short = split_secret_with_options(b"short", 2, 3, None, &options)?;
+///     let longer = split_secret_with_options(b"a longer secret", 2, 3, None, &options)?;
+///
+///     assert_eq!(short[0].payload.len(), longer[0].payload.len());
+///     assert_eq!(combine_shares(&short[..2], None)?, b"short");
+///     Ok(())
+/// }
+/// ```
+pub fn split_secret_with_options(
+    secret: &[u8],
+    k: u8,
+    n: u8,
+    passphrase: Option<&[u8]>,
+    options: &SplitOptions,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    // Stealth sets bind a zero threshold and share count, the values their
+    // packets carry.
+    let (header_k, header_n) = if options.stealth { (0, 0) } else { (k, n) };
+    let Protected {
+        set_id,
+        data,
+        crypto_params,
+        marks,
+    } = protect_and_tag(secret, passphrase, options, header_k, header_n, None)?;
+    let shares = if options.stealth {
+        if k == 0 || n == 0 || k > n {
+            return Err(CoreError::InvalidKAndN { k, n });
+        }
+        sss::split_at(&data, k, &sss::random_xs(n), set_id)?
     } else {
-        (secret.to_vec(), None)
+        sss::split(&data, k, n, set_id)?
+    };
+
+    Ok(shares
+        .into_iter()
+        .map(|mut share| {
+            if options.stealth {
+                share.k = 0;
+                share.n = 0;
+            }
+            packet::SharePacket::from_raw_share(share)
+                .with_crypto_params(crypto_params)
+                .with_metadata(marks.metadata)
+                .with_padded(marks.padded)
+                .with_compressed(marks.compressed)
+                .with_compact(options.compact || options.stealth)
+                .with_stealth(options.stealth)
+                .with_authenticated_header(true)
+        })
+        .collect())
+}
+
+/// Like [`split_secret_with_suite`], storing `metadata` with the secret.
+///
+/// The metadata is encrypted and tagged together with the secret, so a single
+/// share reveals nothing about it. [`combine_shares`] returns the secret
+/// alone; [`combine_shares_with_metadata`] and [`combine_shares_robust`] also
+/// return the metadata. [`reshare`] carries it over to the new set.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::metadata::SecretMetadata;
+/// use safeparts_core::{combine_shares_with_metadata, split_secret_with_metadata, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let metadata = SecretMetadata {
+///         filename: Some("wallet.dat".to_string()),
+///         ..SecretMetadata::now()
+///     };
+///     let shares =
+///         split_secret_with_metadata(b"example secret", 2, 3, None, Default::default(), &metadata)?;
+///     let recovered = combine_shares_with_metadata(&shares[..2], None)?;
+///
+///     assert_eq!(recovered.secret, b"example secret");
+///     assert_eq!(recovered.metadata, Some(metadata));
+///     Ok(())
+/// }
+/// ```
+pub fn split_secret_with_metadata(
+    secret: &[u8],
+    k: u8,
+    n: u8,
+    passphrase: Option<&[u8]>,
+    suite: crypto::CipherSuite,
+    metadata: &metadata::SecretMetadata,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    let options = SplitOptions {
+        suite,
+        metadata: Some(metadata.clone()),
+        ..SplitOptions::default()
     };
+    split_secret_with_options(secret, k, n, passphrase, &options)
+}
 
-    let tag = blake3::hash(&data_to_split);
-    data_to_split.extend_from_slice(tag.as_bytes());
+/// Split with several independent passphrases, any one of which opens the
+/// secret, LUKS-style.
+///
+/// The secret is encrypted once under a random data key, and each
+/// `(label, passphrase)` in `slots` wraps that key with its own Argon2id
+/// salt, using `suite`'s costs and AEAD. [`combine_shares`] accepts the
+/// passphrase of any slot. The slot table travels inside the shared data, so
+/// [`list_key_slots`] reads it once `k` packets are present, and
+/// [`reshare_with_key_slots`] adds or removes slots.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::crypto::{CipherSuite, KdfParams};
+/// use safeparts_core::{combine_shares, split_secret_with_key_slots, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let slots: [(&str, &[u8]); 2] = [("ops", b"ops pass"), ("break-glass", b"bg pass")];
+///     let suite = CipherSuite::from(KdfParams::INTERACTIVE);
+///     let shares = split_secret_with_key_slots(b"example secret", 2, 3, &slots, suite)?;
+///
+///     assert_eq!(combine_shares(&shares[..2], Some(b"ops pass"))?, b"example secret");
+///     assert_eq!(combine_shares(&shares[1..], Some(b"bg pass"))?, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn split_secret_with_key_slots(
+    secret: &[u8],
+    k: u8,
+    n: u8,
+    slots: &[(&str, &[u8])],
+    suite: crypto::CipherSuite,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    let (envelope, key) = crypto::SlotEnvelope::new(slots, suite)?;
+    split_envelope(secret, envelope, &key, k, n)
+}
 
+/// Encrypt `secret` into `envelope` under a new set header, then split the
+/// envelope and its integrity tag.
+fn split_envelope(
+    secret: &[u8],
+    mut envelope: crypto::SlotEnvelope,
+    key: &crypto::SlotKey,
+    k: u8,
+    n: u8,
+) -> CoreResult<Vec<packet::SharePacket>> {
     let set_id = sss::SetId::random();
-    let shares = sss::split(&data_to_split, k, n, set_id)?;
+    let header = packet::set_header(
+        set_id,
+        k,
+        n,
+        None,
+        packet::SetMarks {
+            key_slots: true,
+            ..Default::default()
+        },
+        None,
+    );
+    envelope.encrypt(key, secret, &header)?;
+
+    let mut data = Zeroizing::new(envelope.to_bytes());
+    let tag = integrity_tag(Some(&header), &data);
+    data.extend_from_slice(tag.as_bytes());
+    let shares = sss::split(&data, k, n, set_id)?;
+
+    Ok(shares
+        .into_iter()
+        .map(|share| {
+            packet::SharePacket::from_raw_share(share)
+                .with_key_slots(true)
+                .with_authenticated_header(true)
+        })
+        .collect())
+}
+
+/// Split secret bytes into share packets that custodians can verify alone.
+///
+/// This behaves like [`split_secret`], but shares the data with
+/// [`vss::split`] and returns the set's [`vss::Commitments`] next to the
+/// packets. Publish the commitments, or their fingerprint, to every custodian
+/// through a channel the dealer cannot rewrite per custodian. Each custodian
+/// can then call [`verify_share`] on their own packet without any other share.
+/// `suite` sets the passphrase encryption as in [`split_secret_with_suite`].
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::{combine_shares, split_secret_verifiable, verify_share, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let (shares, commitments) = split_secret_verifiable(b"example secret", 2, 3, None, Default::default())?;
+///     verify_share(&shares[1], &commitments)?;
+///
+///     let recovered = combine_shares(&shares[..2], None)?;
+///     assert_eq!(recovered, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn split_secret_verifiable(
+    secret: &[u8],
+    k: u8,
+    n: u8,
+    passphrase: Option<&[u8]>,
+    suite: crypto::CipherSuite,
+) -> CoreResult<(Vec<packet::SharePacket>, vss::Commitments)> {
+    let Protected {
+        set_id,
+        data: data_to_split,
+        crypto_params,
+        ..
+    } = protect_and_tag(secret, passphrase, &suite.into(), k, n, None)?;
+    let (shares, commitments) = vss::split(&data_to_split, k, n, set_id)?;
+
+    let packets = shares
+        .into_iter()
+        .map(|share| {
+            packet::SharePacket::from_raw_share(share)
+                .with_crypto_params(crypto_params)
+                .with_commitments(Some(commitments.clone()))
+                .with_authenticated_header(true)
+        })
+        .collect();
+
+    Ok((packets, commitments))
+}
+
+/// Split secret bytes across the holders of an access-structure policy.
+///
+/// Returns one packet per holder, in the order of [`policy::Policy::holders`].
+/// Recovery with [`combine_shares`] succeeds once the supplied packets satisfy
+/// the policy; otherwise it returns [`CoreError::PolicyNotSatisfied`] naming
+/// the groups that are still missing. `suite` sets the passphrase encryption
+/// as in [`split_secret_with_suite`].
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::policy::Policy;
+/// use safeparts_core::{combine_shares, split_secret_with_policy, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let policy = Policy::parse("all of (ops: 2 of (a, b, c), legal: 1 of (d, e))")?;
+///     let shares = split_secret_with_policy(b"example secret", &policy, None, Default::default())?;
+///
+///     let recovered = combine_shares(&[shares[0].clone(), shares[2].clone(), shares[4].clone()], None)?;
+///     assert_eq!(recovered, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn split_secret_with_policy(
+    secret: &[u8],
+    policy: &policy::Policy,
+    passphrase: Option<&[u8]>,
+    suite: crypto::CipherSuite,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    let Protected {
+        set_id,
+        data: data_to_split,
+        crypto_params,
+        ..
+    } = protect_and_tag(secret, passphrase, &suite.into(), 0, 0, Some(policy))?;
+    let shares = policy::split(&data_to_split, policy, set_id)?;
 
     Ok(shares
         .into_iter()
-        .map(|share| packet::SharePacket::from_raw_share(share).with_crypto_params(crypto_params))
+        .map(|entry| {
+            let placement = policy::Placement {
+                policy: policy.clone(),
+                path: entry.path,
+            };
+            packet::SharePacket::from_raw_share(entry.share)
+                .with_crypto_params(crypto_params)
+                .with_policy(Some(placement))
+                .with_authenticated_header(true)
+        })
+        .collect())
+}
+
+/// Split a large secret into shares of about `|secret| / k` bytes each.
+///
+/// [`split_secret`] makes every share as large as the secret. This mode
+/// (Krawczyk's computational secret sharing) encrypts the protected secret
+/// under a fresh random key, shares only the key with [`sss`], and disperses
+/// the ciphertext with [`ida`]. Each packet carries one key share and one
+/// ciphertext fragment. Recovery with [`combine_shares`] needs the same `k`
+/// packets and passphrase as a plain split. `suite` sets the passphrase
+/// encryption as in [`split_secret_with_suite`].
+///
+/// The secrecy of the ciphertext rests on ChaCha20-Poly1305 rather than on
+/// the information-theoretic guarantee of plain Shamir shares.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::{combine_shares, split_secret_large, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let secret = vec![7u8; 30_000];
+///     let shares = split_secret_large(&secret, 3, 7, None, Default::default())?;
+///     assert!(shares[0].payload.len() < 11_000);
+///
+///     let recovered = combine_shares(&shares[2..5], None)?;
+///     assert_eq!(recovered, secret);
+///     Ok(())
+/// }
+/// ```
+pub fn split_secret_large(
+    secret: &[u8],
+    k: u8,
+    n: u8,
+    passphrase: Option<&[u8]>,
+    suite: crypto::CipherSuite,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    if k == 0 || n == 0 || k > n {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+    let Protected {
+        set_id,
+        data: data_to_split,
+        crypto_params,
+        ..
+    } = protect_and_tag(secret, passphrase, &suite.into(), k, n, None)?;
+
+    let key = crypto::random_key();
+    let ciphertext = crypto::encrypt_with_key(&data_to_split, &key)?;
+    let dispersed_len = ciphertext.len() as u64;
+
+    let key_shares = sss::split(key.as_ref(), k, n, set_id)?;
+    let fragments = ida::disperse(&ciphertext, k, n, set_id)?;
+
+    Ok(key_shares
+        .into_iter()
+        .zip(fragments)
+        .map(|(mut share, fragment)| {
+            share.y.extend_from_slice(&fragment.y);
+            packet::SharePacket::from_raw_share(share)
+                .with_crypto_params(crypto_params)
+                .with_dispersed_len(Some(dispersed_len))
+                .with_authenticated_header(true)
+        })
         .collect())
 }
 
+/// Check that one packet from a verifiable split lies on the committed
+/// polynomials.
+///
+/// `commitments` should come from the dealer's published copy, not from the
+/// packet itself. The packet's embedded commitments must match them, so a
+/// dealer cannot hand one custodian a private, self-consistent set.
+pub fn verify_share(
+    packet: &packet::SharePacket,
+    commitments: &vss::Commitments,
+) -> CoreResult<()> {
+    if packet.is_locked() {
+        return Err(CoreError::ShareLocked { x: packet.x });
+    }
+    let embedded = packet
+        .commitments
+        .as_ref()
+        .ok_or(CoreError::NotVerifiable)?;
+    if embedded != commitments {
+        return Err(CoreError::CommitmentsMismatch);
+    }
+
+    vss::verify(&packet.to_raw_share()?, commitments)
+}
+
+/// Lock one share's payload under that share's own passphrase.
+///
+/// Unlike the split passphrase, which protects the whole secret and is shared
+/// by every custodian, a share lock protects a single share: each custodian
+/// can pick their own PIN or passphrase. The lock carries its own salt and
+/// binds the set header and the share index, so a passphrase unlocks only the
+/// share it was set on. Locked packets do not combine until they are
+/// unlocked with [`unlock_share`] or [`unlock_shares`].
+///
+/// Only version 3 packets can be locked; re-split older sets first.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::crypto::{CipherSuite, KdfParams};
+/// use safeparts_core::{combine_shares, lock_share, split_secret, unlock_share, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let shares = split_secret(b"example secret", 2, 3, None)?;
+///     let suite = CipherSuite::from(KdfParams::INTERACTIVE);
+///     let locked = lock_share(&shares[0], b"1234", suite)?;
+///     assert!(combine_shares(&[locked.clone(), shares[1].clone()], None).is_err());
+///
+///     let unlocked = unlock_share(&locked, b"1234")?;
+///     let recovered = combine_shares(&[unlocked, shares[1].clone()], None)?;
+///     assert_eq!(recovered, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn lock_share(
+    packet: &packet::SharePacket,
+    passphrase: &[u8],
+    suite: crypto::CipherSuite,
+) -> CoreResult<packet::SharePacket> {
+    if packet.is_locked() {
+        return Err(CoreError::InvalidPacket(
+            "share is already locked".to_string(),
+        ));
+    }
+    if !packet.authenticated_header {
+        return Err(CoreError::InvalidPacket(
+            "only version 3 packets can be locked".to_string(),
+        ));
+    }
+    suite.kdf.validate()?;
+
+    let lock = crypto::CryptoParams::random(suite);
+    let payload = crypto::encrypt_with_aad(&packet.payload, passphrase, &lock, &lock_aad(packet))?;
+    Ok(packet::SharePacket {
+        payload,
+        share_lock: Some(lock),
+        ..packet.clone()
+    })
+}
+
+/// Remove the per-share lock set by [`lock_share`].
+///
+/// A wrong passphrase returns [`CoreError::ShareUnlockFailed`] naming the
+/// share, so callers can ask again for that share only.
+pub fn unlock_share(
+    packet: &packet::SharePacket,
+    passphrase: &[u8],
+) -> CoreResult<packet::SharePacket> {
+    let lock = packet
+        .share_lock
+        .ok_or_else(|| CoreError::InvalidPacket("share is not locked".to_string()))?;
+    let payload = crypto::decrypt_with_aad(&packet.payload, passphrase, &lock, &lock_aad(packet))
+        .map_err(|err| match err {
+        CoreError::DecryptFailed => CoreError::ShareUnlockFailed { x: packet.x },
+        other => other,
+    })?;
+    Ok(packet::SharePacket {
+        payload,
+        share_lock: None,
+        ..packet.clone()
+    })
+}
+
+/// Unlock every locked packet with a passphrase chosen per share.
+///
+/// `passphrase_for` is called once for each locked packet, in input order.
+/// Returning `None` skips that share, which is dropped from the result;
+/// unlocked packets pass through unchanged.
+pub fn unlock_shares<F>(
+    packets: &[packet::SharePacket],
+    mut passphrase_for: F,
+) -> CoreResult<Vec<packet::SharePacket>>
+where
+    F: FnMut(&packet::SharePacket) -> Option<Zeroizing<Vec<u8>>>,
+{
+    let mut out = Vec::with_capacity(packets.len());
+    for packet in packets {
+        if !packet.is_locked() {
+            out.push(packet.clone());
+        } else if let Some(passphrase) = passphrase_for(packet) {
+            out.push(unlock_share(packet, &passphrase)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Associated data of a share lock: the set header plus the share's own
+/// index and epoch.
+fn lock_aad(packet: &packet::SharePacket) -> Vec<u8> {
+    let mut aad = packet.set_header();
+    aad.push(packet.x);
+    aad.extend_from_slice(&packet.epoch.to_be_bytes());
+    aad
+}
+
+/// Prepend the metadata and append the padding of `options`, encrypt when a
+/// passphrase is given, then append the integrity tag.
+///
+/// Both bind the set header of the packets about to be created, under a new
+/// set id, so the packets are written as version 3 or 4. The id is random,
+/// short for compact sets, and zero for stealth sets.
+fn protect_and_tag(
+    secret: &[u8],
+    passphrase: Option<&[u8]>,
+    options: &SplitOptions,
+    k: u8,
+    n: u8,
+    policy: Option<&policy::Policy>,
+) -> CoreResult<Protected> {
+    let suite = options.suite;
+    if passphrase.is_some() {
+        suite.kdf.validate()?;
+    }
+    let set_id = if options.stealth {
+        sss::SetId([0; 16])
+    } else if options.compact {
+        sss::SetId::random_short()
+    } else {
+        sss::SetId::random()
+    };
+    let crypto_params = passphrase.map(|_| {
+        if options.compact || options.stealth {
+            crypto::CryptoParams::random_derived(suite)
+        } else {
+            crypto::CryptoParams::random(suite)
+        }
+    });
+
+    let wrapped = match &options.metadata {
+        Some(metadata) => metadata.wrap(secret)?,
+        None => Zeroizing::new(secret.to_vec()),
+    };
+    let compressed = options.compression.compress(&wrapped)?;
+    let marks = packet::SetMarks {
+        metadata: options.metadata.is_some(),
+        padded: !options.padding.is_none(),
+        compressed: compressed.is_some(),
+        ..Default::default()
+    };
+    let header = packet::set_header(set_id, k, n, crypto_params.as_ref(), marks, policy);
+
+    let wrapped = compressed.unwrap_or(wrapped);
+    let padded = match options.padding {
+        padding::Padding::None => wrapped,
+        padding => padding.pad(&wrapped)?,
+    };
+    let data = match (passphrase, &crypto_params) {
+        (Some(passphrase), Some(params)) => {
+            crypto::encrypt_with_aad(&padded, passphrase, params, &header)?
+        }
+        _ => padded.to_vec(),
+    };
+
+    let mut data = Zeroizing::new(data);
+    let tag = integrity_tag(Some(&header), &data);
+    data.extend_from_slice(tag.as_bytes());
+    Ok(Protected {
+        set_id,
+        data,
+        crypto_params,
+        marks,
+    })
+}
+
+/// Protected data of a new set, ready to share.
+struct Protected {
+    set_id: sss::SetId,
+    data: Zeroizing<Vec<u8>>,
+    crypto_params: Option<crypto::CryptoParams>,
+    marks: packet::SetMarks,
+}
+
+/// BLAKE3 tag over combined data, keyed to the set header for version 3
+/// packets and plain for older ones.
+fn integrity_tag(header: Option<&[u8]>, data: &[u8]) -> blake3::Hash {
+    match header {
+        None => blake3::hash(data),
+        Some(header) => blake3::Hasher::new_derive_key(INTEGRITY_TAG_CONTEXT)
+            .update(&(header.len() as u64).to_be_bytes())
+            .update(header)
+            .update(data)
+            .finalize(),
+    }
+}
+
 /// Recover the original secret bytes from share packets.
 ///
 /// The input must contain at least `k` packets from the same split set. Extra
 /// packets are allowed, but duplicate share coordinates or mixed metadata cause
-/// typed errors.
+/// typed errors. Packets from a verifiable split are each checked against the
+/// set's commitments first, so a bad share is reported by its index.
 ///
 /// Pass `Some(passphrase)` when the shares were created with passphrase
 /// protection. Passing `None` for encrypted shares returns
@@ -126,40 +784,1408 @@ pub fn combine_shares(
     packets: &[packet::SharePacket],
     passphrase: Option<&[u8]>,
 ) -> CoreResult<Vec<u8>> {
-    if packets.is_empty() {
-        return Err(CoreError::NotEnoughShares { k: 1, m: 0 });
+    combine_shares_with_metadata(packets, passphrase).map(|recovered| recovered.secret)
+}
+
+/// Recovered secret plus the metadata stored with it, if any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recovered {
+    pub secret: Vec<u8>,
+    /// Present for sets from [`split_secret_with_metadata`].
+    pub metadata: Option<metadata::SecretMetadata>,
+    /// Length of the data before its padding was removed, for padded sets.
+    pub padded_len: Option<usize>,
+}
+
+/// Like [`combine_shares`], also returning the metadata stored by
+/// [`split_secret_with_metadata`] and the padded length.
+pub fn combine_shares_with_metadata(
+    packets: &[packet::SharePacket],
+    passphrase: Option<&[u8]>,
+) -> CoreResult<Recovered> {
+    let (_, commitments) = set_params(packets)?;
+    if let Some(combined) = combine_policy(packets)?.or(combine_dispersed(packets)?) {
+        return open_combined(&combined, &packets[0], passphrase);
+    }
+    if packets[0].stealth {
+        return combine_stealth(packets, passphrase).map(|(recovered, _)| recovered);
     }
+    let shares = raw_shares(packets)?;
 
-    let crypto_params = packets[0].crypto_params;
-    for p in packets {
-        if p.crypto_params != crypto_params {
-            return Err(CoreError::CryptoParamsMismatch);
+    let combined = match commitments {
+        None => sss::combine(&shares)?,
+        Some(commitments) => {
+            sss::check_shares(&shares)?;
+            for share in &shares {
+                vss::verify(share, commitments)?;
+            }
+            vss::combine(&shares)?
         }
-    }
+    };
 
-    let shares: Vec<sss::RawShare> = packets
-        .iter()
-        .map(packet::SharePacket::to_raw_share)
-        .collect::<CoreResult<_>>()?;
+    open_combined(&combined, &packets[0], passphrase)
+}
 
-    let combined = sss::combine(&shares)?;
-    if combined.len() < INTEGRITY_TAG_LEN {
-        return Err(CoreError::InvalidCombinedLength {
-            len: combined.len(),
-        });
+/// Issue a new share of an existing set from at least `k` of its packets.
+///
+/// The new packet evaluates the set's share polynomial at `new_x`, so it
+/// combines with every share already handed out. Use it to replace a lost
+/// share, by passing the lost share's index, or to add a custodian. The
+/// secret is never returned, and no passphrase is needed: the new packet keeps
+/// the set id, threshold, crypto parameters, and commitments of the inputs.
+///
+/// `n` is advisory once a set has been extended. Version 3 packets bind `n`
+/// into the integrity tag, so the new packet keeps the set's `n` even when
+/// `new_x` is larger. Older packets record `max(n, new_x)`, and combine
+/// accepts their differing `n`.
+///
+/// Large-secret packets from [`split_secret_large`] extend the same way: key
+/// shares and ciphertext fragments are both polynomial evaluations at `x`.
+///
+/// Shares from a verifiable split are checked against their commitments
+/// first. Plain shares cannot be checked here, so a corrupted input yields a
+/// corrupted new share; confirm the input shares combine before relying on it.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::{combine_shares, derive_share, split_secret, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let shares = split_secret(b"example secret", 2, 3, None)?;
+///     let fourth = derive_share(&shares[..2], 4)?;
+///
+///     let recovered = combine_shares(&[shares[2].clone(), fourth], None)?;
+///     assert_eq!(recovered, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn derive_share(packets: &[packet::SharePacket], new_x: u8) -> CoreResult<packet::SharePacket> {
+    let (crypto_params, commitments) = set_params(packets)?;
+    if packets[0].policy.is_some() {
+        return Err(CoreError::InvalidPolicy(
+            "shares of a policy split cannot be extended".to_string(),
+        ));
+    }
+    if packets[0].stealth {
+        return Err(CoreError::InvalidPacket(
+            "stealth shares hide their threshold and cannot be extended".to_string(),
+        ));
+    }
+    let shares = raw_shares(packets)?;
+
+    let mut share = match commitments {
+        None => sss::derive(&shares, new_x)?,
+        Some(commitments) => {
+            sss::check_shares(&shares)?;
+            for share in &shares {
+                vss::verify(share, commitments)?;
+            }
+            vss::derive(&shares, new_x)?
+        }
+    };
+    // `n` is bound into the integrity tag of version 3 sets, so it stays put
+    // even when `new_x` lies beyond it.
+    if packets[0].authenticated_header {
+        share.n = packets[0].n;
+    }
+
+    Ok(packet::SharePacket::from_raw_share(share)
+        .with_crypto_params(crypto_params)
+        .with_commitments(commitments.cloned())
+        .with_parent_set_id(packets[0].parent_set_id)
+        .with_epoch(packets[0].epoch)
+        .with_dispersed_len(packets[0].dispersed_len)
+        .with_key_slots(packets[0].key_slots)
+        .with_metadata(packets[0].metadata)
+        .with_padded(packets[0].padded)
+        .with_compressed(packets[0].compressed)
+        .with_compact(packets[0].compact)
+        .with_authenticated_header(packets[0].authenticated_header))
+}
+
+/// Move a secret to a new set with a new threshold and share count.
+///
+/// Combines at least `k` packets of the old set, then splits the recovered
+/// secret again with the new `k` and `n`. The secret only ever sits in a
+/// zeroizing buffer inside this call. `old_passphrase` opens the old set;
+/// `new_passphrase` protects the new one. `None` is only accepted for an
+/// unprotected old set: a passphrase-protected set fails with
+/// [`CoreError::NewPassphraseRequired`] and a key-slot set with
+/// [`CoreError::KeySlot`]. Keep key slots with [`reshare_with_key_slots`], or
+/// drop protection on purpose with [`reshare_unprotected`]. A new passphrase
+/// replaces the old set's key slots.
+///
+/// The new packets get a fresh set id and record the old one as their
+/// `parent_set_id`, so tooling can tell that the old shares are superseded.
+/// A verifiable old set stays verifiable; the new commitments are embedded in
+/// every returned packet. A large-secret set stays a large-secret set. An
+/// encrypted old set passes its Argon2id costs and AEAD on to the new one.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::{combine_shares, reshare, split_secret, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let old = split_secret(b"example secret", 2, 3, None)?;
+///     let new = reshare(&old[..2], 3, 5, None, None)?;
+///
+///     assert_eq!(new[0].parent_set_id, Some(old[0].set_id));
+///     assert_eq!(combine_shares(&new[2..], None)?, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn reshare(
+    packets: &[packet::SharePacket],
+    k: u8,
+    n: u8,
+    old_passphrase: Option<&[u8]>,
+    new_passphrase: Option<&[u8]>,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    let first = packets
+        .first()
+        .ok_or(CoreError::NotEnoughShares { k: 1, m: 0 })?;
+    if new_passphrase.is_none() && first.key_slots {
+        return Err(CoreError::KeySlot(
+            "resharing without a new passphrase would drop the key slots; use reshare_with_key_slots"
+                .to_string(),
+        ));
+    }
+    if new_passphrase.is_none() && first.is_encrypted() {
+        return Err(CoreError::NewPassphraseRequired);
+    }
+    reshare_into(packets, k, n, old_passphrase, new_passphrase)
+}
+
+/// Like [`reshare`] with no new passphrase, but also for a protected old set.
+///
+/// The new set has neither a passphrase nor key slots, whatever the old set
+/// had. Use it only when dropping protection is intended.
+pub fn reshare_unprotected(
+    packets: &[packet::SharePacket],
+    k: u8,
+    n: u8,
+    old_passphrase: Option<&[u8]>,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    reshare_into(packets, k, n, old_passphrase, None)
+}
+
+fn reshare_into(
+    packets: &[packet::SharePacket],
+    k: u8,
+    n: u8,
+    old_passphrase: Option<&[u8]>,
+    new_passphrase: Option<&[u8]>,
+) -> CoreResult<Vec<packet::SharePacket>> {
+    let recovered = combine_shares_with_metadata(packets, old_passphrase)?;
+    let secret = Zeroizing::new(recovered.secret);
+    let padding = match recovered.padded_len {
+        None => padding::Padding::None,
+        Some(len) if len <= padding::MAX_PADDING => padding::Padding::Fixed(len),
+        Some(_) => padding::Padding::PowerOfTwo,
+    };
+    let parent = packets[0].set_id;
+    let suite = packets[0]
+        .crypto_params
+        .map(|params| params.suite())
+        .unwrap_or_default();
+
+    let new_packets = if packets[0].is_verifiable() {
+        split_secret_verifiable(&secret, k, n, new_passphrase, suite)?.0
+    } else if packets[0].is_dispersed() {
+        split_secret_large(&secret, k, n, new_passphrase, suite)?
+    } else {
+        let options = SplitOptions {
+            suite,
+            metadata: recovered.metadata,
+            padding,
+            compression: if packets[0].compressed {
+                compression::Compression::Deflate
+            } else {
+                compression::Compression::None
+            },
+            compact: packets[0].compact,
+            stealth: packets[0].stealth,
+        };
+        split_secret_with_options(&secret, k, n, new_passphrase, &options)?
+    };
+
+    // Stealth sets have no id to link to.
+    let parent = (!packets[0].stealth).then_some(parent);
+    Ok(new_packets
+        .into_iter()
+        .map(|packet| packet.with_parent_set_id(parent))
+        .collect())
+}
+
+/// Read the key slots of a [`split_secret_with_key_slots`] set.
+///
+/// Needs at least `k` packets but no passphrase: labels and Argon2id
+/// parameters are not secret, and the wrapped keys stay wrapped.
+pub fn list_key_slots(packets: &[packet::SharePacket]) -> CoreResult<Vec<crypto::KeySlot>> {
+    Ok(open_envelope(packets)?.slots().to_vec())
+}
+
+/// One edit to the slot table in [`reshare_with_key_slots`].
+#[derive(Clone, Copy, Debug)]
+pub enum KeySlotChange<'a> {
+    /// Wrap the data key under a new passphrase.
+    Add {
+        label: &'a str,
+        passphrase: &'a [u8],
+    },
+    /// Drop the slot with this label.
+    Remove(&'a str),
+}
+
+/// Re-split a set with its key slots edited.
+///
+/// `passphrase` must open one of the old set's slots. The data key is kept,
+/// so slots that are neither added nor removed keep working with their
+/// passphrases, which need not be known here. New slots copy the Argon2id
+/// costs and AEAD of the slot `passphrase` opened. The last slot cannot be
+/// removed.
+///
+/// An old set without key slots is converted: `passphrase` opens it as in
+/// [`combine_shares`], and the added slots become the new set's only slots.
+/// The new packets are a plain split recording the old set as their
+/// `parent_set_id`, like [`reshare`].
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::crypto::{CipherSuite, KdfParams};
+/// use safeparts_core::{
+///     combine_shares, list_key_slots, reshare_with_key_slots, split_secret_with_key_slots,
+///     CoreResult, KeySlotChange,
+/// };
+///
+/// fn main() -> CoreResult<()> {
+///     let suite = CipherSuite::from(KdfParams::INTERACTIVE);
+///     let old = split_secret_with_key_slots(b"example secret", 2, 3, &[("ops", b"ops pass")], suite)?;
+///     let changes = [KeySlotChange::Add { label: "break-glass", passphrase: b"bg pass" }];
+///     let new = reshare_with_key_slots(&old[..2], 2, 3, b"ops pass", &changes)?;
+///
+///     assert_eq!(list_key_slots(&new[..2])?.len(), 2);
+///     assert_eq!(combine_shares(&new[1..], Some(b"bg pass"))?, b"example secret");
+///     Ok(())
+/// }
+/// ```
+pub fn reshare_with_key_slots(
+    packets: &[packet::SharePacket],
+    k: u8,
+    n: u8,
+    passphrase: &[u8],
+    changes: &[KeySlotChange<'_>],
+) -> CoreResult<Vec<packet::SharePacket>> {
+    let first = packets
+        .first()
+        .ok_or(CoreError::NotEnoughShares { k: 1, m: 0 })?;
+    let (secret, mut envelope, key, suite) = if first.key_slots {
+        let envelope = open_envelope(packets)?;
+        let (slot, key) = envelope.unlock(passphrase)?;
+        let secret = Zeroizing::new(envelope.decrypt(&key, &first.set_header())?);
+        let suite = envelope.slots()[slot].params.suite();
+        (secret, envelope, key, suite)
+    } else {
+        let secret = Zeroizing::new(combine_shares(packets, Some(passphrase))?);
+        let suite = first
+            .crypto_params
+            .map(|params| params.suite())
+            .unwrap_or_default();
+        let adds: Vec<_> = changes
+            .iter()
+            .filter_map(|change| match change {
+                KeySlotChange::Add { label, passphrase } => Some((*label, *passphrase)),
+                KeySlotChange::Remove(_) => None,
+            })
+            .collect();
+        let (envelope, key) = crypto::SlotEnvelope::new(&adds, suite)?;
+        (secret, envelope, key, suite)
+    };
+
+    for change in changes {
+        match change {
+            KeySlotChange::Add { label, passphrase } if first.key_slots => {
+                envelope.add_slot(&key, label, passphrase, suite)?
+            }
+            KeySlotChange::Add { .. } => {}
+            KeySlotChange::Remove(label) => envelope.remove_slot(label)?,
+        }
+    }
+
+    Ok(split_envelope(&secret, envelope, &key, k, n)?
+        .into_iter()
+        .map(|packet| packet.with_parent_set_id(Some(first.set_id)))
+        .collect())
+}
+
+/// Combine a key-slot set up to its verified [`crypto::SlotEnvelope`].
+fn open_envelope(packets: &[packet::SharePacket]) -> CoreResult<crypto::SlotEnvelope> {
+    set_params(packets)?;
+    if !packets[0].key_slots {
+        return Err(CoreError::KeySlot("shares have no key slots".to_string()));
+    }
+    let combined = Zeroizing::new(sss::combine(&raw_shares(packets)?)?);
+    let data = check_tag(&combined, Some(&packets[0].set_header()))?;
+    crypto::SlotEnvelope::from_bytes(data)
+}
+
+/// Recovered secret plus the shares that had to be ignored to recover it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RobustRecovery {
+    pub secret: Vec<u8>,
+    /// Sorted x coordinates of shares found to be corrupted.
+    pub suspects: Vec<u8>,
+    /// Present for sets from [`split_secret_with_metadata`].
+    pub metadata: Option<metadata::SecretMetadata>,
+}
+
+/// Recover the secret even when some supplied shares are corrupted.
+///
+/// With `m` shares and threshold `k`, up to `(m - k) / 2` corrupted shares can
+/// be located and excluded. Plain shares are decoded column by column with
+/// Berlekamp-Welch; verifiable shares are checked against their commitments.
+/// The result still has to pass the integrity tag, so a successful recovery
+/// is as trustworthy as one from [`combine_shares`].
+///
+/// Returns [`CoreError::UncorrectableShares`] when the shares disagree but
+/// there are too few surplus shares to tell which ones are wrong. Policy and
+/// large-secret packets are combined without correction.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::{combine_shares_robust, split_secret, CoreResult};
+///
+/// fn main() -> CoreResult<()> {
+///     let mut shares = split_secret(b"example secret", 2, 4, None)?;
+///     shares[3].payload[0] ^= 0xff;
+///
+///     let recovery = combine_shares_robust(&shares, None)?;
+///     assert_eq!(recovery.secret, b"example secret");
+///     assert_eq!(recovery.suspects, vec![4]);
+///     Ok(())
+/// }
+/// ```
+pub fn combine_shares_robust(
+    packets: &[packet::SharePacket],
+    passphrase: Option<&[u8]>,
+) -> CoreResult<RobustRecovery> {
+    let (_, commitments) = set_params(packets)?;
+    if let Some(combined) = combine_policy(packets)?.or(combine_dispersed(packets)?) {
+        let recovered = open_combined(&combined, &packets[0], passphrase)?;
+        return Ok(RobustRecovery {
+            secret: recovered.secret,
+            suspects: Vec::new(),
+            metadata: recovered.metadata,
+        });
+    }
+    if packets[0].stealth {
+        let (recovered, suspects) = combine_stealth(packets, passphrase)?;
+        return Ok(RobustRecovery {
+            secret: recovered.secret,
+            suspects,
+            metadata: recovered.metadata,
+        });
+    }
+    let shares = raw_shares(packets)?;
+
+    let (combined, suspects) = match commitments {
+        None => sss::combine_robust(&shares)?,
+        Some(commitments) => {
+            sss::check_shares(&shares)?;
+            let (valid, invalid): (Vec<_>, Vec<_>) = shares
+                .into_iter()
+                .partition(|share| vss::verify(share, commitments).is_ok());
+
+            let mut suspects: Vec<u8> = invalid.iter().map(|share| share.x).collect();
+            suspects.sort_unstable();
+            (vss::combine(&valid)?, suspects)
+        }
+    };
+
+    let recovered = open_combined(&combined, &packets[0], passphrase)?;
+    Ok(RobustRecovery {
+        secret: recovered.secret,
+        suspects,
+        metadata: recovered.metadata,
+    })
+}
+
+/// Combine a stealth set, whose packets do not record `k`.
+///
+/// Tries each threshold from 1 up to the number of packets with error
+/// correction; only the right one passes the integrity tag, which is checked
+/// before any passphrase work. Returns the secret and the suspect indexes.
+fn combine_stealth(
+    packets: &[packet::SharePacket],
+    passphrase: Option<&[u8]>,
+) -> CoreResult<(Recovered, Vec<u8>)> {
+    let mut shares = raw_shares(packets)?;
+    sss::check_shares(&shares)?;
+    let header = packets[0].set_header();
+
+    for k in 1..=shares.len() {
+        for share in &mut shares {
+            share.k = k as u8;
+        }
+        let Ok((combined, suspects)) = sss::combine_robust(&shares) else {
+            continue;
+        };
+        if check_tag(&combined, Some(&header)).is_ok() {
+            return Ok((open_combined(&combined, &packets[0], passphrase)?, suspects));
+        }
+    }
+    Err(CoreError::StealthNotRecovered { m: shares.len() })
+}
+
+fn set_params(
+    packets: &[packet::SharePacket],
+) -> CoreResult<(Option<crypto::CryptoParams>, Option<&vss::Commitments>)> {
+    let first = packets
+        .first()
+        .ok_or(CoreError::NotEnoughShares { k: 1, m: 0 })?;
+
+    for p in packets {
+        if p.is_locked() {
+            return Err(CoreError::ShareLocked { x: p.x });
+        }
+        if p.is_streamed() {
+            return Err(CoreError::InvalidPacket(
+                "stream share headers combine through stream::ShareReader".to_string(),
+            ));
+        }
+        if p.authenticated_header != first.authenticated_header {
+            return Err(CoreError::HeaderMismatch("packet version"));
+        }
+        if p.authenticated_header && p.set_id == first.set_id && p.policy.is_none() {
+            if p.k != first.k {
+                return Err(CoreError::HeaderMismatch("threshold k"));
+            }
+            if p.n != first.n {
+                return Err(CoreError::HeaderMismatch("share count n"));
+            }
+        }
+        if p.set_id == first.set_id && p.epoch != first.epoch {
+            return Err(CoreError::EpochMismatch {
+                expected: first.epoch,
+                found: p.epoch,
+            });
+        }
+        if p.crypto_params != first.crypto_params || p.key_slots != first.key_slots {
+            return Err(CoreError::CryptoParamsMismatch);
+        }
+        if p.commitments != first.commitments {
+            return Err(CoreError::CommitmentsMismatch);
+        }
+        if p.parent_set_id != first.parent_set_id
+            || p.dispersed_len != first.dispersed_len
+            || p.metadata != first.metadata
+            || p.padded != first.padded
+            || p.compressed != first.compressed
+            || p.stealth != first.stealth
+            || p.policy.as_ref().map(|placement| &placement.policy)
+                != first.policy.as_ref().map(|placement| &placement.policy)
+        {
+            return Err(CoreError::InconsistentMetadata);
+        }
+    }
+
+    Ok((first.crypto_params, first.commitments.as_ref()))
+}
+
+/// Combine packets of a policy split, or return `None` for flat splits.
+///
+/// Callers must run [`set_params`] first so every packet carries the same
+/// policy.
+fn combine_policy(packets: &[packet::SharePacket]) -> CoreResult<Option<Vec<u8>>> {
+    let Some(placement) = packets.first().and_then(|p| p.policy.as_ref()) else {
+        return Ok(None);
+    };
+
+    let shares = packets
+        .iter()
+        .map(|packet| {
+            Ok(policy::PolicyShare {
+                path: packet
+                    .policy
+                    .as_ref()
+                    .ok_or(CoreError::InconsistentMetadata)?
+                    .path
+                    .clone(),
+                share: packet.to_raw_share()?,
+            })
+        })
+        .collect::<CoreResult<Vec<_>>>()?;
+
+    policy::combine(&placement.policy, &shares).map(Some)
+}
+
+/// Rebuild the protected data of a large-secret split, or return `None` for
+/// other splits.
+///
+/// Callers must run [`set_params`] first so every packet carries the same
+/// dispersed length.
+fn combine_dispersed(packets: &[packet::SharePacket]) -> CoreResult<Option<Vec<u8>>> {
+    let Some(dispersed_len) = packets.first().and_then(|p| p.dispersed_len) else {
+        return Ok(None);
+    };
+    let dispersed_len = usize::try_from(dispersed_len)
+        .map_err(|_| CoreError::InvalidPacket("dispersed length too large".to_string()))?;
+
+    let shares = raw_shares(packets)?;
+    sss::check_shares(&shares)?;
+    if shares[0].y.len() < crypto::KEY_LEN {
+        return Err(CoreError::InconsistentMetadata);
+    }
+
+    let (key_shares, fragments): (Vec<_>, Vec<_>) = shares
+        .into_iter()
+        .map(|mut share| {
+            let fragment = share.y.split_off(crypto::KEY_LEN);
+            let fragment = sss::RawShare {
+                y: fragment,
+                ..share.clone()
+            };
+            (share, fragment)
+        })
+        .unzip();
+
+    let key = Zeroizing::new(sss::combine(&key_shares)?);
+    let key: &[u8; crypto::KEY_LEN] = key
+        .as_slice()
+        .try_into()
+        .map_err(|_| CoreError::InconsistentMetadata)?;
+    let ciphertext = ida::recover(&fragments, dispersed_len)?;
+
+    // A wrong key or fragment fails authentication before the integrity tag.
+    crypto::decrypt_with_key(&ciphertext, key)
+        .map(Some)
+        .map_err(|_| CoreError::IntegrityCheckFailed)
+}
+
+fn raw_shares(packets: &[packet::SharePacket]) -> CoreResult<Vec<sss::RawShare>> {
+    packets
+        .iter()
+        .map(packet::SharePacket::to_raw_share)
+        .collect()
+}
+
+/// Check the integrity tag on combined bytes, undo passphrase protection, and
+/// strip the padding, compression, and metadata.
+fn open_combined(
+    combined: &[u8],
+    first: &packet::SharePacket,
+    passphrase: Option<&[u8]>,
+) -> CoreResult<Recovered> {
+    let header = first.authenticated_header.then(|| first.set_header());
+    let data = check_tag(combined, header.as_deref())?;
+
+    let secret = if first.key_slots {
+        let passphrase = passphrase.ok_or(CoreError::PassphraseRequired)?;
+        let envelope = crypto::SlotEnvelope::from_bytes(data)?;
+        let (_, key) = envelope.unlock(passphrase)?;
+        envelope.decrypt(&key, header.as_deref().unwrap_or(&[]))?
+    } else {
+        match first.crypto_params {
+            None => data.to_vec(),
+            Some(params) => {
+                let passphrase = passphrase.ok_or(CoreError::PassphraseRequired)?;
+                crypto::decrypt_with_aad(
+                    data,
+                    passphrase,
+                    &params,
+                    header.as_deref().unwrap_or(&[]),
+                )?
+            }
+        }
+    };
+
+    let mut secret = Zeroizing::new(secret);
+    let padded_len = first.padded.then_some(secret.len());
+    if first.padded {
+        let len = padding::Padding::unpad(&secret)?.len();
+        secret.truncate(len);
+    }
+    if first.compressed {
+        secret = compression::decompress(&secret)?;
+    }
+    let (secret, metadata) = if first.metadata {
+        let (metadata, secret) = metadata::SecretMetadata::unwrap(&secret)?;
+        (secret, Some(metadata))
+    } else {
+        (secret.to_vec(), None)
+    };
+    Ok(Recovered {
+        secret,
+        metadata,
+        padded_len,
+    })
+}
+
+/// Check the integrity tag on combined bytes and return the data before it.
+fn check_tag<'a>(combined: &'a [u8], header: Option<&[u8]>) -> CoreResult<&'a [u8]> {
+    if combined.len() < INTEGRITY_TAG_LEN {
+        return Err(CoreError::InvalidCombinedLength {
+            len: combined.len(),
+        });
     }
 
     let (data, tag) = combined.split_at(combined.len() - INTEGRITY_TAG_LEN);
-    let expected = blake3::hash(data);
-    if expected.as_bytes() != tag {
+    let tag: [u8; INTEGRITY_TAG_LEN] = tag
+        .try_into()
+        .map_err(|_| CoreError::IntegrityCheckFailed)?;
+    if integrity_tag(header, data) != blake3::Hash::from(tag) {
         return Err(CoreError::IntegrityCheckFailed);
     }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifiable_split_combines_and_verifies_each_share() {
+        let passphrase = b"pass";
+        let (shares, commitments) =
+            split_secret_verifiable(b"verifiable", 2, 3, Some(passphrase), Default::default())
+                .unwrap();
+
+        for share in &shares {
+            verify_share(share, &commitments).unwrap();
+        }
+        let recovered = combine_shares(&shares[1..], Some(passphrase)).unwrap();
+        assert_eq!(recovered, b"verifiable");
+    }
+
+    #[test]
+    fn combine_reports_share_that_fails_commitments() {
+        let (mut shares, _) =
+            split_secret_verifiable(b"verifiable", 2, 3, None, Default::default()).unwrap();
+        shares[1].payload[3] ^= 0x40;
+
+        let err = combine_shares(&shares[..2], None).unwrap_err();
+        assert!(matches!(err, CoreError::ShareVerificationFailed { x: 2 }));
+    }
+
+    #[test]
+    fn verify_share_rejects_foreign_commitments_and_plain_packets() {
+        let (shares, _) = split_secret_verifiable(b"one", 2, 3, None, Default::default()).unwrap();
+        let (_, other) = split_secret_verifiable(b"two", 2, 3, None, Default::default()).unwrap();
+        assert!(matches!(
+            verify_share(&shares[0], &other),
+            Err(CoreError::CommitmentsMismatch)
+        ));
+
+        let plain = split_secret(b"plain", 2, 3, None).unwrap();
+        assert!(matches!(
+            verify_share(&plain[0], &other),
+            Err(CoreError::NotVerifiable)
+        ));
+    }
+
+    #[test]
+    fn derived_share_keeps_set_metadata_and_combines() {
+        let passphrase = b"pass";
+        let shares = split_secret(b"derive", 2, 3, Some(passphrase)).unwrap();
+
+        let derived = derive_share(&shares[1..], 5).unwrap();
+        assert_eq!(derived.set_id, shares[0].set_id);
+        assert_eq!(derived.crypto_params, shares[0].crypto_params);
+        assert_eq!((derived.k, derived.n, derived.x), (2, 3, 5));
+
+        let recovered = combine_shares(&[shares[0].clone(), derived], Some(passphrase)).unwrap();
+        assert_eq!(recovered, b"derive");
+    }
+
+    #[test]
+    fn derived_verifiable_share_passes_verify_share() {
+        let (shares, commitments) =
+            split_secret_verifiable(b"derive vss", 2, 3, None, Default::default()).unwrap();
+
+        let derived = derive_share(&shares[..2], 3).unwrap();
+        assert_eq!(derived, shares[2]);
+        verify_share(&derived, &commitments).unwrap();
+    }
+
+    #[test]
+    fn reshare_changes_policy_and_records_parent_set() {
+        let old = split_secret(b"reshare", 2, 3, Some(b"old")).unwrap();
+
+        let new = reshare(&old[1..], 3, 5, Some(b"old"), Some(b"new")).unwrap();
+        assert_eq!(new.len(), 5);
+        assert!(new.iter().all(|p| p.k == 3 && p.set_id != old[0].set_id));
+        assert!(new.iter().all(|p| p.parent_set_id == Some(old[0].set_id)));
+
+        assert_eq!(combine_shares(&new[..3], Some(b"new")).unwrap(), b"reshare");
+        assert!(matches!(
+            combine_shares(&new[..2], Some(b"new")),
+            Err(CoreError::NotEnoughShares { k: 3, m: 2 })
+        ));
+        assert!(matches!(
+            reshare(&old[..2], 3, 5, Some(b"wrong"), Some(b"new")),
+            Err(CoreError::DecryptFailed)
+        ));
+    }
+
+    #[test]
+    fn reshare_keeps_protection_unless_dropped_on_purpose() {
+        let old = split_secret(b"protected", 2, 3, Some(b"old")).unwrap();
+        assert!(matches!(
+            reshare(&old[..2], 2, 3, Some(b"old"), None),
+            Err(CoreError::NewPassphraseRequired)
+        ));
+        let new = reshare_unprotected(&old[..2], 2, 3, Some(b"old")).unwrap();
+        assert!(!new[0].is_encrypted());
+        assert_eq!(combine_shares(&new[1..], None).unwrap(), b"protected");
+
+        let suite = crypto::CipherSuite::from(crypto::KdfParams::new(1024, 1, 1).unwrap());
+        let slotted =
+            split_secret_with_key_slots(b"slots", 2, 3, &[("ops", b"ops")], suite).unwrap();
+        assert!(matches!(
+            reshare(&slotted[..2], 2, 3, Some(b"ops"), None),
+            Err(CoreError::KeySlot(_))
+        ));
+        let replaced = reshare(&slotted[..2], 2, 3, Some(b"ops"), Some(b"new")).unwrap();
+        assert!(!replaced[0].key_slots);
+        assert_eq!(
+            combine_shares(&replaced[1..], Some(b"new")).unwrap(),
+            b"slots"
+        );
+    }
+
+    #[test]
+    fn reshare_keeps_verifiable_sets_verifiable() {
+        let (old, _) =
+            split_secret_verifiable(b"reshare vss", 2, 3, None, Default::default()).unwrap();
+
+        let new = reshare(&old[..2], 2, 4, None, None).unwrap();
+        let commitments = new[0].commitments.clone().unwrap();
+        for packet in &new {
+            verify_share(packet, &commitments).unwrap();
+        }
+        assert_eq!(combine_shares(&new[2..], None).unwrap(), b"reshare vss");
+    }
+
+    #[test]
+    fn policy_split_recovers_and_reports_missing_groups() {
+        let policy =
+            policy::Policy::parse("all of (eng: 2 of (e1, e2, e3), legal: 1 of (l1, l2))").unwrap();
+        let shares =
+            split_secret_with_policy(b"policy", &policy, Some(b"pw"), Default::default()).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        let picked = [shares[0].clone(), shares[2].clone(), shares[3].clone()];
+        assert_eq!(combine_shares(&picked, Some(b"pw")).unwrap(), b"policy");
+
+        let err = combine_shares(&shares[..3], Some(b"pw")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "access policy not satisfied; still missing policy: 1 more of (legal); legal: 1 more of (l1, l2)"
+        );
+    }
+
+    #[test]
+    fn large_split_shrinks_shares_and_extends() {
+        let secret: Vec<u8> = (0..=255u8).cycle().take(9_000).collect();
+        let shares = split_secret_large(&secret, 3, 5, Some(b"pw"), Default::default()).unwrap();
+        assert!(shares.iter().all(|share| share.payload.len() < 3_100));
+
+        let sixth = derive_share(&shares[..3], 6).unwrap();
+        assert!(sixth.is_dispersed());
+        let picked = [shares[1].clone(), shares[4].clone(), sixth];
+        assert_eq!(combine_shares(&picked, Some(b"pw")).unwrap(), secret);
+
+        let new = reshare_unprotected(&shares[2..], 2, 3, Some(b"pw")).unwrap();
+        assert!(new[0].is_dispersed());
+        assert_eq!(combine_shares(&new[1..], None).unwrap(), secret);
+    }
+
+    #[test]
+    fn large_split_rejects_corrupted_fragment() {
+        let mut shares = split_secret_large(b"large", 2, 3, None, Default::default()).unwrap();
+        let last = shares[1].payload.len() - 1;
+        shares[1].payload[last] ^= 0x01;
+
+        assert!(matches!(
+            combine_shares(&shares[..2], None),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+        let intact = [shares[0].clone(), shares[2].clone()];
+        assert_eq!(combine_shares(&intact, None).unwrap(), b"large");
+    }
+
+    #[test]
+    fn robust_combine_reports_corrupted_share_index() {
+        let mut shares = split_secret(b"robust", 2, 5, Some(b"pw")).unwrap();
+        shares[0].payload[2] ^= 0x01;
+
+        assert!(matches!(
+            combine_shares(&shares, Some(b"pw")),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+
+        let recovery = combine_shares_robust(&shares, Some(b"pw")).unwrap();
+        assert_eq!(recovery.secret, b"robust");
+        assert_eq!(recovery.suspects, vec![1]);
+    }
+
+    #[test]
+    fn robust_combine_excludes_verifiable_shares_that_fail_commitments() {
+        let (mut shares, _) =
+            split_secret_verifiable(b"robust vss", 2, 3, None, Default::default()).unwrap();
+        shares[2].payload[0] ^= 0x01;
+
+        let recovery = combine_shares_robust(&shares, None).unwrap();
+        assert_eq!(recovery.secret, b"robust vss");
+        assert_eq!(recovery.suspects, vec![3]);
+    }
+
+    #[test]
+    fn altered_set_header_fails_integrity_check() {
+        let shares = split_secret(b"header", 2, 3, Some(b"pw")).unwrap();
+        assert!(shares.iter().all(|share| share.authenticated_header));
+
+        // Raising k on every share still interpolates the same line.
+        let raised: Vec<_> = shares
+            .iter()
+            .map(|share| packet::SharePacket {
+                k: 3,
+                ..share.clone()
+            })
+            .collect();
+        assert!(matches!(
+            combine_shares(&raised, Some(b"pw")),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+
+        let weakened: Vec<_> = shares
+            .iter()
+            .map(|share| {
+                let mut share = share.clone();
+                if let Some(params) = share.crypto_params.as_mut() {
+                    params.mem_cost_kib /= 2;
+                }
+                share
+            })
+            .collect();
+        assert!(matches!(
+            combine_shares(&weakened, Some(b"pw")),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn shares_disagreeing_on_header_fail_with_header_mismatch() {
+        let shares = split_secret(b"header", 2, 3, None).unwrap();
+
+        let mut altered = shares[1].clone();
+        altered.n = 5;
+        assert!(matches!(
+            combine_shares(&[shares[0].clone(), altered], None),
+            Err(CoreError::HeaderMismatch("share count n"))
+        ));
+
+        let legacy = shares[1].clone().with_authenticated_header(false);
+        assert!(matches!(
+            combine_shares(&[shares[0].clone(), legacy], None),
+            Err(CoreError::HeaderMismatch("packet version"))
+        ));
+    }
+
+    #[test]
+    fn version_two_packets_still_combine() {
+        let mut data = b"legacy".to_vec();
+        data.extend_from_slice(blake3::hash(b"legacy").as_bytes());
+        let packets: Vec<_> = sss::split(&data, 2, 3, sss::SetId([5u8; 16]))
+            .unwrap()
+            .into_iter()
+            .map(packet::SharePacket::from_raw_share)
+            .collect();
+
+        let decoded =
+            packet::SharePacket::decode_binary(&packets[2].encode_binary().unwrap()).unwrap();
+        assert!(!decoded.authenticated_header);
+        let recovered = combine_shares(&[packets[0].clone(), decoded], None).unwrap();
+        assert_eq!(recovered, b"legacy");
+
+        let derived = derive_share(&packets[..2], 5).unwrap();
+        assert_eq!(derived.n, 5);
+    }
+
+    #[test]
+    fn kdf_params_are_recorded_and_kept_by_reshare() {
+        let kdf = crypto::KdfParams::new(1024, 1, 1).unwrap();
+        let shares = split_secret_with_suite(b"kdf", 2, 3, Some(b"pw"), kdf.into()).unwrap();
+        assert_eq!(
+            shares[0].crypto_params.map(|params| params.kdf()),
+            Some(kdf)
+        );
+        assert_eq!(combine_shares(&shares[..2], Some(b"pw")).unwrap(), b"kdf");
+
+        let new = reshare(&shares[..2], 2, 2, Some(b"pw"), Some(b"new")).unwrap();
+        assert_eq!(new[0].crypto_params.map(|params| params.kdf()), Some(kdf));
+
+        let invalid = crypto::KdfParams {
+            mem_cost_kib: 4,
+            ..kdf
+        };
+        assert!(matches!(
+            split_secret_with_suite(b"kdf", 2, 3, Some(b"pw"), invalid.into()),
+            Err(CoreError::InvalidKdfParams(_))
+        ));
+        assert!(split_secret_with_suite(b"kdf", 2, 3, None, invalid.into()).is_ok());
+    }
+
+    #[test]
+    fn every_cipher_round_trips_and_is_kept_by_reshare() {
+        let kdf = crypto::KdfParams::new(1024, 1, 1).unwrap();
+        for &aead in crypto::AeadAlgorithm::ALL {
+            let suite = crypto::CipherSuite { kdf, aead };
+            let shares = split_secret_with_suite(b"cipher", 2, 3, Some(b"pw"), suite).unwrap();
+            assert_eq!(shares[0].crypto_params.unwrap().aead, aead);
+            assert_eq!(
+                combine_shares(&shares[1..], Some(b"pw")).unwrap(),
+                b"cipher"
+            );
+            assert!(matches!(
+                combine_shares(&shares[1..], Some(b"wrong")),
+                Err(CoreError::DecryptFailed)
+            ));
+
+            let large = split_secret_large(b"cipher", 2, 3, Some(b"pw"), suite).unwrap();
+            assert_eq!(combine_shares(&large[..2], Some(b"pw")).unwrap(), b"cipher");
 
-    match crypto_params {
-        None => Ok(data.to_vec()),
-        Some(params) => {
-            let passphrase = passphrase.ok_or(CoreError::PassphraseRequired)?;
-            crypto::decrypt(data, passphrase, params)
+            let new = reshare(&shares[..2], 2, 2, Some(b"pw"), Some(b"new")).unwrap();
+            assert_eq!(
+                new[0].crypto_params.map(|params| params.suite()),
+                Some(suite)
+            );
+            assert_eq!(combine_shares(&new, Some(b"new")).unwrap(), b"cipher");
         }
     }
+
+    #[test]
+    fn relabelled_cipher_fails_integrity_check() {
+        let suite = crypto::CipherSuite {
+            kdf: crypto::KdfParams::new(1024, 1, 1).unwrap(),
+            aead: crypto::AeadAlgorithm::ChaCha20Poly1305,
+        };
+        let mut shares = split_secret_with_suite(b"cipher", 2, 3, Some(b"pw"), suite).unwrap();
+        for share in &mut shares {
+            let params = share.crypto_params.as_mut().unwrap();
+            params.aead = crypto::AeadAlgorithm::Aes256GcmSiv;
+        }
+        assert!(matches!(
+            combine_shares(&shares[..2], Some(b"pw")),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn share_lock_unlocks_only_its_own_share() {
+        let suite = crypto::CipherSuite::from(crypto::KdfParams::new(1024, 1, 1).unwrap());
+        let shares = split_secret(b"locked", 2, 3, None).unwrap();
+        let first = lock_share(&shares[0], b"alice-pin", suite).unwrap();
+        let second = lock_share(&shares[1], b"bob-pin", suite).unwrap();
+        assert!(first.is_locked());
+        assert_ne!(first.payload, shares[0].payload);
+
+        let decoded = packet::SharePacket::decode_binary(&first.encode_binary().unwrap()).unwrap();
+        assert_eq!(decoded, first);
+
+        assert!(matches!(
+            combine_shares(&[first.clone(), shares[2].clone()], None),
+            Err(CoreError::ShareLocked { x: 1 })
+        ));
+        assert!(matches!(
+            unlock_share(&first, b"bob-pin"),
+            Err(CoreError::ShareUnlockFailed { x: 1 })
+        ));
+
+        // Moving a lock onto another share's payload does not unlock it.
+        let swapped = packet::SharePacket {
+            payload: second.payload.clone(),
+            ..first.clone()
+        };
+        assert!(unlock_share(&swapped, b"bob-pin").is_err());
+
+        let unlocked = unlock_shares(&[first, second, shares[2].clone()], |packet| {
+            let pin: &[u8] = match packet.x {
+                1 => b"alice-pin",
+                _ => return None,
+            };
+            Some(Zeroizing::new(pin.to_vec()))
+        })
+        .unwrap();
+        assert_eq!(unlocked, vec![shares[0].clone(), shares[2].clone()]);
+        assert_eq!(combine_shares(&unlocked, None).unwrap(), b"locked");
+    }
+
+    #[test]
+    fn share_lock_is_independent_of_split_passphrase() {
+        let kdf = crypto::KdfParams::new(1024, 1, 1).unwrap();
+        let suite = crypto::CipherSuite {
+            kdf,
+            aead: crypto::AeadAlgorithm::XChaCha20Poly1305,
+        };
+        let shares = split_secret_with_suite(b"both", 2, 2, Some(b"pw"), kdf.into()).unwrap();
+        let locked = lock_share(&shares[1], b"pin", suite).unwrap();
+        assert!(matches!(
+            lock_share(&locked, b"pin", suite),
+            Err(CoreError::InvalidPacket(_))
+        ));
+
+        let unlocked = unlock_share(&locked, b"pin").unwrap();
+        assert_eq!(
+            combine_shares(&[shares[0].clone(), unlocked], Some(b"pw")).unwrap(),
+            b"both"
+        );
+
+        let legacy = shares[0].clone().with_authenticated_header(false);
+        assert!(matches!(
+            lock_share(&legacy, b"pin", suite),
+            Err(CoreError::InvalidPacket(_))
+        ));
+    }
+
+    #[test]
+    fn key_slots_open_with_any_passphrase_and_edit_on_reshare() {
+        let suite = crypto::CipherSuite::from(crypto::KdfParams::new(1024, 1, 1).unwrap());
+        let slots: [(&str, &[u8]); 2] = [("ops", b"ops"), ("break-glass", b"bg")];
+        let shares = split_secret_with_key_slots(b"slotted", 2, 3, &slots, suite).unwrap();
+
+        assert!(shares[0].is_encrypted());
+        assert_eq!(
+            combine_shares(&shares[..2], Some(b"ops")).unwrap(),
+            b"slotted"
+        );
+        assert_eq!(
+            combine_shares(&shares[1..], Some(b"bg")).unwrap(),
+            b"slotted"
+        );
+        assert!(matches!(
+            combine_shares(&shares[..2], None),
+            Err(CoreError::PassphraseRequired)
+        ));
+        assert!(matches!(
+            combine_shares(&shares[..2], Some(b"wrong")),
+            Err(CoreError::DecryptFailed)
+        ));
+        let labels: Vec<_> = list_key_slots(&shares[..2])
+            .unwrap()
+            .into_iter()
+            .map(|slot| slot.label)
+            .collect();
+        assert_eq!(labels, ["ops", "break-glass"]);
+
+        let fourth = derive_share(&shares[..2], 4).unwrap();
+        assert_eq!(
+            combine_shares(&[shares[2].clone(), fourth], Some(b"bg")).unwrap(),
+            b"slotted"
+        );
+
+        let changes = [
+            KeySlotChange::Remove("ops"),
+            KeySlotChange::Add {
+                label: "security",
+                passphrase: b"sec",
+            },
+        ];
+        let new = reshare_with_key_slots(&shares[..2], 3, 4, b"ops", &changes).unwrap();
+        assert_eq!(new[0].parent_set_id, Some(shares[0].set_id));
+        assert_eq!(combine_shares(&new[1..], Some(b"bg")).unwrap(), b"slotted");
+        assert_eq!(combine_shares(&new[..3], Some(b"sec")).unwrap(), b"slotted");
+        assert!(combine_shares(&new[..3], Some(b"ops")).is_err());
+
+        let last = [
+            KeySlotChange::Remove("break-glass"),
+            KeySlotChange::Remove("security"),
+        ];
+        assert!(matches!(
+            reshare_with_key_slots(&new[..3], 2, 3, b"bg", &last),
+            Err(CoreError::KeySlot(_))
+        ));
+    }
+
+    #[test]
+    fn reshare_with_key_slots_converts_single_passphrase_sets() {
+        let suite = crypto::CipherSuite::from(crypto::KdfParams::new(1024, 1, 1).unwrap());
+        let old = split_secret_with_suite(b"legacy", 2, 3, Some(b"old"), suite).unwrap();
+        assert!(matches!(
+            list_key_slots(&old[..2]),
+            Err(CoreError::KeySlot(_))
+        ));
+
+        let changes = [
+            KeySlotChange::Add {
+                label: "ops",
+                passphrase: b"ops",
+            },
+            KeySlotChange::Add {
+                label: "break-glass",
+                passphrase: b"bg",
+            },
+        ];
+        let new = reshare_with_key_slots(&old[..2], 2, 3, b"old", &changes).unwrap();
+        let slots = list_key_slots(&new[..2]).unwrap();
+        assert_eq!(slots[1].params.kdf(), suite.kdf);
+        assert_eq!(combine_shares(&new[..2], Some(b"bg")).unwrap(), b"legacy");
+        assert!(combine_shares(&new[..2], Some(b"old")).is_err());
+    }
+
+    #[test]
+    fn metadata_travels_inside_the_protected_data() {
+        let suite = crypto::CipherSuite::from(crypto::KdfParams::new(1024, 1, 1).unwrap());
+        let metadata = metadata::SecretMetadata {
+            filename: Some("notes.txt".to_string()),
+            content_type: Some("text/plain".to_string()),
+            label: Some("team notes".to_string()),
+            ..metadata::SecretMetadata::now()
+        };
+        let shares =
+            split_secret_with_metadata(b"hello", 2, 3, Some(b"pw"), suite, &metadata).unwrap();
+        assert!(shares.iter().all(|share| share.metadata));
+
+        assert_eq!(combine_shares(&shares[..2], Some(b"pw")).unwrap(), b"hello");
+        let recovered = combine_shares_with_metadata(&shares[1..], Some(b"pw")).unwrap();
+        assert_eq!(recovered.metadata.as_ref(), Some(&metadata));
+        let robust = combine_shares_robust(&shares, Some(b"pw")).unwrap();
+        assert_eq!(robust.metadata.as_ref(), Some(&metadata));
+
+        let stripped: Vec<_> = shares
+            .iter()
+            .map(|share| share.clone().with_metadata(false))
+            .collect();
+        assert!(matches!(
+            combine_shares(&stripped[..2], Some(b"pw")),
+            Err(CoreError::DecryptFailed | CoreError::IntegrityCheckFailed)
+        ));
+
+        let fourth = derive_share(&shares[..2], 4).unwrap();
+        let new = reshare_unprotected(&[shares[0].clone(), fourth], 3, 4, Some(b"pw")).unwrap();
+        let recovered = combine_shares_with_metadata(&new[1..], None).unwrap();
+        assert_eq!(recovered.secret, b"hello");
+        assert_eq!(recovered.metadata, Some(metadata));
+
+        let plain = split_secret(b"hello", 2, 3, None).unwrap();
+        assert_eq!(
+            combine_shares_with_metadata(&plain[..2], None)
+                .unwrap()
+                .metadata,
+            None
+        );
+    }
+
+    #[test]
+    fn padding_hides_length_and_survives_reshare() {
+        let options = SplitOptions {
+            padding: padding::Padding::PowerOfTwo,
+            ..SplitOptions::default()
+        };
+        let short = split_secret_with_options(b"pin", 2, 3, None, &options).unwrap();
+        let long = split_secret_with_options(&[7; 40], 2, 3, None, &options).unwrap();
+        assert!(short.iter().all(|share| share.padded));
+        assert_eq!(short[0].payload.len(), long[0].payload.len());
+
+        let recovered = combine_shares_with_metadata(&short[..2], None).unwrap();
+        assert_eq!(recovered.secret, b"pin");
+        assert_eq!(recovered.padded_len, Some(padding::MIN_BUCKET));
+        assert_eq!(combine_shares_robust(&long, None).unwrap().secret, [7; 40]);
+
+        let stripped: Vec<_> = short
+            .iter()
+            .map(|share| share.clone().with_padded(false))
+            .collect();
+        assert!(matches!(
+            combine_shares(&stripped[..2], None),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+
+        let options = SplitOptions {
+            metadata: Some(metadata::SecretMetadata::now()),
+            padding: padding::Padding::Fixed(200),
+            ..SplitOptions::default()
+        };
+        let shares = split_secret_with_options(b"pin", 2, 3, Some(b"pw"), &options).unwrap();
+        let new = reshare(&shares[..2], 2, 2, Some(b"pw"), Some(b"new")).unwrap();
+        assert_eq!(new[0].payload.len(), shares[0].payload.len());
+        let recovered = combine_shares_with_metadata(&new, Some(b"new")).unwrap();
+        assert_eq!(recovered.secret, b"pin");
+        assert!(recovered.metadata.is_some());
+    }
+
+    #[test]
+    fn compression_shrinks_text_and_survives_reshare() {
+        let env = b"DATABASE_URL=postgres://app@db/app\nREDIS_URL=redis://cache\n".repeat(8);
+        let options = SplitOptions {
+            compression: compression::Compression::Deflate,
+            ..SplitOptions::default()
+        };
+        let shares = split_secret_with_options(&env, 2, 3, None, &options).unwrap();
+        let plain = split_secret(&env, 2, 3, None).unwrap();
+        assert!(shares.iter().all(|share| share.compressed));
+        assert!(shares[0].payload.len() < plain[0].payload.len() / 2);
+        assert_eq!(combine_shares(&shares[1..], None).unwrap(), env);
+        assert_eq!(combine_shares_robust(&shares, None).unwrap().secret, env);
+
+        let stripped: Vec<_> = shares
+            .iter()
+            .map(|share| share.clone().with_compressed(false))
+            .collect();
+        assert!(matches!(
+            combine_shares(&stripped[..2], None),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+
+        let random = split_secret_with_options(&[0x5a, 0x13, 0xc7], 2, 2, None, &options).unwrap();
+        assert!(!random[0].compressed);
+
+        let options = SplitOptions {
+            metadata: Some(metadata::SecretMetadata::now()),
+            padding: padding::Padding::PowerOfTwo,
+            ..options
+        };
+        let shares = split_secret_with_options(&env, 2, 3, Some(b"pw"), &options).unwrap();
+        let new = reshare(&shares[..2], 2, 2, Some(b"pw"), Some(b"new")).unwrap();
+        assert!(new[0].compressed);
+        assert_eq!(new[0].payload.len(), shares[0].payload.len());
+        let recovered = combine_shares_with_metadata(&new, Some(b"new")).unwrap();
+        assert_eq!(recovered.secret, env);
+        assert!(recovered.metadata.is_some());
+    }
+
+    #[test]
+    fn compact_shares_are_shorter_and_survive_reshare() {
+        let compact = SplitOptions {
+            compact: true,
+            ..SplitOptions::default()
+        };
+        let shares = split_secret_with_options(b"k3y", 2, 3, Some(b"pw"), &compact).unwrap();
+        let full = split_secret(b"k3y", 2, 3, Some(b"pw")).unwrap();
+        assert!(shares[0].set_id.is_short());
+        assert!(shares[0].crypto_params.unwrap().has_derived_nonce());
+
+        let encoded = shares[0].encode_binary().unwrap();
+        assert!(encoded.len() + 30 < full[0].encode_binary().unwrap().len());
+        let decoded = packet::SharePacket::decode_binary(&encoded).unwrap();
+        assert_eq!(decoded, shares[0]);
+        assert_eq!(
+            combine_shares(&[decoded, shares[2].clone()], Some(b"pw")).unwrap(),
+            b"k3y"
+        );
+
+        let derived = derive_share(&shares[..2], 7).unwrap();
+        assert!(derived.compact);
+        let locked = lock_share(&derived, b"mine", crypto::CipherSuite::default()).unwrap();
+        let locked = packet::SharePacket::decode_binary(&locked.encode_binary().unwrap()).unwrap();
+        let unlocked = unlock_share(&locked, b"mine").unwrap();
+        assert_eq!(
+            combine_shares(&[shares[1].clone(), unlocked], Some(b"pw")).unwrap(),
+            b"k3y"
+        );
+
+        let new = reshare_unprotected(&shares[..2], 2, 2, Some(b"pw")).unwrap();
+        assert!(new[0].compact && new[0].set_id.is_short());
+        let decoded = packet::SharePacket::decode_binary(&new[1].encode_binary().unwrap()).unwrap();
+        assert_eq!(decoded.parent_set_id, Some(shares[0].set_id));
+        assert_eq!(
+            combine_shares(&[new[0].clone(), decoded], None).unwrap(),
+            b"k3y"
+        );
+    }
+
+    #[test]
+    fn stealth_shares_hide_the_threshold_and_still_combine() {
+        let stealth = SplitOptions {
+            stealth: true,
+            ..SplitOptions::default()
+        };
+        let shares = split_secret_with_options(b"k3y", 3, 5, Some(b"pw"), &stealth).unwrap();
+        for share in &shares {
+            assert!(share.stealth && share.compact);
+            assert_eq!(
+                (share.k, share.n, share.set_id),
+                (0, 0, sss::SetId([0; 16]))
+            );
+            let bytes = share.encode_binary().unwrap();
+            assert_eq!(&packet::SharePacket::decode_binary(&bytes).unwrap(), share);
+        }
+
+        // Every share pasted together, in any order, with one corrupted.
+        let mut pasted: Vec<_> = shares.iter().rev().cloned().collect();
+        pasted[0].payload[0] ^= 1;
+        let recovery = combine_shares_robust(&pasted, Some(b"pw")).unwrap();
+        assert_eq!(recovery.secret, b"k3y");
+        assert_eq!(recovery.suspects, vec![shares[4].x]);
+        assert_eq!(combine_shares(&shares[1..4], Some(b"pw")).unwrap(), b"k3y");
+        assert!(matches!(
+            combine_shares(&shares[..2], Some(b"pw")),
+            Err(CoreError::StealthNotRecovered { m: 2 })
+        ));
+        assert!(matches!(
+            combine_shares(&shares[..3], Some(b"wrong")),
+            Err(CoreError::DecryptFailed)
+        ));
+
+        let kdf = crypto::KdfParams::new(1024, 1, 1).unwrap();
+        let locked = lock_share(&shares[0], b"pin", kdf.into()).unwrap();
+        let bytes = locked.encode_binary().unwrap();
+        let locked = packet::SharePacket::decode_binary(&bytes).unwrap();
+        assert_eq!(unlock_share(&locked, b"pin").unwrap(), shares[0]);
+
+        assert!(derive_share(&shares, 9).is_err());
+        assert!(refresh::RefreshRound::new(&shares[0], &[1, 2, 3]).is_err());
+        let new = reshare_unprotected(&shares[..3], 2, 2, Some(b"pw")).unwrap();
+        assert!(new[0].stealth && new[0].parent_set_id.is_none());
+        assert_eq!(combine_shares(&new, None).unwrap(), b"k3y");
+    }
+
+    #[test]
+    fn mixed_stealth_sets_look_like_one_set() {
+        let stealth = SplitOptions {
+            stealth: true,
+            ..SplitOptions::default()
+        };
+        let first = split_secret_with_options(b"first", 2, 3, None, &stealth).unwrap();
+        let second = split_secret_with_options(b"other", 2, 3, None, &stealth).unwrap();
+        // Random indexes may collide across sets; pick one that does not.
+        let stray = second
+            .iter()
+            .find(|share| first.iter().all(|other| other.x != share.x))
+            .unwrap()
+            .clone();
+
+        // One share of each set: no threshold passes the integrity tag.
+        assert!(matches!(
+            combine_shares(&[first[0].clone(), stray.clone()], None),
+            Err(CoreError::StealthNotRecovered { m: 2 })
+        ));
+
+        // Enough shares of one set: it recovers, and the stray share from
+        // the other set is only reported as a suspect.
+        let mut pasted = first.clone();
+        pasted.push(stray.clone());
+        assert_eq!(combine_shares(&pasted, None).unwrap(), b"first");
+        let recovery = combine_shares_robust(&pasted, None).unwrap();
+        assert_eq!(recovery.secret, b"first");
+        assert_eq!(recovery.suspects, vec![stray.x]);
+
+        // Secrets of different lengths, or passphrase-protected sets with
+        // their random salts, are told apart before any combining.
+        let longer = split_secret_with_options(b"longer", 2, 3, None, &stealth).unwrap();
+        assert!(matches!(
+            combine_shares(&[first[0].clone(), longer[0].clone()], None),
+            Err(CoreError::InconsistentMetadata)
+        ));
+        let a = split_secret_with_options(b"a", 2, 3, Some(b"pw"), &stealth).unwrap();
+        let b = split_secret_with_options(b"b", 2, 3, Some(b"pw"), &stealth).unwrap();
+        assert!(matches!(
+            combine_shares(&[a[0].clone(), b[0].clone()], Some(b"pw")),
+            Err(CoreError::CryptoParamsMismatch)
+        ));
+    }
 }
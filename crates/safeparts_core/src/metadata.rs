@@ -0,0 +1,182 @@
+//! Metadata stored inside the secret: original filename, content type,
+//! label, creation time, and the Safeparts version that split it.
+//!
+//! [`crate::split_secret_with_metadata`] prepends the encoded metadata to the
+//! secret before encryption and the integrity tag, so no single share reveals
+//! it. Combine strips it again; [`crate::combine_shares_with_metadata`]
+//! returns it next to the secret bytes.
+//!
+//! Encoding: a version byte, then fields as `tag || u16 length || value`,
+//! ended by tag 0. Readers skip tags they do not know.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+
+const VERSION: u8 = 1;
+
+const TAG_END: u8 = 0;
+const TAG_FILENAME: u8 = 1;
+const TAG_CONTENT_TYPE: u8 = 2;
+const TAG_LABEL: u8 = 3;
+const TAG_CREATED_AT: u8 = 4;
+const TAG_SAFEPARTS_VERSION: u8 = 5;
+
+/// Describes a split secret. Every field is optional.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecretMetadata {
+    /// Original file name. Use [`SecretMetadata::safe_filename`] before
+    /// writing to disk.
+    pub filename: Option<String>,
+    /// MIME type such as `text/plain` or `application/octet-stream`.
+    pub content_type: Option<String>,
+    pub label: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub created_at: Option<u64>,
+    pub safeparts_version: Option<String>,
+}
+
+impl SecretMetadata {
+    /// Metadata stamped with the current time and this crate's version.
+    pub fn now() -> Self {
+        Self {
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|elapsed| elapsed.as_secs()),
+            safeparts_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// The stored filename when it is a plain file name, safe to join onto an
+    /// output directory: no path separators, no `.` or `..`, no control
+    /// characters.
+    pub fn safe_filename(&self) -> Option<&str> {
+        self.filename.as_deref().filter(|name| {
+            !name.is_empty()
+                && *name != "."
+                && *name != ".."
+                && !name.contains(['/', '\\'])
+                && !name.chars().any(char::is_control)
+        })
+    }
+
+    /// Prepend the encoded metadata to `secret`.
+    pub fn wrap(&self, secret: &[u8]) -> CoreResult<Zeroizing<Vec<u8>>> {
+        let mut out = Zeroizing::new(Vec::with_capacity(64 + secret.len()));
+        out.push(VERSION);
+        let created_at = self.created_at.map(u64::to_be_bytes);
+        let fields = [
+            (TAG_FILENAME, self.filename.as_deref().map(str::as_bytes)),
+            (
+                TAG_CONTENT_TYPE,
+                self.content_type.as_deref().map(str::as_bytes),
+            ),
+            (TAG_LABEL, self.label.as_deref().map(str::as_bytes)),
+            (TAG_CREATED_AT, created_at.as_ref().map(|bytes| &bytes[..])),
+            (
+                TAG_SAFEPARTS_VERSION,
+                self.safeparts_version.as_deref().map(str::as_bytes),
+            ),
+        ];
+        for (tag, value) in fields {
+            let Some(value) = value else { continue };
+            let len = u16::try_from(value.len())
+                .map_err(|_| CoreError::Encoding("metadata field too long".to_string()))?;
+            out.push(tag);
+            out.extend_from_slice(&len.to_be_bytes());
+            out.extend_from_slice(value);
+        }
+        out.push(TAG_END);
+        out.extend_from_slice(secret);
+        Ok(out)
+    }
+
+    /// Split data written by [`SecretMetadata::wrap`] into metadata and secret.
+    pub fn unwrap(data: &[u8]) -> CoreResult<(Self, Vec<u8>)> {
+        let malformed = || CoreError::Encoding("malformed secret metadata".to_string());
+        if data.first() != Some(&VERSION) {
+            return Err(malformed());
+        }
+
+        let mut metadata = Self::default();
+        let mut offset = 1;
+        loop {
+            let tag = *data.get(offset).ok_or_else(malformed)?;
+            offset += 1;
+            if tag == TAG_END {
+                break;
+            }
+            let len = data
+                .get(offset..offset + 2)
+                .map(|len| u16::from_be_bytes([len[0], len[1]]) as usize)
+                .ok_or_else(malformed)?;
+            offset += 2;
+            let value = data.get(offset..offset + len).ok_or_else(malformed)?;
+            offset += len;
+
+            let text = || {
+                std::str::from_utf8(value)
+                    .map(str::to_string)
+                    .map_err(|_| malformed())
+            };
+            match tag {
+                TAG_FILENAME => metadata.filename = Some(text()?),
+                TAG_CONTENT_TYPE => metadata.content_type = Some(text()?),
+                TAG_LABEL => metadata.label = Some(text()?),
+                TAG_CREATED_AT => {
+                    let bytes: [u8; 8] = value.try_into().map_err(|_| malformed())?;
+                    metadata.created_at = Some(u64::from_be_bytes(bytes));
+                }
+                TAG_SAFEPARTS_VERSION => metadata.safeparts_version = Some(text()?),
+                _ => {}
+            }
+        }
+
+        Ok((metadata, data[offset..].to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_round_trips_and_skips_unknown_fields() {
+        let metadata = SecretMetadata {
+            filename: Some("id_ed25519".to_string()),
+            content_type: Some("text/plain".to_string()),
+            label: Some("deploy key".to_string()),
+            ..SecretMetadata::now()
+        };
+        let wrapped = metadata.wrap(b"key bytes").unwrap();
+        assert_eq!(
+            SecretMetadata::unwrap(&wrapped).unwrap(),
+            (metadata, b"key bytes".to_vec())
+        );
+
+        let mut future = vec![VERSION, 9, 0, 2, b'h', b'i', TAG_LABEL, 0, 1, b'x', TAG_END];
+        future.extend_from_slice(b"data");
+        let (metadata, secret) = SecretMetadata::unwrap(&future).unwrap();
+        assert_eq!(metadata.label.as_deref(), Some("x"));
+        assert_eq!(secret, b"data");
+
+        assert!(SecretMetadata::unwrap(&[VERSION, TAG_LABEL, 0, 5, b'x']).is_err());
+        assert!(SecretMetadata::unwrap(&[2, TAG_END]).is_err());
+    }
+
+    #[test]
+    fn safe_filename_rejects_paths() {
+        let named = |name: &str| SecretMetadata {
+            filename: Some(name.to_string()),
+            ..SecretMetadata::default()
+        };
+        assert_eq!(named("backup.tar").safe_filename(), Some("backup.tar"));
+        for unsafe_name in ["", ".", "..", "../etc/passwd", "dir/file", "c:\\x", "a\nb"] {
+            assert_eq!(named(unsafe_name).safe_filename(), None, "{unsafe_name:?}");
+        }
+    }
+}
@@ -3,6 +3,7 @@ use core::convert::TryInto;
 use bip39::{Language, Mnemonic};
 
 use crate::error::{CoreError, CoreResult};
+use crate::mnemo_fix;
 use crate::packet::{self, SharePacket};
 
 const ENTROPY_LEN: usize = 32;
@@ -46,19 +47,31 @@ pub fn decode_packet(s: &str) -> CoreResult<SharePacket> {
         return Err(CoreError::Encoding("no bip39 phrases provided".to_string()));
     }
 
+    // Resolve every word first so a typo anywhere is reported by its
+    // position in the whole share.
+    let words: Vec<&str> = phrases
+        .iter()
+        .flat_map(|phrase| phrase.split_whitespace())
+        .collect();
+    let indices =
+        mnemo_fix::resolve_words(&words).map_err(|corrections| CoreError::MisspelledWords {
+            share: 1,
+            corrections,
+        })?;
+    let word_list = Language::English.word_list();
+    let mut indices = indices.into_iter();
+
     let mut expected_count: Option<u16> = None;
     let mut chunks: Vec<Option<[u8; CHUNK_LEN]>> = Vec::new();
 
     for phrase in phrases {
-        let lowered;
-        let phrase = if phrase.bytes().any(|b| b.is_ascii_uppercase()) {
-            lowered = phrase.to_ascii_lowercase();
-            lowered.as_str()
-        } else {
-            phrase
-        };
+        let phrase: Vec<&str> = phrase
+            .split_whitespace()
+            .zip(indices.by_ref())
+            .map(|(_, index)| word_list[index as usize])
+            .collect();
 
-        let mnemonic = Mnemonic::parse_in(Language::English, phrase)
+        let mnemonic = Mnemonic::parse_in(Language::English, phrase.join(" "))
             .map_err(|e| CoreError::Encoding(e.to_string()))?;
 
         let entropy_vec = mnemonic.to_entropy();
@@ -148,18 +161,53 @@ pub fn decode_packet(s: &str) -> CoreResult<SharePacket> {
 mod tests {
     use super::*;
     use crate::packet::SharePacket;
-    use crate::sss::SetId;
+    use crate::sss::{RawShare, SetId};
+
+    #[test]
+    fn typos_are_reported_by_position_across_phrases() {
+        let pkt = crate::packet::SharePacket::from_raw_share(crate::sss::RawShare {
+            set_id: SetId([5u8; 16]),
+            k: 2,
+            n: 3,
+            x: 1,
+            y: (0u8..40).collect(),
+        });
+        let encoded = encode_packet(&pkt).unwrap();
+        assert!(encoded.contains(FRAME_SEPARATOR));
+
+        let shortened: Vec<String> = encoded
+            .split_whitespace()
+            .map(|word| word.get(..4).unwrap_or(word).to_ascii_uppercase())
+            .collect();
+        assert_eq!(decode_packet(&shortened.join(" ")).unwrap(), pkt);
+
+        // Word 30 sits in the second phrase.
+        let mut words: Vec<String> = encoded
+            .split_whitespace()
+            .filter(|word| *word != "/")
+            .map(str::to_string)
+            .collect();
+        let expected = words[29].clone();
+        words[29].push('q');
+        words.insert(24, "/".to_string());
+        let Err(CoreError::MisspelledWords { corrections, .. }) = decode_packet(&words.join(" "))
+        else {
+            panic!("expected a misspelled word");
+        };
+        assert_eq!(corrections.len(), 1);
+        assert_eq!(corrections[0].position, 30);
+        assert!(corrections[0].candidates.contains(&expected));
+    }
 
     #[test]
     fn round_trip_multi_frame_packet() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([7u8; 16]),
             k: 3,
             n: 5,
             x: 4,
-            payload: (0u8..200).collect(),
-            crypto_params: None,
-        };
+            y: (0u8..200).collect(),
+        });
 
         let s = encode_packet(&pkt).unwrap();
         let decoded = decode_packet(&s).unwrap();
@@ -168,14 +216,13 @@ mod tests {
 
     #[test]
     fn decode_accepts_mixed_case_words() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([7u8; 16]),
             k: 3,
             n: 5,
             x: 4,
-            payload: (0u8..200).collect(),
-            crypto_params: None,
-        };
+            y: (0u8..200).collect(),
+        });
 
         let s = encode_packet(&pkt).unwrap();
         let phrases: Vec<String> = s
@@ -206,14 +253,13 @@ mod tests {
 
     #[test]
     fn missing_frame_is_rejected() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([1u8; 16]),
             k: 2,
             n: 3,
             x: 1,
-            payload: (0u8..120).collect(),
-            crypto_params: None,
-        };
+            y: (0u8..120).collect(),
+        });
 
         let s = encode_packet(&pkt).unwrap();
         let mut parts: Vec<&str> = s
@@ -230,14 +276,13 @@ mod tests {
 
     #[test]
     fn invalid_phrase_is_rejected() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([2u8; 16]),
             k: 2,
             n: 3,
             x: 2,
-            payload: vec![1, 2, 3, 4, 5, 6],
-            crypto_params: None,
-        };
+            y: vec![1, 2, 3, 4, 5, 6],
+        });
 
         let s = encode_packet(&pkt).unwrap();
         let mut phrases: Vec<String> = s
@@ -0,0 +1,237 @@
+//! Typo tolerance for the BIP-39 English word list behind both mnemonic
+//! encodings.
+//!
+//! [`resolve`] accepts a word in any case, or the first four or more letters
+//! of exactly one word, which BIP-39 guarantees are unique. Anything else is
+//! reported as a [`WordCorrection`] listing the closest words: an edit
+//! distance where a slip to a neighbouring QWERTY key and a swap of two
+//! letters cost half an edit.
+
+use std::fmt;
+
+use bip39::Language;
+
+/// Letters that identify a BIP-39 English word.
+pub const PREFIX_LEN: usize = 4;
+
+/// Most candidates suggested for one word.
+pub const MAX_CANDIDATES: usize = 5;
+
+/// Largest distance suggested, in half edits: two plain typos.
+const MAX_DISTANCE: usize = 4;
+
+const KEYBOARD_ROWS: [&[u8]; 3] = [b"qwertyuiop", b"asdfghjkl", b"zxcvbnm"];
+
+/// A word of a share that is misspelled or, for `mnemo-words`, breaks the
+/// checksum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordCorrection {
+    /// 1-based position of the word in its share, not counting `/`
+    /// separators between BIP-39 phrases.
+    pub position: usize,
+    /// The word as typed.
+    pub typed: String,
+    /// Likely intended words, best first; empty when nothing is close.
+    pub candidates: Vec<String>,
+    /// Putting the first candidate in place makes the share's checksum pass.
+    pub verified: bool,
+}
+
+impl fmt::Display for WordCorrection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match resolve(&self.typed) {
+            Some(_) => "fails the checksum",
+            None => "is not in the word list",
+        };
+        write!(f, "word {} {:?} {problem}", self.position, self.typed)?;
+        if !self.candidates.is_empty() {
+            write!(f, "; did you mean {}?", self.candidates.join(", "))?;
+        }
+        if self.verified {
+            write!(f, " ({} passes the checksum)", self.candidates[0])?;
+        }
+        Ok(())
+    }
+}
+
+/// Join corrections for an error message.
+pub fn describe(corrections: &[WordCorrection]) -> String {
+    corrections
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// The word-list index of `word`, written in full in any case or as a
+/// prefix of at least [`PREFIX_LEN`] letters that only one word starts with.
+pub fn resolve(word: &str) -> Option<u16> {
+    let list = Language::English.word_list();
+    let word = word.to_ascii_lowercase();
+    if let Ok(index) = list.binary_search(&word.as_str()) {
+        return Some(index as u16);
+    }
+    if word.len() < PREFIX_LEN {
+        return None;
+    }
+    let start = list.partition_point(|entry| *entry < word.as_str());
+    let matches = list[start..]
+        .iter()
+        .take_while(|entry| entry.starts_with(&word))
+        .count();
+    (matches == 1).then_some(start as u16)
+}
+
+/// Resolve every word, or describe each one that does not resolve.
+pub fn resolve_words(words: &[&str]) -> Result<Vec<u16>, Vec<WordCorrection>> {
+    let mut indices = Vec::with_capacity(words.len());
+    let mut corrections = Vec::new();
+    for (position, word) in (1..).zip(words) {
+        match resolve(word) {
+            Some(index) => indices.push(index),
+            None => corrections.push(WordCorrection {
+                position,
+                typed: word.to_string(),
+                candidates: suggest(word).into_iter().map(str::to_string).collect(),
+                verified: false,
+            }),
+        }
+    }
+    if corrections.is_empty() {
+        Ok(indices)
+    } else {
+        Err(corrections)
+    }
+}
+
+/// Up to [`MAX_CANDIDATES`] words close to `typed`, closest first. Words
+/// sharing its first [`PREFIX_LEN`] letters come before the rest.
+pub fn suggest(typed: &str) -> Vec<&'static str> {
+    let typed = typed.to_ascii_lowercase();
+    let prefix = typed.get(..PREFIX_LEN);
+    let mut ranked: Vec<(bool, usize, &'static str)> = Language::English
+        .word_list()
+        .iter()
+        .map(|&word| {
+            let same_prefix = prefix.is_some_and(|prefix| word.starts_with(prefix));
+            (
+                !same_prefix,
+                distance(typed.as_bytes(), word.as_bytes()),
+                word,
+            )
+        })
+        .filter(|&(other_prefix, distance, _)| !other_prefix || distance <= MAX_DISTANCE)
+        .collect();
+    ranked.sort_unstable();
+    ranked
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .map(|(_, _, word)| word)
+        .collect()
+}
+
+/// Optimal string alignment distance in half edits: inserting, deleting,
+/// or replacing a letter costs 2, while hitting a neighbouring key or
+/// swapping two adjacent letters costs 1.
+fn distance(a: &[u8], b: &[u8]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = 2 * i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = 2 * j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let replace = match (a[i - 1], b[j - 1]) {
+                (x, y) if x == y => 0,
+                (x, y) if adjacent_keys(x, y) => 1,
+                _ => 2,
+            };
+            let mut best = (rows[i - 1][j] + 2)
+                .min(rows[i][j - 1] + 2)
+                .min(rows[i - 1][j - 1] + replace);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// True for keys that touch on a QWERTY keyboard, where each row sits
+/// half a key to the right of the one above.
+fn adjacent_keys(a: u8, b: u8) -> bool {
+    let position = |key: u8| {
+        KEYBOARD_ROWS.iter().enumerate().find_map(|(row, keys)| {
+            keys.iter()
+                .position(|&k| k == key)
+                .map(|column| (row, column))
+        })
+    };
+    let (Some((row_a, col_a)), Some((row_b, col_b))) = (position(a), position(b)) else {
+        return false;
+    };
+    match row_a.abs_diff(row_b) {
+        0 => col_a.abs_diff(col_b) == 1,
+        1 => {
+            let (upper, lower) = if row_a < row_b {
+                (col_a, col_b)
+            } else {
+                (col_b, col_a)
+            };
+            lower == upper || lower + 1 == upper
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_accepts_case_and_unique_prefixes() {
+        assert_eq!(resolve("abandon"), Some(0));
+        assert_eq!(resolve("ZOO"), Some(2047));
+        assert_eq!(resolve("aban"), Some(0));
+        assert_eq!(resolve("abando"), Some(0));
+        assert_eq!(resolve("Zoo"), Some(2047));
+        // Too short to be a prefix, and a prefix of no word.
+        assert_eq!(resolve("aba"), None);
+        assert_eq!(resolve("abandonx"), None);
+    }
+
+    #[test]
+    fn suggestions_favour_neighbouring_keys_and_swaps() {
+        assert_eq!(suggest("abandpn")[0], "abandon");
+        assert_eq!(suggest("abnadon")[0], "abandon");
+        assert_eq!(suggest("wrodl")[0], "world");
+        assert_eq!(suggest("zoi")[0], "zoo");
+        assert!(suggest("qqqqqqqqqq").is_empty());
+        assert!(suggest("act").len() <= MAX_CANDIDATES);
+        assert!(adjacent_keys(b'a', b'w') && adjacent_keys(b'x', b'd'));
+        assert!(!adjacent_keys(b's', b'q') && !adjacent_keys(b'a', b'l'));
+    }
+
+    #[test]
+    fn resolve_words_reports_positions_and_candidates() {
+        assert_eq!(resolve_words(&["zoo", "ABAN"]), Ok(vec![2047, 0]));
+        let corrections = resolve_words(&["zoo", "abandpn", "xyzzy"]).unwrap_err();
+        assert_eq!(corrections.len(), 2);
+        assert_eq!(
+            (corrections[0].position, corrections[0].typed.as_str()),
+            (2, "abandpn")
+        );
+        assert_eq!(corrections[0].candidates[0], "abandon");
+        assert_eq!(corrections[1].position, 3);
+        assert_eq!(
+            corrections[0].to_string(),
+            format!(
+                "word 2 \"abandpn\" is not in the word list; did you mean {}?",
+                corrections[0].candidates.join(", ")
+            )
+        );
+    }
+}
@@ -1,20 +1,24 @@
-use std::collections::HashMap;
-
 use bip39::Language;
 
 use crate::error::{CoreError, CoreResult};
-use crate::packet::SharePacket;
+use crate::mnemo_fix::{self, WordCorrection};
+use crate::packet::{self, SharePacket};
 
 const CRC16_POLY: u16 = 0x1021;
 const CRC16_INIT: u16 = 0xFFFF;
 
+/// Frame a packet as words: a `u32` length, the packet, and a CRC-16.
+///
+/// Compact packets carry their own length, so their frame drops the `u32`.
 pub fn encode_packet(packet: &SharePacket) -> CoreResult<String> {
     let payload = packet.encode_binary()?;
 
     let mut framed = Vec::with_capacity(4 + payload.len() + 2);
-    let payload_len = u32::try_from(payload.len())
-        .map_err(|_| CoreError::Encoding("packet too large".to_string()))?;
-    framed.extend_from_slice(&payload_len.to_be_bytes());
+    if !packet.compact {
+        let payload_len = u32::try_from(payload.len())
+            .map_err(|_| CoreError::Encoding("packet too large".to_string()))?;
+        framed.extend_from_slice(&payload_len.to_be_bytes());
+    }
     framed.extend_from_slice(&payload);
 
     let crc = crc16_ccitt_false(&framed);
@@ -24,26 +28,64 @@ pub fn encode_packet(packet: &SharePacket) -> CoreResult<String> {
     Ok(words.join(" "))
 }
 
+/// Decode a `mnemo-words` share.
+///
+/// Words resolve through [`mnemo_fix::resolve`]. A misspelled word, or a
+/// single valid word that breaks the CRC, fails with
+/// [`CoreError::MisspelledWords`], its candidates ranked by whether they make
+/// the CRC pass.
 pub fn decode_packet(s: &str) -> CoreResult<SharePacket> {
     let words: Vec<&str> = s.split_whitespace().collect();
     if words.is_empty() {
         return Err(CoreError::Encoding("no words provided".to_string()));
     }
 
-    let bytes = words_to_bytes(&words)?;
+    let indices = match mnemo_fix::resolve_words(&words) {
+        Ok(indices) => indices,
+        Err(mut corrections) => {
+            if let [correction] = corrections.as_mut_slice() {
+                rank_by_checksum(&words, correction);
+            }
+            return Err(CoreError::MisspelledWords {
+                share: 1,
+                corrections,
+            });
+        }
+    };
+
+    decode_indices(&indices).map_err(|error| {
+        let corrections = checksum_substitutions(&words, &indices);
+        if corrections.is_empty() {
+            error
+        } else {
+            CoreError::MisspelledWords {
+                share: 1,
+                corrections,
+            }
+        }
+    })
+}
+
+fn decode_indices(indices: &[u16]) -> CoreResult<SharePacket> {
+    let bytes = indices_to_bytes(indices);
     if bytes.len() < 4 + 2 {
         return Err(CoreError::Encoding(
             "mnemo-words payload too short".to_string(),
         ));
     }
 
-    let len = u32::from_be_bytes(
-        bytes[0..4]
-            .try_into()
-            .map_err(|_| CoreError::Encoding("mnemo-words length header missing".to_string()))?,
-    ) as usize;
-
-    let expected_total = 4usize
+    // A full frame starts with a small length, never the compact marker.
+    let (header_len, len): (usize, usize) = if packet::is_compact(&bytes) {
+        (0, packet::binary_total_len(&bytes)?)
+    } else {
+        let len =
+            u32::from_be_bytes(bytes[0..4].try_into().map_err(|_| {
+                CoreError::Encoding("mnemo-words length header missing".to_string())
+            })?) as usize;
+        (4, len)
+    };
+
+    let expected_total = header_len
         .checked_add(len)
         .and_then(|v| v.checked_add(2))
         .ok_or_else(|| CoreError::Encoding("mnemo-words length overflow".to_string()))?;
@@ -64,10 +106,56 @@ pub fn decode_packet(s: &str) -> CoreResult<SharePacket> {
         return Err(CoreError::Encoding("mnemo-words crc mismatch".to_string()));
     }
 
-    let payload = &data[4..];
+    let payload = &data[header_len..];
     SharePacket::decode_binary(payload)
 }
 
+/// Move the candidates that make the frame decode to the front when
+/// `correction` is the only unknown word.
+fn rank_by_checksum(words: &[&str], correction: &mut WordCorrection) {
+    let mut indices: Vec<u16> = words
+        .iter()
+        .map(|word| mnemo_fix::resolve(word).unwrap_or(0))
+        .collect();
+    let (passing, failing): (Vec<String>, Vec<String>) =
+        correction.candidates.drain(..).partition(|candidate| {
+            indices[correction.position - 1] = mnemo_fix::resolve(candidate).unwrap_or(0);
+            decode_indices(&indices).is_ok()
+        });
+    correction.verified = !passing.is_empty();
+    correction.candidates = passing.into_iter().chain(failing).collect();
+}
+
+/// Single-word substitutions among each word's neighbours that make the
+/// frame decode, one correction per position where any does.
+fn checksum_substitutions(words: &[&str], indices: &[u16]) -> Vec<WordCorrection> {
+    let word_list = Language::English.word_list();
+    let mut trial = indices.to_vec();
+    let mut corrections = Vec::new();
+    for (position, &index) in indices.iter().enumerate() {
+        let current = word_list[index as usize];
+        let candidates: Vec<String> = mnemo_fix::suggest(current)
+            .into_iter()
+            .filter(|&candidate| candidate != current)
+            .filter(|candidate| {
+                trial[position] = mnemo_fix::resolve(candidate).unwrap_or(index);
+                decode_indices(&trial).is_ok()
+            })
+            .map(str::to_string)
+            .collect();
+        trial[position] = index;
+        if !candidates.is_empty() {
+            corrections.push(WordCorrection {
+                position: position + 1,
+                typed: words[position].to_string(),
+                candidates,
+                verified: true,
+            });
+        }
+    }
+    corrections
+}
+
 fn bytes_to_words(bytes: &[u8]) -> Vec<String> {
     let word_list = Language::English.word_list();
 
@@ -97,21 +185,12 @@ fn bytes_to_words(bytes: &[u8]) -> Vec<String> {
     out
 }
 
-fn words_to_bytes(words: &[&str]) -> CoreResult<Vec<u8>> {
-    let word_indices: HashMap<_, _> = Language::English
-        .word_list()
-        .iter()
-        .enumerate()
-        .map(|(index, word)| (*word, index as u16))
-        .collect();
-
+fn indices_to_bytes(indices: &[u16]) -> Vec<u8> {
     let mut out = Vec::new();
     let mut acc: u32 = 0;
     let mut acc_bits: u8 = 0;
 
-    for &word in words {
-        let index = word_index(word, &word_indices)?;
-
+    for &index in indices {
         acc = (acc << 11) | u32::from(index);
         acc_bits += 11;
 
@@ -132,22 +211,7 @@ fn words_to_bytes(words: &[&str]) -> CoreResult<Vec<u8>> {
         out.push(byte);
     }
 
-    Ok(out)
-}
-
-fn word_index(word: &str, indices: &HashMap<&str, u16>) -> CoreResult<u16> {
-    if let Some(&index) = indices.get(word) {
-        return Ok(index);
-    }
-
-    if word.bytes().any(|byte| byte.is_ascii_uppercase()) {
-        let lowercase = word.to_ascii_lowercase();
-        if let Some(&index) = indices.get(lowercase.as_str()) {
-            return Ok(index);
-        }
-    }
-
-    Err(CoreError::Encoding(format!("unknown word: {word}")))
+    out
 }
 
 fn crc16_ccitt_false(bytes: &[u8]) -> u16 {
@@ -168,18 +232,17 @@ fn crc16_ccitt_false(bytes: &[u8]) -> u16 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sss::SetId;
+    use crate::sss::{RawShare, SetId};
 
     #[test]
     fn round_trip_packet() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([1u8; 16]),
             k: 3,
             n: 5,
             x: 2,
-            payload: vec![9, 8, 7, 6, 5, 4, 3],
-            crypto_params: None,
-        };
+            y: vec![9, 8, 7, 6, 5, 4, 3],
+        });
 
         let s = encode_packet(&pkt).unwrap();
         let decoded = decode_packet(&s).unwrap();
@@ -188,14 +251,13 @@ mod tests {
 
     #[test]
     fn decode_accepts_mixed_case_words() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([1u8; 16]),
             k: 3,
             n: 5,
             x: 2,
-            payload: vec![9, 8, 7, 6, 5, 4, 3],
-            crypto_params: None,
-        };
+            y: vec![9, 8, 7, 6, 5, 4, 3],
+        });
 
         let s = encode_packet(&pkt).unwrap();
         let mixed: Vec<String> = s
@@ -216,14 +278,13 @@ mod tests {
 
     #[test]
     fn crc_mismatch_detected() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([2u8; 16]),
             k: 2,
             n: 3,
             x: 1,
-            payload: vec![1, 2, 3, 4, 5],
-            crypto_params: None,
-        };
+            y: vec![1, 2, 3, 4, 5],
+        });
 
         let mut words: Vec<String> = encode_packet(&pkt)
             .unwrap()
@@ -240,4 +301,85 @@ mod tests {
         let err = decode_packet(&corrupted).unwrap_err();
         assert!(matches!(err, CoreError::Encoding(_)));
     }
+
+    #[test]
+    fn typos_are_reported_with_checksum_ranked_candidates() {
+        let pkt = SharePacket::from_raw_share(crate::sss::RawShare {
+            set_id: SetId([4u8; 16]),
+            k: 2,
+            n: 3,
+            x: 3,
+            y: b"typo tolerance".to_vec(),
+        });
+        let encoded = encode_packet(&pkt).unwrap();
+        let words: Vec<&str> = encoded.split_whitespace().collect();
+
+        // Unique four-letter prefixes decode as the full word.
+        let prefixes: Vec<&str> = words
+            .iter()
+            .map(|word| word.get(..mnemo_fix::PREFIX_LEN).unwrap_or(word))
+            .collect();
+        assert_eq!(decode_packet(&prefixes.join(" ")).unwrap(), pkt);
+
+        // A word with a doubled letter is not in the list.
+        let mut typo: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+        typo[5] = format!("{}{}", &words[5][..1], words[5]);
+        let Err(CoreError::MisspelledWords { share, corrections }) = decode_packet(&typo.join(" "))
+        else {
+            panic!("expected a misspelled word");
+        };
+        assert_eq!((share, corrections.len()), (1, 1));
+        assert_eq!(corrections[0].position, 6);
+        assert_eq!(corrections[0].typed, typo[5]);
+        assert_eq!(corrections[0].candidates[0], words[5]);
+        assert!(corrections[0].verified);
+
+        // A valid neighbour of the right word fails the CRC, which finds it.
+        let (position, neighbour) = words
+            .iter()
+            .enumerate()
+            .find_map(|(position, &word)| {
+                mnemo_fix::suggest(word)
+                    .into_iter()
+                    .find(|&near| near != word && mnemo_fix::suggest(near).contains(&word))
+                    .map(|near| (position, near))
+            })
+            .unwrap();
+        let mut swapped = words.clone();
+        swapped[position] = neighbour;
+        let Err(CoreError::MisspelledWords { corrections, .. }) = decode_packet(&swapped.join(" "))
+        else {
+            panic!("expected a checksum correction");
+        };
+        let correction = corrections
+            .iter()
+            .find(|correction| correction.position == position + 1)
+            .unwrap();
+        assert_eq!(correction.typed, neighbour);
+        assert!(correction.candidates.iter().any(|c| c == words[position]));
+        assert!(correction.verified);
+    }
+
+    #[test]
+    fn compact_packets_drop_the_length_header() {
+        let full = SharePacket::from_raw_share(crate::sss::RawShare {
+            set_id: SetId([3, 1, 4, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            k: 2,
+            n: 3,
+            x: 1,
+            y: vec![5u8; 33],
+        })
+        .with_authenticated_header(true);
+        let compact = full.clone().with_compact(true);
+
+        let words = encode_packet(&compact).unwrap();
+        let full_words = encode_packet(&full).unwrap();
+        assert!(words.split_whitespace().count() + 15 < full_words.split_whitespace().count());
+        assert_eq!(decode_packet(&words).unwrap(), compact);
+        assert_eq!(decode_packet(&full_words).unwrap(), full);
+
+        let mut words: Vec<&str> = words.split_whitespace().collect();
+        words.swap(3, 4);
+        assert!(decode_packet(&words.join(" ")).is_err());
+    }
 }
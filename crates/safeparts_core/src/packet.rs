@@ -1,19 +1,76 @@
 use core::convert::TryInto;
 
-use crate::crypto::CryptoParams;
+use crate::crypto::{self, AeadAlgorithm, CryptoParams};
 use crate::error::{CoreError, CoreResult};
+use crate::policy::{Placement, Policy};
 use crate::sss::{RawShare, SetId};
+use crate::vss::{COMMITMENT_LEN, Commitments};
 
 const MAGIC: [u8; 4] = *b"SMN1";
+const SET_HEADER_MAGIC: [u8; 4] = *b"SPH3";
 const VERSION_V1: u8 = 1;
 const VERSION_V2: u8 = 2;
+const VERSION_V3: u8 = 3;
+const VERSION_V4: u8 = 4;
 
 const FLAG_ENCRYPTED: u8 = 0b0000_0001;
+const FLAG_VERIFIABLE: u8 = 0b0000_0010;
+const FLAG_RESHARED: u8 = 0b0000_0100;
+const FLAG_REFRESHED: u8 = 0b0000_1000;
+const FLAG_POLICY: u8 = 0b0001_0000;
+const FLAG_DISPERSED: u8 = 0b0010_0000;
+const FLAG_STREAMED: u8 = 0b0100_0000;
+const FLAG_CIPHER_SUITE: u8 = 0b1000_0000;
+
+// Version 4 packets add a second flags byte after the base header.
+const EXT_FLAG_LOCKED: u8 = 0b0000_0001;
+const EXT_FLAG_KEY_SLOTS: u8 = 0b0000_0010;
+const EXT_FLAG_METADATA: u8 = 0b0000_0100;
+const EXT_FLAG_PADDED: u8 = 0b0000_1000;
+const EXT_FLAG_COMPRESSED: u8 = 0b0001_0000;
+const KNOWN_EXT_FLAGS: u8 = EXT_FLAG_LOCKED
+    | EXT_FLAG_KEY_SLOTS
+    | EXT_FLAG_METADATA
+    | EXT_FLAG_PADDED
+    | EXT_FLAG_COMPRESSED;
+
+/// First byte of a compact packet; full packets start with [`MAGIC`].
+const COMPACT_MAGIC: u8 = 0xC5;
+
+const COMPACT_FLAG_ENCRYPTED: u8 = 0b0000_0001;
+const COMPACT_FLAG_CIPHER_SUITE: u8 = 0b0000_0010;
+const COMPACT_FLAG_NONCE: u8 = 0b0000_0100;
+const COMPACT_FLAG_FULL_SET_ID: u8 = 0b0000_1000;
+const COMPACT_FLAG_METADATA: u8 = 0b0001_0000;
+const COMPACT_FLAG_PADDED: u8 = 0b0010_0000;
+const COMPACT_FLAG_COMPRESSED: u8 = 0b0100_0000;
+const COMPACT_FLAG_MORE: u8 = 0b1000_0000;
+
+// Second compact flags byte, present when COMPACT_FLAG_MORE is set.
+const COMPACT_EXT_RESHARED: u8 = 0b0000_0001;
+const COMPACT_EXT_FULL_PARENT_ID: u8 = 0b0000_0010;
+const COMPACT_EXT_REFRESHED: u8 = 0b0000_0100;
+const COMPACT_EXT_LOCKED: u8 = 0b0000_1000;
+const COMPACT_EXT_STEALTH: u8 = 0b0001_0000;
+const KNOWN_COMPACT_EXT_FLAGS: u8 = COMPACT_EXT_RESHARED
+    | COMPACT_EXT_FULL_PARENT_ID
+    | COMPACT_EXT_REFRESHED
+    | COMPACT_EXT_LOCKED
+    | COMPACT_EXT_STEALTH;
 
 const BASE_HEADER_LEN: usize = 4 + 1 + 1 + 1 + 1 + 1 + 16;
 const PAYLOAD_LEN_FIELD_LEN: usize = 4;
 
 const CRYPTO_PARAMS_LEN: usize = 16 + 12 + 4 + 4 + 4;
+const ALGORITHM_IDS_LEN: usize = 2;
+const COMMITMENT_COUNT_LEN: usize = 4;
+const PARENT_SET_ID_LEN: usize = 16;
+const EPOCH_LEN: usize = 4;
+const POLICY_LEN_FIELD_LEN: usize = 2;
+const POLICY_PATH_LEN_FIELD_LEN: usize = 1;
+const DISPERSED_LEN_LEN: usize = 8;
+const FRAME_LEN_LEN: usize = 4;
+const EXT_FLAGS_LEN: usize = 1;
 
 /// A self-describing Safeparts share.
 ///
@@ -23,6 +80,69 @@ const CRYPTO_PARAMS_LEN: usize = 16 + 12 + 4 + 4 + 4;
 ///
 /// The metadata fields are not secret. They let the combine path reject mixed
 /// share sets, duplicate coordinates, and incompatible encryption parameters.
+///
+/// Packets from a verifiable split carry the set's [`Commitments`], and their
+/// payload holds scalar shares from [`crate::vss`] instead of GF(256) bytes.
+///
+/// Packets from [`crate::reshare`] record the `parent_set_id` of the set they
+/// replace, so tooling can tell that the parent's shares are superseded.
+/// `epoch` counts [`crate::refresh`] rounds; shares from different epochs of
+/// one set do not combine. Packets from [`crate::split_secret_with_policy`]
+/// carry the whole access policy and their holder's place in it.
+///
+/// Packets from [`crate::split_secret_large`] set `dispersed_len` to the
+/// length of the dispersed ciphertext. Their payload is a share of the
+/// encryption key followed by one [`crate::ida`] fragment of the ciphertext.
+///
+/// Version 3 packets set `authenticated_header`: their set-level header (see
+/// [`set_header`]) is bound into the integrity tag and, for passphrase
+/// splits, passed to the AEAD as associated data. Version 1 and 2
+/// packets still decode, with `authenticated_header` unset.
+///
+/// Passphrase splits that use an AEAD other than ChaCha20-Poly1305 set the
+/// cipher-suite flag and start their crypto section with the KDF and AEAD ids.
+/// Without the flag the section means Argon2id with ChaCha20-Poly1305, so
+/// packets of the original scheme are unchanged.
+///
+/// A `frame_len` marks the header of a [`crate::stream`] share. Its payload is
+/// a share of the stream's MAC key; the secret follows in frames outside the
+/// packet.
+///
+/// A `share_lock` means the payload is encrypted under this share's own
+/// passphrase (see [`crate::lock_share`]). Locked packets are written as
+/// version 4, which is version 3 plus a second flags byte, and must be
+/// unlocked with [`crate::unlock_share`] before they combine.
+///
+/// `key_slots` marks a split from [`crate::split_secret_with_key_slots`]. Its
+/// combined data is a [`crypto::SlotEnvelope`] that any of several
+/// passphrases opens; the slots themselves are only readable after combine.
+/// Such packets have no `crypto_params` and are written as version 4.
+///
+/// `metadata` marks a split from [`crate::split_secret_with_metadata`]: the
+/// protected data starts with a [`crate::metadata::SecretMetadata`] record.
+/// Such packets are written as version 4.
+///
+/// `padded` marks a split whose protected data ends in [`crate::padding`], so
+/// share length does not reveal the secret length. Such packets are written
+/// as version 4.
+///
+/// `compressed` marks a split whose protected data was compressed with
+/// [`crate::compression`] before padding and encryption. Such packets are
+/// written as version 4.
+///
+/// `compact` selects the compact encoding, which starts with a single marker
+/// byte instead of `SMN1`, writes lengths and Argon2 costs as varints, stores
+/// short set ids (see [`SetId::random_short`]) in four bytes, and omits
+/// nonces derived from the salt (see [`CryptoParams::random_derived`]). It
+/// covers plain, passphrase, reshared, refreshed, and locked shares with
+/// metadata, padding, and compression, but not verifiable, policy, large,
+/// streamed, or key-slot splits. [`SharePacket::decode_binary`] reads both
+/// encodings.
+///
+/// `stealth` packets are compact packets that leave out `k`, `n`, and the set
+/// id, which are all zero in memory, and whose `x` was drawn at random from
+/// `1..=255` (see [`crate::SplitOptions::stealth`]). A share found alone then
+/// says nothing about how many others exist or are needed.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SharePacket {
     pub set_id: SetId,
@@ -31,6 +151,20 @@ pub struct SharePacket {
     pub x: u8,
     pub payload: Vec<u8>,
     pub crypto_params: Option<CryptoParams>,
+    pub commitments: Option<Commitments>,
+    pub parent_set_id: Option<SetId>,
+    pub epoch: u32,
+    pub policy: Option<Placement>,
+    pub dispersed_len: Option<u64>,
+    pub frame_len: Option<u32>,
+    pub authenticated_header: bool,
+    pub share_lock: Option<CryptoParams>,
+    pub key_slots: bool,
+    pub metadata: bool,
+    pub padded: bool,
+    pub compressed: bool,
+    pub compact: bool,
+    pub stealth: bool,
 }
 
 impl SharePacket {
@@ -42,6 +176,20 @@ impl SharePacket {
             x: share.x,
             payload: share.y,
             crypto_params: None,
+            commitments: None,
+            parent_set_id: None,
+            epoch: 0,
+            policy: None,
+            dispersed_len: None,
+            frame_len: None,
+            authenticated_header: false,
+            share_lock: None,
+            key_slots: false,
+            metadata: false,
+            padded: false,
+            compressed: false,
+            compact: false,
+            stealth: false,
         }
     }
 
@@ -50,9 +198,122 @@ impl SharePacket {
         self
     }
 
-    /// Return true when the packet belongs to a passphrase-protected split.
+    pub fn with_commitments(mut self, commitments: Option<Commitments>) -> Self {
+        self.commitments = commitments;
+        self
+    }
+
+    pub fn with_parent_set_id(mut self, parent: Option<SetId>) -> Self {
+        self.parent_set_id = parent;
+        self
+    }
+
+    pub fn with_epoch(mut self, epoch: u32) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: Option<Placement>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_dispersed_len(mut self, len: Option<u64>) -> Self {
+        self.dispersed_len = len;
+        self
+    }
+
+    pub fn with_frame_len(mut self, frame_len: Option<u32>) -> Self {
+        self.frame_len = frame_len;
+        self
+    }
+
+    pub fn with_authenticated_header(mut self, authenticated: bool) -> Self {
+        self.authenticated_header = authenticated;
+        self
+    }
+
+    pub fn with_share_lock(mut self, lock: Option<CryptoParams>) -> Self {
+        self.share_lock = lock;
+        self
+    }
+
+    pub fn with_key_slots(mut self, key_slots: bool) -> Self {
+        self.key_slots = key_slots;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn with_padded(mut self, padded: bool) -> Self {
+        self.padded = padded;
+        self
+    }
+
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    pub fn with_stealth(mut self, stealth: bool) -> Self {
+        self.stealth = stealth;
+        self
+    }
+
+    /// Return true when the packet belongs to a passphrase-protected split,
+    /// with one passphrase or with key slots.
     pub fn is_encrypted(&self) -> bool {
-        self.crypto_params.is_some()
+        self.crypto_params.is_some() || self.key_slots
+    }
+
+    /// Return true when the packet belongs to a verifiable split.
+    pub fn is_verifiable(&self) -> bool {
+        self.commitments.is_some()
+    }
+
+    /// Return true when the packet belongs to a large-secret split.
+    pub fn is_dispersed(&self) -> bool {
+        self.dispersed_len.is_some()
+    }
+
+    /// Return true when the packet is the header of a streamed share.
+    pub fn is_streamed(&self) -> bool {
+        self.frame_len.is_some()
+    }
+
+    /// Return true when the payload is locked under a per-share passphrase.
+    pub fn is_locked(&self) -> bool {
+        self.share_lock.is_some()
+    }
+
+    /// The set-level header this packet's set binds, see [`set_header`].
+    pub fn set_header(&self) -> Vec<u8> {
+        set_header(
+            self.set_id,
+            self.k,
+            self.n,
+            self.crypto_params.as_ref(),
+            self.marks(),
+            self.policy.as_ref().map(|placement| &placement.policy),
+        )
+    }
+
+    /// The set-level marks of this packet, see [`SetMarks`].
+    pub fn marks(&self) -> SetMarks {
+        SetMarks {
+            key_slots: self.key_slots,
+            metadata: self.metadata,
+            padded: self.padded,
+            compressed: self.compressed,
+        }
     }
 
     pub fn to_raw_share(&self) -> CoreResult<RawShare> {
@@ -70,38 +331,160 @@ impl SharePacket {
     /// Most applications should prefer the text encoders in [`crate::encoding`]
     /// unless they control a binary storage format.
     pub fn encode_binary(&self) -> CoreResult<Vec<u8>> {
-        let flags = if let Some(params) = self.crypto_params {
+        if self.compact {
+            return self.encode_compact();
+        }
+        if self.stealth {
+            return Err(CoreError::InvalidPacket(
+                "stealth packets use the compact encoding".to_string(),
+            ));
+        }
+        let mut flags = 0;
+        if let Some(params) = self.crypto_params {
             // Validate params are sane.
-            if params.mem_cost_kib == 0 || params.time_cost == 0 || params.parallelism == 0 {
+            params
+                .kdf()
+                .validate()
+                .map_err(|_| CoreError::InvalidPacket("invalid crypto params".to_string()))?;
+            flags |= FLAG_ENCRYPTED;
+            if has_algorithm_ids(&params) {
+                flags |= FLAG_CIPHER_SUITE;
+            }
+        }
+        if let Some(commitments) = &self.commitments {
+            commitments.check_shape()?;
+            if commitments.k != self.k {
                 return Err(CoreError::InvalidPacket(
-                    "invalid crypto params".to_string(),
+                    "commitments threshold mismatch".to_string(),
                 ));
             }
-            FLAG_ENCRYPTED
-        } else {
-            0
+            flags |= FLAG_VERIFIABLE;
+        }
+        if self.parent_set_id.is_some() {
+            flags |= FLAG_RESHARED;
+        }
+        if self.epoch != 0 {
+            flags |= FLAG_REFRESHED;
+        }
+        let policy = match &self.policy {
+            Some(placement) => {
+                let text = placement.policy.to_string();
+                let text_len = u16::try_from(text.len())
+                    .map_err(|_| CoreError::InvalidPacket("policy too large".to_string()))?;
+                let path_len = u8::try_from(placement.path.len())
+                    .map_err(|_| CoreError::InvalidPacket("policy too deep".to_string()))?;
+                flags |= FLAG_POLICY;
+                Some((text, text_len, path_len, &placement.path))
+            }
+            None => None,
         };
+        if self.dispersed_len.is_some() {
+            flags |= FLAG_DISPERSED;
+        }
+        if self.frame_len.is_some() {
+            flags |= FLAG_STREAMED;
+        }
+        let mut ext_flags = 0;
+        if let Some(lock) = self.share_lock {
+            if !self.authenticated_header {
+                return Err(CoreError::InvalidPacket(
+                    "only version 3 packets can be locked".to_string(),
+                ));
+            }
+            lock.kdf()
+                .validate()
+                .map_err(|_| CoreError::InvalidPacket("invalid share lock".to_string()))?;
+            ext_flags |= EXT_FLAG_LOCKED;
+        }
+        if self.key_slots {
+            if !self.authenticated_header || self.crypto_params.is_some() {
+                return Err(CoreError::InvalidPacket(
+                    "key slots need a version 3 packet without crypto params".to_string(),
+                ));
+            }
+            ext_flags |= EXT_FLAG_KEY_SLOTS;
+        }
+        if (self.metadata || self.padded || self.compressed) && !self.authenticated_header {
+            return Err(CoreError::InvalidPacket(
+                "metadata, padding, and compression need a version 3 packet".to_string(),
+            ));
+        }
+        if self.metadata {
+            ext_flags |= EXT_FLAG_METADATA;
+        }
+        if self.padded {
+            ext_flags |= EXT_FLAG_PADDED;
+        }
+        if self.compressed {
+            ext_flags |= EXT_FLAG_COMPRESSED;
+        }
 
         let payload_len_u32 = u32::try_from(self.payload.len())
             .map_err(|_| CoreError::InvalidPacket("payload too large".to_string()))?;
 
         let mut out = Vec::with_capacity(
-            BASE_HEADER_LEN + CRYPTO_PARAMS_LEN + PAYLOAD_LEN_FIELD_LEN + self.payload.len(),
+            BASE_HEADER_LEN
+                + ALGORITHM_IDS_LEN
+                + crypto::MAX_NONCE_LEN
+                + CRYPTO_PARAMS_LEN
+                + PAYLOAD_LEN_FIELD_LEN
+                + self.payload.len(),
         );
         out.extend_from_slice(&MAGIC);
-        out.push(VERSION_V2);
+        out.push(if ext_flags != 0 {
+            VERSION_V4
+        } else if self.authenticated_header {
+            VERSION_V3
+        } else {
+            VERSION_V2
+        });
         out.push(flags);
         out.push(self.k);
         out.push(self.n);
         out.push(self.x);
         out.extend_from_slice(&self.set_id.0);
+        if ext_flags != 0 {
+            out.push(ext_flags);
+        }
 
-        if let Some(params) = self.crypto_params {
-            out.extend_from_slice(&params.salt);
-            out.extend_from_slice(&params.nonce);
-            out.extend_from_slice(&params.mem_cost_kib.to_be_bytes());
-            out.extend_from_slice(&params.time_cost.to_be_bytes());
-            out.extend_from_slice(&params.parallelism.to_be_bytes());
+        if let Some(params) = &self.crypto_params {
+            encode_crypto_params(params, &mut out);
+        }
+
+        if let Some(commitments) = &self.commitments {
+            let count = u32::try_from(commitments.points.len())
+                .map_err(|_| CoreError::InvalidPacket("too many commitments".to_string()))?;
+            out.extend_from_slice(&count.to_be_bytes());
+            for point in &commitments.points {
+                out.extend_from_slice(point);
+            }
+        }
+
+        if let Some(parent) = self.parent_set_id {
+            out.extend_from_slice(&parent.0);
+        }
+
+        if self.epoch != 0 {
+            out.extend_from_slice(&self.epoch.to_be_bytes());
+        }
+
+        if let Some((text, text_len, path_len, path)) = policy {
+            out.extend_from_slice(&text_len.to_be_bytes());
+            out.extend_from_slice(text.as_bytes());
+            out.push(path_len);
+            out.extend_from_slice(path);
+        }
+
+        if let Some(len) = self.dispersed_len {
+            out.extend_from_slice(&len.to_be_bytes());
+        }
+
+        if let Some(len) = self.frame_len {
+            out.extend_from_slice(&len.to_be_bytes());
+        }
+
+        if let Some(lock) = &self.share_lock {
+            encode_suite_params(lock, &mut out);
         }
 
         out.extend_from_slice(&payload_len_u32.to_be_bytes());
@@ -109,8 +492,147 @@ impl SharePacket {
         Ok(out)
     }
 
-    /// Decode a packet from Safeparts binary packet format.
+    /// Compact encoding, see [`SharePacket::compact`].
+    fn encode_compact(&self) -> CoreResult<Vec<u8>> {
+        if !self.authenticated_header {
+            return Err(CoreError::InvalidPacket(
+                "compact packets need an authenticated header".to_string(),
+            ));
+        }
+        if self.commitments.is_some()
+            || self.policy.is_some()
+            || self.dispersed_len.is_some()
+            || self.frame_len.is_some()
+            || self.key_slots
+        {
+            return Err(CoreError::InvalidPacket(
+                "compact packets do not support verifiable, policy, large, streamed, or key-slot splits"
+                    .to_string(),
+            ));
+        }
+        if self.stealth
+            && (self.k != 0
+                || self.n != 0
+                || self.set_id != SetId([0; 16])
+                || self.parent_set_id.is_some()
+                || self.epoch != 0)
+        {
+            return Err(CoreError::InvalidPacket(
+                "stealth packets carry no threshold, set id, parent, or epoch".to_string(),
+            ));
+        }
+
+        let mut flags = 0;
+        if let Some(params) = &self.crypto_params {
+            params
+                .kdf()
+                .validate()
+                .map_err(|_| CoreError::InvalidPacket("invalid crypto params".to_string()))?;
+            flags |= COMPACT_FLAG_ENCRYPTED;
+            if has_algorithm_ids(params) {
+                flags |= COMPACT_FLAG_CIPHER_SUITE;
+            }
+            if !params.has_derived_nonce() {
+                flags |= COMPACT_FLAG_NONCE;
+            }
+        }
+        if !self.stealth && !self.set_id.is_short() {
+            flags |= COMPACT_FLAG_FULL_SET_ID;
+        }
+        if self.metadata {
+            flags |= COMPACT_FLAG_METADATA;
+        }
+        if self.padded {
+            flags |= COMPACT_FLAG_PADDED;
+        }
+        if self.compressed {
+            flags |= COMPACT_FLAG_COMPRESSED;
+        }
+
+        let mut ext_flags = 0;
+        if let Some(parent) = self.parent_set_id {
+            ext_flags |= COMPACT_EXT_RESHARED;
+            if !parent.is_short() {
+                ext_flags |= COMPACT_EXT_FULL_PARENT_ID;
+            }
+        }
+        if self.epoch != 0 {
+            ext_flags |= COMPACT_EXT_REFRESHED;
+        }
+        if let Some(lock) = &self.share_lock {
+            lock.kdf()
+                .validate()
+                .map_err(|_| CoreError::InvalidPacket("invalid share lock".to_string()))?;
+            ext_flags |= COMPACT_EXT_LOCKED;
+        }
+        if self.stealth {
+            ext_flags |= COMPACT_EXT_STEALTH;
+        }
+        if ext_flags != 0 {
+            flags |= COMPACT_FLAG_MORE;
+        }
+
+        let payload_len = u32::try_from(self.payload.len())
+            .map_err(|_| CoreError::InvalidPacket("payload too large".to_string()))?;
+
+        let mut out = Vec::with_capacity(
+            BASE_HEADER_LEN + ALGORITHM_IDS_LEN + CRYPTO_PARAMS_LEN + self.payload.len(),
+        );
+        out.push(COMPACT_MAGIC);
+        out.push(flags);
+        if ext_flags != 0 {
+            out.push(ext_flags);
+        }
+        if self.stealth {
+            out.push(self.x);
+        } else {
+            out.push(self.k);
+            out.push(self.n);
+            out.push(self.x);
+            out.extend_from_slice(compact_set_id(&self.set_id));
+        }
+
+        if let Some(params) = &self.crypto_params {
+            if has_algorithm_ids(params) {
+                out.push(crypto::KDF_ARGON2ID);
+                out.push(params.aead.id());
+            }
+            out.extend_from_slice(&params.salt);
+            if !params.has_derived_nonce() {
+                out.extend_from_slice(params.nonce());
+            }
+            write_varint(params.mem_cost_kib, &mut out);
+            write_varint(params.time_cost, &mut out);
+            write_varint(params.parallelism, &mut out);
+        }
+
+        if let Some(parent) = &self.parent_set_id {
+            out.extend_from_slice(compact_set_id(parent));
+        }
+
+        if self.epoch != 0 {
+            write_varint(self.epoch, &mut out);
+        }
+
+        if let Some(lock) = &self.share_lock {
+            encode_suite_params(lock, &mut out);
+        }
+
+        write_varint(payload_len, &mut out);
+        out.extend_from_slice(&self.payload);
+        Ok(out)
+    }
+
+    /// Decode a packet from Safeparts binary packet format, full or compact.
     pub fn decode_binary(bytes: &[u8]) -> CoreResult<Self> {
+        if is_compact(bytes) {
+            let (packet, len) = decode_compact(bytes)?;
+            if len != bytes.len() {
+                return Err(CoreError::InvalidPacket("length mismatch".to_string()));
+            }
+            return Ok(packet);
+        }
+
         let total_len = binary_total_len(bytes)?;
         if bytes.len() != total_len {
             return Err(CoreError::InvalidPacket("length mismatch".to_string()));
@@ -125,78 +647,126 @@ impl SharePacket {
         let mut set_id = [0u8; 16];
         set_id.copy_from_slice(&bytes[9..25]);
 
-        let (crypto_params, payload_len_offset) = match version {
-            VERSION_V1 => (None, 25),
-            VERSION_V2 => {
-                let mut offset = BASE_HEADER_LEN;
-                let params =
-                    if (flags & FLAG_ENCRYPTED) != 0 {
-                        if bytes.len() < offset + CRYPTO_PARAMS_LEN {
-                            return Err(CoreError::InvalidPacket(
-                                "truncated crypto params".to_string(),
-                            ));
-                        }
-                        let mut salt = [0u8; 16];
-                        salt.copy_from_slice(&bytes[offset..offset + 16]);
-                        offset += 16;
-
-                        let mut nonce = [0u8; 12];
-                        nonce.copy_from_slice(&bytes[offset..offset + 12]);
-                        offset += 12;
-
-                        let mem_cost_kib =
-                            u32::from_be_bytes(bytes[offset..offset + 4].try_into().map_err(
-                                |_| CoreError::InvalidPacket("bad mem_cost".to_string()),
-                            )?);
-                        offset += 4;
-
-                        let time_cost =
-                            u32::from_be_bytes(bytes[offset..offset + 4].try_into().map_err(
-                                |_| CoreError::InvalidPacket("bad time_cost".to_string()),
-                            )?);
-                        offset += 4;
-
-                        let parallelism =
-                            u32::from_be_bytes(bytes[offset..offset + 4].try_into().map_err(
-                                |_| CoreError::InvalidPacket("bad parallelism".to_string()),
-                            )?);
-                        offset += 4;
-
-                        Some(CryptoParams {
-                            salt,
-                            nonce,
-                            mem_cost_kib,
-                            time_cost,
-                            parallelism,
+        let mut packet = Self::from_raw_share(RawShare {
+            set_id: SetId(set_id),
+            k,
+            n,
+            x,
+            y: Vec::new(),
+        });
+
+        let mut offset = BASE_HEADER_LEN;
+        let ext_flags = if version == VERSION_V4 {
+            offset += EXT_FLAGS_LEN;
+            bytes[BASE_HEADER_LEN]
+        } else {
+            0
+        };
+        match version {
+            VERSION_V1 => {}
+            VERSION_V2 | VERSION_V3 | VERSION_V4 => {
+                packet.authenticated_header = version != VERSION_V2;
+
+                if (flags & FLAG_ENCRYPTED) != 0 {
+                    let len = crypto_params_len(bytes, offset, flags)?;
+                    packet.crypto_params =
+                        Some(decode_crypto_params(&bytes[offset..offset + len], flags)?);
+                    offset += len;
+                }
+
+                if (flags & FLAG_VERIFIABLE) != 0 {
+                    let count = read_u32(bytes, offset)? as usize;
+                    offset += COMMITMENT_COUNT_LEN;
+                    let points = bytes[offset..offset + count * COMMITMENT_LEN]
+                        .chunks_exact(COMMITMENT_LEN)
+                        .map(|chunk| {
+                            let mut point = [0u8; COMMITMENT_LEN];
+                            point.copy_from_slice(chunk);
+                            point
                         })
-                    } else {
-                        None
-                    };
+                        .collect();
+                    offset += count * COMMITMENT_LEN;
+
+                    let commitments = Commitments { k, points };
+                    commitments.check_shape()?;
+                    packet.commitments = Some(commitments);
+                }
+
+                if (flags & FLAG_RESHARED) != 0 {
+                    let mut parent = [0u8; PARENT_SET_ID_LEN];
+                    parent.copy_from_slice(&bytes[offset..offset + PARENT_SET_ID_LEN]);
+                    offset += PARENT_SET_ID_LEN;
+                    packet.parent_set_id = Some(SetId(parent));
+                }
+
+                if (flags & FLAG_REFRESHED) != 0 {
+                    packet.epoch = read_u32(bytes, offset)?;
+                    offset += EPOCH_LEN;
+                    if packet.epoch == 0 {
+                        return Err(CoreError::InvalidPacket("invalid epoch".to_string()));
+                    }
+                }
+
+                if (flags & FLAG_POLICY) != 0 {
+                    let text_len = read_u16(bytes, offset)? as usize;
+                    offset += POLICY_LEN_FIELD_LEN;
+                    let text = std::str::from_utf8(&bytes[offset..offset + text_len])
+                        .map_err(|_| CoreError::InvalidPacket("invalid policy text".to_string()))?;
+                    let policy = Policy::parse(text)?;
+                    offset += text_len;
+
+                    let path_len = bytes[offset] as usize;
+                    offset += POLICY_PATH_LEN_FIELD_LEN;
+                    let path = bytes[offset..offset + path_len].to_vec();
+                    offset += path_len;
+                    if policy.holder_at(&path).is_none() {
+                        return Err(CoreError::InvalidPacket("invalid policy path".to_string()));
+                    }
+                    packet.policy = Some(Placement { policy, path });
+                }
+
+                if (flags & FLAG_DISPERSED) != 0 {
+                    packet.dispersed_len = Some(read_u64(bytes, offset)?);
+                    offset += DISPERSED_LEN_LEN;
+                }
 
-                (params, offset)
+                if (flags & FLAG_STREAMED) != 0 {
+                    packet.frame_len = Some(read_u32(bytes, offset)?);
+                    offset += FRAME_LEN_LEN;
+                }
+
+                if (ext_flags & EXT_FLAG_LOCKED) != 0 {
+                    let len = suite_params_len(bytes, offset)?;
+                    packet.share_lock = Some(decode_suite_params(&bytes[offset..offset + len])?);
+                    offset += len;
+                }
+
+                packet.key_slots = (ext_flags & EXT_FLAG_KEY_SLOTS) != 0;
+                packet.metadata = (ext_flags & EXT_FLAG_METADATA) != 0;
+                packet.padded = (ext_flags & EXT_FLAG_PADDED) != 0;
+                packet.compressed = (ext_flags & EXT_FLAG_COMPRESSED) != 0;
             }
             _ => return Err(CoreError::InvalidPacket("unsupported version".to_string())),
-        };
-
-        let payload_len_bytes: [u8; 4] = bytes[payload_len_offset..payload_len_offset + 4]
-            .try_into()
-            .map_err(|_| CoreError::InvalidPacket("bad length field".to_string()))?;
-        let payload_len = u32::from_be_bytes(payload_len_bytes) as usize;
-        let payload_start = payload_len_offset + 4;
-        let payload_end = payload_start + payload_len;
+        }
 
-        Ok(Self {
-            set_id: SetId(set_id),
-            k,
-            n,
-            x,
-            payload: bytes[payload_start..payload_end].to_vec(),
-            crypto_params,
-        })
+        let payload_len = read_u32(bytes, offset)? as usize;
+        let payload_start = offset + PAYLOAD_LEN_FIELD_LEN;
+        packet.payload = bytes[payload_start..payload_start + payload_len].to_vec();
+        Ok(packet)
     }
 }
 
+/// True when `bytes` start with a compact packet rather than a full one.
+pub fn is_compact(bytes: &[u8]) -> bool {
+    bytes.first() == Some(&COMPACT_MAGIC)
+}
+
+/// Length of the packet at the start of `bytes`, full or compact.
 pub fn binary_total_len(bytes: &[u8]) -> CoreResult<usize> {
+    if is_compact(bytes) {
+        return decode_compact(bytes).map(|(_, len)| len);
+    }
+
     let min_len = BASE_HEADER_LEN + PAYLOAD_LEN_FIELD_LEN;
     if bytes.len() < min_len {
         return Err(CoreError::InvalidPacket("too short".to_string()));
@@ -210,12 +780,84 @@ pub fn binary_total_len(bytes: &[u8]) -> CoreResult<usize> {
 
     let payload_len_offset = match version {
         VERSION_V1 => 25,
-        VERSION_V2 => {
+        VERSION_V2 | VERSION_V3 | VERSION_V4 => {
+            // Every flag bit is assigned.
             let flags = bytes[5];
+            let ext_flags = if version == VERSION_V4 {
+                bytes[BASE_HEADER_LEN]
+            } else {
+                0
+            };
+            if (ext_flags & !KNOWN_EXT_FLAGS) != 0 {
+                return Err(CoreError::InvalidPacket("unknown flags".to_string()));
+            }
+            if version == VERSION_V4 && ext_flags == 0 {
+                // Unflagged packets are written as version 3; keep one encoding.
+                return Err(CoreError::InvalidPacket(
+                    "empty extension flags".to_string(),
+                ));
+            }
+
+            if (ext_flags & EXT_FLAG_KEY_SLOTS) != 0 && (flags & FLAG_ENCRYPTED) != 0 {
+                return Err(CoreError::InvalidPacket(
+                    "key slots with crypto params".to_string(),
+                ));
+            }
+            if (flags & FLAG_CIPHER_SUITE) != 0 && (flags & FLAG_ENCRYPTED) == 0 {
+                return Err(CoreError::InvalidPacket(
+                    "cipher suite without encryption".to_string(),
+                ));
+            }
+
             let mut offset = BASE_HEADER_LEN;
+            if version == VERSION_V4 {
+                offset += EXT_FLAGS_LEN;
+            }
             if (flags & FLAG_ENCRYPTED) != 0 {
                 offset = offset
-                    .checked_add(CRYPTO_PARAMS_LEN)
+                    .checked_add(crypto_params_len(bytes, offset, flags)?)
+                    .ok_or_else(|| CoreError::InvalidPacket("offset overflow".to_string()))?;
+            }
+            if (flags & FLAG_VERIFIABLE) != 0 {
+                let count = read_u32(bytes, offset)? as usize;
+                offset = count
+                    .checked_mul(COMMITMENT_LEN)
+                    .and_then(|len| len.checked_add(COMMITMENT_COUNT_LEN))
+                    .and_then(|len| len.checked_add(offset))
+                    .ok_or_else(|| CoreError::InvalidPacket("offset overflow".to_string()))?;
+            }
+            if (flags & FLAG_RESHARED) != 0 {
+                offset = offset
+                    .checked_add(PARENT_SET_ID_LEN)
+                    .ok_or_else(|| CoreError::InvalidPacket("offset overflow".to_string()))?;
+            }
+            if (flags & FLAG_REFRESHED) != 0 {
+                offset = offset
+                    .checked_add(EPOCH_LEN)
+                    .ok_or_else(|| CoreError::InvalidPacket("offset overflow".to_string()))?;
+            }
+            if (flags & FLAG_POLICY) != 0 {
+                let text_len = read_u16(bytes, offset)? as usize;
+                let path_len_offset = offset + POLICY_LEN_FIELD_LEN + text_len;
+                let path_len = *bytes
+                    .get(path_len_offset)
+                    .ok_or_else(|| CoreError::InvalidPacket("truncated field".to_string()))?
+                    as usize;
+                offset = path_len_offset + POLICY_PATH_LEN_FIELD_LEN + path_len;
+            }
+            if (flags & FLAG_DISPERSED) != 0 {
+                offset = offset
+                    .checked_add(DISPERSED_LEN_LEN)
+                    .ok_or_else(|| CoreError::InvalidPacket("offset overflow".to_string()))?;
+            }
+            if (flags & FLAG_STREAMED) != 0 {
+                offset = offset
+                    .checked_add(FRAME_LEN_LEN)
+                    .ok_or_else(|| CoreError::InvalidPacket("offset overflow".to_string()))?;
+            }
+            if (ext_flags & EXT_FLAG_LOCKED) != 0 {
+                offset = offset
+                    .checked_add(suite_params_len(bytes, offset)?)
                     .ok_or_else(|| CoreError::InvalidPacket("offset overflow".to_string()))?;
             }
             offset
@@ -238,20 +880,401 @@ pub fn binary_total_len(bytes: &[u8]) -> CoreResult<usize> {
         .ok_or_else(|| CoreError::InvalidPacket("length overflow".to_string()))
 }
 
+/// Parse the compact packet at the start of `bytes`, returning it and its
+/// length. Fields that have a shorter form must use it, so each packet has
+/// one encoding.
+fn decode_compact(bytes: &[u8]) -> CoreResult<(SharePacket, usize)> {
+    let mut reader = Reader { bytes, offset: 1 };
+    let flags = reader.byte()?;
+    let ext_flags = if (flags & COMPACT_FLAG_MORE) != 0 {
+        let ext_flags = reader.byte()?;
+        if ext_flags == 0 {
+            return Err(CoreError::InvalidPacket(
+                "empty extension flags".to_string(),
+            ));
+        }
+        if (ext_flags & !KNOWN_COMPACT_EXT_FLAGS) != 0 {
+            return Err(CoreError::InvalidPacket("unknown flags".to_string()));
+        }
+        ext_flags
+    } else {
+        0
+    };
+    if (flags & COMPACT_FLAG_ENCRYPTED) == 0
+        && (flags & (COMPACT_FLAG_CIPHER_SUITE | COMPACT_FLAG_NONCE)) != 0
+    {
+        return Err(CoreError::InvalidPacket(
+            "cipher suite without encryption".to_string(),
+        ));
+    }
+    if (ext_flags & COMPACT_EXT_FULL_PARENT_ID) != 0 && (ext_flags & COMPACT_EXT_RESHARED) == 0 {
+        return Err(CoreError::InvalidPacket(
+            "parent id without reshare".to_string(),
+        ));
+    }
+
+    let stealth = (ext_flags & COMPACT_EXT_STEALTH) != 0;
+    if stealth
+        && ((flags & COMPACT_FLAG_FULL_SET_ID) != 0
+            || (ext_flags & (COMPACT_EXT_RESHARED | COMPACT_EXT_REFRESHED)) != 0)
+    {
+        return Err(CoreError::InvalidPacket(
+            "stealth packet with set metadata".to_string(),
+        ));
+    }
+
+    let (k, n, x, set_id) = if stealth {
+        (0, 0, reader.byte()?, SetId([0; 16]))
+    } else {
+        let k = reader.byte()?;
+        let n = reader.byte()?;
+        let x = reader.byte()?;
+        (
+            k,
+            n,
+            x,
+            reader.set_id((flags & COMPACT_FLAG_FULL_SET_ID) != 0)?,
+        )
+    };
+
+    let mut packet = SharePacket::from_raw_share(RawShare {
+        set_id,
+        k,
+        n,
+        x,
+        y: Vec::new(),
+    })
+    .with_authenticated_header(true)
+    .with_compact(true)
+    .with_stealth(stealth)
+    .with_metadata((flags & COMPACT_FLAG_METADATA) != 0)
+    .with_padded((flags & COMPACT_FLAG_PADDED) != 0)
+    .with_compressed((flags & COMPACT_FLAG_COMPRESSED) != 0);
+
+    if (flags & COMPACT_FLAG_ENCRYPTED) != 0 {
+        let aead = if (flags & COMPACT_FLAG_CIPHER_SUITE) != 0 {
+            let aead = read_algorithm_ids(bytes, reader.offset)?;
+            reader.offset += ALGORITHM_IDS_LEN;
+            aead
+        } else {
+            AeadAlgorithm::ChaCha20Poly1305
+        };
+        let mut salt = [0u8; crypto::SALT_LEN];
+        salt.copy_from_slice(reader.take(crypto::SALT_LEN)?);
+        let derived = crypto::derived_nonce(&salt, aead);
+        let nonce = if (flags & COMPACT_FLAG_NONCE) != 0 {
+            let mut nonce = [0u8; crypto::MAX_NONCE_LEN];
+            nonce[..aead.nonce_len()].copy_from_slice(reader.take(aead.nonce_len())?);
+            if nonce == derived {
+                return Err(CoreError::InvalidPacket("redundant nonce".to_string()));
+            }
+            nonce
+        } else {
+            derived
+        };
+        packet.crypto_params = Some(CryptoParams {
+            salt,
+            nonce,
+            mem_cost_kib: reader.varint()?,
+            time_cost: reader.varint()?,
+            parallelism: reader.varint()?,
+            aead,
+        });
+    }
+
+    if (ext_flags & COMPACT_EXT_RESHARED) != 0 {
+        packet.parent_set_id = Some(reader.set_id((ext_flags & COMPACT_EXT_FULL_PARENT_ID) != 0)?);
+    }
+
+    if (ext_flags & COMPACT_EXT_REFRESHED) != 0 {
+        packet.epoch = reader.varint()?;
+        if packet.epoch == 0 {
+            return Err(CoreError::InvalidPacket("invalid epoch".to_string()));
+        }
+    }
+
+    if (ext_flags & COMPACT_EXT_LOCKED) != 0 {
+        let len = suite_params_len(bytes, reader.offset)?;
+        packet.share_lock = Some(decode_suite_params(reader.take(len)?)?);
+    }
+
+    let payload_len = reader.varint()? as usize;
+    packet.payload = reader.take(payload_len)?.to_vec();
+    Ok((packet, reader.offset))
+}
+
+/// Bytes of `set_id` that a compact packet stores.
+fn compact_set_id(set_id: &SetId) -> &[u8] {
+    if set_id.is_short() {
+        &set_id.0[..SetId::SHORT_LEN]
+    } else {
+        &set_id.0
+    }
+}
+
+/// Unsigned LEB128, at most five bytes for a `u32`.
+fn write_varint(mut value: u32, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Cursor over a compact packet.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> CoreResult<&'a [u8]> {
+        let field = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.offset..end))
+            .ok_or_else(|| CoreError::InvalidPacket("truncated field".to_string()))?;
+        self.offset += len;
+        Ok(field)
+    }
+
+    fn byte(&mut self) -> CoreResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn set_id(&mut self, full: bool) -> CoreResult<SetId> {
+        let mut id = [0u8; 16];
+        if full {
+            id.copy_from_slice(self.take(16)?);
+            if SetId(id).is_short() {
+                return Err(CoreError::InvalidPacket("redundant set id".to_string()));
+            }
+        } else {
+            id[..SetId::SHORT_LEN].copy_from_slice(self.take(SetId::SHORT_LEN)?);
+        }
+        Ok(SetId(id))
+    }
+
+    fn varint(&mut self) -> CoreResult<u32> {
+        let mut value = 0u32;
+        for index in 0..5 {
+            let byte = self.byte()?;
+            let bits = u32::from(byte & 0x7f);
+            if index == 4 && bits > 0x0f {
+                return Err(CoreError::InvalidPacket("varint overflow".to_string()));
+            }
+            value |= bits << (7 * index);
+            if byte & 0x80 == 0 {
+                if index > 0 && byte == 0 {
+                    return Err(CoreError::InvalidPacket("overlong varint".to_string()));
+                }
+                return Ok(value);
+            }
+        }
+        Err(CoreError::InvalidPacket("varint overflow".to_string()))
+    }
+}
+
+/// Packets record algorithm ids only when they differ from the original
+/// Argon2id and ChaCha20-Poly1305 scheme.
+fn has_algorithm_ids(params: &CryptoParams) -> bool {
+    params.aead != AeadAlgorithm::ChaCha20Poly1305
+}
+
+fn crypto_params_len(bytes: &[u8], offset: usize, flags: u8) -> CoreResult<usize> {
+    if (flags & FLAG_CIPHER_SUITE) == 0 {
+        return Ok(CRYPTO_PARAMS_LEN);
+    }
+    let aead = read_algorithm_ids(bytes, offset)?;
+    Ok(ALGORITHM_IDS_LEN + CRYPTO_PARAMS_LEN - crypto::NONCE_LEN + aead.nonce_len())
+}
+
+fn read_algorithm_ids(bytes: &[u8], offset: usize) -> CoreResult<AeadAlgorithm> {
+    match read_suite_ids(bytes, offset)? {
+        // The original scheme is written without ids; keep one encoding.
+        AeadAlgorithm::ChaCha20Poly1305 => Err(CoreError::InvalidPacket(
+            "redundant cipher suite".to_string(),
+        )),
+        aead => Ok(aead),
+    }
+}
+
+fn read_suite_ids(bytes: &[u8], offset: usize) -> CoreResult<AeadAlgorithm> {
+    let ids = bytes
+        .get(offset..offset + ALGORITHM_IDS_LEN)
+        .ok_or_else(|| CoreError::InvalidPacket("truncated field".to_string()))?;
+    if ids[0] != crypto::KDF_ARGON2ID {
+        return Err(CoreError::InvalidPacket(
+            "unsupported key derivation".to_string(),
+        ));
+    }
+    AeadAlgorithm::from_id(ids[1])
+        .ok_or_else(|| CoreError::InvalidPacket("unsupported cipher".to_string()))
+}
+
+/// Length of a share-lock section, which always starts with its algorithm ids.
+pub(crate) fn suite_params_len(bytes: &[u8], offset: usize) -> CoreResult<usize> {
+    let aead = read_suite_ids(bytes, offset)?;
+    Ok(ALGORITHM_IDS_LEN + CRYPTO_PARAMS_LEN - crypto::NONCE_LEN + aead.nonce_len())
+}
+
+pub(crate) fn decode_suite_params(bytes: &[u8]) -> CoreResult<CryptoParams> {
+    let aead = read_suite_ids(bytes, 0)?;
+    read_params_body(&bytes[ALGORITHM_IDS_LEN..], aead)
+}
+
+fn decode_crypto_params(bytes: &[u8], flags: u8) -> CoreResult<CryptoParams> {
+    let (aead, bytes) = if (flags & FLAG_CIPHER_SUITE) != 0 {
+        (read_algorithm_ids(bytes, 0)?, &bytes[ALGORITHM_IDS_LEN..])
+    } else {
+        (AeadAlgorithm::ChaCha20Poly1305, bytes)
+    };
+    read_params_body(bytes, aead)
+}
+
+/// Read salt, nonce, and Argon2id costs following any algorithm ids.
+fn read_params_body(bytes: &[u8], aead: AeadAlgorithm) -> CoreResult<CryptoParams> {
+    let mut salt = [0u8; 16];
+    salt.copy_from_slice(&bytes[0..16]);
+
+    let nonce_end = 16 + aead.nonce_len();
+    let mut nonce = [0u8; crypto::MAX_NONCE_LEN];
+    nonce[..aead.nonce_len()].copy_from_slice(&bytes[16..nonce_end]);
+
+    Ok(CryptoParams {
+        salt,
+        nonce,
+        mem_cost_kib: read_u32(bytes, nonce_end)?,
+        time_cost: read_u32(bytes, nonce_end + 4)?,
+        parallelism: read_u32(bytes, nonce_end + 8)?,
+        aead,
+    })
+}
+
+/// Set-level marks that change how combined data is opened.
+///
+/// They are bound into [`set_header`], so stripping one from every share makes
+/// combine fail instead of returning the wrong bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SetMarks {
+    pub key_slots: bool,
+    pub metadata: bool,
+    pub padded: bool,
+    pub compressed: bool,
+}
+
+/// Set-level header bound into version 3 integrity tags and AEAD associated
+/// data.
+///
+/// Covers the set id, threshold, share count, crypto parameters, and the
+/// [`SetMarks`], so shares whose `k`, `n`, Argon2 costs, cipher, or marks
+/// were altered fail to combine. Policy
+/// splits bind the canonical policy instead of `k` and `n`, which differ
+/// between its groups. Per-share fields (`x`, epoch, commitments) are not
+/// covered; they are checked by interpolation and commitments instead.
+pub fn set_header(
+    set_id: SetId,
+    k: u8,
+    n: u8,
+    crypto_params: Option<&CryptoParams>,
+    marks: SetMarks,
+    policy: Option<&Policy>,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(SET_HEADER_MAGIC.len() + 16 + 2 + CRYPTO_PARAMS_LEN + 2);
+    out.extend_from_slice(&SET_HEADER_MAGIC);
+    out.extend_from_slice(&set_id.0);
+
+    match crypto_params {
+        Some(params) => {
+            out.push(1);
+            encode_crypto_params(params, &mut out);
+        }
+        None if marks.key_slots => out.push(2),
+        None => out.push(0),
+    }
+
+    match policy {
+        Some(policy) => {
+            let text = policy.to_string();
+            out.push(1);
+            out.extend_from_slice(&(text.len() as u32).to_be_bytes());
+            out.extend_from_slice(text.as_bytes());
+        }
+        None => {
+            out.push(0);
+            out.push(k);
+            out.push(n);
+        }
+    }
+    // Appended only when set, so headers of unmarked sets are unchanged.
+    let data_marks = u8::from(marks.metadata)
+        | (u8::from(marks.padded) << 1)
+        | (u8::from(marks.compressed) << 2);
+    if data_marks != 0 {
+        out.push(data_marks);
+    }
+    out
+}
+
+fn encode_crypto_params(params: &CryptoParams, out: &mut Vec<u8>) {
+    if has_algorithm_ids(params) {
+        out.push(crypto::KDF_ARGON2ID);
+        out.push(params.aead.id());
+    }
+    encode_params_body(params, out);
+}
+
+pub(crate) fn encode_suite_params(params: &CryptoParams, out: &mut Vec<u8>) {
+    out.push(crypto::KDF_ARGON2ID);
+    out.push(params.aead.id());
+    encode_params_body(params, out);
+}
+
+fn encode_params_body(params: &CryptoParams, out: &mut Vec<u8>) {
+    out.extend_from_slice(&params.salt);
+    out.extend_from_slice(params.nonce());
+    out.extend_from_slice(&params.mem_cost_kib.to_be_bytes());
+    out.extend_from_slice(&params.time_cost.to_be_bytes());
+    out.extend_from_slice(&params.parallelism.to_be_bytes());
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> CoreResult<u16> {
+    let field: [u8; 2] = bytes
+        .get(offset..offset + 2)
+        .and_then(|field| field.try_into().ok())
+        .ok_or_else(|| CoreError::InvalidPacket("truncated field".to_string()))?;
+    Ok(u16::from_be_bytes(field))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> CoreResult<u64> {
+    let field: [u8; 8] = bytes
+        .get(offset..offset + 8)
+        .and_then(|field| field.try_into().ok())
+        .ok_or_else(|| CoreError::InvalidPacket("truncated field".to_string()))?;
+    Ok(u64::from_be_bytes(field))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> CoreResult<u32> {
+    let field: [u8; 4] = bytes
+        .get(offset..offset + 4)
+        .and_then(|field| field.try_into().ok())
+        .ok_or_else(|| CoreError::InvalidPacket("truncated field".to_string()))?;
+    Ok(u32::from_be_bytes(field))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn binary_round_trip_v2_unencrypted() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([7u8; 16]),
             k: 3,
             n: 5,
             x: 1,
-            payload: vec![1, 2, 3, 4],
-            crypto_params: None,
-        };
+            y: vec![1, 2, 3, 4],
+        });
 
         let enc = pkt.encode_binary().unwrap();
         let dec = SharePacket::decode_binary(&enc).unwrap();
@@ -260,23 +1283,467 @@ mod tests {
 
     #[test]
     fn binary_round_trip_v2_encrypted() {
-        let pkt = SharePacket {
+        let pkt = SharePacket::from_raw_share(RawShare {
             set_id: SetId([8u8; 16]),
             k: 2,
             n: 3,
             x: 2,
-            payload: vec![9, 9, 9, 9, 9],
-            crypto_params: Some(CryptoParams {
-                salt: [1u8; 16],
-                nonce: [2u8; 12],
-                mem_cost_kib: 1024,
-                time_cost: 1,
-                parallelism: 1,
-            }),
-        };
+            y: vec![9, 9, 9, 9, 9],
+        })
+        .with_crypto_params(Some(CryptoParams {
+            salt: [1u8; 16],
+            nonce: [
+                2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            mem_cost_kib: 1024,
+            time_cost: 1,
+            parallelism: 1,
+            aead: AeadAlgorithm::ChaCha20Poly1305,
+        }));
+
+        let enc = pkt.encode_binary().unwrap();
+        let dec = SharePacket::decode_binary(&enc).unwrap();
+        assert_eq!(dec, pkt);
+    }
+
+    #[test]
+    fn binary_round_trip_v2_verifiable() {
+        let pkt = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([9u8; 16]),
+            k: 2,
+            n: 3,
+            x: 3,
+            y: vec![5u8; 64],
+        })
+        .with_commitments(Some(Commitments {
+            k: 2,
+            points: vec![[1u8; 32], [2u8; 32]],
+        }));
+
+        let enc = pkt.encode_binary().unwrap();
+        assert_eq!(binary_total_len(&enc).unwrap(), enc.len());
+        let dec = SharePacket::decode_binary(&enc).unwrap();
+        assert_eq!(dec, pkt);
+    }
+
+    #[test]
+    fn binary_round_trip_v2_reshared() {
+        let pkt = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([3u8; 16]),
+            k: 3,
+            n: 5,
+            x: 4,
+            y: vec![6u8; 12],
+        })
+        .with_parent_set_id(Some(SetId([2u8; 16])));
+
+        let enc = pkt.encode_binary().unwrap();
+        assert_eq!(binary_total_len(&enc).unwrap(), enc.len());
+        let dec = SharePacket::decode_binary(&enc).unwrap();
+        assert_eq!(dec, pkt);
+    }
+
+    #[test]
+    fn binary_round_trip_v2_refreshed() {
+        let pkt = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([4u8; 16]),
+            k: 2,
+            n: 3,
+            x: 2,
+            y: vec![7u8; 10],
+        })
+        .with_epoch(3);
+
+        let enc = pkt.encode_binary().unwrap();
+        assert_eq!(binary_total_len(&enc).unwrap(), enc.len());
+        let dec = SharePacket::decode_binary(&enc).unwrap();
+        assert_eq!(dec, pkt);
+    }
+
+    #[test]
+    fn binary_round_trip_v2_policy() {
+        let pkt = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([5u8; 16]),
+            k: 2,
+            n: 4,
+            x: 1,
+            y: vec![8u8; 16],
+        })
+        .with_policy(Some(Placement {
+            policy: Policy::parse("all of (ops: 2 of (a*2, b, c), d)").unwrap(),
+            path: vec![0, 0],
+        }));
 
         let enc = pkt.encode_binary().unwrap();
+        assert_eq!(binary_total_len(&enc).unwrap(), enc.len());
         let dec = SharePacket::decode_binary(&enc).unwrap();
         assert_eq!(dec, pkt);
     }
+
+    #[test]
+    fn binary_round_trip_v2_dispersed_and_streamed() {
+        let pkt = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([6u8; 16]),
+            k: 3,
+            n: 7,
+            x: 4,
+            y: vec![9u8; 40],
+        })
+        .with_dispersed_len(Some(300_000_000))
+        .with_frame_len(Some(65_536));
+
+        let enc = pkt.encode_binary().unwrap();
+        assert_eq!(binary_total_len(&enc).unwrap(), enc.len());
+        let dec = SharePacket::decode_binary(&enc).unwrap();
+        assert_eq!(dec, pkt);
+    }
+
+    #[test]
+    fn binary_round_trip_v3_authenticated_header() {
+        let pkt = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([8u8; 16]),
+            k: 2,
+            n: 3,
+            x: 2,
+            y: vec![4u8; 12],
+        })
+        .with_crypto_params(Some(CryptoParams::random_default()))
+        .with_authenticated_header(true);
+
+        let enc = pkt.encode_binary().unwrap();
+        assert_eq!(enc[4], VERSION_V3);
+        assert_eq!(binary_total_len(&enc).unwrap(), enc.len());
+        let dec = SharePacket::decode_binary(&enc).unwrap();
+        assert_eq!(dec, pkt);
+
+        let other = SharePacket {
+            n: 4,
+            ..pkt.clone()
+        };
+        assert_ne!(other.set_header(), pkt.set_header());
+        let other = SharePacket {
+            x: 3,
+            ..pkt.clone()
+        };
+        assert_eq!(other.set_header(), pkt.set_header());
+    }
+
+    #[test]
+    fn binary_round_trip_v4_share_lock() {
+        let lock = CryptoParams::random(crypto::AeadAlgorithm::ChaCha20Poly1305.into());
+        let packet = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([3u8; 16]),
+            k: 2,
+            n: 3,
+            x: 2,
+            y: vec![9; 20],
+        })
+        .with_authenticated_header(true)
+        .with_share_lock(Some(lock));
+
+        let mut bytes = packet.encode_binary().unwrap();
+        assert_eq!(bytes[4], VERSION_V4);
+        assert_eq!(bytes[BASE_HEADER_LEN], EXT_FLAG_LOCKED);
+        assert_eq!(SharePacket::decode_binary(&bytes).unwrap(), packet);
+
+        let legacy = packet.clone().with_authenticated_header(false);
+        assert!(legacy.encode_binary().is_err());
+
+        bytes[BASE_HEADER_LEN] |= 0b1000_0000;
+        assert!(SharePacket::decode_binary(&bytes).is_err());
+        bytes[BASE_HEADER_LEN] = 0;
+        assert!(SharePacket::decode_binary(&bytes).is_err());
+    }
+
+    #[test]
+    fn binary_round_trip_v4_key_slots() {
+        let packet = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([4u8; 16]),
+            k: 2,
+            n: 3,
+            x: 1,
+            y: vec![7; 24],
+        })
+        .with_authenticated_header(true)
+        .with_key_slots(true);
+
+        let bytes = packet.encode_binary().unwrap();
+        assert_eq!(bytes[4], VERSION_V4);
+        assert_eq!(bytes[BASE_HEADER_LEN], EXT_FLAG_KEY_SLOTS);
+        assert_eq!(SharePacket::decode_binary(&bytes).unwrap(), packet);
+        assert!(packet.is_encrypted());
+        assert_ne!(
+            packet.set_header(),
+            packet.clone().with_key_slots(false).set_header()
+        );
+
+        let params = CryptoParams::random_default();
+        let both = packet.clone().with_crypto_params(Some(params));
+        assert!(both.encode_binary().is_err());
+    }
+
+    #[test]
+    fn binary_round_trip_v4_metadata() {
+        let packet = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([5u8; 16]),
+            k: 2,
+            n: 3,
+            x: 1,
+            y: vec![7; 24],
+        })
+        .with_authenticated_header(true)
+        .with_metadata(true);
+
+        let bytes = packet.encode_binary().unwrap();
+        assert_eq!(bytes[4], VERSION_V4);
+        assert_eq!(bytes[BASE_HEADER_LEN], EXT_FLAG_METADATA);
+        assert_eq!(SharePacket::decode_binary(&bytes).unwrap(), packet);
+        let plain = packet.clone().with_metadata(false);
+        assert_ne!(packet.set_header(), plain.set_header());
+        assert_eq!(plain.encode_binary().unwrap()[4], VERSION_V3);
+
+        let padded = packet.clone().with_padded(true);
+        let bytes = padded.encode_binary().unwrap();
+        assert_eq!(bytes[BASE_HEADER_LEN], EXT_FLAG_METADATA | EXT_FLAG_PADDED);
+        assert_eq!(SharePacket::decode_binary(&bytes).unwrap(), padded);
+        assert_ne!(padded.set_header(), packet.set_header());
+
+        let compressed = padded.clone().with_compressed(true);
+        let bytes = compressed.encode_binary().unwrap();
+        assert_eq!(
+            bytes[BASE_HEADER_LEN],
+            EXT_FLAG_METADATA | EXT_FLAG_PADDED | EXT_FLAG_COMPRESSED
+        );
+        assert_eq!(SharePacket::decode_binary(&bytes).unwrap(), compressed);
+        assert_ne!(compressed.set_header(), padded.set_header());
+
+        let legacy = packet.with_authenticated_header(false);
+        assert!(legacy.encode_binary().is_err());
+    }
+
+    #[test]
+    fn binary_round_trip_cipher_suites() {
+        let mut headers = Vec::new();
+        for &aead in AeadAlgorithm::ALL {
+            let mut params = CryptoParams::random(aead.into());
+            params.salt = [3u8; 16];
+            params.nonce = [0u8; crypto::MAX_NONCE_LEN];
+            params.nonce[..aead.nonce_len()].fill(5);
+            let pkt = SharePacket::from_raw_share(RawShare {
+                set_id: SetId([8u8; 16]),
+                k: 2,
+                n: 3,
+                x: 1,
+                y: vec![4u8; 12],
+            })
+            .with_crypto_params(Some(params))
+            .with_authenticated_header(true);
+
+            let enc = pkt.encode_binary().unwrap();
+            let has_ids = aead != AeadAlgorithm::ChaCha20Poly1305;
+            assert_eq!(enc[5] & FLAG_CIPHER_SUITE != 0, has_ids);
+            assert_eq!(binary_total_len(&enc).unwrap(), enc.len());
+            assert_eq!(SharePacket::decode_binary(&enc).unwrap(), pkt);
+            headers.push(pkt.set_header());
+
+            if has_ids {
+                let ids = BASE_HEADER_LEN;
+                assert_eq!(enc[ids..ids + 2], [crypto::KDF_ARGON2ID, aead.id()]);
+                for (field, value) in [(ids, 9), (ids + 1, 9), (ids + 1, 1)] {
+                    let mut bad = enc.clone();
+                    bad[field] = value;
+                    assert!(matches!(
+                        SharePacket::decode_binary(&bad),
+                        Err(CoreError::InvalidPacket(_))
+                    ));
+                }
+            }
+        }
+        // Same salt, nonce, and costs: only the cipher tells the headers apart.
+        assert_ne!(headers[0], headers[1]);
+        assert_ne!(headers[1], headers[2]);
+    }
+
+    #[test]
+    fn unknown_flags_are_rejected() {
+        let pkt = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([7u8; 16]),
+            k: 2,
+            n: 3,
+            x: 1,
+            y: vec![1, 2, 3],
+        });
+
+        // The last flag bit is only valid on encrypted packets.
+        let mut enc = pkt.encode_binary().unwrap();
+        enc[5] |= 0b1000_0000;
+        let err = SharePacket::decode_binary(&enc).unwrap_err();
+        assert!(matches!(err, CoreError::InvalidPacket(_)));
+    }
+
+    #[test]
+    fn binary_round_trip_compact() {
+        let plain = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([9, 8, 7, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            k: 2,
+            n: 3,
+            x: 1,
+            y: vec![4u8; 48],
+        })
+        .with_authenticated_header(true)
+        .with_compact(true);
+        let bytes = plain.encode_binary().unwrap();
+        assert_eq!(bytes[..2], [COMPACT_MAGIC, 0]);
+        assert_eq!(bytes.len(), 2 + 3 + SetId::SHORT_LEN + 1 + 48);
+        assert_eq!(binary_total_len(&bytes).unwrap(), bytes.len());
+        assert_eq!(SharePacket::decode_binary(&bytes).unwrap(), plain);
+
+        let lock = CryptoParams::random_default();
+        let mut variants = vec![
+            plain
+                .clone()
+                .with_crypto_params(Some(CryptoParams::random_derived(
+                    AeadAlgorithm::XChaCha20Poly1305.into(),
+                ))),
+            plain
+                .clone()
+                .with_crypto_params(Some(CryptoParams::random_default()))
+                .with_metadata(true)
+                .with_padded(true)
+                .with_compressed(true),
+            plain
+                .clone()
+                .with_parent_set_id(Some(SetId([3u8; 16])))
+                .with_epoch(300)
+                .with_share_lock(Some(lock)),
+        ];
+        variants.push(SharePacket {
+            set_id: SetId([5u8; 16]),
+            parent_set_id: Some(plain.set_id),
+            ..variants[0].clone()
+        });
+        for packet in &variants {
+            let bytes = packet.encode_binary().unwrap();
+            assert_eq!(bytes[0], COMPACT_MAGIC);
+            assert_eq!(binary_total_len(&bytes).unwrap(), bytes.len());
+            assert_eq!(&SharePacket::decode_binary(&bytes).unwrap(), packet);
+            let full = packet.clone().with_compact(false).encode_binary().unwrap();
+            assert!(bytes.len() < full.len());
+            // Trailing bytes are allowed when measuring, not when decoding.
+            let mut padded = bytes.clone();
+            padded.extend_from_slice(&[0, 0]);
+            assert_eq!(binary_total_len(&padded).unwrap(), bytes.len());
+            assert!(SharePacket::decode_binary(&padded).is_err());
+            assert!(SharePacket::decode_binary(&bytes[..bytes.len() - 1]).is_err());
+        }
+
+        let policy = Policy::parse("2 of (a, b, c)").unwrap();
+        let unsupported = [
+            plain.clone().with_authenticated_header(false),
+            plain.clone().with_key_slots(true),
+            plain.clone().with_dispersed_len(Some(9)),
+            plain.clone().with_frame_len(Some(9)),
+            plain.clone().with_policy(Some(Placement {
+                policy,
+                path: vec![0],
+            })),
+        ];
+        for packet in unsupported {
+            assert!(packet.encode_binary().is_err());
+        }
+    }
+
+    #[test]
+    fn compact_packets_have_one_encoding() {
+        let plain = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([1u8; 16]),
+            k: 2,
+            n: 3,
+            x: 1,
+            y: vec![4u8; 200],
+        })
+        .with_authenticated_header(true)
+        .with_compact(true);
+        let bytes = plain.encode_binary().unwrap();
+        assert_eq!(bytes[1], COMPACT_FLAG_FULL_SET_ID);
+        let len_at = 2 + 3 + 16;
+        assert_eq!(bytes[len_at..len_at + 2], [0xc8, 0x01]);
+
+        let mut overlong = bytes.clone();
+        overlong.splice(len_at..len_at + 2, [0xc8, 0x81, 0x00]);
+        let mut short_id = bytes.clone();
+        short_id[5..21].copy_from_slice(&[1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let mut empty_ext = bytes.clone();
+        empty_ext[1] |= COMPACT_FLAG_MORE;
+        empty_ext.insert(2, 0);
+        let mut unknown_ext = empty_ext.clone();
+        unknown_ext[2] = 0b1000_0000;
+        let mut nonce_only = bytes.clone();
+        nonce_only[1] |= COMPACT_FLAG_NONCE;
+        for bad in [overlong, short_id, empty_ext, unknown_ext, nonce_only] {
+            assert!(matches!(
+                SharePacket::decode_binary(&bad),
+                Err(CoreError::InvalidPacket(_))
+            ));
+        }
+
+        let params = CryptoParams::random_derived(Default::default());
+        let mut explicit = params;
+        explicit.nonce = [2u8; crypto::MAX_NONCE_LEN];
+        let encrypted = plain.clone().with_crypto_params(Some(explicit));
+        let mut bytes = encrypted.encode_binary().unwrap();
+        assert_ne!(bytes[1] & COMPACT_FLAG_NONCE, 0);
+        let nonce_at = 2 + 3 + 16 + crypto::SALT_LEN;
+        bytes[nonce_at..nonce_at + crypto::NONCE_LEN].copy_from_slice(
+            &crypto::derived_nonce(&explicit.salt, explicit.aead)[..crypto::NONCE_LEN],
+        );
+        assert!(SharePacket::decode_binary(&bytes).is_err());
+    }
+
+    #[test]
+    fn stealth_packets_omit_threshold_and_set_id() {
+        let stealth = SharePacket::from_raw_share(RawShare {
+            set_id: SetId([0; 16]),
+            k: 0,
+            n: 0,
+            x: 201,
+            y: vec![4u8; 40],
+        })
+        .with_authenticated_header(true)
+        .with_compact(true)
+        .with_stealth(true);
+        let bytes = stealth.encode_binary().unwrap();
+        assert_eq!(
+            bytes[..4],
+            [COMPACT_MAGIC, COMPACT_FLAG_MORE, COMPACT_EXT_STEALTH, 201]
+        );
+        assert_eq!(bytes.len(), 4 + 1 + 40);
+        assert_eq!(SharePacket::decode_binary(&bytes).unwrap(), stealth);
+
+        let locked = stealth
+            .clone()
+            .with_share_lock(Some(CryptoParams::random_default()));
+        let locked_bytes = locked.encode_binary().unwrap();
+        assert_eq!(SharePacket::decode_binary(&locked_bytes).unwrap(), locked);
+
+        for leaky in [
+            stealth.clone().with_compact(false),
+            SharePacket {
+                k: 2,
+                ..stealth.clone()
+            },
+            SharePacket {
+                set_id: SetId([1; 16]),
+                ..stealth.clone()
+            },
+            stealth.clone().with_parent_set_id(Some(SetId([1; 16]))),
+            stealth.clone().with_epoch(1),
+        ] {
+            assert!(leaky.encode_binary().is_err());
+        }
+
+        let mut with_id = bytes.clone();
+        with_id[1] |= COMPACT_FLAG_FULL_SET_ID;
+        assert!(SharePacket::decode_binary(&with_id).is_err());
+    }
 }
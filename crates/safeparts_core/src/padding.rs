@@ -0,0 +1,173 @@
+//! Length-hiding padding for split secrets.
+//!
+//! Every share is as long as the protected data, so one share reveals the
+//! secret's exact size. [`crate::split_secret_with_options`] can pad the data
+//! first, inside the encryption and the integrity tag, so the size is only
+//! known up to a bucket.
+//!
+//! Padding is ISO/IEC 7816-4 style: a `0x80` byte, then zero bytes. The last
+//! non-zero byte therefore marks the end of the secret, whatever it contains.
+
+use std::fmt;
+
+use rand::Rng;
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+
+/// Smallest bucket of [`Padding::PowerOfTwo`], so 16- and 32-byte keys look
+/// the same.
+pub const MIN_BUCKET: usize = 64;
+
+/// Largest fixed size or random amount accepted.
+pub const MAX_PADDING: usize = 1 << 20;
+
+const MARKER: u8 = 0x80;
+
+/// How much padding to add before splitting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Padding {
+    /// No padding; share length reveals the secret length.
+    #[default]
+    None,
+    /// Pad to the next power of two, at least [`MIN_BUCKET`] bytes.
+    PowerOfTwo,
+    /// Pad to exactly this many bytes. Longer secrets are rejected.
+    Fixed(usize),
+    /// Add between 1 and this many plus one bytes, chosen at random.
+    Random(usize),
+}
+
+impl Padding {
+    pub fn is_none(&self) -> bool {
+        matches!(self, Padding::None)
+    }
+
+    fn validate(&self) -> CoreResult<()> {
+        match *self {
+            Padding::Fixed(size) | Padding::Random(size) if size > MAX_PADDING => Err(
+                CoreError::InvalidPadding(format!("{size} bytes is above {MAX_PADDING}")),
+            ),
+            Padding::Fixed(0) => Err(CoreError::InvalidPadding(
+                "fixed size must be positive".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Append the marker and this mode's zero bytes to `data`.
+    pub fn pad(&self, data: &[u8]) -> CoreResult<Zeroizing<Vec<u8>>> {
+        self.validate()?;
+        let min_len = data.len() + 1;
+        let len = match *self {
+            Padding::None => min_len,
+            Padding::PowerOfTwo => min_len.next_power_of_two().max(MIN_BUCKET),
+            Padding::Fixed(size) if min_len > size => {
+                return Err(CoreError::InvalidPadding(format!(
+                    "secret needs {min_len} bytes but the fixed size is {size}"
+                )));
+            }
+            Padding::Fixed(size) => size,
+            Padding::Random(max) => min_len + OsRng.gen_range(0..=max),
+        };
+
+        let mut out = Zeroizing::new(Vec::with_capacity(len));
+        out.extend_from_slice(data);
+        out.push(MARKER);
+        out.resize(len, 0);
+        Ok(out)
+    }
+
+    /// Strip padding added by [`Padding::pad`].
+    pub fn unpad(data: &[u8]) -> CoreResult<&[u8]> {
+        let end = data
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .filter(|&end| data[end] == MARKER)
+            .ok_or_else(|| CoreError::InvalidPadding("missing padding marker".to_string()))?;
+        Ok(&data[..end])
+    }
+}
+
+impl fmt::Display for Padding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Padding::None => f.write_str("none"),
+            Padding::PowerOfTwo => f.write_str("pow2"),
+            Padding::Fixed(size) => write!(f, "fixed:{size}"),
+            Padding::Random(max) => write!(f, "random:{max}"),
+        }
+    }
+}
+
+/// Parses `none`, `pow2`, `fixed:BYTES`, or `random:MAX_BYTES`.
+impl std::str::FromStr for Padding {
+    type Err = CoreError;
+
+    fn from_str(text: &str) -> CoreResult<Self> {
+        let text = text.trim().to_ascii_lowercase();
+        let bytes = |value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| CoreError::InvalidPadding(format!("not a byte count: {value}")))
+        };
+        let padding = match text.split_once(':') {
+            None if text == "none" => Padding::None,
+            None if text == "pow2" => Padding::PowerOfTwo,
+            Some(("fixed", size)) => Padding::Fixed(bytes(size)?),
+            Some(("random", max)) => Padding::Random(bytes(max)?),
+            _ => {
+                return Err(CoreError::InvalidPadding(format!(
+                    "unknown mode {text:?}; expected none, pow2, fixed:BYTES, or random:MAX_BYTES"
+                )));
+            }
+        };
+        padding.validate()?;
+        Ok(padding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_hides_length_and_unpads_exactly() {
+        for secret in [&b""[..], b"\x00\x00", b"ends with marker \x80", &[7u8; 40]] {
+            for padding in [
+                Padding::None,
+                Padding::PowerOfTwo,
+                Padding::Fixed(100),
+                Padding::Random(10),
+            ] {
+                let padded = padding.pad(secret).unwrap();
+                assert_eq!(Padding::unpad(&padded).unwrap(), secret, "{padding}");
+            }
+        }
+
+        assert_eq!(Padding::PowerOfTwo.pad(&[1; 16]).unwrap().len(), 64);
+        assert_eq!(Padding::PowerOfTwo.pad(&[1; 32]).unwrap().len(), 64);
+        assert_eq!(Padding::PowerOfTwo.pad(&[1; 64]).unwrap().len(), 128);
+        assert_eq!(Padding::Fixed(100).pad(&[1; 33]).unwrap().len(), 100);
+        assert!(Padding::Fixed(32).pad(&[1; 32]).is_err());
+        assert!(Padding::unpad(&[1, 2, 0, 0]).is_err());
+        assert!(Padding::unpad(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn parse_round_trips_display() {
+        for padding in [
+            Padding::None,
+            Padding::PowerOfTwo,
+            Padding::Fixed(256),
+            Padding::Random(64),
+        ] {
+            assert_eq!(padding.to_string().parse::<Padding>().unwrap(), padding);
+        }
+        assert_eq!(" POW2 ".parse::<Padding>().unwrap(), Padding::PowerOfTwo);
+        for bad in ["", "pad", "fixed:", "fixed:0", "random:x", "fixed:99999999"] {
+            assert!(bad.parse::<Padding>().is_err(), "{bad}");
+        }
+    }
+}
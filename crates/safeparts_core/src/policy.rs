@@ -0,0 +1,609 @@
+//! Access-structure policies: nested and weighted thresholds.
+//!
+//! A policy is a tree of threshold groups over named holders, for example
+//!
+//! ```text
+//! all of (engineers: 2 of (e1, e2, e3, e4), legal: 1 of (l1, l2))
+//! 2 of (ceo*2, cfo, cto)
+//! ```
+//!
+//! Grammar:
+//!
+//! ```text
+//! policy    := group | holder
+//! group     := [label ":"] threshold "of" "(" policy ("," policy)* ")"
+//! threshold := NUMBER | "all" | "any"
+//! holder    := NAME ["*" NUMBER]
+//! ```
+//!
+//! Each group splits its value with [`crate::sss`] into one share per unit of
+//! child weight. A holder with weight `w` receives `w` consecutive x
+//! coordinates in one packet; a nested group receives one coordinate and
+//! splits that share again. Every packet records the whole policy and its path
+//! in the tree, so combine can tell which groups are still missing.
+
+use std::fmt;
+
+use crate::error::{CoreError, CoreResult};
+use crate::sss::{self, RawShare, SetId};
+
+/// One node of an access-structure policy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Policy {
+    /// A named share holder who counts as `weight` shares of their group.
+    Holder { name: String, weight: u8 },
+    /// A group satisfied when the weight of satisfied children reaches `k`.
+    Group {
+        label: Option<String>,
+        k: u8,
+        children: Vec<Policy>,
+    },
+}
+
+/// Where a packet sits in its policy tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub policy: Policy,
+    /// Child indexes from the root group down to the holder.
+    pub path: Vec<u8>,
+}
+
+/// One holder's share of a policy split, before packet wrapping.
+///
+/// `share.k` and `share.n` describe the holder's parent group, `share.x` is
+/// the holder's first coordinate, and `share.y` concatenates one value per
+/// unit of weight.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyShare {
+    pub path: Vec<u8>,
+    pub share: RawShare,
+}
+
+impl Policy {
+    /// Parse and validate a policy expression.
+    pub fn parse(input: &str) -> CoreResult<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let policy = parser.policy()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(invalid("unexpected trailing input"));
+        }
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Weight this node carries inside its parent group.
+    pub fn weight(&self) -> u8 {
+        match self {
+            Policy::Holder { weight, .. } => *weight,
+            Policy::Group { .. } => 1,
+        }
+    }
+
+    /// Holders in split order, with their paths.
+    pub fn holders(&self) -> Vec<(Vec<u8>, &str)> {
+        let mut out = Vec::new();
+        self.collect_holders(&mut Vec::new(), &mut out);
+        out
+    }
+
+    /// Name of the holder at `path`, if there is one.
+    pub fn holder_at(&self, path: &[u8]) -> Option<&str> {
+        match (self, path.split_first()) {
+            (Policy::Holder { name, .. }, None) => Some(name),
+            (Policy::Group { children, .. }, Some((&index, rest))) => {
+                children.get(index as usize)?.holder_at(rest)
+            }
+            _ => None,
+        }
+    }
+
+    /// Describe every unsatisfied group, given the paths of available holders.
+    ///
+    /// Returns an empty list when the policy is satisfied.
+    pub fn missing(&self, present: &[Vec<u8>]) -> Vec<String> {
+        let name = match self {
+            Policy::Group {
+                label: Some(label), ..
+            } => label.as_str(),
+            _ => "policy",
+        };
+        let mut out = Vec::new();
+        self.collect_missing(name, &mut Vec::new(), present, &mut out);
+        out
+    }
+
+    fn validate(&self) -> CoreResult<()> {
+        let Policy::Group { .. } = self else {
+            return Err(invalid("the top level must be a group"));
+        };
+
+        let mut names: Vec<&str> = self.holders().into_iter().map(|(_, name)| name).collect();
+        names.sort_unstable();
+        if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(invalid(&format!("holder {} appears twice", pair[0])));
+        }
+        self.validate_node()
+    }
+
+    fn validate_node(&self) -> CoreResult<()> {
+        match self {
+            Policy::Holder { weight, .. } if *weight == 0 => {
+                Err(invalid("weight must be at least 1"))
+            }
+            Policy::Holder { .. } => Ok(()),
+            Policy::Group { k, children, .. } => {
+                let total = total_weight(children)?;
+                if *k == 0 || *k > total {
+                    return Err(CoreError::InvalidKAndN { k: *k, n: total });
+                }
+                children.iter().try_for_each(Policy::validate_node)
+            }
+        }
+    }
+
+    fn collect_holders<'a>(&'a self, path: &mut Vec<u8>, out: &mut Vec<(Vec<u8>, &'a str)>) {
+        match self {
+            Policy::Holder { name, .. } => out.push((path.clone(), name)),
+            Policy::Group { children, .. } => {
+                for (index, child) in children.iter().enumerate() {
+                    path.push(index as u8);
+                    child.collect_holders(path, out);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    /// Whether the subtree at `path` is satisfied by `present`.
+    fn satisfied(&self, path: &mut Vec<u8>, present: &[Vec<u8>]) -> bool {
+        match self {
+            Policy::Holder { .. } => present.contains(path),
+            Policy::Group { k, children, .. } => {
+                let mut have = 0usize;
+                for (index, child) in children.iter().enumerate() {
+                    path.push(index as u8);
+                    if child.satisfied(path, present) {
+                        have += child.weight() as usize;
+                    }
+                    path.pop();
+                }
+                have >= *k as usize
+            }
+        }
+    }
+
+    fn collect_missing(
+        &self,
+        name: &str,
+        path: &mut Vec<u8>,
+        present: &[Vec<u8>],
+        out: &mut Vec<String>,
+    ) {
+        let Policy::Group { k, children, .. } = self else {
+            return;
+        };
+
+        let mut have = 0usize;
+        let mut lacking = Vec::new();
+        for (index, child) in children.iter().enumerate() {
+            path.push(index as u8);
+            if child.satisfied(path, present) {
+                have += child.weight() as usize;
+            } else {
+                lacking.push(child.short_label());
+            }
+            path.pop();
+        }
+        if have >= *k as usize {
+            return;
+        }
+
+        out.push(format!(
+            "{name}: {} more of ({})",
+            *k as usize - have,
+            lacking.join(", ")
+        ));
+        for (index, child) in children.iter().enumerate() {
+            path.push(index as u8);
+            if !child.satisfied(path, present) {
+                child.collect_missing(&child.short_label(), path, present, out);
+            }
+            path.pop();
+        }
+    }
+
+    fn short_label(&self) -> String {
+        match self {
+            Policy::Group {
+                label: Some(label), ..
+            }
+            | Policy::Holder { name: label, .. } => label.clone(),
+            Policy::Group { .. } => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Policy {
+    /// Canonical form: numeric thresholds, `name*w` only for weights above 1.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::Holder { name, weight: 1 } => write!(f, "{name}"),
+            Policy::Holder { name, weight } => write!(f, "{name}*{weight}"),
+            Policy::Group { label, k, children } => {
+                if let Some(label) = label {
+                    write!(f, "{label}: ")?;
+                }
+                write!(f, "{k} of (")?;
+                for (index, child) in children.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{child}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Split `data` across the holders of `policy`.
+pub fn split(data: &[u8], policy: &Policy, set_id: SetId) -> CoreResult<Vec<PolicyShare>> {
+    policy.validate()?;
+    let mut out = Vec::new();
+    split_group(data, policy, set_id, &mut Vec::new(), &mut out)?;
+    Ok(out)
+}
+
+fn split_group(
+    data: &[u8],
+    group: &Policy,
+    set_id: SetId,
+    path: &mut Vec<u8>,
+    out: &mut Vec<PolicyShare>,
+) -> CoreResult<()> {
+    let Policy::Group { k, children, .. } = group else {
+        return Err(invalid("expected a group"));
+    };
+
+    let n = total_weight(children)?;
+    let mut shares = sss::split(data, *k, n, set_id)?.into_iter();
+    for (index, child) in children.iter().enumerate() {
+        path.push(index as u8);
+        match child {
+            Policy::Holder { weight, .. } => {
+                let mut taken = shares.by_ref().take(*weight as usize);
+                let mut share = taken.next().ok_or_else(|| invalid("weight overflow"))?;
+                for next in taken {
+                    share.y.extend_from_slice(&next.y);
+                }
+                out.push(PolicyShare {
+                    path: path.clone(),
+                    share,
+                });
+            }
+            Policy::Group { .. } => {
+                let share = shares.next().ok_or_else(|| invalid("weight overflow"))?;
+                split_group(&share.y, child, set_id, path, out)?;
+            }
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+/// Recover the data from the holders' shares, or report the missing groups.
+pub fn combine(policy: &Policy, shares: &[PolicyShare]) -> CoreResult<Vec<u8>> {
+    let first = shares
+        .first()
+        .ok_or(CoreError::NotEnoughShares { k: 1, m: 0 })?;
+    let mut paths: Vec<Vec<u8>> = Vec::with_capacity(shares.len());
+    for entry in shares {
+        if entry.share.set_id != first.share.set_id {
+            return Err(CoreError::InconsistentMetadata);
+        }
+        if paths.contains(&entry.path) {
+            return Err(CoreError::DuplicateX { x: entry.share.x });
+        }
+        if policy.holder_at(&entry.path).is_none() {
+            return Err(invalid("share path is not a holder of the policy"));
+        }
+        paths.push(entry.path.clone());
+    }
+
+    let missing = policy.missing(&paths);
+    if !missing.is_empty() {
+        return Err(CoreError::PolicyNotSatisfied { missing });
+    }
+
+    let value = combine_group(policy, first.share.set_id, &mut Vec::new(), shares)?;
+    value.ok_or_else(|| invalid("policy evaluation failed"))
+}
+
+fn combine_group(
+    group: &Policy,
+    set_id: SetId,
+    path: &mut Vec<u8>,
+    shares: &[PolicyShare],
+) -> CoreResult<Option<Vec<u8>>> {
+    let Policy::Group { k, children, .. } = group else {
+        return Ok(None);
+    };
+    let n = total_weight(children)?;
+
+    let mut points: Vec<RawShare> = Vec::new();
+    let mut x = 1u8;
+    for (index, child) in children.iter().enumerate() {
+        if points.len() >= *k as usize {
+            break;
+        }
+        path.push(index as u8);
+        match child {
+            Policy::Holder { weight, .. } => {
+                if let Some(entry) = shares.iter().find(|entry| entry.path == *path) {
+                    let weight = *weight as usize;
+                    let share = &entry.share;
+                    if share.x != x
+                        || share.k != *k
+                        || share.y.is_empty()
+                        || !share.y.len().is_multiple_of(weight)
+                    {
+                        return Err(CoreError::InconsistentMetadata);
+                    }
+                    let len = share.y.len() / weight;
+                    for (offset, y) in share.y.chunks_exact(len).enumerate() {
+                        points.push(RawShare {
+                            set_id,
+                            k: *k,
+                            n,
+                            x: x + offset as u8,
+                            y: y.to_vec(),
+                        });
+                    }
+                }
+            }
+            Policy::Group { .. } => {
+                if let Some(y) = combine_group(child, set_id, path, shares)? {
+                    points.push(RawShare {
+                        set_id,
+                        k: *k,
+                        n,
+                        x,
+                        y,
+                    });
+                }
+            }
+        }
+        path.pop();
+        x = x.wrapping_add(child.weight());
+    }
+
+    if points.len() < *k as usize {
+        return Ok(None);
+    }
+    points.truncate(*k as usize);
+    sss::combine(&points).map(Some)
+}
+
+fn total_weight(children: &[Policy]) -> CoreResult<u8> {
+    let total: usize = children.iter().map(|child| child.weight() as usize).sum();
+    u8::try_from(total).map_err(|_| invalid("a group may hold at most 255 shares"))
+}
+
+fn invalid(msg: &str) -> CoreError {
+    CoreError::InvalidPolicy(msg.to_string())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Open,
+    Close,
+    Comma,
+    Colon,
+    Star,
+}
+
+fn tokenize(input: &str) -> CoreResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '*' => Token::Star,
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek().filter(|&&c| is_word_char(c)) {
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+                continue;
+            }
+            c => return Err(invalid(&format!("unexpected character {c:?}"))),
+        };
+        tokens.push(token);
+        chars.next();
+    }
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> CoreResult<()> {
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            Err(invalid(&format!("expected {what}")))
+        }
+    }
+
+    fn word(&mut self) -> CoreResult<String> {
+        match self.next() {
+            Some(Token::Word(word)) => Ok(word),
+            _ => Err(invalid("expected a name or threshold")),
+        }
+    }
+
+    fn policy(&mut self) -> CoreResult<Policy> {
+        let first = self.word()?;
+        match self.peek() {
+            Some(Token::Colon) => {
+                self.pos += 1;
+                let threshold = self.word()?;
+                self.group(Some(first), &threshold)
+            }
+            Some(Token::Word(word)) if word == "of" => self.group(None, &first),
+            Some(Token::Star) => {
+                self.pos += 1;
+                let weight = self.number()?;
+                Ok(Policy::Holder {
+                    name: first,
+                    weight,
+                })
+            }
+            _ => Ok(Policy::Holder {
+                name: first,
+                weight: 1,
+            }),
+        }
+    }
+
+    fn group(&mut self, label: Option<String>, threshold: &str) -> CoreResult<Policy> {
+        if self.word()? != "of" {
+            return Err(invalid("expected \"of\""));
+        }
+        self.expect(Token::Open, "\"(\"")?;
+        let mut children = vec![self.policy()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            children.push(self.policy()?);
+        }
+        self.expect(Token::Close, "\")\"")?;
+
+        let k = match threshold {
+            "all" => total_weight(&children)?,
+            "any" => 1,
+            number => parse_number(number)?,
+        };
+        Ok(Policy::Group { label, k, children })
+    }
+
+    fn number(&mut self) -> CoreResult<u8> {
+        let word = self.word()?;
+        parse_number(&word)
+    }
+}
+
+fn parse_number(word: &str) -> CoreResult<u8> {
+    word.parse()
+        .map_err(|_| invalid(&format!("expected a number from 1 to 255, got {word}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEAMS: &str = "all of (engineers: 2 of (e1, e2, e3, e4), legal: any of (l1, l2))";
+
+    fn pick(shares: &[PolicyShare], policy: &Policy, names: &[&str]) -> Vec<PolicyShare> {
+        shares
+            .iter()
+            .filter(|entry| names.contains(&policy.holder_at(&entry.path).unwrap()))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn parse_accepts_keywords_labels_and_weights() {
+        let policy = Policy::parse(TEAMS).unwrap();
+        assert_eq!(
+            policy.to_string(),
+            "2 of (engineers: 2 of (e1, e2, e3, e4), legal: 1 of (l1, l2))"
+        );
+        assert_eq!(Policy::parse(&policy.to_string()).unwrap(), policy);
+
+        let weighted = Policy::parse("2 of (ceo*2, cfo, cto)").unwrap();
+        assert_eq!(weighted.holders().len(), 3);
+        assert_eq!(weighted.holder_at(&[0]), Some("ceo"));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_policies() {
+        for input in [
+            "e1",
+            "3 of (a, b)",
+            "2 of (a, a)",
+            "1 of (a*0)",
+            "2 of (a, b",
+            "2 of (a, b) extra",
+        ] {
+            assert!(Policy::parse(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn nested_groups_recover_only_when_every_group_is_satisfied() {
+        let policy = Policy::parse(TEAMS).unwrap();
+        let shares = split(b"nested", &policy, SetId([1u8; 16])).unwrap();
+        assert_eq!(shares.len(), 6);
+
+        let ok = pick(&shares, &policy, &["e2", "e4", "l2"]);
+        assert_eq!(combine(&policy, &ok).unwrap(), b"nested");
+
+        let short = pick(&shares, &policy, &["e1", "e2", "e3"]);
+        match combine(&policy, &short) {
+            Err(CoreError::PolicyNotSatisfied { missing }) => {
+                assert_eq!(
+                    missing,
+                    vec!["policy: 1 more of (legal)", "legal: 1 more of (l1, l2)"]
+                );
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn weighted_holder_counts_as_several_shares() {
+        let policy = Policy::parse("2 of (ceo*2, cfo, cto)").unwrap();
+        let shares = split(b"weighted", &policy, SetId([2u8; 16])).unwrap();
+
+        assert_eq!(
+            combine(&policy, &pick(&shares, &policy, &["ceo"])).unwrap(),
+            b"weighted"
+        );
+        assert_eq!(
+            combine(&policy, &pick(&shares, &policy, &["cfo", "cto"])).unwrap(),
+            b"weighted"
+        );
+        assert!(matches!(
+            combine(&policy, &pick(&shares, &policy, &["cto"])),
+            Err(CoreError::PolicyNotSatisfied { .. })
+        ));
+    }
+}
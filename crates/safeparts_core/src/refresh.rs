@@ -0,0 +1,411 @@
+//! Proactive share refresh without reconstructing the secret.
+//!
+//! A refresh round re-randomises every participating share while keeping the
+//! secret unchanged. Each participant picks a random polynomial with a zero
+//! constant term, sends `delta_i(x_j)` to every participant `j`, and each
+//! participant adds all deltas addressed to them to their own share. The sum
+//! of the delta polynomials is zero at `x = 0`, so the refreshed shares still
+//! interpolate to the same secret, but old and new shares no longer mix.
+//!
+//! The round is designed to run offline through files:
+//!
+//! 1. [`RefreshRound::new`] fixes the set, epoch, and participant list.
+//! 2. Every participant calls [`contribute`] and delivers each [`SubShare`]
+//!    privately to its recipient.
+//! 3. Every participant calls [`apply`] with exactly one sub-share from each
+//!    participant, including their own.
+//!
+//! Shares whose index is not in the participant list stay in the old epoch and
+//! can no longer be combined with refreshed shares.
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+use crate::gf256::Gf256;
+use crate::packet::SharePacket;
+use crate::sss::SetId;
+
+const ROUND_MAGIC: [u8; 4] = *b"SPR1";
+const SUB_SHARE_MAGIC: [u8; 4] = *b"SPD1";
+const ROUND_ID_LEN: usize = 16;
+
+/// Public description of one refresh round.
+///
+/// Nothing in a round is secret. Share the same round with every participant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefreshRound {
+    pub round_id: [u8; ROUND_ID_LEN],
+    pub set_id: SetId,
+    pub k: u8,
+    /// Epoch of the shares being refreshed. Refreshed shares get `epoch + 1`.
+    pub epoch: u32,
+    pub payload_len: u32,
+    /// Sorted share indexes that take part in the round.
+    pub participants: Vec<u8>,
+}
+
+/// One participant's delta for one recipient.
+///
+/// Sub-shares are secret: anyone holding an old share and every sub-share
+/// addressed to it can compute the refreshed share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubShare {
+    pub round_id: [u8; ROUND_ID_LEN],
+    pub from: u8,
+    pub to: u8,
+    pub delta: Vec<u8>,
+}
+
+impl RefreshRound {
+    /// Start a round for the set `packet` belongs to.
+    ///
+    /// `participants` must name at least `k` distinct share indexes, or the
+    /// refreshed set could not be recovered.
+    pub fn new(packet: &SharePacket, participants: &[u8]) -> CoreResult<Self> {
+        if packet.is_locked() {
+            return Err(CoreError::ShareLocked { x: packet.x });
+        }
+        if packet.is_verifiable() {
+            return Err(CoreError::InvalidRefresh(
+                "verifiable shares cannot be refreshed".to_string(),
+            ));
+        }
+        if packet.policy.is_some() {
+            return Err(CoreError::InvalidRefresh(
+                "shares of a policy split cannot be refreshed".to_string(),
+            ));
+        }
+        if packet.is_dispersed() {
+            return Err(CoreError::InvalidRefresh(
+                "large-secret shares cannot be refreshed".to_string(),
+            ));
+        }
+        if packet.stealth {
+            return Err(CoreError::InvalidRefresh(
+                "stealth shares hide their threshold and cannot be refreshed".to_string(),
+            ));
+        }
+
+        let mut participants = participants.to_vec();
+        participants.sort_unstable();
+        if participants.first() == Some(&0) {
+            return Err(CoreError::InvalidX);
+        }
+        if let Some(pair) = participants.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(CoreError::DuplicateX { x: pair[0] });
+        }
+        if participants.len() < packet.k as usize {
+            return Err(CoreError::NotEnoughShares {
+                k: packet.k,
+                m: participants.len(),
+            });
+        }
+
+        let payload_len = u32::try_from(packet.payload.len())
+            .map_err(|_| CoreError::InvalidPacket("payload too large".to_string()))?;
+        let mut round_id = [0u8; ROUND_ID_LEN];
+        OsRng.fill_bytes(&mut round_id);
+
+        Ok(Self {
+            round_id,
+            set_id: packet.set_id,
+            k: packet.k,
+            epoch: packet.epoch,
+            payload_len,
+            participants,
+        })
+    }
+
+    /// Serialize as `"SPR1" || round id || set id || k || epoch || payload
+    /// length || participant count || participants`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(4 + 16 + 16 + 1 + 4 + 4 + 1 + self.participants.len());
+        out.extend_from_slice(&ROUND_MAGIC);
+        out.extend_from_slice(&self.round_id);
+        out.extend_from_slice(&self.set_id.0);
+        out.push(self.k);
+        out.extend_from_slice(&self.epoch.to_be_bytes());
+        out.extend_from_slice(&self.payload_len.to_be_bytes());
+        out.push(self.participants.len() as u8);
+        out.extend_from_slice(&self.participants);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> CoreResult<Self> {
+        const FIXED_LEN: usize = 4 + 16 + 16 + 1 + 4 + 4 + 1;
+        let malformed = || CoreError::InvalidRefresh("malformed round".to_string());
+        if bytes.len() < FIXED_LEN || bytes[..4] != ROUND_MAGIC {
+            return Err(malformed());
+        }
+
+        let count = bytes[FIXED_LEN - 1] as usize;
+        if bytes.len() != FIXED_LEN + count {
+            return Err(malformed());
+        }
+
+        let round = Self {
+            round_id: bytes[4..20].try_into().map_err(|_| malformed())?,
+            set_id: SetId(bytes[20..36].try_into().map_err(|_| malformed())?),
+            k: bytes[36],
+            epoch: u32::from_be_bytes(bytes[37..41].try_into().map_err(|_| malformed())?),
+            payload_len: u32::from_be_bytes(bytes[41..45].try_into().map_err(|_| malformed())?),
+            participants: bytes[FIXED_LEN..].to_vec(),
+        };
+
+        let sorted = round.participants.windows(2).all(|pair| pair[0] < pair[1]);
+        if round.k == 0
+            || round.participants.len() < round.k as usize
+            || round.participants.first() == Some(&0)
+            || !sorted
+        {
+            return Err(malformed());
+        }
+        Ok(round)
+    }
+
+    /// Check that `packet` is a participant's share for this round.
+    fn check_packet(&self, packet: &SharePacket) -> CoreResult<()> {
+        if packet.is_locked() {
+            return Err(CoreError::ShareLocked { x: packet.x });
+        }
+        if packet.set_id != self.set_id
+            || packet.k != self.k
+            || packet.payload.len() != self.payload_len as usize
+            || packet.is_verifiable()
+            || packet.policy.is_some()
+            || packet.is_dispersed()
+        {
+            return Err(CoreError::InconsistentMetadata);
+        }
+        if packet.epoch != self.epoch {
+            return Err(CoreError::EpochMismatch {
+                expected: self.epoch,
+                found: packet.epoch,
+            });
+        }
+        if !self.participants.contains(&packet.x) {
+            return Err(CoreError::InvalidRefresh(format!(
+                "share {} is not a participant",
+                packet.x
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl SubShare {
+    /// Serialize as `"SPD1" || round id || from || to || delta`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(4 + ROUND_ID_LEN + 2 + self.delta.len());
+        out.extend_from_slice(&SUB_SHARE_MAGIC);
+        out.extend_from_slice(&self.round_id);
+        out.push(self.from);
+        out.push(self.to);
+        out.extend_from_slice(&self.delta);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> CoreResult<Self> {
+        const HEADER_LEN: usize = 4 + ROUND_ID_LEN + 2;
+        if bytes.len() < HEADER_LEN || bytes[..4] != SUB_SHARE_MAGIC {
+            return Err(CoreError::InvalidRefresh("malformed sub-share".to_string()));
+        }
+
+        let mut round_id = [0u8; ROUND_ID_LEN];
+        round_id.copy_from_slice(&bytes[4..4 + ROUND_ID_LEN]);
+        Ok(Self {
+            round_id,
+            from: bytes[HEADER_LEN - 2],
+            to: bytes[HEADER_LEN - 1],
+            delta: bytes[HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+/// Generate this participant's sub-shares, one per participant.
+///
+/// `packet` is the caller's own share; it identifies the contributor and is
+/// checked against the round, but its payload is not used.
+pub fn contribute(round: &RefreshRound, packet: &SharePacket) -> CoreResult<Vec<SubShare>> {
+    round.check_packet(packet)?;
+
+    let mut sub_shares: Vec<SubShare> = round
+        .participants
+        .iter()
+        .map(|&to| SubShare {
+            round_id: round.round_id,
+            from: packet.x,
+            to,
+            delta: vec![0u8; round.payload_len as usize],
+        })
+        .collect();
+
+    // delta(x) = a1*x + ... + a_{k-1}*x^{k-1}, so delta(0) = 0.
+    let mut coeffs = Zeroizing::new(vec![0u8; round.k.saturating_sub(1) as usize]);
+    for byte_index in 0..round.payload_len as usize {
+        if !coeffs.is_empty() {
+            OsRng.fill_bytes(&mut coeffs);
+        }
+
+        for sub_share in &mut sub_shares {
+            let x = Gf256(sub_share.to);
+            let mut x_pow = Gf256(1);
+            let mut y = Gf256(0);
+            for &coef in coeffs.iter() {
+                x_pow = x_pow * x;
+                y = y + Gf256(coef) * x_pow;
+            }
+            sub_share.delta[byte_index] = y.0;
+        }
+    }
+
+    Ok(sub_shares)
+}
+
+/// Add every participant's delta to `packet` and move it to the next epoch.
+///
+/// `sub_shares` must hold exactly one sub-share from each participant,
+/// addressed to `packet`'s index. Every participant must apply the same round,
+/// or the refreshed shares will not combine.
+pub fn apply(
+    round: &RefreshRound,
+    packet: &SharePacket,
+    sub_shares: &[SubShare],
+) -> CoreResult<SharePacket> {
+    round.check_packet(packet)?;
+    let epoch = round
+        .epoch
+        .checked_add(1)
+        .ok_or_else(|| CoreError::InvalidRefresh("epoch overflow".to_string()))?;
+
+    let mut seen = [false; 256];
+    for sub_share in sub_shares {
+        if sub_share.round_id != round.round_id {
+            return Err(CoreError::InvalidRefresh(
+                "sub-share belongs to another round".to_string(),
+            ));
+        }
+        if sub_share.to != packet.x {
+            return Err(CoreError::InvalidRefresh(format!(
+                "sub-share is addressed to share {}",
+                sub_share.to
+            )));
+        }
+        if !round.participants.contains(&sub_share.from) {
+            return Err(CoreError::InvalidRefresh(format!(
+                "share {} is not a participant",
+                sub_share.from
+            )));
+        }
+        if seen[sub_share.from as usize] {
+            return Err(CoreError::DuplicateX { x: sub_share.from });
+        }
+        if sub_share.delta.len() != packet.payload.len() {
+            return Err(CoreError::InvalidRefresh(
+                "sub-share length mismatch".to_string(),
+            ));
+        }
+        seen[sub_share.from as usize] = true;
+    }
+
+    if let Some(&missing) = round.participants.iter().find(|&&x| !seen[x as usize]) {
+        return Err(CoreError::InvalidRefresh(format!(
+            "missing sub-share from share {missing}"
+        )));
+    }
+
+    let mut refreshed = packet.clone().with_epoch(epoch);
+    for sub_share in sub_shares {
+        for (byte, delta) in refreshed.payload.iter_mut().zip(&sub_share.delta) {
+            *byte = (Gf256(*byte) + Gf256(*delta)).0;
+        }
+    }
+    Ok(refreshed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CoreError, combine_shares, split_secret};
+
+    fn run_round(packets: &[SharePacket]) -> Vec<SharePacket> {
+        let participants: Vec<u8> = packets.iter().map(|p| p.x).collect();
+        let round = RefreshRound::new(&packets[0], &participants).unwrap();
+        let round = RefreshRound::from_bytes(&round.to_bytes()).unwrap();
+
+        let all: Vec<SubShare> = packets
+            .iter()
+            .flat_map(|p| contribute(&round, p).unwrap())
+            .map(|s| SubShare::from_bytes(&s.to_bytes()).unwrap())
+            .collect();
+
+        packets
+            .iter()
+            .map(|p| {
+                let mine: Vec<SubShare> = all.iter().filter(|s| s.to == p.x).cloned().collect();
+                apply(&round, p, &mine).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn refreshed_shares_recover_secret_in_next_epoch() {
+        let old = split_secret(b"refresh", 2, 3, Some(b"pw")).unwrap();
+        let new = run_round(&old);
+
+        assert!(
+            new.iter()
+                .all(|p| p.epoch == 1 && p.set_id == old[0].set_id)
+        );
+        assert_ne!(new[0].payload, old[0].payload);
+        assert_eq!(combine_shares(&new[1..], Some(b"pw")).unwrap(), b"refresh");
+
+        let newer = run_round(&new);
+        assert_eq!(
+            combine_shares(&newer[..2], Some(b"pw")).unwrap(),
+            b"refresh"
+        );
+    }
+
+    #[test]
+    fn mixing_epochs_fails_with_typed_error() {
+        let old = split_secret(b"refresh", 2, 3, None).unwrap();
+        let new = run_round(&old);
+
+        let err = combine_shares(&[new[0].clone(), old[1].clone()], None).unwrap_err();
+        assert!(matches!(
+            err,
+            CoreError::EpochMismatch {
+                expected: 1,
+                found: 0
+            }
+        ));
+    }
+
+    #[test]
+    fn apply_requires_every_participant_once() {
+        let old = split_secret(b"refresh", 2, 3, None).unwrap();
+        let round = RefreshRound::new(&old[0], &[1, 2, 3]).unwrap();
+        let subs: Vec<SubShare> = old[..2]
+            .iter()
+            .flat_map(|p| contribute(&round, p).unwrap())
+            .filter(|s| s.to == 1)
+            .collect();
+
+        assert!(matches!(
+            apply(&round, &old[0], &subs),
+            Err(CoreError::InvalidRefresh(_))
+        ));
+        let doubled = [subs[0].clone(), subs[0].clone()];
+        assert!(matches!(
+            apply(&round, &old[0], &doubled),
+            Err(CoreError::DuplicateX { x: 1 })
+        ));
+        assert!(matches!(
+            RefreshRound::new(&old[0], &[2]),
+            Err(CoreError::NotEnoughShares { k: 2, m: 1 })
+        ));
+    }
+}
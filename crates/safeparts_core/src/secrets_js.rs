@@ -0,0 +1,431 @@
+//! Shares of the secrets.js JavaScript library (the `secrets.js-grempe`
+//! package).
+//!
+//! secrets.js shares a hex string over GF(2^bits), where `bits` is 3 to 20
+//! and 8 by default, using [`crate::sss::BinaryField`] with the primitive
+//! polynomial secrets.js picks for each size. A share is one token: the field
+//! size as a base-36 digit, the share id as hex padded to the width of the
+//! largest id, then the share data as hex.
+//!
+//! The secret gets a leading marker 1 bit, is left-padded with zeros to a
+//! multiple of 128 bits, and is shared `bits` bits at a time. Recovery keeps
+//! what follows the marker. Shares carry neither the threshold nor an
+//! integrity tag: too few shares recover wrong bytes.
+//!
+//! secrets.js works on hex strings, and `secrets.str2hex` writes text as
+//! UTF-16 code units, so text shared that way recovers as UTF-16BE bytes here.
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+use crate::sss::{BinaryElement, BinaryField};
+
+pub const MIN_BITS: u8 = 3;
+pub const MAX_BITS: u8 = 20;
+
+/// The field size secrets.js uses unless `secrets.init` picks another.
+pub const DEFAULT_BITS: u8 = 8;
+
+/// `secrets.share` pads the marked secret to a multiple of this many bits.
+const PAD_BITS: usize = 128;
+
+/// The lower terms of the primitive polynomial secrets.js uses for each field
+/// size, indexed by `bits`.
+const PRIMITIVE: [u32; MAX_BITS as usize + 1] = [
+    0, 0, 1, 3, 3, 5, 3, 3, 29, 17, 9, 5, 83, 27, 43, 3, 45, 9, 39, 39, 9,
+];
+
+/// One secrets.js share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub bits: u8,
+    /// The x coordinate, from 1 to `2^bits - 1`.
+    pub id: u32,
+    /// The share data as lowercase hex.
+    pub data: Zeroizing<String>,
+}
+
+impl Share {
+    /// Parse one share as `secrets.share` writes it.
+    pub fn parse(token: &str) -> CoreResult<Self> {
+        let malformed = |reason: &str| CoreError::Encoding(format!("secrets.js share: {reason}"));
+        let token = token.trim();
+        let mut chars = token.chars();
+        let bits = chars
+            .next()
+            .and_then(|c| c.to_digit(36))
+            .filter(|bits| (MIN_BITS as u32..=MAX_BITS as u32).contains(bits))
+            .ok_or_else(|| malformed("first character is not a field size from 3 to 20"))?
+            as u8;
+        let rest = chars.as_str();
+        let id_len = id_width(bits);
+        if rest.len() <= id_len || !rest.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(malformed("expected a hex id and hex data"));
+        }
+        let (id, data) = rest.split_at(id_len);
+        let id = u32::from_str_radix(id, 16).map_err(|_| malformed("id is not hex"))?;
+        if id == 0 || id > max_id(bits) {
+            return Err(malformed("id is out of range for the field size"));
+        }
+        Ok(Self {
+            bits,
+            id,
+            data: Zeroizing::new(data.to_ascii_lowercase()),
+        })
+    }
+
+    /// Write the share as `secrets.share` does.
+    pub fn to_text(&self) -> String {
+        let bits = char::from_digit(self.bits as u32, 36)
+            .unwrap_or('8')
+            .to_ascii_uppercase();
+        format!(
+            "{bits}{:0width$x}{}",
+            self.id,
+            self.data.as_str(),
+            width = id_width(self.bits)
+        )
+    }
+}
+
+/// True when every whitespace-separated token of `input` is a secrets.js
+/// share.
+pub fn looks_like(input: &str) -> bool {
+    let mut tokens = input.split_whitespace();
+    tokens.clone().next().is_some() && tokens.all(|token| Share::parse(token).is_ok())
+}
+
+/// Parse every share in `input`, separated by whitespace.
+pub fn parse_shares(input: &str) -> CoreResult<Vec<Share>> {
+    let shares = input
+        .split_whitespace()
+        .map(Share::parse)
+        .collect::<CoreResult<Vec<_>>>()?;
+    if shares.is_empty() {
+        return Err(CoreError::EmptyShareInput);
+    }
+    Ok(shares)
+}
+
+/// Split `secret` into `n` shares with a threshold of `k` over GF(2^bits),
+/// as `secrets.share` does with its default padding.
+pub fn split(secret: &[u8], k: u8, n: u8, bits: u8) -> CoreResult<Vec<Share>> {
+    let field = field(bits)?;
+    if k < 2 || k > n || n as u32 > max_id(bits) {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+
+    let mut marked = Zeroizing::new(vec![1u8]);
+    marked.extend(
+        secret
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |shift| (byte >> shift) & 1)),
+    );
+    let pad = (PAD_BITS - marked.len() % PAD_BITS) % PAD_BITS;
+    marked.splice(0..0, std::iter::repeat_n(0, pad));
+
+    let mut values: Vec<Zeroizing<Vec<u32>>> = (0..n).map(|_| Zeroizing::new(Vec::new())).collect();
+    let mut coeffs: Vec<BinaryElement> = vec![field.zero(); k as usize];
+    for chunk in to_values(&marked, bits).iter() {
+        coeffs[0] = field.element(u64::from(*chunk));
+        for coeff in coeffs[1..].iter_mut() {
+            *coeff = field.element(u64::from(OsRng.next_u32()));
+        }
+        for (x, share_values) in (1..).zip(values.iter_mut()) {
+            let y = field.evaluate(&coeffs, &field.element(x));
+            share_values.push(field.to_u64(&y) as u32);
+        }
+    }
+
+    Ok((1..)
+        .zip(values)
+        .map(|(id, share_values)| Share {
+            bits,
+            id,
+            data: Zeroizing::new(to_hex(&from_values(&share_values, bits))),
+        })
+        .collect())
+}
+
+/// Recover the secret from shares of one set, as `secrets.combine` does.
+///
+/// Every share is used, and a repeated id keeps its first share. A secret
+/// with an odd number of hex digits comes back with a leading zero digit.
+pub fn combine(shares: &[Share]) -> CoreResult<Zeroizing<Vec<u8>>> {
+    let Some(first) = shares.first() else {
+        return Err(CoreError::EmptyShareInput);
+    };
+    let field = field(first.bits)?;
+
+    let mut ids = Vec::new();
+    let mut columns: Vec<Zeroizing<Vec<u32>>> = Vec::new();
+    for share in shares {
+        if share.bits != first.bits {
+            return Err(CoreError::Encoding(
+                "secrets.js shares use different field sizes".to_string(),
+            ));
+        }
+        if ids.contains(&share.id) {
+            continue;
+        }
+        let values = to_values(&hex_bits(&share.data)?, share.bits);
+        if ids.is_empty() {
+            columns = (0..values.len())
+                .map(|_| Zeroizing::new(Vec::new()))
+                .collect();
+        } else if values.len() != columns.len() {
+            return Err(CoreError::Encoding(
+                "secrets.js shares have different lengths".to_string(),
+            ));
+        }
+        ids.push(share.id);
+        for (column, value) in columns.iter_mut().zip(values.iter()) {
+            column.push(*value);
+        }
+    }
+
+    let recovered = Zeroizing::new(
+        columns
+            .iter()
+            .map(|column| {
+                let points: Vec<_> = ids
+                    .iter()
+                    .zip(column.iter())
+                    .map(|(&id, &value)| {
+                        (
+                            field.element(u64::from(id)),
+                            field.element(u64::from(value)),
+                        )
+                    })
+                    .collect();
+                let secret = field.interpolate_at(&points, &field.zero())?;
+                Ok(field.to_u64(&secret) as u32)
+            })
+            .collect::<CoreResult<Vec<u32>>>()?,
+    );
+    let marked = from_values(&recovered, first.bits);
+    let marker = marked.iter().position(|&bit| bit == 1).ok_or_else(|| {
+        CoreError::Encoding("secrets.js shares did not recover a secret".to_string())
+    })?;
+    Ok(pack(&marked[marker + 1..], 8))
+}
+
+fn field(bits: u8) -> CoreResult<BinaryField> {
+    PRIMITIVE
+        .get(bits as usize)
+        .filter(|_| bits >= MIN_BITS)
+        .and_then(|&primitive| {
+            let low_terms: Vec<usize> = (0..bits as usize)
+                .filter(|&exponent| (primitive >> exponent) & 1 == 1)
+                .collect();
+            BinaryField::new(bits as usize, &low_terms)
+        })
+        .ok_or_else(|| {
+            CoreError::Encoding(format!(
+                "secrets.js field sizes are {MIN_BITS} to {MAX_BITS} bits, got {bits}"
+            ))
+        })
+}
+
+fn max_id(bits: u8) -> u32 {
+    (1 << bits) - 1
+}
+
+/// Hex digits in the largest id, `2^bits - 1`.
+fn id_width(bits: u8) -> usize {
+    (bits as usize).div_ceil(4)
+}
+
+/// The bits of `hex`, most significant first.
+fn hex_bits(hex: &str) -> CoreResult<Zeroizing<Vec<u8>>> {
+    let mut bits = Zeroizing::new(Vec::with_capacity(hex.len() * 4));
+    for c in hex.chars() {
+        let digit = c
+            .to_digit(16)
+            .ok_or_else(|| CoreError::Encoding("secrets.js share: data is not hex".to_string()))?;
+        bits.extend((0..4).rev().map(|shift| ((digit >> shift) & 1) as u8));
+    }
+    Ok(bits)
+}
+
+/// Cut a bit string into `bits`-wide values from its least significant end,
+/// as secrets.js does. The last value holds whatever is left over.
+fn to_values(bit_string: &[u8], bits: u8) -> Zeroizing<Vec<u32>> {
+    Zeroizing::new(
+        bit_string
+            .rchunks(bits as usize)
+            .map(|chunk| chunk.iter().fold(0, |acc, &bit| (acc << 1) | bit as u32))
+            .collect(),
+    )
+}
+
+/// The inverse of [`to_values`], with every value `bits` wide.
+fn from_values(values: &[u32], bits: u8) -> Zeroizing<Vec<u8>> {
+    Zeroizing::new(
+        values
+            .iter()
+            .rev()
+            .flat_map(|&value| {
+                (0..bits)
+                    .rev()
+                    .map(move |shift| ((value >> shift) & 1) as u8)
+            })
+            .collect(),
+    )
+}
+
+/// Left-pad `bit_string` with zeros to a multiple of `width` and pack it
+/// `width` bits to a value, most significant bit first.
+fn pack(bit_string: &[u8], width: usize) -> Zeroizing<Vec<u8>> {
+    let pad = (width - bit_string.len() % width) % width;
+    let mut packed = Zeroizing::new(Vec::with_capacity((pad + bit_string.len()) / width));
+    let mut value = 0u8;
+    for (i, &bit) in std::iter::repeat_n(&0, pad).chain(bit_string).enumerate() {
+        value = (value << 1) | bit;
+        if (i + 1) % width == 0 {
+            packed.push(value);
+            value = 0;
+        }
+    }
+    packed
+}
+
+fn to_hex(bit_string: &[u8]) -> String {
+    pack(bit_string, 4)
+        .iter()
+        .map(|&digit| char::from_digit(digit as u32, 16).unwrap_or('0'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `secrets.share(secret, n, k)` after `secrets.init(bits)`, generated with
+    // a port of secrets.js-grempe's share and combine run under Node. The last
+    // one was shared with a `padLength` of 512.
+    const FIXTURES: [(&str, usize, &[&str]); 6] = [
+        (
+            "6d7920736563726574",
+            3,
+            &[
+                "8012461f89c3f0044f2614d95c6dc2b21f6",
+                "8028a192103f950465a05114f22dbd18be0",
+                "803ae78d99fc65003c51d7ca9816488cf62",
+                "8042679559ec9fd9c53acc9989b456d214f",
+                "8050218ad02f6fdd9ccb4a47e38fa3465cd",
+            ],
+        ),
+        (
+            "c0ffee",
+            2,
+            &[
+                "3116c0cfb3eecac36a4d4aaa9bad5c80d2d",
+                "3204b1952efb94e3f29190c3363a2d1aa68",
+                "3312715a9d155e2098dcda69ad976d958ab",
+                "340961e158d3e3a2d5226d34ff5dfcee629",
+            ],
+        ),
+        (
+            "00ff10",
+            2,
+            &[
+                "C001da16b28b5978f920cb1f49acb0c8fbaaa",
+                "C002b11d641392a3f771963e830b6481b4464",
+                "C0036b0bd698cbdb0e515d21caa7d459401de",
+            ],
+        ),
+        (
+            "0123456789abcdef0123456789abcdef",
+            3,
+            &[
+                "K00001b7e1ea3eb23fe6e24f0aaac31cb77d42dff12f7287cb000d721b57d2e8d19ff07",
+                "K00002e9986af2605d64674544690ebe4cbf40933090d8ae79876d589dfc783a65f8b5b",
+                "K000035e7980ccd26282850a4ec3cda2fbc2025cd38bfc51283bbeda949ffcaa2edb9b3",
+                "K00004ca5dbfd8e6306e41e7c8203d60104e956465365b745f43eb3d79992774c03cd17",
+            ],
+        ),
+        (
+            "0abc",
+            2,
+            &[
+                "8014041d3ba328f8d461b97a5e816592b48",
+                "8028082bb696403078c363357cd2cb27849",
+                "803c0c368d3568c8aca2da4f2253aeb49bd",
+            ],
+        ),
+        (
+            "deadbeef",
+            2,
+            &[
+                "G0001970ad00cc5033728075e414e407ac2de7732728243112d3aa746414969a6b5046a11731d20fab0d718342d9846dc2bc96d66c08203a13b3b25f50634f94baa1c",
+                "G00022e39a0358a2b6e500ebc829c80f48591ee64e50486225a744ea18292d34c6a25d422e63a41f4618330685b308db85792dacc8129074276764bea0c6b91619709",
+                "G0003b93370394f28597809e2c3d2c08e474f99569786c533774ee9e7c3dbbaeadf21be339527610ed154285c76a8cb647c5bb7aa41ab04e34d4d6e1f0a5eb68783fa",
+            ],
+        ),
+    ];
+
+    #[test]
+    fn secrets_js_fixtures_combine_with_any_threshold_of_shares() {
+        for (secret, k, shares) in FIXTURES {
+            let secret = hex::decode(secret).unwrap();
+            let parsed = parse_shares(&shares.join("\n")).unwrap();
+            for (share, text) in parsed.iter().zip(shares) {
+                assert_eq!(share.to_text(), *text);
+            }
+            assert_eq!(combine(&parsed[..k]).unwrap().as_slice(), secret);
+            assert_eq!(
+                combine(&parsed[parsed.len() - k..]).unwrap().as_slice(),
+                secret
+            );
+            assert_ne!(combine(&parsed[..k - 1]).ok().as_deref(), Some(&secret));
+        }
+    }
+
+    #[test]
+    fn split_round_trips_at_every_field_size() {
+        let secret = b"correct horse battery staple";
+        for bits in MIN_BITS..=MAX_BITS {
+            let shares = split(secret, 3, 5, bits).unwrap();
+            assert!(shares.iter().all(|share| share.bits == bits));
+            let text: Vec<String> = shares.iter().map(Share::to_text).collect();
+            assert!(looks_like(&text.join(" ")));
+            let parsed = parse_shares(&text[2..].join("\n")).unwrap();
+            assert_eq!(combine(&parsed).unwrap().as_slice(), secret);
+        }
+        assert_eq!(split(b"", 2, 2, 8).unwrap().len(), 2);
+        assert!(split(secret, 2, 7, 3).is_ok());
+        assert!(split(secret, 2, 8, 3).is_err());
+        assert!(split(secret, 1, 3, 8).is_err());
+        assert!(split(secret, 2, 3, 21).is_err());
+    }
+
+    #[test]
+    fn combine_ignores_repeated_ids_and_rejects_mixed_sets() {
+        let (_, _, shares) = FIXTURES[1];
+        let parsed = parse_shares(&[shares[0], shares[0], shares[2]].join(" ")).unwrap();
+        assert_eq!(combine(&parsed).unwrap().as_slice(), [0xc0, 0xff, 0xee]);
+
+        let mixed = parse_shares(&[FIXTURES[0].2[0], shares[1]].join(" ")).unwrap();
+        assert!(combine(&mixed).is_err());
+        assert!(combine(&[]).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_malformed_shares() {
+        let share = Share::parse("8028A192103F9504").unwrap();
+        assert_eq!(
+            (share.bits, share.id, share.data.as_str()),
+            (8, 2, "8a192103f9504")
+        );
+        for bad in [
+            "", "2010a", "L00001ab", "8", "80", "8001", "800abc", "8zz12", "80112g",
+        ] {
+            assert!(Share::parse(bad).is_err(), "{bad}");
+        }
+        assert!(!looks_like("8012abc not-a-share"));
+        assert!(!looks_like("  "));
+    }
+}
@@ -0,0 +1,866 @@
+//! SLIP-0039 mnemonic shares, for moving secrets between Safeparts and
+//! Trezor-style SLIP-39 tools.
+//!
+//! SLIP-39 shares a master secret of 16 bytes or more in two levels: the
+//! secret is split into groups, and each group share is split again among
+//! that group's members. Each share is written with words from the 1024-word
+//! SLIP-39 list and ends in an RS1024 checksum. The master secret is first
+//! encrypted with a passphrase through a four-round Feistel network keyed
+//! with PBKDF2-HMAC-SHA256. The passphrase may be empty.
+//!
+//! SLIP-39 shares are not [`crate::packet::SharePacket`] values. To move a
+//! SLIP-39 set into Safeparts, recover the master secret with [`combine`] and
+//! pass it to [`crate::split_secret`]. To go the other way, recover the
+//! secret with [`crate::combine_shares`] and pass it to [`split`].
+//!
+//! A wrong passphrase does not fail: by design it decrypts to a different
+//! master secret.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+use crate::sss;
+
+/// Shortest master secret SLIP-39 accepts, in bytes.
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// Most groups in a set, and most members in a group.
+pub const MAX_SHARE_COUNT: u8 = 16;
+
+/// Largest iteration exponent; PBKDF2 runs `2500 << e` times per round.
+pub const MAX_ITERATION_EXPONENT: u8 = 15;
+
+const WORDLIST: &str = include_str!("slip39_wordlist.txt");
+const RADIX_BITS: usize = 10;
+const HEADER_WORDS: usize = 4;
+const CHECKSUM_WORDS: usize = 3;
+const MIN_MNEMONIC_WORDS: usize =
+    HEADER_WORDS + (MIN_SECRET_LEN * 8).div_ceil(RADIX_BITS) + CHECKSUM_WORDS;
+const CUSTOMIZATION: &[u8] = b"shamir";
+const CUSTOMIZATION_EXTENDABLE: &[u8] = b"shamir_extendable";
+const RS1024_GENERATOR: [u32; 10] = [
+    0x00E0_E040,
+    0x01C1_C080,
+    0x0383_8100,
+    0x0707_0200,
+    0x0E0E_0009,
+    0x1C0C_2412,
+    0x3808_6C24,
+    0x3090_FC48,
+    0x21B1_F890,
+    0x03F3_F120,
+];
+const BASE_ITERATION_COUNT: u32 = 10_000;
+const ROUND_COUNT: u8 = 4;
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const DIGEST_LEN: usize = 4;
+
+/// Options for [`split`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slip39Options {
+    /// Write extendable shares, whose encryption does not depend on the
+    /// identifier. On by default, as in current SLIP-39 tools.
+    pub extendable: bool,
+    /// PBKDF2 cost: each of the four rounds runs `2500 << e` iterations.
+    pub iteration_exponent: u8,
+}
+
+impl Default for Slip39Options {
+    fn default() -> Self {
+        Self {
+            extendable: true,
+            iteration_exponent: 1,
+        }
+    }
+}
+
+/// One group of a SLIP-39 split: `threshold` of its `count` members are
+/// needed to rebuild the group share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupSpec {
+    pub threshold: u8,
+    pub count: u8,
+}
+
+/// One parsed SLIP-39 mnemonic share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    /// Random 15-bit identifier shared by every share of a set.
+    pub identifier: u16,
+    pub extendable: bool,
+    pub iteration_exponent: u8,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    /// Parse one mnemonic, checking its words, checksum, and padding.
+    pub fn parse(mnemonic: &str) -> CoreResult<Self> {
+        let words = mnemonic
+            .split_whitespace()
+            .enumerate()
+            .map(|(position, word)| {
+                word_index(word).ok_or_else(|| {
+                    invalid(format!(
+                        "unknown word {word:?} at position {}",
+                        position + 1
+                    ))
+                })
+            })
+            .collect::<CoreResult<Vec<u16>>>()?;
+        if words.len() < MIN_MNEMONIC_WORDS {
+            return Err(invalid(format!(
+                "a share needs at least {MIN_MNEMONIC_WORDS} words, got {}",
+                words.len()
+            )));
+        }
+
+        let extendable = (words[1] >> 4) & 1 == 1;
+        if rs1024_polymod(customization(extendable), &words) != 1 {
+            return Err(invalid("checksum mismatch".to_string()));
+        }
+
+        let value_words = &words[HEADER_WORDS..words.len() - CHECKSUM_WORDS];
+        let padding = value_words.len() * RADIX_BITS % 16;
+        if padding > 8 {
+            return Err(invalid("invalid mnemonic length".to_string()));
+        }
+        let value = words_to_bytes(value_words, padding)
+            .ok_or_else(|| invalid("padding bits are not zero".to_string()))?;
+
+        let group_threshold = ((words[2] >> 2) & 0xF) as u8 + 1;
+        let group_count = (((words[2] & 0x3) << 2) | (words[3] >> 8)) as u8 + 1;
+        if group_threshold > group_count {
+            return Err(invalid(
+                "group threshold is above the group count".to_string(),
+            ));
+        }
+        Ok(Self {
+            identifier: (words[0] << 5) | (words[1] >> 5),
+            extendable,
+            iteration_exponent: (words[1] & 0xF) as u8,
+            group_index: (words[2] >> 6) as u8,
+            group_threshold,
+            group_count,
+            member_index: ((words[3] >> 4) & 0xF) as u8,
+            member_threshold: (words[3] & 0xF) as u8 + 1,
+            value,
+        })
+    }
+
+    /// Write the share as space-separated words with its checksum.
+    pub fn to_mnemonic(&self) -> String {
+        let mut words = vec![
+            self.identifier >> 5,
+            ((self.identifier & 0x1F) << 5)
+                | (u16::from(self.extendable) << 4)
+                | u16::from(self.iteration_exponent),
+            (u16::from(self.group_index) << 6)
+                | (u16::from(self.group_threshold - 1) << 2)
+                | (u16::from(self.group_count - 1) >> 2),
+            ((u16::from(self.group_count - 1) & 0x3) << 8)
+                | (u16::from(self.member_index) << 4)
+                | u16::from(self.member_threshold - 1),
+        ];
+        words.extend(bytes_to_words(&self.value));
+
+        let mut with_checksum = words.clone();
+        with_checksum.extend([0; CHECKSUM_WORDS]);
+        let checksum = rs1024_polymod(customization(self.extendable), &with_checksum) ^ 1;
+        words.extend(
+            (0..CHECKSUM_WORDS)
+                .rev()
+                .map(|i| ((checksum >> (i * RADIX_BITS)) & 0x3FF) as u16),
+        );
+
+        let list = wordlist();
+        words
+            .iter()
+            .map(|&index| list[index as usize])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn same_set(&self, other: &Share) -> bool {
+        self.identifier == other.identifier
+            && self.extendable == other.extendable
+            && self.iteration_exponent == other.iteration_exponent
+            && self.group_threshold == other.group_threshold
+            && self.group_count == other.group_count
+            && self.value.len() == other.value.len()
+    }
+}
+
+/// Encrypt `master_secret` with `passphrase` and split it into SLIP-39
+/// mnemonics, one list per group in `groups`.
+///
+/// `group_threshold` of the groups must be rebuilt to recover the secret. A
+/// group with a threshold of 1 must have exactly one member, as SLIP-39
+/// requires.
+///
+/// # Example
+///
+/// ```
+/// use safeparts_core::slip39::{self, GroupSpec, Slip39Options};
+/// use safeparts_core::CoreResult;
+///
+/// fn main() -> CoreResult<()> {
+///     let secret = [7u8; 16];
+///     let groups = [GroupSpec { threshold: 2, count: 3 }];
+///     let options = Slip39Options { iteration_exponent: 0, ..Slip39Options::default() };
+///     let mnemonics = slip39::split(&secret, b"", 1, &groups, &options)?;
+///
+///     let recovered = slip39::combine(&mnemonics[0][1..], b"")?;
+///     assert_eq!(recovered.as_slice(), secret);
+///     Ok(())
+/// }
+/// ```
+pub fn split(
+    master_secret: &[u8],
+    passphrase: &[u8],
+    group_threshold: u8,
+    groups: &[GroupSpec],
+    options: &Slip39Options,
+) -> CoreResult<Vec<Vec<String>>> {
+    if master_secret.len() < MIN_SECRET_LEN || !master_secret.len().is_multiple_of(2) {
+        return Err(invalid(format!(
+            "the master secret must be an even number of bytes, at least {MIN_SECRET_LEN}"
+        )));
+    }
+    check_passphrase(passphrase)?;
+    if options.iteration_exponent > MAX_ITERATION_EXPONENT {
+        return Err(invalid(format!(
+            "iteration exponent {} is above {MAX_ITERATION_EXPONENT}",
+            options.iteration_exponent
+        )));
+    }
+    let group_count = u8::try_from(groups.len()).unwrap_or(u8::MAX);
+    if group_count == 0 || group_count > MAX_SHARE_COUNT {
+        return Err(invalid(format!(
+            "need 1 to {MAX_SHARE_COUNT} groups, got {}",
+            groups.len()
+        )));
+    }
+    if group_threshold == 0 || group_threshold > group_count {
+        return Err(invalid(format!(
+            "group threshold {group_threshold} is not between 1 and {group_count}"
+        )));
+    }
+    for group in groups {
+        if group.threshold == 0 || group.threshold > group.count || group.count > MAX_SHARE_COUNT {
+            return Err(invalid(format!(
+                "group {} of {} is not a valid member threshold and count",
+                group.threshold, group.count
+            )));
+        }
+        if group.threshold == 1 && group.count > 1 {
+            return Err(invalid(
+                "a group with threshold 1 must have exactly one member".to_string(),
+            ));
+        }
+    }
+
+    let identifier = (OsRng.next_u32() & 0x7FFF) as u16;
+    let encrypted = feistel(
+        master_secret,
+        passphrase,
+        identifier,
+        options.extendable,
+        options.iteration_exponent,
+        true,
+    );
+    let group_shares = split_level(group_threshold, group_count, &encrypted)?;
+
+    groups
+        .iter()
+        .zip(group_shares)
+        .map(|(group, (group_index, group_value))| {
+            Ok(split_level(group.threshold, group.count, &group_value)?
+                .into_iter()
+                .map(|(member_index, value)| {
+                    Share {
+                        identifier,
+                        extendable: options.extendable,
+                        iteration_exponent: options.iteration_exponent,
+                        group_index,
+                        group_threshold,
+                        group_count,
+                        member_index,
+                        member_threshold: group.threshold,
+                        value,
+                    }
+                    .to_mnemonic()
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Recover the master secret from SLIP-39 mnemonics and decrypt it with
+/// `passphrase`.
+///
+/// Extra shares are ignored. Errors name the groups that are still
+/// incomplete when there are not enough shares.
+pub fn combine<S: AsRef<str>>(
+    mnemonics: &[S],
+    passphrase: &[u8],
+) -> CoreResult<Zeroizing<Vec<u8>>> {
+    check_passphrase(passphrase)?;
+    let shares = mnemonics
+        .iter()
+        .map(|mnemonic| Share::parse(mnemonic.as_ref()))
+        .collect::<CoreResult<Vec<_>>>()?;
+    let Some(first) = shares.first() else {
+        return Err(CoreError::EmptyShareInput);
+    };
+    if shares.iter().any(|share| !first.same_set(share)) {
+        return Err(invalid(
+            "the mnemonics come from different sets".to_string(),
+        ));
+    }
+
+    let mut groups: BTreeMap<u8, Vec<&Share>> = BTreeMap::new();
+    for share in &shares {
+        let group = groups.entry(share.group_index).or_default();
+        if group
+            .first()
+            .is_some_and(|member| member.member_threshold != share.member_threshold)
+        {
+            return Err(invalid(format!(
+                "group {} shares disagree on the member threshold",
+                share.group_index + 1
+            )));
+        }
+        match group
+            .iter()
+            .find(|member| member.member_index == share.member_index)
+        {
+            Some(member) if member.value != share.value => {
+                return Err(invalid(format!(
+                    "two different shares have member index {} in group {}",
+                    share.member_index + 1,
+                    share.group_index + 1
+                )));
+            }
+            Some(_) => {}
+            None => group.push(share),
+        }
+    }
+
+    let mut group_shares = Vec::new();
+    let mut incomplete = Vec::new();
+    for (&group_index, members) in &groups {
+        let threshold = members[0].member_threshold;
+        if members.len() < threshold as usize {
+            incomplete.push(format!(
+                "group {} has {} of {threshold} shares",
+                group_index + 1,
+                members.len()
+            ));
+            continue;
+        }
+        let points: Vec<(u8, &[u8])> = members[..threshold as usize]
+            .iter()
+            .map(|member| (member.member_index, member.value.as_slice()))
+            .collect();
+        group_shares.push((group_index, recover_level(threshold, &points)?));
+    }
+    if group_shares.len() < first.group_threshold as usize {
+        let mut reason = format!(
+            "need {} complete group(s), got {}",
+            first.group_threshold,
+            group_shares.len()
+        );
+        if !incomplete.is_empty() {
+            reason.push_str(&format!("; {}", incomplete.join("; ")));
+        }
+        return Err(invalid(reason));
+    }
+
+    let points: Vec<(u8, &[u8])> = group_shares[..first.group_threshold as usize]
+        .iter()
+        .map(|(index, value)| (*index, value.as_slice()))
+        .collect();
+    let encrypted = recover_level(first.group_threshold, &points)?;
+    Ok(feistel(
+        &encrypted,
+        passphrase,
+        first.identifier,
+        first.extendable,
+        first.iteration_exponent,
+        false,
+    ))
+}
+
+fn invalid(reason: String) -> CoreError {
+    CoreError::Slip39(reason)
+}
+
+fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| WORDLIST.lines().collect())
+}
+
+/// Index of `word` in the sorted list, ignoring ASCII case.
+fn word_index(word: &str) -> Option<u16> {
+    let word = word.to_ascii_lowercase();
+    wordlist()
+        .binary_search(&word.as_str())
+        .ok()
+        .map(|index| index as u16)
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        CUSTOMIZATION_EXTENDABLE
+    } else {
+        CUSTOMIZATION
+    }
+}
+
+fn rs1024_polymod(customization: &[u8], words: &[u16]) -> u32 {
+    let values = customization
+        .iter()
+        .map(|&byte| u32::from(byte))
+        .chain(words.iter().map(|&word| u32::from(word)));
+    values.fold(1, |checksum, value| {
+        let top = checksum >> 20;
+        let mut checksum = ((checksum & 0xF_FFFF) << RADIX_BITS) ^ value;
+        for (bit, generator) in RS1024_GENERATOR.iter().enumerate() {
+            if (top >> bit) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    })
+}
+
+/// Pack bytes into 10-bit words, with zero padding bits in front.
+fn bytes_to_words(bytes: &[u8]) -> Vec<u16> {
+    let word_count = (bytes.len() * 8).div_ceil(RADIX_BITS);
+    let mut bits = word_count * RADIX_BITS - bytes.len() * 8;
+    let mut acc: u32 = 0;
+    let mut words = Vec::with_capacity(word_count);
+    for &byte in bytes {
+        acc = (acc << 8) | u32::from(byte);
+        bits += 8;
+        if bits >= RADIX_BITS {
+            bits -= RADIX_BITS;
+            words.push((acc >> bits) as u16);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    words
+}
+
+/// Undo [`bytes_to_words`], or `None` when a padding bit is set.
+fn words_to_bytes(words: &[u16], padding: usize) -> Option<Zeroizing<Vec<u8>>> {
+    let first = *words.first()?;
+    if first >> (RADIX_BITS - padding) != 0 {
+        return None;
+    }
+    let mut acc = u32::from(first);
+    let mut bits = RADIX_BITS - padding;
+    let mut bytes = Zeroizing::new(Vec::with_capacity(words.len() * RADIX_BITS / 8));
+    for &word in &words[1..] {
+        acc = (acc << RADIX_BITS) | u32::from(word);
+        bits += RADIX_BITS;
+        while bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// SLIP-39 passphrases are printable ASCII.
+fn check_passphrase(passphrase: &[u8]) -> CoreResult<()> {
+    if passphrase.iter().all(|byte| (32..=126).contains(byte)) {
+        Ok(())
+    } else {
+        Err(invalid(
+            "the passphrase must be printable ASCII".to_string(),
+        ))
+    }
+}
+
+/// The four-round Feistel network that encrypts the master secret.
+fn feistel(
+    input: &[u8],
+    passphrase: &[u8],
+    identifier: u16,
+    extendable: bool,
+    iteration_exponent: u8,
+    encrypt: bool,
+) -> Zeroizing<Vec<u8>> {
+    let half = input.len() / 2;
+    let mut left = Zeroizing::new(input[..half].to_vec());
+    let mut right = Zeroizing::new(input[half..].to_vec());
+    let mut salt = Vec::new();
+    if !extendable {
+        salt.extend_from_slice(CUSTOMIZATION);
+        salt.extend_from_slice(&identifier.to_be_bytes());
+    }
+    let iterations = (BASE_ITERATION_COUNT << iteration_exponent) / u32::from(ROUND_COUNT);
+
+    let mut rounds: Vec<u8> = (0..ROUND_COUNT).collect();
+    if !encrypt {
+        rounds.reverse();
+    }
+    for round in rounds {
+        let mut password = Zeroizing::new(Vec::with_capacity(1 + passphrase.len()));
+        password.push(round);
+        password.extend_from_slice(passphrase);
+        let mut round_salt = salt.clone();
+        round_salt.extend_from_slice(&right);
+
+        let mut mask = Zeroizing::new(vec![0u8; half]);
+        pbkdf2_sha256(&password, &round_salt, iterations, &mut mask);
+        for (byte, mask) in left.iter_mut().zip(mask.iter()) {
+            *byte ^= mask;
+        }
+        std::mem::swap(&mut left, &mut right);
+    }
+
+    let mut out = Zeroizing::new(Vec::with_capacity(input.len()));
+    out.extend_from_slice(&right);
+    out.extend_from_slice(&left);
+    out
+}
+
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    let prf =
+        <Hmac<Sha256> as Mac>::new_from_slice(password).expect("HMAC accepts keys of any length");
+    for (block, chunk) in out.chunks_mut(32).enumerate() {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&(block as u32 + 1).to_be_bytes());
+        let mut u = Zeroizing::new(mac.finalize().into_bytes().to_vec());
+        let mut t = u.clone();
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = Zeroizing::new(mac.finalize().into_bytes().to_vec());
+            for (t, u) in t.iter_mut().zip(u.iter()) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+fn digest(random_part: &[u8], secret: &[u8]) -> [u8; DIGEST_LEN] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(random_part)
+        .expect("HMAC accepts keys of any length");
+    mac.update(secret);
+    let full = mac.finalize().into_bytes();
+    let mut out = [0u8; DIGEST_LEN];
+    out.copy_from_slice(&full[..DIGEST_LEN]);
+    out
+}
+
+/// Split one level: `count` shares at indexes `0..count`, with the secret at
+/// 255 and a digest of it at 254.
+fn split_level(
+    threshold: u8,
+    count: u8,
+    secret: &[u8],
+) -> CoreResult<Vec<(u8, Zeroizing<Vec<u8>>)>> {
+    if threshold == 1 {
+        return Ok((0..count)
+            .map(|index| (index, Zeroizing::new(secret.to_vec())))
+            .collect());
+    }
+
+    let random_count = threshold - 2;
+    let mut shares: Vec<(u8, Zeroizing<Vec<u8>>)> = (0..random_count)
+        .map(|index| {
+            let mut value = Zeroizing::new(vec![0u8; secret.len()]);
+            OsRng.fill_bytes(&mut value);
+            (index, value)
+        })
+        .collect();
+
+    let mut digest_share = Zeroizing::new(vec![0u8; secret.len()]);
+    OsRng.fill_bytes(&mut digest_share[DIGEST_LEN..]);
+    let tag = digest(&digest_share[DIGEST_LEN..], secret);
+    digest_share[..DIGEST_LEN].copy_from_slice(&tag);
+
+    let mut base: Vec<(u8, &[u8])> = shares
+        .iter()
+        .map(|(index, value)| (*index, value.as_slice()))
+        .collect();
+    base.push((DIGEST_INDEX, &digest_share));
+    base.push((SECRET_INDEX, secret));
+    let computed = (random_count..count)
+        .map(|index| Ok((index, Zeroizing::new(sss::interpolate_at(&base, index)?))))
+        .collect::<CoreResult<Vec<_>>>()?;
+    shares.extend(computed);
+    Ok(shares)
+}
+
+/// Undo [`split_level`] from `threshold` shares, checking the digest.
+fn recover_level(threshold: u8, points: &[(u8, &[u8])]) -> CoreResult<Zeroizing<Vec<u8>>> {
+    if threshold == 1 {
+        return Ok(Zeroizing::new(points[0].1.to_vec()));
+    }
+    let secret = Zeroizing::new(sss::interpolate_at(points, SECRET_INDEX)?);
+    let digest_share = Zeroizing::new(sss::interpolate_at(points, DIGEST_INDEX)?);
+    if digest(&digest_share[DIGEST_LEN..], &secret) != digest_share[..DIGEST_LEN] {
+        return Err(invalid(
+            "digest check failed; the shares do not belong together".to_string(),
+        ));
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // From the official SLIP-0039 test vectors; every vector uses the
+    // passphrase "TREZOR".
+    const VECTORS: &[(&[&str], &str)] = &[
+        (
+            &[
+                "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard",
+            ],
+            "bb54aac4b89dc868ba37d9cc21b2cece",
+        ),
+        (
+            &[
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+                "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+            ],
+            "b43ceb7e57a0ea8766221624d01b0864",
+        ),
+        (
+            &[
+                "theory painting academic academic armed sweater year military elder discuss acne wildlife boring employer fused large satoshi bundle carbon diagnose anatomy hamster leaves tracks paces beyond phantom capital marvel lips brave detect luck",
+            ],
+            "989baf9dcaad5b10ca33dfd8cc75e42477025dce88ae83e75a230086a0e00e92",
+        ),
+        (
+            &[
+                "testify swimming academic academic column loyalty smear include exotic bedroom exotic wrist lobe cover grief golden smart junior estimate learn",
+            ],
+            "1679b4516e0ee5954351d288a838f45e",
+        ),
+    ];
+
+    // Invalid cases from the same vectors, with the reason each is rejected.
+    // The group-threshold vector keeps two of its three mnemonics, which
+    // already fail on their own.
+    const INVALID_VECTORS: &[(&[&str], &str)] = &[
+        (
+            &[
+                "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney",
+            ],
+            "checksum mismatch",
+        ),
+        (
+            &[
+                "duckling enlarge academic academic email result length solution fridge kidney coal piece deal husband erode duke ajar music cargo fitness",
+            ],
+            "padding bits are not zero",
+        ),
+        (
+            &[
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            ],
+            "group 1 has 1 of 2 shares",
+        ),
+        (
+            &[
+                "adequate smoking academic acid debut wine petition glen cluster slow rhyme slow simple epidemic rumor junk tracks treat olympic tolerate",
+                "adequate stay academic agency agency formal party ting frequent learn upstairs remember smear leaf damage anatomy ladle market hush corner",
+            ],
+            "different sets",
+        ),
+        (
+            &[
+                "peasant leaves academic acid desert exact olympic math alive axle trial tackle drug deny decent smear dominant desert bucket remind",
+                "peasant leader academic agency cultural blessing percent network envelope medal junk primary human pumps jacket fragment payroll ticket evoke voice",
+            ],
+            "different sets",
+        ),
+        (
+            &[
+                "liberty category beard echo animal fawn temple briefing math username various wolf aviation fancy visual holy thunder yelp helpful payment",
+                "liberty category beard email beyond should fancy romp founder easel pink holy hairy romp loyalty material victim owner toxic custody",
+                "liberty category academic easy being hazard crush diminish oral lizard reaction cluster force dilemma deploy force club veteran expect photo",
+            ],
+            "different sets",
+        ),
+        (
+            &[
+                "average senior academic leaf broken teacher expect surface hour capture obesity desire negative dynamic dominant pistol mineral mailman iris aide",
+                "average senior academic agency curious pants blimp spew clothes slice script dress wrap firm shaft regular slavery negative theater roster",
+            ],
+            "different sets",
+        ),
+        (
+            &[
+                "music husband acrobat agency advance hunting bike corner density careful material civil evil tactics remind hawk discuss hobo voice rainbow",
+                "music husband beard academic black tricycle clock mayor estimate level photo episode exclude ecology papa source amazing salt verify divorce",
+            ],
+            "group threshold is above the group count",
+        ),
+        (
+            &[
+                "device stay academic always dive coal antenna adult black exceed stadium herald advance soldier busy dryer daughter evaluate minister laser",
+                "device stay academic always dwarf afraid robin gravity crunch adjust soul branch walnut coastal dream costume scholar mortgage mountain pumps",
+            ],
+            "member index 3",
+        ),
+        (
+            &[
+                "hour painting academic academic device formal evoke guitar random modern justice filter withdraw trouble identify mailman insect general cover oven",
+                "hour painting academic agency artist again daisy capital beaver fiber much enjoy suitable symbolic identify photo editor romp float echo",
+            ],
+            "disagree on the member threshold",
+        ),
+        (
+            &[
+                "guilt walnut academic acid deliver remove equip listen vampire tactics nylon rhythm failure husband fatigue alive blind enemy teaspoon rebound",
+                "guilt walnut academic agency brave hamster hobo declare herd taste alpha slim criminal mild arcade formal romp branch pink ambition",
+            ],
+            "digest check failed",
+        ),
+        (
+            &[
+                "junk necklace academic academic acne isolate join hesitate lunar roster dough calcium chemical ladybug amount mobile glasses verify cylinder",
+            ],
+            "at least 20 words",
+        ),
+        (
+            &[
+                "fraction necklace academic academic award teammate mouse regular testify coding building member verdict purchase blind camera duration email prepare spirit quarter",
+            ],
+            "invalid mnemonic length",
+        ),
+    ];
+
+    #[test]
+    fn official_vectors_recover_their_master_secrets() {
+        for (mnemonics, expected) in VECTORS {
+            let secret = combine(mnemonics, b"TREZOR").unwrap();
+            assert_eq!(hex::encode(secret.as_slice()), *expected);
+            for mnemonic in *mnemonics {
+                assert_eq!(Share::parse(mnemonic).unwrap().to_mnemonic(), *mnemonic);
+            }
+        }
+    }
+
+    #[test]
+    fn official_invalid_vectors_are_rejected() {
+        for (mnemonics, reason) in INVALID_VECTORS {
+            match combine(mnemonics, b"TREZOR") {
+                Err(CoreError::Slip39(message)) => {
+                    assert!(message.contains(reason), "{message} for {mnemonics:?}")
+                }
+                other => panic!("{mnemonics:?} gave {:?}", other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn wordlist_is_sorted_with_unique_prefixes() {
+        let list = wordlist();
+        assert_eq!(list.len(), 1024);
+        assert!(list.windows(2).all(|pair| pair[0] < pair[1]));
+        let prefixes: std::collections::HashSet<&str> =
+            list.iter().map(|word| &word[..4]).collect();
+        assert_eq!(prefixes.len(), 1024);
+    }
+
+    #[test]
+    fn checksum_and_word_errors_are_reported() {
+        let (mnemonics, _) = VECTORS[0];
+        let corrupted = mnemonics[0].replace("keyboard", "kidney");
+        assert!(matches!(
+            Share::parse(&corrupted),
+            Err(CoreError::Slip39(reason)) if reason.contains("checksum")
+        ));
+        let unknown = mnemonics[0].replace("fridge", "fridgee");
+        assert!(matches!(
+            Share::parse(&unknown),
+            Err(CoreError::Slip39(reason)) if reason.contains("position 9")
+        ));
+        assert!(Share::parse("duckling enlarge academic").is_err());
+    }
+
+    #[test]
+    fn two_level_split_recovers_from_any_qualifying_groups() {
+        let secret: Vec<u8> = (0..32).collect();
+        let secret = secret.as_slice();
+        let options = Slip39Options {
+            iteration_exponent: 0,
+            ..Slip39Options::default()
+        };
+        let groups = [
+            GroupSpec {
+                threshold: 1,
+                count: 1,
+            },
+            GroupSpec {
+                threshold: 2,
+                count: 3,
+            },
+            GroupSpec {
+                threshold: 3,
+                count: 5,
+            },
+        ];
+        let sets = split(secret, b"pw", 2, &groups, &options).unwrap();
+        assert_eq!(sets.iter().map(Vec::len).collect::<Vec<_>>(), [1, 3, 5]);
+
+        let picked = [
+            sets[0][0].clone(),
+            sets[2][4].clone(),
+            sets[2][0].clone(),
+            sets[2][2].clone(),
+        ];
+        assert_eq!(combine(&picked, b"pw").unwrap().as_slice(), secret);
+        let picked = [sets[1][2].clone(), sets[1][0].clone(), sets[0][0].clone()];
+        assert_eq!(combine(&picked, b"pw").unwrap().as_slice(), secret);
+        assert_ne!(combine(&picked, b"other").unwrap().as_slice(), secret);
+
+        let short = [sets[0][0].clone(), sets[2][1].clone(), sets[2][3].clone()];
+        assert!(matches!(
+            combine(&short, b"pw"),
+            Err(CoreError::Slip39(reason)) if reason.contains("group 3 has 2 of 3")
+        ));
+    }
+
+    #[test]
+    fn split_rejects_what_slip39_forbids() {
+        let options = Slip39Options::default();
+        let one_of_two = [GroupSpec {
+            threshold: 1,
+            count: 2,
+        }];
+        let two_of_three = [GroupSpec {
+            threshold: 2,
+            count: 3,
+        }];
+        assert!(split(&[1; 16], b"", 1, &one_of_two, &options).is_err());
+        assert!(split(&[1; 15], b"", 1, &two_of_three, &options).is_err());
+        assert!(split(&[1; 17], b"", 1, &two_of_three, &options).is_err());
+        assert!(split(&[1; 16], b"", 2, &two_of_three, &options).is_err());
+        assert!(split(&[1; 16], "pässword".as_bytes(), 1, &two_of_three, &options).is_err());
+    }
+}
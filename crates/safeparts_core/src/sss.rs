@@ -2,16 +2,35 @@ use crate::error::{CoreError, CoreResult};
 use crate::gf256::Gf256;
 use rand::RngCore;
 use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use zeroize::Zeroizing;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SetId(pub [u8; 16]);
 
 impl SetId {
+    /// Bytes a compact packet stores for a short id.
+    pub const SHORT_LEN: usize = 4;
+
     pub fn random() -> Self {
         let mut bytes = [0u8; 16];
         OsRng.fill_bytes(&mut bytes);
         Self(bytes)
     }
+
+    /// Random id whose last 12 bytes are zero, which compact packets store in
+    /// [`SetId::SHORT_LEN`] bytes. It still tells mixed-up sets apart; it is
+    /// not meant to be globally unique.
+    pub fn random_short() -> Self {
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes[..Self::SHORT_LEN]);
+        Self(bytes)
+    }
+
+    /// True when every byte past [`SetId::SHORT_LEN`] is zero.
+    pub fn is_short(&self) -> bool {
+        self.0[Self::SHORT_LEN..].iter().all(|&byte| byte == 0)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -27,11 +46,32 @@ pub fn split(secret: &[u8], k: u8, n: u8, set_id: SetId) -> CoreResult<Vec<RawSh
     if k == 0 || n == 0 || k > n {
         return Err(CoreError::InvalidKAndN { k, n });
     }
+    let xs: Vec<u8> = (1..=n).collect();
+    split_at(secret, k, &xs, set_id)
+}
+
+/// Like [`split`], with `xs.len()` shares at the coordinates `xs` instead of
+/// `1..=n`.
+pub fn split_at(secret: &[u8], k: u8, xs: &[u8], set_id: SetId) -> CoreResult<Vec<RawShare>> {
+    let n = u8::try_from(xs.len()).map_err(|_| CoreError::InvalidKAndN { k, n: u8::MAX })?;
+    if k == 0 || n == 0 || k > n {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+    let mut seen = [false; 256];
+    for &x in xs {
+        if x == 0 {
+            return Err(CoreError::InvalidX);
+        }
+        if seen[x as usize] {
+            return Err(CoreError::DuplicateX { x });
+        }
+        seen[x as usize] = true;
+    }
     let secret_len = secret.len();
 
-    // Prepare output shares with x=1..=n
-    let mut shares: Vec<RawShare> = (1..=n)
-        .map(|x| RawShare {
+    let mut shares: Vec<RawShare> = xs
+        .iter()
+        .map(|&x| RawShare {
             set_id,
             k,
             n,
@@ -66,13 +106,38 @@ pub fn split(secret: &[u8], k: u8, n: u8, set_id: SetId) -> CoreResult<Vec<RawSh
 }
 
 pub fn combine(shares: &[RawShare]) -> CoreResult<Vec<u8>> {
+    check_shares(shares)?;
+    let y_len = shares[0].y.len();
+
+    let x_values: Vec<Gf256> = shares.iter().map(|share| Gf256(share.x)).collect();
+    let weights = interpolation_weights_at(&x_values, Gf256(0))?;
+    let mut secret = vec![0u8; y_len];
+
+    for (byte_index, secret_byte) in secret.iter_mut().enumerate() {
+        let value = shares
+            .iter()
+            .zip(&weights)
+            .fold(Gf256(0), |value, (share, weight)| {
+                value + Gf256(share.y[byte_index]) * *weight
+            });
+        *secret_byte = value.0;
+    }
+
+    Ok(secret)
+}
+
+/// Check that `shares` can be combined: at least `k` shares from one set with
+/// distinct non-zero coordinates and equal payload lengths.
+///
+/// `n` is advisory and may differ between shares, because
+/// [`derive`] can add shares to a set after the split.
+pub(crate) fn check_shares(shares: &[RawShare]) -> CoreResult<()> {
     if shares.is_empty() {
         return Err(CoreError::NotEnoughShares { k: 1, m: 0 });
     }
 
     let k = shares[0].k;
     let set_id = shares[0].set_id;
-    let n = shares[0].n;
     let y_len = shares[0].y.len();
 
     if shares.len() < k as usize {
@@ -81,7 +146,7 @@ pub fn combine(shares: &[RawShare]) -> CoreResult<Vec<u8>> {
 
     let mut seen = [false; 256];
     for s in shares {
-        if s.set_id != set_id || s.k != k || s.n != n || s.y.len() != y_len {
+        if s.set_id != set_id || s.k != k || s.y.len() != y_len {
             return Err(CoreError::InconsistentMetadata);
         }
         if s.x == 0 {
@@ -93,24 +158,266 @@ pub fn combine(shares: &[RawShare]) -> CoreResult<Vec<u8>> {
         seen[s.x as usize] = true;
     }
 
+    Ok(())
+}
+
+/// Evaluate the share polynomial at `new_x` to issue another share of the set.
+///
+/// Uses the first `k` of `shares`; the secret itself is never reconstructed.
+/// The new share keeps the set id and threshold, and its advisory `n` is
+/// raised to `new_x` when that is larger.
+pub fn derive(shares: &[RawShare], new_x: u8) -> CoreResult<RawShare> {
+    check_shares(shares)?;
+    check_new_x(shares, new_x)?;
+    let first = &shares[0];
+    let base = &shares[..first.k as usize];
+
+    let x_values: Vec<Gf256> = base.iter().map(|share| Gf256(share.x)).collect();
+    let weights = interpolation_weights_at(&x_values, Gf256(new_x))?;
+    let mut column = vec![Gf256(0); base.len()];
+    let y = (0..first.y.len())
+        .map(|byte_index| {
+            for (value, share) in column.iter_mut().zip(base) {
+                *value = Gf256(share.y[byte_index]);
+            }
+            dot(&weights, &column).0
+        })
+        .collect();
+
+    Ok(RawShare {
+        set_id: first.set_id,
+        k: first.k,
+        n: first.n.max(new_x),
+        x: new_x,
+        y,
+    })
+}
+
+/// `n` distinct coordinates drawn at random from `1..=255`, in ascending
+/// order, so a share's index says nothing about how many others exist.
+pub fn random_xs(n: u8) -> Vec<u8> {
+    let mut all: Vec<u8> = (1..=u8::MAX).collect();
+    let (picked, _) = all.partial_shuffle(&mut OsRng, n as usize);
+    let mut xs = picked.to_vec();
+    xs.sort_unstable();
+    xs
+}
+
+/// Reject coordinates that cannot name a new share of the set.
+pub(crate) fn check_new_x(shares: &[RawShare], new_x: u8) -> CoreResult<()> {
+    if new_x == 0 {
+        return Err(CoreError::InvalidX);
+    }
+    if shares.iter().any(|share| share.x == new_x) {
+        return Err(CoreError::DuplicateX { x: new_x });
+    }
+    Ok(())
+}
+
+/// Combine shares while locating and ignoring corrupted ones.
+///
+/// Shamir shares of one byte column form a Reed-Solomon codeword, so with `m`
+/// shares and threshold `k` up to `(m - k) / 2` corrupted shares per column can
+/// be corrected with Berlekamp-Welch decoding. Returns the combined bytes and
+/// the sorted x coordinates of shares that disagreed with the decoded
+/// polynomial in any column.
+pub fn combine_robust(shares: &[RawShare]) -> CoreResult<(Vec<u8>, Vec<u8>)> {
+    check_shares(shares)?;
+    let k = shares[0].k as usize;
+    let y_len = shares[0].y.len();
+
     let x_values: Vec<Gf256> = shares.iter().map(|share| Gf256(share.x)).collect();
-    let weights = interpolation_weights_at_zero(&x_values)?;
+    let (base_x, extra_x) = x_values.split_at(k);
+    let base_weights = interpolation_weights_at(base_x, Gf256(0))?;
+    let extra_weights = extra_x
+        .iter()
+        .map(|&x| interpolation_weights_at(base_x, x))
+        .collect::<CoreResult<Vec<_>>>()?;
+
     let mut secret = vec![0u8; y_len];
+    let mut suspect = [false; 256];
+    let mut column = vec![Gf256(0); shares.len()];
 
     for (byte_index, secret_byte) in secret.iter_mut().enumerate() {
-        let value = shares
+        for (value, share) in column.iter_mut().zip(shares) {
+            *value = Gf256(share.y[byte_index]);
+        }
+
+        let (base_y, extra_y) = column.split_at(k);
+        let consistent = extra_weights
             .iter()
-            .zip(&weights)
-            .fold(Gf256(0), |value, (share, weight)| {
-                value + Gf256(share.y[byte_index]) * *weight
-            });
-        *secret_byte = value.0;
+            .zip(extra_y)
+            .all(|(weights, &y)| dot(weights, base_y) == y);
+
+        if consistent {
+            *secret_byte = dot(&base_weights, base_y).0;
+            continue;
+        }
+
+        let poly = berlekamp_welch(&x_values, &column, k)?;
+        for ((&x, &y), share) in x_values.iter().zip(&column).zip(shares) {
+            if evaluate(&poly, x) != y {
+                suspect[share.x as usize] = true;
+            }
+        }
+        *secret_byte = poly[0].0;
     }
 
-    Ok(secret)
+    let suspects = (1..=255u8).filter(|&x| suspect[x as usize]).collect();
+    Ok((secret, suspects))
+}
+
+fn dot(weights: &[Gf256], values: &[Gf256]) -> Gf256 {
+    weights
+        .iter()
+        .zip(values)
+        .fold(Gf256(0), |acc, (&w, &v)| acc + w * v)
+}
+
+fn evaluate(coeffs: &[Gf256], x: Gf256) -> Gf256 {
+    coeffs
+        .iter()
+        .rev()
+        .fold(Gf256(0), |acc, &coef| acc * x + coef)
+}
+
+/// Decode one column, returning the `k` coefficients of the share polynomial.
+fn berlekamp_welch(x_values: &[Gf256], y_values: &[Gf256], k: usize) -> CoreResult<Vec<Gf256>> {
+    let m = x_values.len();
+    let e = (m - k) / 2;
+    if e == 0 {
+        return Err(CoreError::UncorrectableShares);
+    }
+
+    // Unknowns: Q(x) with k + e coefficients, then the low e coefficients of
+    // the monic error locator E(x). Each share gives Q(x_i) = y_i * E(x_i).
+    let q_len = k + e;
+    let unknowns = q_len + e;
+    let mut rows: Vec<Vec<Gf256>> = x_values
+        .iter()
+        .zip(y_values)
+        .map(|(&x, &y)| {
+            let mut row = Vec::with_capacity(unknowns + 1);
+            let mut x_pow = Gf256(1);
+            let mut powers = Vec::with_capacity(q_len + 1);
+            for _ in 0..=q_len {
+                powers.push(x_pow);
+                x_pow = x_pow * x;
+            }
+            row.extend_from_slice(&powers[..q_len]);
+            row.extend(powers[..e].iter().map(|&p| y * p));
+            row.push(y * powers[e]);
+            row
+        })
+        .collect();
+
+    let solution = solve(&mut rows, unknowns).ok_or(CoreError::UncorrectableShares)?;
+    let q = &solution[..q_len];
+    let mut locator = solution[q_len..].to_vec();
+    locator.push(Gf256(1));
+
+    let (quotient, remainder_is_zero) = divide(q, &locator)?;
+    if !remainder_is_zero {
+        return Err(CoreError::UncorrectableShares);
+    }
+
+    let mut poly = quotient;
+    poly.resize(k, Gf256(0));
+    let agreeing = x_values
+        .iter()
+        .zip(y_values)
+        .filter(|&(&x, &y)| evaluate(&poly, x) == y)
+        .count();
+    if agreeing < m - e {
+        return Err(CoreError::UncorrectableShares);
+    }
+
+    Ok(poly)
+}
+
+/// Gaussian elimination over GF(256). Free variables are set to zero.
+fn solve(rows: &mut [Vec<Gf256>], unknowns: usize) -> Option<Vec<Gf256>> {
+    let mut pivot_cols = Vec::with_capacity(unknowns);
+    let mut rank = 0;
+
+    for col in 0..unknowns {
+        let Some(pivot) = (rank..rows.len()).find(|&r| rows[r][col] != Gf256(0)) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+
+        let inv = rows[rank][col].inv().ok()?;
+        for value in rows[rank].iter_mut() {
+            *value = *value * inv;
+        }
+
+        let pivot_row = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if r != rank && factor != Gf256(0) {
+                for (value, &pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *value = *value - factor * pivot_value;
+                }
+            }
+        }
+
+        pivot_cols.push(col);
+        rank += 1;
+    }
+
+    if rows[rank..].iter().any(|row| row[unknowns] != Gf256(0)) {
+        return None;
+    }
+
+    let mut solution = vec![Gf256(0); unknowns];
+    for (row, &col) in pivot_cols.iter().enumerate() {
+        solution[col] = rows[row][unknowns];
+    }
+    Some(solution)
 }
 
-fn interpolation_weights_at_zero(x_values: &[Gf256]) -> CoreResult<Vec<Gf256>> {
+/// Divide `numerator` by monic `divisor` (coefficients low to high).
+fn divide(numerator: &[Gf256], divisor: &[Gf256]) -> CoreResult<(Vec<Gf256>, bool)> {
+    let divisor_degree = divisor.len() - 1;
+    let mut remainder = numerator.to_vec();
+    if remainder.len() <= divisor_degree {
+        let is_zero = remainder.iter().all(|&c| c == Gf256(0));
+        return Ok((vec![Gf256(0)], is_zero));
+    }
+
+    let mut quotient = vec![Gf256(0); remainder.len() - divisor_degree];
+    let lead_inv = divisor[divisor_degree].inv()?;
+    for i in (0..quotient.len()).rev() {
+        let coef = remainder[i + divisor_degree] * lead_inv;
+        quotient[i] = coef;
+        for (j, &d) in divisor.iter().enumerate() {
+            remainder[i + j] = remainder[i + j] - coef * d;
+        }
+    }
+
+    let is_zero = remainder.iter().all(|&c| c == Gf256(0));
+    Ok((quotient, is_zero))
+}
+
+/// Evaluate the polynomial through `points` at `at`, one byte column at a
+/// time. Unlike [`combine`], a point may sit at coordinate zero.
+pub(crate) fn interpolate_at(points: &[(u8, &[u8])], at: u8) -> CoreResult<Vec<u8>> {
+    let x_values: Vec<Gf256> = points.iter().map(|&(x, _)| Gf256(x)).collect();
+    let weights = interpolation_weights_at(&x_values, Gf256(at))?;
+    let len = points.first().map_or(0, |(_, y)| y.len());
+    let mut column = vec![Gf256(0); points.len()];
+    Ok((0..len)
+        .map(|byte_index| {
+            for (value, (_, y)) in column.iter_mut().zip(points) {
+                *value = Gf256(y[byte_index]);
+            }
+            dot(&weights, &column).0
+        })
+        .collect())
+}
+
+/// Lagrange basis weights for evaluating the share polynomial at `at`.
+fn interpolation_weights_at(x_values: &[Gf256], at: Gf256) -> CoreResult<Vec<Gf256>> {
     x_values
         .iter()
         .enumerate()
@@ -122,7 +429,7 @@ fn interpolation_weights_at_zero(x_values: &[Gf256]) -> CoreResult<Vec<Gf256>> {
                 .fold(
                     (Gf256(1), Gf256(1)),
                     |(numerator, denominator), (_, &other_x)| {
-                        (numerator * other_x, denominator * (x - other_x))
+                        (numerator * (at - other_x), denominator * (x - other_x))
                     },
                 );
 
@@ -131,10 +438,203 @@ fn interpolation_weights_at_zero(x_values: &[Gf256]) -> CoreResult<Vec<Gf256>> {
         .collect()
 }
 
+/// A [`BinaryField`] element: a polynomial over GF(2) as little-endian
+/// 64-bit limbs.
+pub type BinaryElement = Zeroizing<Vec<u64>>;
+
+/// GF(2^bits) for `bits` up to [`BinaryField::MAX_BITS`], reduced by a
+/// caller-chosen modulus.
+///
+/// [`Gf256`] fixes both the field size and its modulus. Formats that pick
+/// their own, like [`crate::ssss`] and [`crate::secrets_js`], share their
+/// secrets over this field instead. Multiplication is bit-serial, so it
+/// needs no tables at any size.
+#[derive(Clone, Debug)]
+pub struct BinaryField {
+    bits: usize,
+    limbs: usize,
+    low: Vec<u64>,
+}
+
+impl BinaryField {
+    /// Largest supported field size, enough for a 128-byte `ssss` secret.
+    pub const MAX_BITS: usize = 1024;
+
+    /// The field reduced by `x^bits` plus `x^e` for every `e` in
+    /// `low_terms`. `None` when `bits` is outside `1..=MAX_BITS` or a term is
+    /// not below `bits`. The modulus must be irreducible; that is the
+    /// caller's to pick.
+    pub fn new(bits: usize, low_terms: &[usize]) -> Option<Self> {
+        if !(1..=Self::MAX_BITS).contains(&bits) || low_terms.iter().any(|&e| e >= bits) {
+            return None;
+        }
+        let limbs = bits.div_ceil(64);
+        let mut low = vec![0; limbs];
+        for &e in low_terms {
+            low[e / 64] ^= 1 << (e % 64);
+        }
+        Some(Self { bits, limbs, low })
+    }
+
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    pub fn zero(&self) -> BinaryElement {
+        Zeroizing::new(vec![0; self.limbs])
+    }
+
+    /// `value` as an element, keeping its low `bits` bits.
+    pub fn element(&self, value: u64) -> BinaryElement {
+        let mut element = self.zero();
+        element[0] = value;
+        self.truncate(&mut element);
+        element
+    }
+
+    /// A big-endian byte string as an element, keeping its low `bits` bits.
+    pub fn from_bytes(&self, bytes: &[u8]) -> BinaryElement {
+        let mut element = self.zero();
+        for (i, &byte) in bytes.iter().rev().take(8 * self.limbs).enumerate() {
+            element[i / 8] |= u64::from(byte) << (8 * (i % 8));
+        }
+        self.truncate(&mut element);
+        element
+    }
+
+    /// `element` as `bits / 8` big-endian bytes, rounded up.
+    pub fn to_bytes(&self, element: &BinaryElement) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(
+            (0..self.bits.div_ceil(8))
+                .rev()
+                .map(|i| (element[i / 8] >> (8 * (i % 8))) as u8)
+                .collect(),
+        )
+    }
+
+    /// The low 64 bits of `element`.
+    pub fn to_u64(&self, element: &BinaryElement) -> u64 {
+        element[0]
+    }
+
+    pub fn add(&self, a: &BinaryElement, b: &BinaryElement) -> BinaryElement {
+        Zeroizing::new(a.iter().zip(b.iter()).map(|(a, b)| a ^ b).collect())
+    }
+
+    pub fn mul(&self, a: &BinaryElement, b: &BinaryElement) -> BinaryElement {
+        let mut product = self.zero();
+        for index in (0..self.bits).rev() {
+            // product *= x, reducing the x^bits term.
+            let overflow = bit(&product, self.bits - 1);
+            let mut carry = 0;
+            for limb in product.iter_mut() {
+                let next = *limb >> 63;
+                *limb = (*limb << 1) | carry;
+                carry = next;
+            }
+            self.truncate(&mut product);
+            if overflow {
+                for (limb, low) in product.iter_mut().zip(&self.low) {
+                    *limb ^= low;
+                }
+            }
+            if bit(b, index) {
+                for (limb, a) in product.iter_mut().zip(a.iter()) {
+                    *limb ^= a;
+                }
+            }
+        }
+        product
+    }
+
+    pub fn pow(&self, a: &BinaryElement, exponent: usize) -> BinaryElement {
+        let mut result = self.element(1);
+        for _ in 0..exponent {
+            result = self.mul(&result, a);
+        }
+        result
+    }
+
+    /// `a^(2^bits - 2)`, the inverse of a non-zero `a`.
+    pub fn inv(&self, a: &BinaryElement) -> BinaryElement {
+        let mut square = a.clone();
+        let mut result = self.element(1);
+        for _ in 1..self.bits {
+            square = self.mul(&square, &square);
+            result = self.mul(&result, &square);
+        }
+        result
+    }
+
+    /// Evaluate the polynomial with `coeffs`, constant term first, at `x`.
+    pub fn evaluate(&self, coeffs: &[BinaryElement], x: &BinaryElement) -> BinaryElement {
+        coeffs.iter().rev().fold(self.zero(), |acc, coeff| {
+            self.add(&self.mul(&acc, x), coeff)
+        })
+    }
+
+    /// Evaluate the polynomial through `points` at `at`. Every `x` must be
+    /// distinct.
+    pub fn interpolate_at(
+        &self,
+        points: &[(BinaryElement, BinaryElement)],
+        at: &BinaryElement,
+    ) -> CoreResult<BinaryElement> {
+        let mut sum = self.zero();
+        for (i, (x_i, y_i)) in points.iter().enumerate() {
+            let mut numerator = y_i.clone();
+            let mut denominator = self.element(1);
+            for (j, (x_j, _)) in points.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                if x_i == x_j {
+                    return Err(CoreError::DivisionByZero);
+                }
+                numerator = self.mul(&numerator, &self.add(at, x_j));
+                denominator = self.mul(&denominator, &self.add(x_i, x_j));
+            }
+            sum = self.add(&sum, &self.mul(&numerator, &self.inv(&denominator)));
+        }
+        Ok(sum)
+    }
+
+    fn truncate(&self, element: &mut BinaryElement) {
+        if !self.bits.is_multiple_of(64) {
+            element[self.limbs - 1] &= (1 << (self.bits % 64)) - 1;
+        }
+    }
+}
+
+fn bit(element: &BinaryElement, index: usize) -> bool {
+    (element[index / 64] >> (index % 64)) & 1 == 1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn derived_share_combines_with_existing_shares() {
+        let secret = b"derived share";
+        let shares = split(secret, 3, 5, SetId::random()).unwrap();
+
+        let derived = derive(&shares[1..4], 9).unwrap();
+        assert_eq!((derived.x, derived.n), (9, 9));
+        assert_eq!(
+            combine(&[shares[0].clone(), shares[4].clone(), derived]).unwrap(),
+            secret
+        );
+
+        let replacement = derive(&shares[2..], 1).unwrap();
+        assert_eq!(replacement, shares[0]);
+        assert!(matches!(
+            derive(&shares[..3], 2),
+            Err(CoreError::DuplicateX { x: 2 })
+        ));
+        assert!(matches!(derive(&shares[..3], 0), Err(CoreError::InvalidX)));
+    }
+
     #[test]
     fn round_trip_3_of_5() {
         let secret = b"hello world";
@@ -149,6 +649,68 @@ mod tests {
         assert_eq!(recovered2, secret);
     }
 
+    #[test]
+    fn split_at_random_coordinates_round_trips() {
+        let xs = random_xs(4);
+        assert_eq!(xs.len(), 4);
+        assert!(xs.windows(2).all(|pair| pair[0] < pair[1]) && xs[0] != 0);
+
+        let shares = split_at(b"hello", 2, &xs, SetId::random()).unwrap();
+        assert_eq!(shares.iter().map(|s| s.x).collect::<Vec<_>>(), xs);
+        assert_eq!(combine(&shares[2..]).unwrap(), b"hello");
+
+        assert!(matches!(
+            split_at(b"hello", 2, &[7, 7], SetId::random()),
+            Err(CoreError::DuplicateX { x: 7 })
+        ));
+        assert!(split_at(b"hello", 2, &[0, 7], SetId::random()).is_err());
+        assert!(split_at(b"hello", 3, &[1, 7], SetId::random()).is_err());
+    }
+
+    #[test]
+    fn binary_field_interpolates_through_its_points() {
+        // With the AES modulus the field is GF(256) itself.
+        let field = BinaryField::new(8, &[4, 3, 1, 0]).unwrap();
+        for (a, b) in [(0x53, 0xca), (0x02, 0x87), (0xff, 0xff)] {
+            let product = field.mul(&field.element(a), &field.element(b));
+            assert_eq!(
+                field.to_u64(&product),
+                u64::from((Gf256(a as u8) * Gf256(b as u8)).0)
+            );
+        }
+        assert!(BinaryField::new(8, &[8]).is_none());
+        assert!(BinaryField::new(BinaryField::MAX_BITS + 1, &[0]).is_none());
+
+        let coeffs = [0x2a, 0x91, 0x07].map(|c| field.element(c));
+        let points: Vec<_> = (1..=4)
+            .map(|x| (field.element(x), field.evaluate(&coeffs, &field.element(x))))
+            .collect();
+        let zero = field.zero();
+        assert_eq!(
+            field.interpolate_at(&points[..3], &zero).unwrap(),
+            coeffs[0]
+        );
+        assert_eq!(
+            field.interpolate_at(&points[1..], &zero).unwrap(),
+            coeffs[0]
+        );
+        assert_eq!(
+            field.interpolate_at(&points[..3], &points[3].0).unwrap(),
+            points[3].1
+        );
+        let duplicate = [
+            (field.element(1), field.element(2)),
+            (field.element(1), field.element(3)),
+        ];
+        assert!(field.interpolate_at(&duplicate, &zero).is_err());
+
+        // Multi-limb fields invert too.
+        let wide = BinaryField::new(136, &[5, 3, 2, 0]).unwrap();
+        let a = wide.from_bytes(&[0x5a; 17]);
+        assert_eq!(wide.mul(&a, &wide.inv(&a)), wide.element(1));
+        assert_eq!(*wide.to_bytes(&a), [0x5a; 17]);
+    }
+
     #[test]
     fn insufficient_shares_fails() {
         let secret = b"abc";
@@ -157,4 +719,46 @@ mod tests {
         let err = combine(&shares[0..2]).unwrap_err();
         assert!(matches!(err, CoreError::NotEnoughShares { .. }));
     }
+
+    #[test]
+    fn robust_combine_locates_corrupted_share() {
+        let secret = b"robust decoding";
+        let shares = split(secret, 2, 5, SetId::random()).unwrap();
+
+        let mut corrupted = shares.clone();
+        corrupted[3].y[4] ^= 0x5a;
+
+        let (recovered, suspects) = combine_robust(&corrupted).unwrap();
+        assert_eq!(recovered, secret);
+        assert_eq!(suspects, vec![4]);
+    }
+
+    #[test]
+    fn robust_combine_corrects_two_shares_in_different_columns() {
+        let secret = b"two bad shares";
+        let shares = split(secret, 3, 7, SetId::random()).unwrap();
+
+        let mut corrupted = shares.clone();
+        corrupted[0].y[0] ^= 1;
+        corrupted[0].y[1] ^= 2;
+        corrupted[6].y[1] ^= 3;
+
+        let (recovered, suspects) = combine_robust(&corrupted).unwrap();
+        assert_eq!(recovered, secret);
+        assert_eq!(suspects, vec![1, 7]);
+    }
+
+    #[test]
+    fn robust_combine_without_surplus_cannot_correct() {
+        let shares = split(b"abc", 2, 3, SetId::random()).unwrap();
+
+        let (recovered, suspects) = combine_robust(&shares[..2]).unwrap();
+        assert_eq!(recovered, b"abc");
+        assert!(suspects.is_empty());
+
+        let mut corrupted = shares.clone();
+        corrupted[2].y[0] ^= 1;
+        let err = combine_robust(&corrupted).unwrap_err();
+        assert!(matches!(err, CoreError::UncorrectableShares));
+    }
 }
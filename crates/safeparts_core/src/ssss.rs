@@ -0,0 +1,408 @@
+//! Shares of B. Poettering's `ssss` tool (`ssss-split` and `ssss-combine`).
+//!
+//! `ssss` shares a secret of up to 128 bytes over GF(2^n), where `n` is the
+//! security level in bits: a multiple of 8 from 8 to 1024. Each field is a
+//! [`crate::sss::BinaryField`] over the irreducible pentanomial `ssss` picks
+//! for its degree, which is unrelated to the [`crate::gf256`] field. A share is a line `[token-]index-hex`, with the
+//! value as `n / 4` hex digits.
+//!
+//! The share polynomial is monic of degree `t`, so combining must know the
+//! threshold. [`combine`] finds it as the smallest degree that every given
+//! share fits, so any number of shares at or above the threshold works.
+//!
+//! By default `ssss` also runs the secret through a diffusion layer, XTEA with
+//! an all-zero key over overlapping slices, when the level is 64 bits or
+//! more. Shares carry no integrity tag: too few shares recover wrong bytes.
+
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+use crate::sss::{BinaryElement, BinaryField};
+
+/// Smallest security level in bits.
+pub const MIN_DEGREE: usize = 8;
+
+/// Largest security level in bits; secrets are at most 128 bytes.
+pub const MAX_DEGREE: usize = 1024;
+
+/// Smallest security level at which the diffusion layer runs.
+const DIFFUSION_MIN_DEGREE: usize = 64;
+const DIFFUSION_ROUNDS: usize = 40;
+const XTEA_DELTA: u32 = 0x9E37_79B9;
+
+/// The three middle exponents of `x^n + x^a + x^b + x^c + 1` for every
+/// degree `n = 8, 16, ..., 1024`, as in `ssss`. Each is the smallest
+/// irreducible pentanomial of its degree.
+const IRREDUCIBLE: [u8; 3 * MAX_DEGREE / 8] = [
+    4, 3, 1, 5, 3, 1, 4, 3, 1, 7, 3, 2, 5, 4, 3, 5, 3, 2, 7, 4, 2, 4, 3, 1, 10, 9, 3, 9, 4, 2, 7,
+    6, 2, 10, 9, 6, 4, 3, 1, 5, 4, 3, 4, 3, 1, 7, 2, 1, 5, 3, 2, 7, 4, 2, 6, 3, 2, 5, 3, 2, 15, 3,
+    2, 11, 3, 2, 9, 8, 7, 7, 2, 1, 5, 3, 2, 9, 3, 1, 7, 3, 1, 9, 8, 3, 9, 4, 2, 8, 5, 3, 15, 14,
+    10, 10, 5, 2, 9, 6, 2, 9, 3, 2, 9, 5, 2, 11, 10, 1, 7, 3, 2, 11, 2, 1, 9, 7, 4, 4, 3, 1, 8, 3,
+    1, 7, 4, 1, 7, 2, 1, 13, 11, 6, 5, 3, 2, 7, 3, 2, 8, 7, 5, 12, 3, 2, 13, 10, 6, 5, 3, 2, 5, 3,
+    2, 9, 5, 2, 9, 7, 2, 13, 4, 3, 4, 3, 1, 11, 6, 4, 18, 9, 6, 19, 18, 13, 11, 3, 2, 15, 9, 6, 4,
+    3, 1, 16, 5, 2, 15, 14, 6, 8, 5, 2, 15, 11, 2, 11, 6, 2, 7, 5, 3, 8, 3, 1, 19, 16, 9, 11, 9, 6,
+    15, 7, 6, 13, 4, 3, 14, 13, 3, 13, 6, 3, 9, 5, 2, 19, 13, 6, 19, 10, 3, 11, 6, 5, 9, 2, 1, 14,
+    3, 2, 13, 3, 1, 7, 5, 4, 11, 9, 8, 11, 6, 5, 23, 16, 9, 19, 14, 6, 23, 10, 2, 8, 3, 2, 5, 4, 3,
+    9, 6, 4, 4, 3, 2, 13, 8, 6, 13, 11, 1, 13, 10, 3, 11, 6, 5, 19, 17, 4, 15, 14, 7, 13, 9, 6, 9,
+    7, 3, 9, 7, 1, 14, 3, 2, 11, 8, 2, 11, 6, 4, 13, 5, 2, 11, 5, 1, 11, 4, 1, 19, 10, 3, 21, 10,
+    6, 13, 3, 1, 15, 7, 5, 19, 18, 10, 7, 5, 3, 12, 7, 2, 7, 5, 1, 14, 9, 6, 10, 3, 2, 15, 13, 12,
+    12, 11, 9, 16, 9, 7, 12, 9, 3, 9, 5, 2, 17, 10, 6, 24, 9, 3, 17, 15, 13, 5, 4, 3, 19, 17, 8,
+    15, 6, 3, 19, 6, 1,
+];
+
+/// One `ssss` share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    /// Optional name written before the index by `ssss-split -w`.
+    pub token: Option<String>,
+    pub x: u8,
+    /// The share value, big-endian, `degree / 8` bytes.
+    pub y: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    /// Parse one `[token-]index-hex` line.
+    pub fn parse(line: &str) -> CoreResult<Self> {
+        let malformed = |reason: &str| CoreError::Encoding(format!("ssss share: {reason}"));
+        let line = line.trim();
+        let (token, rest) = match line.split_once('-') {
+            Some((head, rest)) if rest.contains('-') => (Some(head.to_string()), rest),
+            _ => (None, line),
+        };
+        let (index, hex) = rest
+            .split_once('-')
+            .ok_or_else(|| malformed("expected [token-]index-hex"))?;
+        if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(malformed("index is not a number"));
+        }
+        let x = index
+            .parse::<u8>()
+            .map_err(|_| malformed("index above 255 is not supported"))?;
+        if x == 0 {
+            return Err(CoreError::InvalidX);
+        }
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(malformed("value is not hex"));
+        }
+        if !degree_is_valid(hex.len() * 4) {
+            return Err(malformed(
+                "value length is not a security level from 8 to 1024 bits",
+            ));
+        }
+        let y = hex
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                let digit = |b: u8| (b as char).to_digit(16).unwrap_or(0) as u8;
+                (digit(pair[0]) << 4) | digit(pair[1])
+            })
+            .collect();
+        Ok(Self {
+            token,
+            x,
+            y: Zeroizing::new(y),
+        })
+    }
+
+    /// Write the share as `ssss-split` does, with the index zero-padded to
+    /// `index_width` digits.
+    pub fn to_text(&self, index_width: usize) -> String {
+        let hex: String = self.y.iter().map(|b| format!("{b:02x}")).collect();
+        let token = self
+            .token
+            .as_ref()
+            .map_or(String::new(), |token| format!("{token}-"));
+        format!("{token}{:0index_width$}-{hex}", self.x)
+    }
+}
+
+/// True when every non-empty line of `input` is an `ssss` share.
+pub fn looks_like(input: &str) -> bool {
+    let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+    lines.clone().next().is_some() && lines.all(|line| Share::parse(line).is_ok())
+}
+
+/// Parse every share in `input`, one per line.
+pub fn parse_shares(input: &str) -> CoreResult<Vec<Share>> {
+    let shares = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(Share::parse)
+        .collect::<CoreResult<Vec<_>>>()?;
+    if shares.is_empty() {
+        return Err(CoreError::EmptyShareInput);
+    }
+    Ok(shares)
+}
+
+/// Write shares as `ssss-split` does, indexes padded to a common width.
+pub fn encode_shares(shares: &[Share]) -> Vec<String> {
+    let width = shares
+        .iter()
+        .map(|share| share.x.to_string().len())
+        .max()
+        .unwrap_or(1);
+    shares.iter().map(|share| share.to_text(width)).collect()
+}
+
+/// Split `secret` into `n` shares with a threshold of `k`, at a security
+/// level of `8 * secret.len()` bits, as `ssss-split` does by default.
+pub fn split(secret: &[u8], k: u8, n: u8, diffusion: bool) -> CoreResult<Vec<Share>> {
+    if k == 0 || k > n {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+    let degree = secret.len() * 8;
+    if !degree_is_valid(degree) {
+        return Err(CoreError::Encoding(format!(
+            "ssss secrets are 1 to {} bytes, got {}",
+            MAX_DEGREE / 8,
+            secret.len()
+        )));
+    }
+    let field = field(degree);
+
+    let mut secret = Zeroizing::new(secret.to_vec());
+    if diffusion && degree >= DIFFUSION_MIN_DEGREE {
+        diffuse(&mut secret, true);
+    }
+    let mut coefficients = vec![field.from_bytes(&secret)];
+    for _ in 1..k {
+        let mut random = Zeroizing::new(vec![0u8; secret.len()]);
+        rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, &mut random);
+        coefficients.push(field.from_bytes(&random));
+    }
+
+    Ok((1..=n)
+        .map(|x| {
+            let x_element = field.element(u64::from(x));
+            // Horner's rule for the monic x^k + c[k-1] x^(k-1) + ... + c[0].
+            let mut y = x_element.clone();
+            for coefficient in coefficients[1..].iter().rev() {
+                y = field.mul(&field.add(&y, coefficient), &x_element);
+            }
+            y = field.add(&y, &coefficients[0]);
+            Share {
+                token: None,
+                x,
+                y: field.to_bytes(&y),
+            }
+        })
+        .collect())
+}
+
+/// Recover the secret from shares of one set, as `degree / 8` big-endian
+/// bytes.
+///
+/// `ssss-combine` prints text secrets without their leading zero bytes.
+pub fn combine(shares: &[Share], diffusion: bool) -> CoreResult<Zeroizing<Vec<u8>>> {
+    let Some(first) = shares.first() else {
+        return Err(CoreError::EmptyShareInput);
+    };
+    let len = first.y.len();
+    let mut seen = [false; 256];
+    for share in shares {
+        if share.y.len() != len {
+            return Err(CoreError::Encoding(
+                "ssss shares have different security levels".to_string(),
+            ));
+        }
+        if seen[share.x as usize] {
+            return Err(CoreError::DuplicateX { x: share.x });
+        }
+        seen[share.x as usize] = true;
+    }
+
+    let field = field(len * 8);
+    let points: Vec<(BinaryElement, BinaryElement)> = shares
+        .iter()
+        .map(|share| {
+            (
+                field.element(u64::from(share.x)),
+                field.from_bytes(&share.y),
+            )
+        })
+        .collect();
+
+    // The smallest threshold whose polynomial passes through every share.
+    let threshold = (1..points.len())
+        .find(|&t| {
+            points[t..]
+                .iter()
+                .all(|(x, y)| eval_monic(&field, &points[..t], x).is_ok_and(|value| value == *y))
+        })
+        .unwrap_or(points.len());
+    let zero = field.zero();
+    let mut secret = field.to_bytes(&eval_monic(&field, &points[..threshold], &zero)?);
+    if diffusion && len * 8 >= DIFFUSION_MIN_DEGREE {
+        diffuse(&mut secret, false);
+    }
+    Ok(secret)
+}
+
+fn degree_is_valid(degree: usize) -> bool {
+    (MIN_DEGREE..=MAX_DEGREE).contains(&degree) && degree.is_multiple_of(8)
+}
+
+/// GF(2^degree) reduced by the `ssss` pentanomial of that degree.
+fn field(degree: usize) -> BinaryField {
+    let row = &IRREDUCIBLE[3 * (degree / 8 - 1)..3 * (degree / 8)];
+    let low_terms: Vec<usize> = row.iter().map(|&e| e as usize).chain([0]).collect();
+    BinaryField::new(degree, &low_terms).expect("ssss pentanomials fit their degree")
+}
+
+/// Evaluate at `at` the monic polynomial of degree `points.len()` through
+/// `points`.
+fn eval_monic(
+    field: &BinaryField,
+    points: &[(BinaryElement, BinaryElement)],
+    at: &BinaryElement,
+) -> CoreResult<BinaryElement> {
+    let t = points.len();
+    // Interpolating y - x^t gives the lower coefficients; add at^t back.
+    let lower: Vec<_> = points
+        .iter()
+        .map(|(x, y)| (x.clone(), field.add(y, &field.pow(x, t))))
+        .collect();
+    Ok(field.add(&field.pow(at, t), &field.interpolate_at(&lower, at)?))
+}
+
+/// Run the `ssss` diffusion layer over a big-endian secret, forward or back.
+///
+/// `ssss` works on the secret as 16-bit words, least significant word first,
+/// each word big-endian; an odd top byte goes last.
+fn diffuse(secret: &mut [u8], encode: bool) {
+    let len = secret.len();
+    let mut v: Vec<u8> = Vec::with_capacity(len);
+    for word in secret[len % 2..].rchunks(2) {
+        v.extend_from_slice(word);
+    }
+    if len % 2 == 1 {
+        v.push(secret[0]);
+    }
+
+    let slices = (0..DIFFUSION_ROUNDS * len).step_by(2);
+    if encode {
+        for index in slices {
+            process_slice(&mut v, index, xtea_encipher);
+        }
+    } else {
+        for index in slices.rev() {
+            process_slice(&mut v, index, xtea_decipher);
+        }
+    }
+
+    if len % 2 == 1 {
+        secret[0] = v[len - 1];
+    }
+    for (word, chunk) in secret[len % 2..].rchunks_mut(2).zip(v.chunks(2)) {
+        word.copy_from_slice(chunk);
+    }
+}
+
+fn process_slice(data: &mut [u8], index: usize, process: fn(&mut [u32; 2])) {
+    let len = data.len();
+    let at = |i: usize| (index + i) % len;
+    let mut block = [0u32; 2];
+    for (half, word) in block.iter_mut().enumerate() {
+        *word = u32::from_be_bytes(std::array::from_fn(|b| data[at(4 * half + b)]));
+    }
+    process(&mut block);
+    for (half, word) in block.iter().enumerate() {
+        for (b, byte) in word.to_be_bytes().into_iter().enumerate() {
+            data[at(4 * half + b)] = byte;
+        }
+    }
+}
+
+/// XTEA with an all-zero key.
+fn xtea_encipher(v: &mut [u32; 2]) {
+    let mut sum: u32 = 0;
+    for _ in 0..32 {
+        v[0] = v[0].wrapping_add((((v[1] << 4) ^ (v[1] >> 5)).wrapping_add(v[1])) ^ sum);
+        sum = sum.wrapping_add(XTEA_DELTA);
+        v[1] = v[1].wrapping_add((((v[0] << 4) ^ (v[0] >> 5)).wrapping_add(v[0])) ^ sum);
+    }
+}
+
+fn xtea_decipher(v: &mut [u32; 2]) {
+    let mut sum = XTEA_DELTA.wrapping_mul(32);
+    for _ in 0..32 {
+        v[1] = v[1].wrapping_sub((((v[0] << 4) ^ (v[0] >> 5)).wrapping_add(v[0])) ^ sum);
+        sum = sum.wrapping_sub(XTEA_DELTA);
+        v[0] = v[0].wrapping_sub((((v[1] << 4) ^ (v[1] >> 5)).wrapping_add(v[1])) ^ sum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `ssss-split -t 3 -n 5` of "my secret root password" at 184 bits, from
+    // the example on the ssss home page.
+    const HOMEPAGE: [&str; 4] = [
+        "1-1c41ef496eccfbeba439714085df8437236298da8dd824",
+        "2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665",
+        "3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309",
+        "4-468de7d6eb36674c9cf008c8e8fc8c566537ad6301eb9e",
+    ];
+
+    #[test]
+    fn homepage_example_combines_with_any_three_or_more_shares() {
+        for picked in [&HOMEPAGE[..3], &HOMEPAGE[1..], &HOMEPAGE[..]] {
+            let shares = parse_shares(&picked.join("\n")).unwrap();
+            assert_eq!(
+                combine(&shares, true).unwrap().as_slice(),
+                b"my secret root password"
+            );
+        }
+        let two = parse_shares(&HOMEPAGE[..2].join("\n")).unwrap();
+        assert_ne!(
+            combine(&two, true).unwrap().as_slice(),
+            b"my secret root password"
+        );
+    }
+
+    #[test]
+    fn split_round_trips_at_every_width_and_without_diffusion() {
+        for len in [1, 7, 8, 16, 23, 33, 128] {
+            let secret: Vec<u8> = (0..len as u8).map(|b| b.wrapping_mul(37) ^ 0x5a).collect();
+            for diffusion in [true, false] {
+                let shares = split(&secret, 3, 4, diffusion).unwrap();
+                assert!(shares.iter().all(|share| share.y.len() == len));
+                let text = encode_shares(&shares).join("\n");
+                let parsed = parse_shares(&text).unwrap();
+                assert_eq!(combine(&parsed[1..], diffusion).unwrap().as_slice(), secret);
+            }
+        }
+        assert!(split(&[0; 129], 2, 3, true).is_err());
+        assert!(split(&[], 2, 3, true).is_err());
+    }
+
+    #[test]
+    fn diffusion_is_a_permutation() {
+        for len in [8, 9, 23, 32] {
+            let original: Vec<u8> = (0..len as u8).collect();
+            let mut data = original.clone();
+            diffuse(&mut data, true);
+            assert_ne!(data, original);
+            diffuse(&mut data, false);
+            assert_eq!(data, original);
+        }
+    }
+
+    #[test]
+    fn parse_accepts_tokens_and_rejects_malformed_lines() {
+        let share = Share::parse("vault-07-0a1b").unwrap();
+        assert_eq!(
+            (share.token.as_deref(), share.x, share.y.as_slice()),
+            (Some("vault"), 7, &[0x0a, 0x1b][..])
+        );
+        assert_eq!(share.to_text(2), "vault-07-0a1b");
+        assert!(looks_like(&HOMEPAGE.join("\n")));
+        for bad in ["1-abc", "0-0a", "x-0a", "1-zz", "300-0a", "0a1b"] {
+            assert!(Share::parse(bad).is_err(), "{bad}");
+        }
+        assert!(!looks_like("U01OMQ-not-a-share"));
+    }
+}
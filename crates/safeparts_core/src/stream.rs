@@ -0,0 +1,607 @@
+//! Streaming split and combine in constant memory.
+//!
+//! [`ShareWriter`] splits whatever is written to it into `n` share streams,
+//! and [`ShareReader`] reads the secret back from `k` of them. Neither holds
+//! more than a few frames in memory, so secrets larger than RAM can be split.
+//!
+//! A share stream is a `u32` length, a header [`SharePacket`], and then one
+//! record per frame: a `u32` length and the share's bytes of that frame. The
+//! header payload is a share of a random MAC key. Each frame holds up to
+//! `frame_len` bytes of the secret, encrypted frame by frame when a passphrase
+//! is set, followed by a keyed BLAKE3 tag over the frame index, a last-frame
+//! marker, and the frame bytes. The tags replace the single trailing tag of
+//! [`crate::split_secret`]: a corrupted, reordered, or truncated frame fails
+//! as soon as it is read.
+//!
+//! Header packets are written as version 3, so the set header (set id, `k`,
+//! `n`, and crypto parameters) is bound into every frame tag and into the
+//! associated data of every encrypted frame.
+//!
+//! [`ShareReader`] returns each frame once its tag checks out, but a later
+//! frame can still fail. Treat output as incomplete until the reader reaches
+//! the end without an error.
+
+use std::io::{self, Read, Write};
+
+use zeroize::Zeroizing;
+
+use crate::crypto::{self, CipherSuite, CryptoParams, FrameCipher};
+use crate::error::{CoreError, CoreResult};
+use crate::packet::{self, SharePacket};
+use crate::sss::{self, RawShare, SetId};
+
+pub const DEFAULT_FRAME_LEN: u32 = 64 * 1024;
+const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+const MAX_HEADER_LEN: u32 = 1024 * 1024;
+const LEN_FIELD_LEN: usize = 4;
+const FRAME_TAG_LEN: usize = 32;
+const AEAD_TAG_LEN: usize = 16;
+
+/// Splits a secret written to it into `n` share streams.
+///
+/// Call [`ShareWriter::finish`] after the last write; until then the final
+/// frame is held back and the share streams do not combine.
+pub struct ShareWriter<W: Write> {
+    outputs: Vec<W>,
+    set_id: SetId,
+    k: u8,
+    n: u8,
+    mac_key: Zeroizing<[u8; crypto::KEY_LEN]>,
+    set_header: Vec<u8>,
+    cipher: Option<FrameCipher>,
+    frame_len: usize,
+    buffer: Zeroizing<Vec<u8>>,
+    index: u64,
+}
+
+impl<W: Write> ShareWriter<W> {
+    /// Start a `k`-of-`outputs.len()` split and write every share's header.
+    pub fn new(outputs: Vec<W>, k: u8, passphrase: Option<&[u8]>) -> CoreResult<Self> {
+        Self::with_options(
+            outputs,
+            k,
+            passphrase,
+            CipherSuite::default(),
+            DEFAULT_FRAME_LEN,
+        )
+    }
+
+    /// Like [`ShareWriter::new`] with a custom passphrase cipher suite and a
+    /// custom frame size in bytes.
+    pub fn with_options(
+        mut outputs: Vec<W>,
+        k: u8,
+        passphrase: Option<&[u8]>,
+        suite: CipherSuite,
+        frame_len: u32,
+    ) -> CoreResult<Self> {
+        let n = u8::try_from(outputs.len()).map_err(|_| CoreError::InvalidKAndN { k, n: 0 })?;
+        if frame_len == 0 || frame_len > MAX_FRAME_LEN {
+            return Err(CoreError::InvalidPacket("invalid frame length".to_string()));
+        }
+
+        let set_id = SetId::random();
+        let mac_key = crypto::random_key();
+        if passphrase.is_some() {
+            suite.kdf.validate()?;
+        }
+        let crypto_params = passphrase.map(|_| CryptoParams::random(suite));
+        let set_header = packet::set_header(
+            set_id,
+            k,
+            n,
+            crypto_params.as_ref(),
+            packet::SetMarks::default(),
+            None,
+        );
+        let cipher = match (passphrase, &crypto_params) {
+            (Some(passphrase), Some(params)) => {
+                Some(FrameCipher::new(passphrase, params, set_header.clone())?)
+            }
+            _ => None,
+        };
+
+        let key_shares = sss::split(mac_key.as_ref(), k, n, set_id)?;
+        for (output, share) in outputs.iter_mut().zip(key_shares) {
+            let header = SharePacket::from_raw_share(share)
+                .with_crypto_params(crypto_params)
+                .with_frame_len(Some(frame_len))
+                .with_authenticated_header(true)
+                .encode_binary()?;
+            write_record(output, &header)?;
+        }
+
+        Ok(Self {
+            outputs,
+            set_id,
+            k,
+            n,
+            mac_key,
+            set_header,
+            cipher,
+            frame_len: frame_len as usize,
+            buffer: Zeroizing::new(Vec::with_capacity(frame_len as usize + 1)),
+            index: 0,
+        })
+    }
+
+    pub fn set_id(&self) -> SetId {
+        self.set_id
+    }
+
+    /// Write the final frame, flush, and return the share streams.
+    pub fn finish(mut self) -> CoreResult<Vec<W>> {
+        if self.buffer.len() > self.frame_len {
+            self.write_full_frame()?;
+        }
+        let frame = Zeroizing::new(std::mem::take(&mut *self.buffer));
+        self.write_frame(&frame, true)?;
+
+        for output in &mut self.outputs {
+            output.flush().map_err(io_error)?;
+        }
+        Ok(self.outputs)
+    }
+
+    fn write_full_frame(&mut self) -> CoreResult<()> {
+        let frame: Zeroizing<Vec<u8>> =
+            Zeroizing::new(self.buffer.drain(..self.frame_len).collect());
+        self.write_frame(&frame, false)
+    }
+
+    fn write_frame(&mut self, frame: &[u8], last: bool) -> CoreResult<()> {
+        let mut data = Zeroizing::new(match &self.cipher {
+            Some(cipher) => cipher.encrypt(self.index, frame)?,
+            None => frame.to_vec(),
+        });
+        let tag = frame_tag(&self.mac_key, &self.set_header, self.index, last, &data);
+        data.extend_from_slice(tag.as_bytes());
+
+        let shares = sss::split(&data, self.k, self.n, self.set_id)?;
+        for (output, share) in self.outputs.iter_mut().zip(shares) {
+            write_record(output, &share.y)?;
+        }
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for ShareWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Hold one byte past a full frame so `finish` always has a last frame.
+        if self.buffer.len() > self.frame_len {
+            self.write_full_frame().map_err(io::Error::other)?;
+        }
+        let take = buf.len().min(self.frame_len + 1 - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..take]);
+        Ok(take)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.outputs.iter_mut().try_for_each(Write::flush)
+    }
+}
+
+/// Reads a secret back from at least `k` share streams.
+pub struct ShareReader<R: Read> {
+    inputs: Vec<R>,
+    shares: Vec<RawShare>,
+    mac_key: Zeroizing<[u8; crypto::KEY_LEN]>,
+    set_header: Vec<u8>,
+    cipher: Option<FrameCipher>,
+    max_record_len: usize,
+    next: Option<Vec<Vec<u8>>>,
+    frame: Zeroizing<Vec<u8>>,
+    pos: usize,
+    index: u64,
+}
+
+impl<R: Read> ShareReader<R> {
+    /// Read every input's header and prepare to combine the first `k`.
+    ///
+    /// Surplus inputs are checked for matching headers and then dropped.
+    pub fn new(mut inputs: Vec<R>, passphrase: Option<&[u8]>) -> CoreResult<Self> {
+        let headers = inputs
+            .iter_mut()
+            .map(read_header)
+            .collect::<CoreResult<Vec<_>>>()?;
+        let first = headers
+            .first()
+            .ok_or(CoreError::NotEnoughShares { k: 1, m: 0 })?;
+        for header in &headers {
+            if header.crypto_params != first.crypto_params {
+                return Err(CoreError::CryptoParamsMismatch);
+            }
+            if header.frame_len != first.frame_len {
+                return Err(CoreError::InconsistentMetadata);
+            }
+            if header.set_id == first.set_id {
+                if header.k != first.k {
+                    return Err(CoreError::HeaderMismatch("threshold k"));
+                }
+                if header.n != first.n {
+                    return Err(CoreError::HeaderMismatch("share count n"));
+                }
+            }
+        }
+
+        let mut shares = headers
+            .iter()
+            .map(SharePacket::to_raw_share)
+            .collect::<CoreResult<Vec<_>>>()?;
+        sss::check_shares(&shares)?;
+        shares.truncate(first.k as usize);
+        inputs.truncate(first.k as usize);
+
+        let combined = Zeroizing::new(sss::combine(&shares)?);
+        let mut mac_key = Zeroizing::new([0u8; crypto::KEY_LEN]);
+        if combined.len() != crypto::KEY_LEN {
+            return Err(CoreError::InconsistentMetadata);
+        }
+        mac_key.copy_from_slice(&combined);
+
+        let set_header = first.set_header();
+        let cipher = match (first.crypto_params, passphrase) {
+            (None, _) => None,
+            (Some(params), Some(passphrase)) => {
+                Some(FrameCipher::new(passphrase, &params, set_header.clone())?)
+            }
+            (Some(_), None) => return Err(CoreError::PassphraseRequired),
+        };
+        let overhead = FRAME_TAG_LEN + if cipher.is_some() { AEAD_TAG_LEN } else { 0 };
+        let frame_len = first.frame_len.unwrap_or_default() as usize;
+
+        let mut reader = Self {
+            inputs,
+            shares,
+            mac_key,
+            set_header,
+            cipher,
+            max_record_len: frame_len + overhead,
+            next: None,
+            frame: Zeroizing::new(Vec::new()),
+            pos: 0,
+            index: 0,
+        };
+        reader.next = reader.read_records()?;
+        if reader.next.is_none() {
+            return Err(CoreError::InvalidPacket(
+                "stream share has no frames".to_string(),
+            ));
+        }
+        Ok(reader)
+    }
+
+    /// Read one record from every input, or `None` when all inputs ended.
+    fn read_records(&mut self) -> CoreResult<Option<Vec<Vec<u8>>>> {
+        let records = self
+            .inputs
+            .iter_mut()
+            .map(|input| read_record(input, self.max_record_len))
+            .collect::<CoreResult<Vec<_>>>()?;
+        if records.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+        records
+            .into_iter()
+            .map(|record| record.ok_or_else(truncated))
+            .collect::<CoreResult<Vec<_>>>()
+            .map(Some)
+    }
+
+    /// Combine and check the next frame; returns false at the end.
+    fn next_frame(&mut self) -> CoreResult<bool> {
+        let Some(records) = self.next.take() else {
+            return Ok(false);
+        };
+        self.next = self.read_records()?;
+        let last = self.next.is_none();
+
+        let shares: Vec<RawShare> = self
+            .shares
+            .iter()
+            .zip(records)
+            .map(|(share, y)| RawShare { y, ..share.clone() })
+            .collect();
+        let data = Zeroizing::new(sss::combine(&shares)?);
+        if data.len() < FRAME_TAG_LEN {
+            return Err(CoreError::IntegrityCheckFailed);
+        }
+
+        let (body, tag) = data.split_at(data.len() - FRAME_TAG_LEN);
+        let tag: [u8; FRAME_TAG_LEN] = tag
+            .try_into()
+            .map_err(|_| CoreError::IntegrityCheckFailed)?;
+        let expected = frame_tag(&self.mac_key, &self.set_header, self.index, last, body);
+        if expected != blake3::Hash::from(tag) {
+            return Err(CoreError::IntegrityCheckFailed);
+        }
+
+        self.frame = Zeroizing::new(match &self.cipher {
+            Some(cipher) => cipher.decrypt(self.index, body)?,
+            None => body.to_vec(),
+        });
+        self.pos = 0;
+        self.index += 1;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for ShareReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.frame.len() {
+            if !self.next_frame().map_err(io::Error::other)? {
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.frame.len() - self.pos);
+        buf[..len].copy_from_slice(&self.frame[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Split everything read from `input` into one share stream per output.
+///
+/// `suite` sets the Argon2id costs and AEAD when `passphrase` is set. Returns
+/// the outputs after the final frame is written and flushed.
+pub fn split<R: Read, W: Write>(
+    mut input: R,
+    outputs: Vec<W>,
+    k: u8,
+    passphrase: Option<&[u8]>,
+    suite: CipherSuite,
+) -> CoreResult<Vec<W>> {
+    let mut writer = ShareWriter::with_options(outputs, k, passphrase, suite, DEFAULT_FRAME_LEN)?;
+    io::copy(&mut input, &mut writer).map_err(from_io)?;
+    writer.finish()
+}
+
+/// Recover a streamed secret from at least `k` share streams into `output`.
+///
+/// Returns the number of secret bytes written. On error, `output` may already
+/// hold part of the secret and must be discarded.
+pub fn combine<R: Read, W: Write>(
+    inputs: Vec<R>,
+    mut output: W,
+    passphrase: Option<&[u8]>,
+) -> CoreResult<u64> {
+    let mut reader = ShareReader::new(inputs, passphrase)?;
+    let written = io::copy(&mut reader, &mut output).map_err(from_io)?;
+    output.flush().map_err(io_error)?;
+    Ok(written)
+}
+
+/// Keyed tag over one frame, bound to the set header.
+fn frame_tag(
+    key: &[u8; crypto::KEY_LEN],
+    set_header: &[u8],
+    index: u64,
+    last: bool,
+    data: &[u8],
+) -> blake3::Hash {
+    blake3::Hasher::new_keyed(key)
+        .update(&(set_header.len() as u64).to_be_bytes())
+        .update(set_header)
+        .update(&index.to_be_bytes())
+        .update(&[u8::from(last)])
+        .update(data)
+        .finalize()
+}
+
+fn write_record<W: Write>(output: &mut W, bytes: &[u8]) -> CoreResult<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| CoreError::InvalidPacket("record too large".to_string()))?;
+    output.write_all(&len.to_be_bytes()).map_err(io_error)?;
+    output.write_all(bytes).map_err(io_error)
+}
+
+fn read_header<R: Read>(input: &mut R) -> CoreResult<SharePacket> {
+    let header = read_record(input, MAX_HEADER_LEN as usize)?.ok_or_else(truncated)?;
+    let packet = SharePacket::decode_binary(&header)?;
+    if !packet.is_streamed() {
+        return Err(CoreError::InvalidPacket("not a stream share".to_string()));
+    }
+    if !packet.authenticated_header {
+        return Err(CoreError::InvalidPacket(
+            "stream share header is not authenticated".to_string(),
+        ));
+    }
+    Ok(packet)
+}
+
+/// Read one length-prefixed record, or `None` at a clean end of input.
+fn read_record<R: Read>(input: &mut R, max_len: usize) -> CoreResult<Option<Vec<u8>>> {
+    let mut len = [0u8; LEN_FIELD_LEN];
+    let mut filled = 0;
+    while filled < LEN_FIELD_LEN {
+        match input.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(truncated()),
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max_len {
+        return Err(CoreError::InvalidPacket(
+            "stream record too large".to_string(),
+        ));
+    }
+    let mut record = vec![0u8; len];
+    input.read_exact(&mut record).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => truncated(),
+        _ => io_error(e),
+    })?;
+    Ok(Some(record))
+}
+
+fn truncated() -> CoreError {
+    CoreError::InvalidPacket("truncated stream share".to_string())
+}
+
+fn io_error(e: io::Error) -> CoreError {
+    CoreError::Io(e.to_string())
+}
+
+/// Recover a [`CoreError`] passed through an `io::Error` by the stream types.
+fn from_io(e: io::Error) -> CoreError {
+    match e.downcast::<CoreError>() {
+        Ok(e) => e,
+        Err(e) => io_error(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_to_vecs(secret: &[u8], k: u8, n: usize, frame_len: u32) -> Vec<Vec<u8>> {
+        let mut writer = ShareWriter::with_options(
+            vec![Vec::new(); n],
+            k,
+            None,
+            CipherSuite::default(),
+            frame_len,
+        )
+        .unwrap();
+        writer.write_all(secret).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn round_trip_across_frame_boundaries() {
+        let secret: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        for len in [0, 1, 99, 100, 101, 1000] {
+            let shares = split_to_vecs(&secret[..len], 2, 3, 100);
+
+            let mut recovered = Vec::new();
+            let inputs = vec![&shares[2][..], &shares[0][..]];
+            assert_eq!(combine(inputs, &mut recovered, None).unwrap(), len as u64);
+            assert_eq!(recovered, &secret[..len]);
+        }
+    }
+
+    #[test]
+    fn passphrase_round_trip() {
+        let outputs = split(
+            &b"streamed secret"[..],
+            vec![Vec::new(); 3],
+            2,
+            Some(b"pw"),
+            crypto::KdfParams::INTERACTIVE.into(),
+        )
+        .unwrap();
+
+        let mut recovered = Vec::new();
+        combine(
+            vec![&outputs[0][..], &outputs[1][..]],
+            &mut recovered,
+            Some(b"pw"),
+        )
+        .unwrap();
+        assert_eq!(recovered, b"streamed secret");
+
+        for &aead in crypto::AeadAlgorithm::ALL {
+            let suite = CipherSuite {
+                kdf: crypto::KdfParams::new(1024, 1, 1).unwrap(),
+                aead,
+            };
+            let mut writer =
+                ShareWriter::with_options(vec![Vec::new(); 2], 2, Some(b"pw"), suite, 4).unwrap();
+            writer.write_all(b"several frames of secret").unwrap();
+            let outputs = writer.finish().unwrap();
+
+            let mut recovered = Vec::new();
+            combine(
+                vec![&outputs[0][..], &outputs[1][..]],
+                &mut recovered,
+                Some(b"pw"),
+            )
+            .unwrap();
+            assert_eq!(recovered, b"several frames of secret");
+        }
+
+        assert!(matches!(
+            ShareReader::new(vec![&outputs[0][..], &outputs[1][..]], None),
+            Err(CoreError::PassphraseRequired)
+        ));
+    }
+
+    #[test]
+    fn truncated_or_corrupted_streams_fail() {
+        let secret = vec![7u8; 250];
+        let shares = split_to_vecs(&secret, 2, 2, 100);
+
+        // Drop the last frame record from both shares.
+        let last_record = 4 + 50 + FRAME_TAG_LEN;
+        let cut: Vec<&[u8]> = shares
+            .iter()
+            .map(|share| &share[..share.len() - last_record])
+            .collect();
+        assert!(matches!(
+            combine(cut, Vec::new(), None),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+
+        let mut corrupted = shares.clone();
+        let end = corrupted[1].len() - 1;
+        corrupted[1][end] ^= 0x01;
+        let inputs: Vec<&[u8]> = corrupted.iter().map(Vec::as_slice).collect();
+        assert!(matches!(
+            combine(inputs, Vec::new(), None),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn too_few_streams_fail() {
+        let shares = split_to_vecs(b"few", 2, 3, 100);
+        assert!(matches!(
+            ShareReader::new(vec![&shares[0][..]], None),
+            Err(CoreError::NotEnoughShares { k: 2, m: 1 })
+        ));
+    }
+
+    #[test]
+    fn altered_headers_fail() {
+        let shares = split_to_vecs(b"header", 2, 3, 100);
+        // Length prefix, magic, version, and flags precede `k` and `n`.
+        let n_offset = LEN_FIELD_LEN + 4 + 2 + 1;
+
+        let mut one = shares.clone();
+        one[1][n_offset] = 4;
+        assert!(matches!(
+            ShareReader::new(vec![&one[0][..], &one[1][..]], None),
+            Err(CoreError::HeaderMismatch("share count n"))
+        ));
+
+        let mut all = shares.clone();
+        for share in &mut all {
+            share[n_offset] = 4;
+        }
+        assert!(matches!(
+            combine(vec![&all[0][..], &all[1][..]], Vec::new(), None),
+            Err(CoreError::IntegrityCheckFailed)
+        ));
+
+        // Every stream share binds its header; one that does not is rejected.
+        let header_len =
+            u32::from_be_bytes(shares[0][..LEN_FIELD_LEN].try_into().unwrap()) as usize;
+        let body = &shares[0][LEN_FIELD_LEN..];
+        let unbound = SharePacket::decode_binary(&body[..header_len])
+            .unwrap()
+            .with_authenticated_header(false)
+            .encode_binary()
+            .unwrap();
+        let mut share = (unbound.len() as u32).to_be_bytes().to_vec();
+        share.extend_from_slice(&unbound);
+        share.extend_from_slice(&body[header_len..]);
+        assert!(matches!(
+            ShareReader::new(vec![&share[..], &shares[1][..]], None),
+            Err(CoreError::InvalidPacket(message)) if message.contains("not authenticated")
+        ));
+    }
+}
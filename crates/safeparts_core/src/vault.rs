@@ -0,0 +1,223 @@
+//! HashiCorp Vault and OpenBao unseal keys.
+//!
+//! Vault splits its root key with Shamir's scheme over the same GF(256) as
+//! [`crate::sss`], with the AES polynomial `x^8 + x^4 + x^3 + x + 1`, so no
+//! separate field arithmetic is needed. An unseal key is the share bytes
+//! followed by a one-byte x coordinate, shown as base64 or hex. Vault picks
+//! the coordinates at random, never zero.
+//!
+//! Unseal keys carry no threshold, set id, or integrity tag. Combining too
+//! few keys, or keys of different sets, returns wrong bytes without an
+//! error; Vault rejects those bytes when it tries to unseal.
+
+use base64::Engine;
+use zeroize::Zeroizing;
+
+use crate::error::{CoreError, CoreResult};
+use crate::sss::{self, SetId};
+
+const KEY_PREFIX: &str = "Unseal Key";
+
+/// One Vault unseal key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsealKey {
+    pub x: u8,
+    pub y: Zeroizing<Vec<u8>>,
+}
+
+impl UnsealKey {
+    /// Parse one key in base64, as `vault operator init` prints it, or in
+    /// hex, as in its `keys` JSON field.
+    pub fn parse(text: &str) -> CoreResult<Self> {
+        let text = text.trim();
+        let bytes = if text.len().is_multiple_of(2) && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+            decode_hex(text)
+        } else {
+            base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .map_err(|e| CoreError::Encoding(format!("vault key: {e}")))?
+        };
+        let bytes = Zeroizing::new(bytes);
+        let Some((&x, y)) = bytes.split_last().filter(|(_, y)| !y.is_empty()) else {
+            return Err(CoreError::Encoding("vault key is too short".to_string()));
+        };
+        if x == 0 {
+            return Err(CoreError::InvalidX);
+        }
+        Ok(Self {
+            x,
+            y: Zeroizing::new(y.to_vec()),
+        })
+    }
+
+    /// Base64 text, the form Vault prints and accepts in `vault operator unseal`.
+    pub fn to_base64(&self) -> String {
+        let mut bytes = Zeroizing::new(self.y.to_vec());
+        bytes.push(self.x);
+        base64::engine::general_purpose::STANDARD.encode(bytes.as_slice())
+    }
+}
+
+/// Parse every unseal key in `input`, one per line.
+///
+/// Lines copied from `vault operator init` output, such as
+/// `Unseal Key 1: <base64>`, are accepted as they are.
+pub fn parse_keys(input: &str) -> CoreResult<Vec<UnsealKey>> {
+    let keys = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.strip_prefix(KEY_PREFIX) {
+            Some(rest) => rest.split_once(':').map_or(rest, |(_, key)| key),
+            None => line,
+        })
+        .map(UnsealKey::parse)
+        .collect::<CoreResult<Vec<_>>>()?;
+    if keys.is_empty() {
+        return Err(CoreError::EmptyShareInput);
+    }
+    Ok(keys)
+}
+
+/// Split `secret` into `n` unseal keys with a threshold of `k`, the way
+/// `vault operator init` does.
+///
+/// Vault requires `2 <= k <= n <= 255`.
+pub fn split(secret: &[u8], k: u8, n: u8) -> CoreResult<Vec<UnsealKey>> {
+    if k < 2 || k > n {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+    if secret.is_empty() {
+        return Err(CoreError::InvalidCombinedLength { len: 0 });
+    }
+    let shares = sss::split_at(secret, k, &sss::random_xs(n), SetId([0; 16]))?;
+    Ok(shares
+        .into_iter()
+        .map(|share| UnsealKey {
+            x: share.x,
+            y: Zeroizing::new(share.y),
+        })
+        .collect())
+}
+
+/// Recover the secret from unseal keys.
+///
+/// Every key given is used, so pass exactly the threshold or more keys of
+/// one set.
+pub fn combine(keys: &[UnsealKey]) -> CoreResult<Zeroizing<Vec<u8>>> {
+    if keys.len() < 2 {
+        return Err(CoreError::NotEnoughShares {
+            k: 2,
+            m: keys.len(),
+        });
+    }
+    let len = keys[0].y.len();
+    let mut seen = [false; 256];
+    for key in keys {
+        if key.y.len() != len {
+            return Err(CoreError::InconsistentMetadata);
+        }
+        if seen[key.x as usize] {
+            return Err(CoreError::DuplicateX { x: key.x });
+        }
+        seen[key.x as usize] = true;
+    }
+
+    let points: Vec<(u8, &[u8])> = keys.iter().map(|key| (key.x, key.y.as_slice())).collect();
+    Ok(Zeroizing::new(sss::interpolate_at(&points, 0)?))
+}
+
+fn decode_hex(text: &str) -> Vec<u8> {
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let digit = |b: u8| (b as char).to_digit(16).unwrap_or(0) as u8;
+            (digit(pair[0]) << 4) | digit(pair[1])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generated by an independent implementation of Vault's `shamir.Split`:
+    // its constant-time `mult`, random x coordinates, and `y || x` layout.
+    const FIXTURES: &[(&str, &[&str])] = &[
+        (
+            "3ca33472d7fbe17a0129389332e605fba06bcb80b2b6c027ae2d9593ea489e0c",
+            &[
+                "Unseal Key 1: sMgqTjjPYVMZTH01o+UHiytEma2kAj8B2HML6Q5FVrfa",
+                "Unseal Key 3: tVJ4QxT/U7gVZ9Sr/F3FMtxZqjp1CRk3CTX6ZVx+vZZf",
+                "Unseal Key 5: FTo/g0VUgcysP+GD2GYqnNjR2Y1Eave4BSqYEKECIo93",
+            ],
+        ),
+        (
+            "e6072f00e72a657e3d1689b64f02a0fb4572f243942bd551459cd2c74c64c007",
+            &[
+                "3dd8b57505c85fbd56d92ed697c0f5dd709e900af5b3eb2f92e3efd0e73871c264",
+                "0cf8406c15d834eabdbdeb55ec5864c1623896ecb9c3915e905ddfcf11446401f6",
+            ],
+        ),
+        (
+            "426f46525fc78bd9d428c49c5fb05986",
+            &[
+                "6Yg2ztHU97/vuQ0y9/mHj3c=",
+                "cBze3rYbDa6gpKMAmzY80IA=",
+                "2e0798bf1ab35a34193585415f8e6a8dc8",
+                "6HxmB9bOuCYQbI+K3nwsLtE=",
+            ],
+        ),
+    ];
+
+    #[test]
+    fn fixture_keys_combine_to_their_root_keys() {
+        for (secret, keys) in FIXTURES {
+            let keys = parse_keys(&keys.join("\n")).unwrap();
+            assert_eq!(hex::encode(combine(&keys).unwrap().as_slice()), *secret);
+        }
+
+        let (_, keys) = FIXTURES[1];
+        let key = UnsealKey::parse(keys[0]).unwrap();
+        assert_eq!(key.x, 0x64);
+        assert_eq!(
+            UnsealKey::parse(&key.to_base64()).unwrap(),
+            key,
+            "hex and base64 forms agree"
+        );
+    }
+
+    #[test]
+    fn split_round_trips_through_base64() {
+        let secret = [0x5a; 32];
+        let keys = split(&secret, 3, 5).unwrap();
+        assert!(keys.iter().all(|key| key.x != 0 && key.y.len() == 32));
+        let text: Vec<String> = keys.iter().map(UnsealKey::to_base64).collect();
+        let parsed = parse_keys(&text[1..4].join("\n")).unwrap();
+        assert_eq!(combine(&parsed).unwrap().as_slice(), secret);
+
+        assert!(split(&secret, 1, 3).is_err());
+        assert!(split(&[], 2, 3).is_err());
+    }
+
+    #[test]
+    fn combine_rejects_malformed_key_sets() {
+        let keys = split(&[1, 2, 3], 2, 3).unwrap();
+        assert!(matches!(
+            combine(&keys[..1]),
+            Err(CoreError::NotEnoughShares { k: 2, m: 1 })
+        ));
+        assert!(matches!(
+            combine(&[keys[0].clone(), keys[0].clone()]),
+            Err(CoreError::DuplicateX { .. })
+        ));
+        let mut short = keys[1].clone();
+        short.y.pop();
+        assert!(matches!(
+            combine(&[keys[0].clone(), short]),
+            Err(CoreError::InconsistentMetadata)
+        ));
+        assert!(UnsealKey::parse("AA==").is_err());
+        assert!(parse_keys("not base64!").is_err());
+    }
+}
@@ -0,0 +1,422 @@
+//! Verifiable secret sharing with Pedersen commitments over Ristretto255.
+//!
+//! The byte-wise GF(256) sharing in [`crate::sss`] has no homomorphic
+//! commitment, so verifiable splits share the data over the Ristretto255
+//! scalar field instead. The data is framed with its length, cut into
+//! [`CHUNK_LEN`]-byte chunks, and each chunk becomes the constant term of its
+//! own polynomial. A second random polynomial blinds every coefficient, so the
+//! published commitments `C_j = a_j·G + b_j·H` reveal nothing about the data,
+//! even when the secret has low entropy.
+//!
+//! Each share payload is a sequence of [`SHARE_CHUNK_LEN`]-byte records holding
+//! `a(x) || b(x)` for every chunk. A custodian can check their record against
+//! the commitments without seeing any other share.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{CoreError, CoreResult};
+use crate::sss::{self, RawShare, SetId};
+
+/// Data bytes carried by one scalar. 31 bytes always fit below the group order.
+pub const CHUNK_LEN: usize = 31;
+
+/// Bytes of share payload per chunk: the data share and the blinding share.
+pub const SHARE_CHUNK_LEN: usize = 64;
+
+/// Length of one compressed Ristretto255 commitment.
+pub const COMMITMENT_LEN: usize = 32;
+
+const LEN_PREFIX_LEN: usize = 4;
+const H_GENERATOR_CONTEXT: &str = "safeparts 2024 vss pedersen generator H";
+
+/// Published commitments for one verifiable split.
+///
+/// `points` is chunk-major: the commitment to coefficient `j` of chunk `c` is
+/// `points[c * k + j]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commitments {
+    pub k: u8,
+    pub points: Vec<[u8; COMMITMENT_LEN]>,
+}
+
+impl Commitments {
+    /// Number of chunks covered by these commitments.
+    pub fn chunk_count(&self) -> usize {
+        if self.k == 0 {
+            0
+        } else {
+            self.points.len() / self.k as usize
+        }
+    }
+
+    /// BLAKE3 fingerprint custodians can compare out of band.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        *blake3::hash(&self.to_bytes()).as_bytes()
+    }
+
+    /// Serialize as `k || point count (u32 BE) || points`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + 4 + self.points.len() * COMMITMENT_LEN);
+        out.push(self.k);
+        out.extend_from_slice(&(self.points.len() as u32).to_be_bytes());
+        for point in &self.points {
+            out.extend_from_slice(point);
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> CoreResult<Self> {
+        if bytes.len() < 5 {
+            return Err(CoreError::InvalidPacket(
+                "truncated commitments".to_string(),
+            ));
+        }
+
+        let k = bytes[0];
+        let count = u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize;
+        let body = &bytes[5..];
+        if count.checked_mul(COMMITMENT_LEN) != Some(body.len()) {
+            return Err(CoreError::InvalidPacket(
+                "commitments length mismatch".to_string(),
+            ));
+        }
+
+        let commitments = Self {
+            k,
+            points: body
+                .chunks_exact(COMMITMENT_LEN)
+                .map(|chunk| {
+                    let mut point = [0u8; COMMITMENT_LEN];
+                    point.copy_from_slice(chunk);
+                    point
+                })
+                .collect(),
+        };
+        commitments.check_shape()?;
+        Ok(commitments)
+    }
+
+    pub(crate) fn check_shape(&self) -> CoreResult<()> {
+        if self.k == 0
+            || self.points.is_empty()
+            || !self.points.len().is_multiple_of(self.k as usize)
+        {
+            return Err(CoreError::InvalidPacket(
+                "invalid commitments shape".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Split `data` into `n` verifiable shares with threshold `k`.
+pub fn split(data: &[u8], k: u8, n: u8, set_id: SetId) -> CoreResult<(Vec<RawShare>, Commitments)> {
+    if k == 0 || n == 0 || k > n {
+        return Err(CoreError::InvalidKAndN { k, n });
+    }
+
+    let framed = frame(data)?;
+    let chunk_count = framed.len() / CHUNK_LEN;
+    let h = h_generator();
+
+    let mut shares: Vec<RawShare> = (1..=n)
+        .map(|x| RawShare {
+            set_id,
+            k,
+            n,
+            x,
+            y: Vec::with_capacity(chunk_count * SHARE_CHUNK_LEN),
+        })
+        .collect();
+    let mut points = Vec::with_capacity(chunk_count * k as usize);
+
+    let mut a = vec![Scalar::ZERO; k as usize];
+    let mut b = vec![Scalar::ZERO; k as usize];
+
+    for chunk in framed.chunks_exact(CHUNK_LEN) {
+        a[0] = chunk_to_scalar(chunk);
+        b[0] = random_scalar();
+        for j in 1..k as usize {
+            a[j] = random_scalar();
+            b[j] = random_scalar();
+        }
+
+        for (a_j, b_j) in a.iter().zip(&b) {
+            let point = RISTRETTO_BASEPOINT_POINT * a_j + h * b_j;
+            points.push(point.compress().to_bytes());
+        }
+
+        for share in &mut shares {
+            let x = Scalar::from(share.x);
+            share.y.extend_from_slice(&evaluate(&a, x).to_bytes());
+            share.y.extend_from_slice(&evaluate(&b, x).to_bytes());
+        }
+    }
+
+    a.zeroize();
+    b.zeroize();
+
+    Ok((shares, Commitments { k, points }))
+}
+
+/// Check one share against the published commitments.
+pub fn verify(share: &RawShare, commitments: &Commitments) -> CoreResult<()> {
+    commitments.check_shape()?;
+    if share.x == 0 {
+        return Err(CoreError::InvalidX);
+    }
+    if commitments.k != share.k
+        || !share.y.len().is_multiple_of(SHARE_CHUNK_LEN)
+        || share.y.len() / SHARE_CHUNK_LEN != commitments.chunk_count()
+    {
+        return Err(CoreError::CommitmentsMismatch);
+    }
+
+    let h = h_generator();
+    let x = Scalar::from(share.x);
+    let failed = CoreError::ShareVerificationFailed { x: share.x };
+
+    for (record, chunk_points) in share
+        .y
+        .chunks_exact(SHARE_CHUNK_LEN)
+        .zip(commitments.points.chunks_exact(commitments.k as usize))
+    {
+        let (Some(s), Some(t)) = (
+            canonical_scalar(&record[..32]),
+            canonical_scalar(&record[32..]),
+        ) else {
+            return Err(failed);
+        };
+
+        let mut expected = RistrettoPoint::default();
+        let mut x_pow = Scalar::ONE;
+        for point in chunk_points {
+            let Some(point) = CompressedRistretto(*point).decompress() else {
+                return Err(CoreError::InvalidPacket(
+                    "invalid commitment point".to_string(),
+                ));
+            };
+            expected += point * x_pow;
+            x_pow *= x;
+        }
+
+        if RISTRETTO_BASEPOINT_POINT * s + h * t != expected {
+            return Err(failed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reconstruct the data from at least `k` verifiable shares.
+///
+/// This does not check commitments; callers that have them should call
+/// [`verify`] on each share first so a bad share is reported by index.
+pub fn combine(shares: &[RawShare]) -> CoreResult<Vec<u8>> {
+    sss::check_shares(shares)?;
+    let y_len = shares[0].y.len();
+    if y_len == 0 || !y_len.is_multiple_of(SHARE_CHUNK_LEN) {
+        return Err(CoreError::InvalidCombinedLength { len: y_len });
+    }
+
+    let x_values: Vec<Scalar> = shares.iter().map(|share| Scalar::from(share.x)).collect();
+    let weights = interpolation_weights_at(&x_values, Scalar::ZERO);
+
+    let chunk_count = y_len / SHARE_CHUNK_LEN;
+    let mut framed = Zeroizing::new(Vec::with_capacity(chunk_count * CHUNK_LEN));
+    for chunk_index in 0..chunk_count {
+        let offset = chunk_index * SHARE_CHUNK_LEN;
+        let mut value = Scalar::ZERO;
+        for (share, weight) in shares.iter().zip(&weights) {
+            let s = canonical_scalar(&share.y[offset..offset + 32])
+                .ok_or(CoreError::IntegrityCheckFailed)?;
+            value += s * weight;
+        }
+
+        let bytes = Zeroizing::new(value.to_bytes());
+        if bytes[CHUNK_LEN] != 0 {
+            return Err(CoreError::IntegrityCheckFailed);
+        }
+        framed.extend_from_slice(&bytes[..CHUNK_LEN]);
+    }
+
+    unframe(&framed)
+}
+
+/// Evaluate both committed polynomials at `new_x` to issue another share.
+///
+/// The result verifies against the same commitments as the input shares.
+/// Like [`combine`], this does not check commitments itself.
+pub fn derive(shares: &[RawShare], new_x: u8) -> CoreResult<RawShare> {
+    sss::check_shares(shares)?;
+    sss::check_new_x(shares, new_x)?;
+    let first = &shares[0];
+    let base = &shares[..first.k as usize];
+    if !first.y.len().is_multiple_of(SHARE_CHUNK_LEN) {
+        return Err(CoreError::InvalidCombinedLength { len: first.y.len() });
+    }
+
+    let x_values: Vec<Scalar> = base.iter().map(|share| Scalar::from(share.x)).collect();
+    let weights = interpolation_weights_at(&x_values, Scalar::from(new_x));
+
+    let mut y = Vec::with_capacity(first.y.len());
+    for offset in (0..first.y.len()).step_by(32) {
+        let mut value = Scalar::ZERO;
+        for (share, weight) in base.iter().zip(&weights) {
+            let s = canonical_scalar(&share.y[offset..offset + 32])
+                .ok_or(CoreError::ShareVerificationFailed { x: share.x })?;
+            value += s * weight;
+        }
+        y.extend_from_slice(&value.to_bytes());
+    }
+
+    Ok(RawShare {
+        set_id: first.set_id,
+        k: first.k,
+        n: first.n.max(new_x),
+        x: new_x,
+        y,
+    })
+}
+
+fn frame(data: &[u8]) -> CoreResult<Zeroizing<Vec<u8>>> {
+    let len = u32::try_from(data.len())
+        .map_err(|_| CoreError::InvalidPacket("secret too large".to_string()))?;
+    let framed_len = (LEN_PREFIX_LEN + data.len()).div_ceil(CHUNK_LEN) * CHUNK_LEN;
+
+    let mut framed = Zeroizing::new(Vec::with_capacity(framed_len));
+    framed.extend_from_slice(&len.to_be_bytes());
+    framed.extend_from_slice(data);
+    framed.resize(framed_len, 0);
+    Ok(framed)
+}
+
+fn unframe(framed: &[u8]) -> CoreResult<Vec<u8>> {
+    if framed.len() < LEN_PREFIX_LEN {
+        return Err(CoreError::InvalidCombinedLength { len: framed.len() });
+    }
+    let len = u32::from_be_bytes([framed[0], framed[1], framed[2], framed[3]]) as usize;
+    let end = LEN_PREFIX_LEN
+        .checked_add(len)
+        .filter(|end| *end <= framed.len())
+        .ok_or(CoreError::IntegrityCheckFailed)?;
+    if framed[end..].iter().any(|&b| b != 0) {
+        return Err(CoreError::IntegrityCheckFailed);
+    }
+    Ok(framed[LEN_PREFIX_LEN..end].to_vec())
+}
+
+fn chunk_to_scalar(chunk: &[u8]) -> Scalar {
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    bytes[..CHUNK_LEN].copy_from_slice(chunk);
+    Scalar::from_bytes_mod_order(*bytes)
+}
+
+fn canonical_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+    Option::from(Scalar::from_canonical_bytes(bytes))
+}
+
+fn random_scalar() -> Scalar {
+    let mut wide = Zeroizing::new([0u8; 64]);
+    OsRng.fill_bytes(wide.as_mut());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+fn evaluate(coeffs: &[Scalar], x: Scalar) -> Scalar {
+    coeffs
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |acc, coef| acc * x + coef)
+}
+
+fn interpolation_weights_at(x_values: &[Scalar], at: Scalar) -> Vec<Scalar> {
+    x_values
+        .iter()
+        .enumerate()
+        .map(|(share_index, &x)| {
+            let (numerator, denominator) = x_values
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != share_index)
+                .fold(
+                    (Scalar::ONE, Scalar::ONE),
+                    |(numerator, denominator), (_, &other_x)| {
+                        (numerator * (at - other_x), denominator * (x - other_x))
+                    },
+                );
+
+            numerator * denominator.invert()
+        })
+        .collect()
+}
+
+/// Second Pedersen generator with no known discrete log relative to `G`.
+fn h_generator() -> RistrettoPoint {
+    let mut uniform = [0u8; 64];
+    blake3::Hasher::new_derive_key(H_GENERATOR_CONTEXT)
+        .finalize_xof()
+        .fill(&mut uniform);
+    RistrettoPoint::from_uniform_bytes(&uniform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_any_k_subset() {
+        let data = b"verifiable secret sharing spans more than one chunk of data";
+        let (shares, commitments) = split(data, 3, 5, SetId([3u8; 16])).unwrap();
+
+        assert_eq!(commitments.k, 3);
+        for share in &shares {
+            verify(share, &commitments).unwrap();
+        }
+
+        assert_eq!(combine(&shares[..3]).unwrap(), data);
+        assert_eq!(
+            combine(&[shares[4].clone(), shares[1].clone(), shares[2].clone()]).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn tampered_share_fails_verification() {
+        let (mut shares, commitments) = split(b"abc", 2, 3, SetId([4u8; 16])).unwrap();
+        shares[1].y[0] ^= 1;
+
+        let err = verify(&shares[1], &commitments).unwrap_err();
+        assert!(matches!(err, CoreError::ShareVerificationFailed { x: 2 }));
+    }
+
+    #[test]
+    fn share_from_other_split_fails_verification() {
+        let (shares, _) = split(b"abc", 2, 3, SetId([5u8; 16])).unwrap();
+        let (_, other) = split(b"abc", 2, 3, SetId([5u8; 16])).unwrap();
+
+        assert!(verify(&shares[0], &other).is_err());
+    }
+
+    #[test]
+    fn derived_share_verifies_against_original_commitments() {
+        let (shares, commitments) = split(b"abc", 2, 3, SetId([7u8; 16])).unwrap();
+
+        let derived = derive(&shares[1..], 7).unwrap();
+        verify(&derived, &commitments).unwrap();
+        assert_eq!(combine(&[shares[0].clone(), derived]).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn commitments_bytes_round_trip() {
+        let (_, commitments) = split(b"abc", 2, 3, SetId([6u8; 16])).unwrap();
+        let decoded = Commitments::from_bytes(&commitments.to_bytes()).unwrap();
+        assert_eq!(decoded, commitments);
+        assert_eq!(decoded.fingerprint(), commitments.fingerprint());
+    }
+}
@@ -16,7 +16,16 @@ use tui_textarea::{Input, TextArea};
 use zeroize::Zeroizing;
 
 use crate::clipboard::Clipboard;
-use crate::domain::{Encoding, combine_shares, set_id_hex, split_secret};
+use safeparts_core::crypto::{AeadAlgorithm, CipherSuite, KdfParams};
+use safeparts_core::mnemo_fix::WordCorrection;
+use safeparts_core::padding::Padding;
+use safeparts_core::policy::Policy;
+use safeparts_core::{CoreError, SplitOptions};
+
+use crate::domain::{
+    Encoding, SharePassphrases, combine_shares, holder_name, kdf_label, load_policy, parse_kdf,
+    set_id_hex, split_secret, split_secret_with_policy, suspects_label,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum TabId {
@@ -27,9 +36,12 @@ enum TabId {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum ModalKind {
     LoadSecretFile,
+    LoadPolicyFile,
+    SetKdf,
     LoadShareFiles,
     SaveSharesDir,
     SaveSecretFile,
+    UnlockShare { x: u8 },
 }
 
 #[derive(Debug)]
@@ -53,6 +65,9 @@ enum Focus {
     SplitN,
     SplitEncoding,
     SplitPassphrase,
+    SplitKdf,
+    SplitCipher,
+    SplitPadding,
     SplitShares,
 
     CombineShares,
@@ -105,8 +120,12 @@ pub struct App {
     split_secret_file_len: Option<usize>,
     split_k: u8,
     split_n: u8,
+    split_policy: Option<(PathBuf, Policy)>,
     split_encoding: Encoding,
     split_passphrase: Zeroizing<String>,
+    split_kdf: KdfParams,
+    split_cipher: AeadAlgorithm,
+    split_padding: Padding,
     split_shares: Vec<String>,
     split_packets: Vec<safeparts_core::packet::SharePacket>,
     split_selected_share: usize,
@@ -115,9 +134,14 @@ pub struct App {
     combine_shares_text: TextArea<'static>,
     combine_encoding: Encoding,
     combine_passphrase: Zeroizing<String>,
+    combine_share_passphrases: SharePassphrases,
     combine_recovered: Option<Zeroizing<Vec<u8>>>,
     combine_recovered_text: Option<Zeroizing<String>>,
     combine_used_encoding: Option<Encoding>,
+    /// Stored filename of the recovered secret, offered by the save dialog.
+    combine_suggested_name: Option<String>,
+    /// Misspelled words of the last failed combine, with the share they are in.
+    combine_corrections: Option<(usize, Vec<WordCorrection>)>,
 
     // common
     clipboard: Clipboard,
@@ -146,8 +170,12 @@ impl App {
             split_secret_file_len: None,
             split_k: 2,
             split_n: 3,
+            split_policy: None,
             split_encoding: Encoding::Base64url,
             split_passphrase: Zeroizing::new(String::new()),
+            split_kdf: KdfParams::default(),
+            split_cipher: AeadAlgorithm::default(),
+            split_padding: Padding::None,
             split_shares: Vec::new(),
             split_packets: Vec::new(),
             split_selected_share: 0,
@@ -155,9 +183,12 @@ impl App {
             combine_shares_text,
             combine_encoding: Encoding::Auto,
             combine_passphrase: Zeroizing::new(String::new()),
+            combine_share_passphrases: SharePassphrases::new(),
             combine_recovered: None,
             combine_recovered_text: None,
             combine_used_encoding: None,
+            combine_suggested_name: None,
+            combine_corrections: None,
 
             clipboard: Clipboard::new(),
             status: None,
@@ -261,6 +292,22 @@ impl App {
             return Ok(false);
         }
 
+        if is_control_key(key, 'p') && self.tab == TabId::Split {
+            self.modal = Some(Modal::new(
+                ModalKind::LoadPolicyFile,
+                "Enter access policy file path (empty to use k/n)",
+            ));
+            return Ok(false);
+        }
+
+        if is_control_key(key, 'k') && self.tab == TabId::Split {
+            self.modal = Some(Modal::new(
+                ModalKind::SetKdf,
+                "interactive, 65536 3 1, or 500ms to calibrate",
+            ));
+            return Ok(false);
+        }
+
         if is_control_key(key, 's') {
             self.on_save();
             return Ok(false);
@@ -361,6 +408,15 @@ impl App {
             Focus::SplitEncoding => {
                 self.split_encoding = cycle_encoding(self.split_encoding, -1, Encoding::SPLIT);
             }
+            Focus::SplitKdf => {
+                self.split_kdf = cycle_kdf(self.split_kdf, -1);
+            }
+            Focus::SplitCipher => {
+                self.split_cipher = cycle_cipher(self.split_cipher, -1);
+            }
+            Focus::SplitPadding => {
+                self.split_padding = cycle_padding(self.split_padding, -1);
+            }
             Focus::CombineEncoding => {
                 self.combine_encoding = cycle_encoding(self.combine_encoding, -1, Encoding::ALL);
             }
@@ -385,6 +441,15 @@ impl App {
             Focus::SplitEncoding => {
                 self.split_encoding = cycle_encoding(self.split_encoding, 1, Encoding::SPLIT);
             }
+            Focus::SplitKdf => {
+                self.split_kdf = cycle_kdf(self.split_kdf, 1);
+            }
+            Focus::SplitCipher => {
+                self.split_cipher = cycle_cipher(self.split_cipher, 1);
+            }
+            Focus::SplitPadding => {
+                self.split_padding = cycle_padding(self.split_padding, 1);
+            }
             Focus::CombineEncoding => {
                 self.combine_encoding = cycle_encoding(self.combine_encoding, 1, Encoding::ALL);
             }
@@ -423,10 +488,16 @@ impl App {
                 self.set_info("nothing to save");
                 return;
             }
-            TabId::Combine => Modal::new(
-                ModalKind::SaveSecretFile,
-                "Enter output file path for recovered secret",
-            ),
+            TabId::Combine => {
+                let mut modal = Modal::new(
+                    ModalKind::SaveSecretFile,
+                    "Enter output file path for recovered secret",
+                );
+                if let Some(name) = &self.combine_suggested_name {
+                    modal.input.insert_str(name);
+                }
+                modal
+            }
         };
         self.modal = Some(modal);
     }
@@ -499,7 +570,10 @@ impl App {
 
     fn on_modal_key(&mut self, key: KeyEvent) -> Result<bool> {
         if key.code == KeyCode::Esc {
-            self.modal = None;
+            if let Some(ModalKind::UnlockShare { .. }) = self.modal.take().map(|modal| modal.kind) {
+                self.combine_share_passphrases.clear();
+                self.set_info("combine cancelled");
+            }
             return Ok(false);
         }
 
@@ -508,7 +582,12 @@ impl App {
         };
 
         if key.code == KeyCode::Enter {
-            let text = modal.input.lines().join("\n").trim().to_string();
+            let text = modal.input.lines().join("\n");
+            // Passphrases are taken verbatim; paths and values are trimmed.
+            let text = match modal.kind {
+                ModalKind::UnlockShare { .. } => text,
+                _ => text.trim().to_string(),
+            };
 
             let kind = modal.kind;
             self.modal = None;
@@ -535,6 +614,30 @@ impl App {
 
                 self.set_ok("loaded secret file");
             }
+            ModalKind::LoadPolicyFile => {
+                if text.is_empty() {
+                    self.split_policy = None;
+                    self.set_info("using k/n threshold");
+                    return Ok(());
+                }
+
+                let p = PathBuf::from(text);
+                match load_policy(&p) {
+                    Ok(policy) => {
+                        let holders = policy.holders().len();
+                        self.split_policy = Some((p, policy));
+                        self.set_ok(format!("loaded policy with {holders} holders"));
+                    }
+                    Err(e) => self.set_err(format!("policy error: {e:#}")),
+                }
+            }
+            ModalKind::SetKdf => match parse_kdf(&text) {
+                Ok(kdf) => {
+                    self.split_kdf = kdf;
+                    self.set_ok(format!("Argon2id: {}", kdf_label(&kdf)));
+                }
+                Err(e) => self.set_err(format!("kdf error: {e:#}")),
+            },
             ModalKind::LoadShareFiles => {
                 let mut combined = String::new();
                 for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
@@ -562,7 +665,12 @@ impl App {
 
                 for (idx, share) in self.split_shares.iter().enumerate() {
                     let i = idx + 1;
-                    let filename = format!("safeparts-{set_id}-share-{i}-of-{n}.txt");
+                    // Codex32 splits have no packets.
+                    let holder = self.split_packets.get(idx).and_then(holder_name);
+                    let filename = match holder {
+                        Some(holder) => format!("safeparts-{set_id}-{holder}.txt"),
+                        None => format!("safeparts-{set_id}-share-{i}-of-{n}.txt"),
+                    };
                     let path = dir.join(filename);
                     fs::write(&path, format!("{share}\n"))
                         .with_context(|| format!("write {}", path.display()))?;
@@ -586,6 +694,11 @@ impl App {
                     .with_context(|| format!("write {}", path.display()))?;
                 self.set_ok("saved recovered secret");
             }
+            ModalKind::UnlockShare { x } => {
+                self.combine_share_passphrases
+                    .insert(x, Zeroizing::new(text));
+                self.do_combine()?;
+            }
         }
 
         Ok(())
@@ -610,13 +723,37 @@ impl App {
             Some(self.split_passphrase.as_bytes())
         };
 
-        match split_secret(
-            secret_bytes.as_slice(),
-            self.split_k,
-            self.split_n,
-            self.split_encoding,
-            passphrase,
-        ) {
+        let suite = CipherSuite {
+            kdf: self.split_kdf,
+            aead: self.split_cipher,
+        };
+        let result = match &self.split_policy {
+            Some(_) if !self.split_padding.is_none() => {
+                self.set_err("padding is not available for policy splits");
+                return Ok(());
+            }
+            Some((_, policy)) => split_secret_with_policy(
+                secret_bytes.as_slice(),
+                policy,
+                self.split_encoding,
+                passphrase,
+                suite,
+            ),
+            None => split_secret(
+                secret_bytes.as_slice(),
+                self.split_k,
+                self.split_n,
+                self.split_encoding,
+                passphrase,
+                &SplitOptions {
+                    suite,
+                    padding: self.split_padding,
+                    ..SplitOptions::default()
+                },
+            ),
+        };
+
+        match result {
             Ok((packets, shares)) => {
                 self.split_packets = packets;
                 self.split_shares = shares;
@@ -639,29 +776,83 @@ impl App {
             Some(self.combine_passphrase.as_bytes())
         };
 
-        match combine_shares(&input, self.combine_encoding, passphrase) {
-            Ok((_packets, bytes, used_enc)) => {
-                let recovered = Zeroizing::new(bytes);
+        let result = combine_shares(
+            &input,
+            self.combine_encoding,
+            passphrase,
+            &self.combine_share_passphrases,
+        );
+        self.combine_corrections = match result.as_ref().err().and_then(|e| e.downcast_ref()) {
+            Some(CoreError::MisspelledWords { share, corrections }) => {
+                Some((*share, corrections.clone()))
+            }
+            _ => None,
+        };
+        match result.as_ref().err().and_then(|e| e.downcast_ref()) {
+            Some(&CoreError::ShareLocked { x }) => {
+                self.open_unlock_share(x);
+                self.set_info(format!("share {x} is locked"));
+                return Ok(());
+            }
+            Some(&CoreError::ShareUnlockFailed { x }) => {
+                self.combine_share_passphrases.remove(&x);
+                self.open_unlock_share(x);
+                self.set_err(format!("wrong passphrase for share {x}"));
+                return Ok(());
+            }
+            _ => self.combine_share_passphrases.clear(),
+        }
+
+        match result {
+            Ok(combined) => {
+                let used_enc = combined.encoding;
+                let recovered = Zeroizing::new(combined.secret);
                 let recovered_text = String::from_utf8(recovered.as_slice().to_vec())
                     .ok()
                     .map(Zeroizing::new);
 
                 self.combine_used_encoding = Some(used_enc);
+                self.combine_suggested_name = combined
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.safe_filename())
+                    .map(str::to_string);
                 self.combine_recovered_text = recovered_text;
                 self.combine_recovered = Some(recovered);
-                self.set_ok(format!("combined ok ({})", used_enc.label()));
+                if combined.suspects.is_empty() {
+                    self.set_ok(format!("combined ok ({})", used_enc.label()));
+                } else {
+                    self.set_info(format!(
+                        "combined ok ({}); ignored corrupted share(s) {}",
+                        used_enc.label(),
+                        suspects_label(&combined.suspects)
+                    ));
+                }
             }
             Err(e) => {
                 self.combine_recovered = None;
                 self.combine_recovered_text = None;
                 self.combine_used_encoding = None;
-                self.set_err(format!("combine error: {e}"));
+                self.combine_suggested_name = None;
+                match &self.combine_corrections {
+                    Some((share, _)) => {
+                        self.set_err(format!("combine error: share {share} has words to correct"))
+                    }
+                    None => self.set_err(format!("combine error: {e:#}")),
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Ask for one locked share's own passphrase, then combine again.
+    fn open_unlock_share(&mut self, x: u8) {
+        let mut modal = Modal::new(ModalKind::UnlockShare { x }, "Share passphrase");
+        modal.input.set_mask_char('•');
+        self.modal = Some(modal);
+    }
+
     fn next_tab(&mut self) {
         self.tab = match self.tab {
             TabId::Split => TabId::Combine,
@@ -683,7 +874,10 @@ impl App {
             (TabId::Split, Focus::SplitK) => Focus::SplitN,
             (TabId::Split, Focus::SplitN) => Focus::SplitEncoding,
             (TabId::Split, Focus::SplitEncoding) => Focus::SplitPassphrase,
-            (TabId::Split, Focus::SplitPassphrase) => Focus::SplitShares,
+            (TabId::Split, Focus::SplitPassphrase) => Focus::SplitKdf,
+            (TabId::Split, Focus::SplitKdf) => Focus::SplitCipher,
+            (TabId::Split, Focus::SplitCipher) => Focus::SplitPadding,
+            (TabId::Split, Focus::SplitPadding) => Focus::SplitShares,
             (TabId::Split, Focus::SplitShares) => Focus::SplitSecret,
 
             (TabId::Combine, Focus::CombineShares) => Focus::CombineEncoding,
@@ -702,7 +896,10 @@ impl App {
             (TabId::Split, Focus::SplitN) => Focus::SplitK,
             (TabId::Split, Focus::SplitEncoding) => Focus::SplitN,
             (TabId::Split, Focus::SplitPassphrase) => Focus::SplitEncoding,
-            (TabId::Split, Focus::SplitShares) => Focus::SplitPassphrase,
+            (TabId::Split, Focus::SplitKdf) => Focus::SplitPassphrase,
+            (TabId::Split, Focus::SplitCipher) => Focus::SplitKdf,
+            (TabId::Split, Focus::SplitPadding) => Focus::SplitCipher,
+            (TabId::Split, Focus::SplitShares) => Focus::SplitPadding,
 
             (TabId::Combine, Focus::CombineShares) => Focus::CombinePassphrase,
             (TabId::Combine, Focus::CombineEncoding) => Focus::CombineShares,
@@ -774,7 +971,7 @@ impl App {
     fn render_footer(&self, f: &mut Frame, area: Rect) {
         let shortcuts = match self.tab {
             TabId::Split => {
-                "Enter split • Ctrl+L load • Ctrl+S export • Ctrl+C copy • Tab focus • ? help • Ctrl+Q quit"
+                "Enter split • Ctrl+L load • Ctrl+P policy • Ctrl+S export • Ctrl+C copy • Tab focus • ? help • Ctrl+Q quit"
             }
             TabId::Combine => {
                 "Enter combine • Ctrl+L load • Ctrl+S save • Ctrl+C copy • Tab focus • ? help • Ctrl+Q quit"
@@ -823,7 +1020,7 @@ impl App {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Min(10),
-                Constraint::Length(8),
+                Constraint::Length(10),
                 Constraint::Length(4),
             ])
             .split(layout[0]);
@@ -917,19 +1114,19 @@ impl App {
     }
 
     fn split_settings_table(&self) -> Table<'_> {
-        let rows = vec![
-            settings_row(
-                "k",
-                format!("{}  (↑/↓)", self.split_k),
-                self.focus == Focus::SplitK,
-                self.theme,
+        let (k_label, n_label) = match &self.split_policy {
+            Some((path, _)) => (
+                "policy file (Ctrl+P)".to_string(),
+                path.display().to_string(),
             ),
-            settings_row(
-                "n",
+            None => (
+                format!("{}  (↑/↓)", self.split_k),
                 format!("{}  (↑/↓)", self.split_n),
-                self.focus == Focus::SplitN,
-                self.theme,
             ),
+        };
+        let rows = vec![
+            settings_row("k", k_label, self.focus == Focus::SplitK, self.theme),
+            settings_row("n", n_label, self.focus == Focus::SplitN, self.theme),
             settings_row(
                 "encoding",
                 format!("{}  (↑/↓)", self.split_encoding.label()),
@@ -945,10 +1142,28 @@ impl App {
                 self.focus == Focus::SplitPassphrase,
                 self.theme,
             ),
+            settings_row(
+                "kdf",
+                format!("{}  (↑/↓, Ctrl+K)", kdf_label(&self.split_kdf)),
+                self.focus == Focus::SplitKdf,
+                self.theme,
+            ),
+            settings_row(
+                "cipher",
+                format!("{}  (↑/↓)", self.split_cipher.label()),
+                self.focus == Focus::SplitCipher,
+                self.theme,
+            ),
+            settings_row(
+                "padding",
+                format!("{}  (↑/↓)", self.split_padding),
+                self.focus == Focus::SplitPadding,
+                self.theme,
+            ),
             Row::new(vec![
                 Cell::from(Span::styled("actions", Style::default().fg(self.theme.dim))),
                 Cell::from(Span::styled(
-                    "Enter split • Ctrl+L load file",
+                    "Enter split • Ctrl+L load file • Ctrl+P policy • Ctrl+K kdf",
                     Style::default().fg(self.theme.dim),
                 )),
             ]),
@@ -1026,10 +1241,18 @@ impl App {
             .map(|b| base64::engine::general_purpose::STANDARD.encode(b.as_slice()))
             .unwrap_or_default();
 
-        let text_view = Paragraph::new(utf8_text)
-            .block(self.block("Text view (UTF-8)"))
-            .wrap(Wrap { trim: false });
-        f.render_widget(text_view, right[1]);
+        if let Some((share, corrections)) = &self.combine_corrections {
+            let corrections_view =
+                Paragraph::new(correction_lines(*share, corrections, self.theme))
+                    .block(self.block("Word corrections"))
+                    .wrap(Wrap { trim: false });
+            f.render_widget(corrections_view, right[1]);
+        } else {
+            let text_view = Paragraph::new(utf8_text)
+                .block(self.block("Text view (UTF-8)"))
+                .wrap(Wrap { trim: false });
+            f.render_widget(text_view, right[1]);
+        }
 
         let base64_view = Paragraph::new(base64_text)
             .block(self.block("Bytes view (base64)"))
@@ -1097,6 +1320,8 @@ impl App {
             )),
             Line::from("  Enter: run split/combine"),
             Line::from("  Ctrl+L: load secret/share file(s)"),
+            Line::from("  Ctrl+P: load access policy file for split"),
+            Line::from("  Ctrl+K: set Argon2id costs or calibrate for split"),
             Line::from("  Ctrl+S: save/export"),
             Line::from("  Ctrl+C: copy (UTF-8 if possible, else base64)"),
             Line::from("  Ctrl+V: paste into focused editor"),
@@ -1121,11 +1346,20 @@ impl App {
     fn render_modal(f: &mut Frame, area: Rect, modal: &mut Modal, theme: Theme) {
         f.render_widget(Clear, area);
 
+        let unlock_title;
         let (title, helper) = match modal.kind {
             ModalKind::LoadSecretFile => (
                 "Load secret file",
                 "Paste a path and press Enter (Esc cancels)",
             ),
+            ModalKind::LoadPolicyFile => (
+                "Load access policy",
+                "Paste a policy file path; empty returns to k/n (Esc cancels)",
+            ),
+            ModalKind::SetKdf => (
+                "Argon2id costs",
+                "Profile, MEMORY_KIB ITERATIONS [LANES], or 500ms to calibrate",
+            ),
             ModalKind::LoadShareFiles => {
                 ("Load share files", "One file path per line (Esc cancels)")
             }
@@ -1134,6 +1368,13 @@ impl App {
                 "Enter a directory; exports one file per share",
             ),
             ModalKind::SaveSecretFile => ("Save secret", "Enter file path for recovered bytes"),
+            ModalKind::UnlockShare { x } => {
+                unlock_title = format!("Unlock share {x}");
+                (
+                    unlock_title.as_str(),
+                    "Enter this share's own passphrase; empty skips the share",
+                )
+            }
         };
 
         let outer = Block::default()
@@ -1166,7 +1407,11 @@ impl App {
         modal.input.set_block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" Path ")
+                .title(match modal.kind {
+                    ModalKind::SetKdf => " Value ",
+                    ModalKind::UnlockShare { .. } => " Passphrase ",
+                    _ => " Path ",
+                })
                 .border_style(Style::default().fg(theme.accent)),
         );
         f.render_widget(&modal.input, parts[1]);
@@ -1217,6 +1462,40 @@ fn settings_row(label: &'static str, value: String, active: bool, theme: Theme)
     ])
 }
 
+/// One line per misspelled word: its position, what was typed, and the
+/// likely words, with a mark when the first one passes the checksum.
+fn correction_lines(
+    share: usize,
+    corrections: &[WordCorrection],
+    theme: Theme,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(Span::styled(
+        format!("share {share}"),
+        Style::default().fg(theme.dim),
+    ))];
+    for correction in corrections {
+        let candidates = if correction.candidates.is_empty() {
+            "no close words".to_string()
+        } else {
+            correction.candidates.join(", ")
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!("word {}: ", correction.position),
+                Style::default().fg(theme.dim),
+            ),
+            Span::styled(correction.typed.clone(), Style::default().fg(theme.err)),
+            Span::styled(" → ", Style::default().fg(theme.dim)),
+            Span::styled(candidates, Style::default().fg(theme.accent)),
+        ];
+        if correction.verified {
+            spans.push(Span::styled("  ✓ checksum", Style::default().fg(theme.ok)));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
 fn masked_passphrase(p: &str) -> String {
     if p.is_empty() {
         "(none)".to_string()
@@ -1243,6 +1522,42 @@ fn cycle_encoding(current: Encoding, delta: i32, allowed: &[Encoding]) -> Encodi
     allowed[next]
 }
 
+/// Step through the named profiles; custom costs step from the default.
+fn cycle_kdf(current: KdfParams, delta: i32) -> KdfParams {
+    let profiles = KdfParams::PROFILES;
+    let idx = profiles
+        .iter()
+        .position(|(_, params)| *params == current)
+        .or_else(|| {
+            profiles
+                .iter()
+                .position(|(_, params)| *params == KdfParams::default())
+        })
+        .unwrap_or(0) as i32;
+    let next = (idx + delta).rem_euclid(profiles.len() as i32) as usize;
+    profiles[next].1
+}
+
+fn cycle_cipher(current: AeadAlgorithm, delta: i32) -> AeadAlgorithm {
+    let all = AeadAlgorithm::ALL;
+    let idx = all.iter().position(|aead| *aead == current).unwrap_or(0) as i32;
+    let next = (idx + delta).rem_euclid(all.len() as i32) as usize;
+    all[next]
+}
+
+/// Padding modes offered by the split settings; the CLI takes any size.
+const PADDING_CHOICES: [Padding; 3] = [Padding::None, Padding::PowerOfTwo, Padding::Random(256)];
+
+fn cycle_padding(current: Padding, delta: i32) -> Padding {
+    let all = PADDING_CHOICES;
+    let idx = all
+        .iter()
+        .position(|padding| *padding == current)
+        .unwrap_or(0) as i32;
+    let next = (idx + delta).rem_euclid(all.len() as i32) as usize;
+    all[next]
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1272,6 +1587,7 @@ fn list_state(selected: usize) -> ratatui::widgets::ListState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use safeparts_core::metadata::SecretMetadata;
 
     #[test]
     fn focus_navigation_wraps_within_each_tab() {
@@ -1282,6 +1598,9 @@ mod tests {
             Focus::SplitN,
             Focus::SplitEncoding,
             Focus::SplitPassphrase,
+            Focus::SplitKdf,
+            Focus::SplitCipher,
+            Focus::SplitPadding,
             Focus::SplitShares,
             Focus::SplitSecret,
         ] {
@@ -1321,15 +1640,228 @@ mod tests {
         assert!(app.modal.is_none());
     }
 
+    #[test]
+    fn policy_shortcut_opens_modal_and_empty_path_restores_k_n() {
+        let mut app = App::new();
+
+        app.on_key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(
+            app.modal.as_ref().map(|modal| modal.kind),
+            Some(ModalKind::LoadPolicyFile)
+        );
+
+        app.on_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+        assert!(app.modal.is_none());
+        assert!(app.split_policy.is_none());
+        assert_eq!(
+            app.status.as_ref().map(|status| status.msg.as_str()),
+            Some("using k/n threshold")
+        );
+    }
+
     #[test]
     fn encoding_cycles_wrap_in_both_directions() {
         assert_eq!(
             cycle_encoding(Encoding::Base64url, -1, Encoding::SPLIT),
-            Encoding::MnemoBip39
+            Encoding::Codex32
         );
         assert_eq!(
-            cycle_encoding(Encoding::MnemoBip39, 1, Encoding::SPLIT),
+            cycle_encoding(Encoding::Codex32, 1, Encoding::SPLIT),
             Encoding::Base64url
         );
     }
+
+    #[test]
+    fn kdf_row_cycles_profiles_and_modal_sets_custom_costs() {
+        let mut app = App::new();
+        app.focus = Focus::SplitKdf;
+
+        app.on_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.split_kdf, KdfParams::INTERACTIVE);
+        app.on_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.split_kdf, KdfParams::PARANOID);
+
+        app.on_key(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(
+            app.modal.as_ref().map(|modal| modal.kind),
+            Some(ModalKind::SetKdf)
+        );
+        app.on_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        app.apply_modal(ModalKind::SetKdf, "2048 1".to_string())
+            .unwrap();
+        assert_eq!(app.split_kdf, KdfParams::new(2048, 1, 1).unwrap());
+
+        app.on_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.split_kdf, KdfParams::PARANOID);
+    }
+
+    #[test]
+    fn cipher_row_cycles_and_reaches_split() {
+        let mut app = App::new();
+        app.focus = Focus::SplitCipher;
+
+        app.on_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.split_cipher, AeadAlgorithm::XChaCha20Poly1305);
+        app.on_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE))
+            .unwrap();
+        app.on_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.split_cipher, AeadAlgorithm::Aes256GcmSiv);
+
+        app.split_secret_text = TextArea::from(["cipher"]);
+        app.split_passphrase = Zeroizing::new("pw".to_string());
+        app.split_kdf = KdfParams::new(1024, 1, 1).unwrap();
+        app.do_split().unwrap();
+        assert_eq!(
+            app.split_packets[0].crypto_params.map(|params| params.aead),
+            Some(AeadAlgorithm::Aes256GcmSiv)
+        );
+    }
+
+    #[test]
+    fn save_dialog_suggests_the_stored_filename() {
+        let mut app = App::new();
+        let metadata = SecretMetadata {
+            filename: Some("notes.txt".to_string()),
+            ..SecretMetadata::now()
+        };
+        let packets = safeparts_core::split_secret_with_metadata(
+            b"notes",
+            2,
+            2,
+            None,
+            CipherSuite::default(),
+            &metadata,
+        )
+        .unwrap();
+        let shares: Vec<String> = packets
+            .iter()
+            .map(|packet| {
+                safeparts_core::encoding::encode_packet(
+                    packet,
+                    safeparts_core::encoding::Encoding::Base64url,
+                )
+                .unwrap()
+            })
+            .collect();
+        app.tab = TabId::Combine;
+        app.combine_shares_text = TextArea::from(shares);
+
+        app.do_combine().unwrap();
+        app.on_save();
+        let modal = app.modal.as_ref().unwrap();
+        assert_eq!(modal.kind, ModalKind::SaveSecretFile);
+        assert_eq!(modal.input.lines(), ["notes.txt"]);
+    }
+
+    #[test]
+    fn misspelled_words_are_listed_until_combine_succeeds() {
+        let mut app = App::new();
+        let (_, shares) = split_secret(
+            b"typo",
+            2,
+            2,
+            Encoding::MnemoWords,
+            None,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+        let mut words: Vec<String> = shares[1].split_whitespace().map(str::to_string).collect();
+        let expected = words[3].clone();
+        words[3].insert(1, 'q');
+        app.combine_shares_text =
+            TextArea::from([shares[0].clone(), String::new(), words.join(" ")]);
+
+        app.do_combine().unwrap();
+        let (share, corrections) = app.combine_corrections.as_ref().unwrap();
+        assert_eq!((*share, corrections[0].position), (2, 4));
+        assert_eq!(corrections[0].candidates[0], expected);
+        assert!(
+            app.status
+                .as_ref()
+                .is_some_and(|status| status.msg.contains("share 2"))
+        );
+
+        words[3] = expected;
+        app.combine_shares_text =
+            TextArea::from([shares[0].clone(), String::new(), words.join(" ")]);
+        app.do_combine().unwrap();
+        assert!(app.combine_corrections.is_none());
+        assert!(app.combine_recovered.is_some());
+    }
+
+    #[test]
+    fn locked_share_opens_unlock_modal_until_combine_succeeds() {
+        let mut app = App::new();
+        let (packets, shares) = split_secret(
+            b"locked",
+            2,
+            2,
+            Encoding::Base64url,
+            None,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+        let suite = CipherSuite::from(KdfParams::new(1024, 1, 1).unwrap());
+        let locked = safeparts_core::lock_share(&packets[1], b"pin", suite).unwrap();
+        let locked = safeparts_core::encoding::encode_packet(
+            &locked,
+            safeparts_core::encoding::Encoding::Base64url,
+        )
+        .unwrap();
+        app.combine_shares_text = TextArea::from([shares[0].clone(), locked]);
+
+        app.do_combine().unwrap();
+        assert_eq!(
+            app.modal.as_ref().map(|modal| modal.kind),
+            Some(ModalKind::UnlockShare { x: 2 })
+        );
+
+        app.modal = None;
+        app.apply_modal(ModalKind::UnlockShare { x: 2 }, "wrong".to_string())
+            .unwrap();
+        assert_eq!(
+            app.modal.as_ref().map(|modal| modal.kind),
+            Some(ModalKind::UnlockShare { x: 2 })
+        );
+        assert!(app.combine_recovered.is_none());
+
+        app.modal = None;
+        app.apply_modal(ModalKind::UnlockShare { x: 2 }, "pin".to_string())
+            .unwrap();
+        assert!(app.modal.is_none());
+        assert_eq!(
+            app.combine_recovered
+                .as_ref()
+                .map(|secret| secret.as_slice()),
+            Some(b"locked".as_slice())
+        );
+        assert!(app.combine_share_passphrases.is_empty());
+    }
+
+    #[test]
+    fn padding_row_cycles_and_hides_secret_length() {
+        let mut app = App::new();
+        app.focus = Focus::SplitPadding;
+        app.on_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.split_padding, Padding::PowerOfTwo);
+
+        app.split_secret_text = TextArea::from(["pin"]);
+        app.do_split().unwrap();
+        let short = app.split_shares[0].len();
+        assert!(app.split_packets[0].padded);
+
+        app.split_secret_text = TextArea::from(["a somewhat longer secret"]);
+        app.do_split().unwrap();
+        assert_eq!(app.split_shares[0].len(), short);
+    }
 }
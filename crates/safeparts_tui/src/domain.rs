@@ -1,6 +1,17 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
 use anyhow::{Context, Result, anyhow};
+use safeparts_core::SplitOptions;
+use safeparts_core::codex32;
+use safeparts_core::crypto::{self, CipherSuite, KdfParams};
 use safeparts_core::encoding as core_encoding;
+use safeparts_core::metadata::SecretMetadata;
 use safeparts_core::packet::SharePacket;
+use safeparts_core::policy::Policy;
+use zeroize::Zeroizing;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Encoding {
@@ -9,6 +20,7 @@ pub enum Encoding {
     Base58check,
     MnemoWords,
     MnemoBip39,
+    Codex32,
 }
 
 impl Encoding {
@@ -18,6 +30,7 @@ impl Encoding {
         Encoding::Base58check,
         Encoding::MnemoWords,
         Encoding::MnemoBip39,
+        Encoding::Codex32,
     ];
 
     pub const SPLIT: &'static [Encoding] = &[
@@ -25,19 +38,23 @@ impl Encoding {
         Encoding::Base58check,
         Encoding::MnemoWords,
         Encoding::MnemoBip39,
+        Encoding::Codex32,
     ];
 
     pub fn label(self) -> &'static str {
-        self.core().label()
+        self.core()
+            .map_or("codex32", core_encoding::Encoding::label)
     }
 
-    fn core(self) -> core_encoding::Encoding {
+    /// The share packet encoding; codex32 strings are not packets.
+    fn core(self) -> Option<core_encoding::Encoding> {
         match self {
-            Encoding::Auto => core_encoding::Encoding::Auto,
-            Encoding::Base64url => core_encoding::Encoding::Base64url,
-            Encoding::Base58check => core_encoding::Encoding::Base58check,
-            Encoding::MnemoWords => core_encoding::Encoding::MnemoWords,
-            Encoding::MnemoBip39 => core_encoding::Encoding::MnemoBip39,
+            Encoding::Auto => Some(core_encoding::Encoding::Auto),
+            Encoding::Base64url => Some(core_encoding::Encoding::Base64url),
+            Encoding::Base58check => Some(core_encoding::Encoding::Base58check),
+            Encoding::MnemoWords => Some(core_encoding::Encoding::MnemoWords),
+            Encoding::MnemoBip39 => Some(core_encoding::Encoding::MnemoBip39),
+            Encoding::Codex32 => None,
         }
     }
 
@@ -59,31 +76,179 @@ pub fn split_secret(
     n: u8,
     encoding: Encoding,
     passphrase: Option<&[u8]>,
+    options: &SplitOptions,
 ) -> Result<(Vec<SharePacket>, Vec<String>)> {
-    let packets = safeparts_core::split_secret(secret, k, n, passphrase)
+    let Some(packet_encoding) = encoding.core() else {
+        if passphrase.is_some() || !options.padding.is_none() {
+            return Err(anyhow!("codex32 strings take no passphrase or padding"));
+        }
+        let shares = codex32::split(secret, k, n, &codex32::random_identifier())
+            .map_err(|e| anyhow!(e))
+            .with_context(|| format!("split failed (k={k}, n={n})"))?;
+        return Ok((Vec::new(), shares.iter().map(ToString::to_string).collect()));
+    };
+
+    let packets = safeparts_core::split_secret_with_options(secret, k, n, passphrase, options)
         .map_err(|e| anyhow!(e))
         .with_context(|| format!("split failed (k={k}, n={n})"))?;
 
     let shares = packets
         .iter()
-        .map(|packet| core_encoding::encode_packet(packet, encoding.core()).map_err(|e| anyhow!(e)))
+        .map(|packet| core_encoding::encode_packet(packet, packet_encoding).map_err(|e| anyhow!(e)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((packets, shares))
+}
+
+pub fn load_policy(path: &Path) -> Result<Policy> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("read policy {}", path.display()))?;
+    Policy::parse(&text).map_err(|e| anyhow!(e))
+}
+
+pub fn split_secret_with_policy(
+    secret: &[u8],
+    policy: &Policy,
+    encoding: Encoding,
+    passphrase: Option<&[u8]>,
+    suite: CipherSuite,
+) -> Result<(Vec<SharePacket>, Vec<String>)> {
+    let packet_encoding = encoding
+        .core()
+        .ok_or_else(|| anyhow!("codex32 strings cannot hold policy shares"))?;
+    let packets = safeparts_core::split_secret_with_policy(secret, policy, passphrase, suite)
+        .map_err(|e| anyhow!(e))
+        .context("split failed")?;
+
+    let shares = packets
+        .iter()
+        .map(|packet| core_encoding::encode_packet(packet, packet_encoding).map_err(|e| anyhow!(e)))
         .collect::<Result<Vec<_>>>()?;
 
     Ok((packets, shares))
 }
 
+/// Name of the policy holder a packet belongs to, if it comes from a policy split.
+pub fn holder_name(packet: &SharePacket) -> Option<&str> {
+    let placement = packet.policy.as_ref()?;
+    placement.policy.holder_at(&placement.path)
+}
+
+pub struct Combined {
+    pub secret: Vec<u8>,
+    pub encoding: Encoding,
+    /// Indexes of corrupted shares that were ignored.
+    pub suspects: Vec<u8>,
+    /// Filename, label, and the like stored with the secret.
+    pub metadata: Option<SecretMetadata>,
+}
+
+/// Passphrases typed for locked shares, by share index. An empty passphrase
+/// skips that share.
+pub type SharePassphrases = BTreeMap<u8, Zeroizing<String>>;
+
+/// Combine pasted shares, unlocking locked ones with `share_passphrases`.
+///
+/// A locked share without an entry fails with
+/// [`CoreError::ShareLocked`](safeparts_core::CoreError::ShareLocked), so the
+/// caller can ask for that share's passphrase and try again.
 pub fn combine_shares(
     input: &str,
     encoding: Encoding,
     passphrase: Option<&[u8]>,
-) -> Result<(Vec<SharePacket>, Vec<u8>, Encoding)> {
-    let parsed = core_encoding::parse_share_packets_wrapped_mnemonics(input, encoding.core())
+    share_passphrases: &SharePassphrases,
+) -> Result<Combined> {
+    // Pasted codex32 strings are what no packet encoding reads.
+    let packet_encoding = match encoding.core() {
+        Some(core_encoding::Encoding::Auto)
+            if !matches!(core_encoding::detect_encoding(input), Ok(Some(_)))
+                && codex32::looks_like(input) =>
+        {
+            None
+        }
+        packet_encoding => packet_encoding,
+    };
+    let Some(packet_encoding) = packet_encoding else {
+        if passphrase.is_some() {
+            return Err(anyhow!("codex32 strings take no passphrase"));
+        }
+        let shares = codex32::parse_shares(input).map_err(|e| anyhow!(e))?;
+        let secret = codex32::combine(&shares)
+            .map_err(|e| anyhow!(e))
+            .context("combine failed")?;
+        return Ok(Combined {
+            secret: secret.to_vec(),
+            encoding: Encoding::Codex32,
+            suspects: Vec::new(),
+            metadata: None,
+        });
+    };
+
+    let parsed = core_encoding::parse_share_packets_wrapped_mnemonics(input, packet_encoding)
         .map_err(|e| anyhow!(e))?;
-    let secret = safeparts_core::combine_shares(&parsed.packets, passphrase)
+    if let Some(packet) = parsed
+        .packets
+        .iter()
+        .find(|p| p.is_locked() && !share_passphrases.contains_key(&p.x))
+    {
+        return Err(anyhow!(safeparts_core::CoreError::ShareLocked {
+            x: packet.x
+        }));
+    }
+    let packets = safeparts_core::unlock_shares(&parsed.packets, |packet| {
+        share_passphrases
+            .get(&packet.x)
+            .filter(|p| !p.is_empty())
+            .map(|p| Zeroizing::new(p.as_bytes().to_vec()))
+    })
+    .map_err(|e| anyhow!(e))?;
+    let recovery = safeparts_core::combine_shares_robust(&packets, passphrase)
         .map_err(|e| anyhow!(e))
         .context("combine failed")?;
 
-    Ok((parsed.packets, secret, Encoding::from_core(parsed.encoding)))
+    Ok(Combined {
+        secret: recovery.secret,
+        encoding: Encoding::from_core(parsed.encoding),
+        suspects: recovery.suspects,
+        metadata: recovery.metadata,
+    })
+}
+
+pub fn suspects_label(suspects: &[u8]) -> String {
+    suspects
+        .iter()
+        .map(|x| format!("#{x}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parse Argon2id settings typed into the TUI.
+///
+/// Accepts a profile name, `MEMORY_KIB ITERATIONS [LANES]`, or a target such
+/// as `500ms` to calibrate on this machine.
+pub fn parse_kdf(text: &str) -> Result<KdfParams> {
+    let text = text.trim();
+    if let Some(ms) = text.strip_suffix("ms") {
+        let ms: u64 = ms.trim().parse().context("calibration target in ms")?;
+        return crypto::calibrate(Duration::from_millis(ms), KdfParams::PARANOID.mem_cost_kib)
+            .map_err(|e| anyhow!(e));
+    }
+    text.parse()
+        .map_err(|e: safeparts_core::CoreError| anyhow!(e))
+}
+
+pub fn kdf_label(kdf: &KdfParams) -> String {
+    let memory = if kdf.mem_cost_kib.is_multiple_of(1024) {
+        format!("{} MiB", kdf.mem_cost_kib / 1024)
+    } else {
+        format!("{} KiB", kdf.mem_cost_kib)
+    };
+    format!(
+        "{} ({memory}, t={}, p={})",
+        kdf.profile_name().unwrap_or("custom"),
+        kdf.time_cost,
+        kdf.parallelism
+    )
 }
 
 pub fn set_id_hex(packets: &[SharePacket]) -> Option<String> {
@@ -99,6 +264,159 @@ pub fn set_id_hex(packets: &[SharePacket]) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn combine_reports_ignored_corrupted_shares() {
+        let (mut packets, _) = split_secret(
+            b"tui",
+            2,
+            4,
+            Encoding::Base64url,
+            None,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+        packets[0].payload[1] ^= 0x01;
+        let input = packets
+            .iter()
+            .map(|p| core_encoding::encode_packet(p, core_encoding::Encoding::Base64url).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let combined =
+            combine_shares(&input, Encoding::Auto, None, &SharePassphrases::new()).unwrap();
+        assert_eq!(combined.secret, b"tui");
+        assert_eq!(suspects_label(&combined.suspects), "#1");
+    }
+
+    #[test]
+    fn combine_names_missing_policy_groups() {
+        let policy = Policy::parse("all of (ops: 2 of (a, b, c), legal: any of (d, e))").unwrap();
+        let (packets, shares) = split_secret_with_policy(
+            b"tui",
+            &policy,
+            Encoding::Base64url,
+            None,
+            CipherSuite::default(),
+        )
+        .unwrap();
+        assert_eq!(holder_name(&packets[3]), Some("d"));
+
+        let err = combine_shares(
+            &shares[..3].join("\n"),
+            Encoding::Auto,
+            None,
+            &SharePassphrases::new(),
+        )
+        .err()
+        .unwrap();
+        assert!(format!("{err:#}").contains("legal: 1 more of (d, e)"));
+
+        let input = [shares[0].as_str(), shares[1].as_str(), shares[4].as_str()].join("\n");
+        let combined =
+            combine_shares(&input, Encoding::Auto, None, &SharePassphrases::new()).unwrap();
+        assert_eq!(combined.secret, b"tui");
+    }
+
+    #[test]
+    fn combine_asks_for_locked_shares_and_skips_empty_passphrases() {
+        let (packets, shares) = split_secret(
+            b"tui",
+            2,
+            3,
+            Encoding::Base64url,
+            None,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+        let suite = CipherSuite::from(KdfParams::new(1024, 1, 1).unwrap());
+        let locked = safeparts_core::lock_share(&packets[0], b"pin", suite).unwrap();
+        let locked =
+            core_encoding::encode_packet(&locked, core_encoding::Encoding::Base64url).unwrap();
+        let input = [locked.as_str(), shares[1].as_str(), shares[2].as_str()].join("\n");
+
+        let mut passphrases = SharePassphrases::new();
+        let err = combine_shares(&input, Encoding::Auto, None, &passphrases)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(safeparts_core::CoreError::ShareLocked { x: 1 })
+        ));
+
+        passphrases.insert(1, Zeroizing::new("nope".to_string()));
+        let err = combine_shares(&input, Encoding::Auto, None, &passphrases)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(safeparts_core::CoreError::ShareUnlockFailed { x: 1 })
+        ));
+
+        passphrases.insert(1, Zeroizing::new("pin".to_string()));
+        let combined = combine_shares(&input, Encoding::Auto, None, &passphrases).unwrap();
+        assert_eq!(combined.secret, b"tui");
+
+        passphrases.insert(1, Zeroizing::new(String::new()));
+        let combined = combine_shares(&input, Encoding::Auto, None, &passphrases).unwrap();
+        assert_eq!(combined.secret, b"tui");
+    }
+
+    #[test]
+    fn kdf_settings_parse_profiles_and_custom_costs() {
+        assert_eq!(parse_kdf("Interactive").unwrap(), KdfParams::INTERACTIVE);
+        assert_eq!(
+            parse_kdf("32768, 2").unwrap(),
+            KdfParams::new(32768, 2, 1).unwrap()
+        );
+        assert_eq!(
+            kdf_label(&parse_kdf("1000 1 2").unwrap()),
+            "custom (1000 KiB, t=1, p=2)"
+        );
+        assert_eq!(
+            kdf_label(&KdfParams::MODERATE),
+            "moderate (64 MiB, t=3, p=1)"
+        );
+        assert!(parse_kdf("fast").is_err());
+        assert!(parse_kdf("65536").is_err());
+        assert!(parse_kdf("0ms").is_err());
+    }
+
+    #[test]
+    fn codex32_splits_and_combines_without_packets() {
+        let secret = [0x42; 16];
+        let (packets, shares) = split_secret(
+            &secret,
+            2,
+            3,
+            Encoding::Codex32,
+            None,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+        assert!(packets.is_empty());
+        let combined = combine_shares(
+            &shares[1..].join("\n"),
+            Encoding::Auto,
+            None,
+            &SharePassphrases::new(),
+        )
+        .unwrap();
+        assert_eq!(combined.secret, secret);
+        assert_eq!(combined.encoding, Encoding::Codex32);
+
+        assert!(
+            split_secret(
+                &secret,
+                2,
+                3,
+                Encoding::Codex32,
+                Some(b"pw"),
+                &SplitOptions::default(),
+            )
+            .is_err()
+        );
+    }
+
     #[test]
     fn labels_use_core_canonical_names() {
         assert_eq!(Encoding::Base64url.label(), "base64url");
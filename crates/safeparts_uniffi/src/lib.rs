@@ -1,9 +1,15 @@
 use safeparts_core::{
-    CoreError,
+    CoreError, SplitOptions, codex32,
+    crypto::{self, AeadAlgorithm, CipherSuite, KdfParams},
     encoding::{self, Encoding},
     packet::SharePacket,
+    padding::Padding,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    time::Duration,
 };
-use std::{collections::HashSet, ops::Deref};
 use zeroize::{Zeroize, Zeroizing};
 
 struct SensitivePackets(Vec<SharePacket>);
@@ -37,6 +43,36 @@ pub enum ShareEncoding {
     Base58check,
     MnemoWords,
     MnemoBip39,
+    Codex32,
+}
+
+/// Argon2id costs for passphrase-protected splits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum KdfProfile {
+    Interactive,
+    Moderate,
+    Paranoid,
+    Custom {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+/// AEAD for passphrase-protected splits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum ShareCipher {
+    ChaCha20Poly1305,
+    XChaCha20Poly1305,
+    Aes256GcmSiv,
+}
+
+/// Length-hiding padding added before splitting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum SharePadding {
+    PowerOfTwo,
+    Fixed { size: u32 },
+    Random { max: u32 },
 }
 
 #[derive(Debug, uniffi::Error)]
@@ -52,6 +88,10 @@ pub enum BridgeError {
     IncorrectPassphrase,
     IntegrityFailure,
     Internal,
+    InvalidKdfParameters,
+    ShareLocked,
+    IncorrectSharePassphrase,
+    InvalidPadding,
 }
 impl std::fmt::Display for BridgeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -67,6 +107,10 @@ impl std::fmt::Display for BridgeError {
             Self::IncorrectPassphrase => "The passphrase is incorrect.",
             Self::IntegrityFailure => "Recovery share integrity verification failed.",
             Self::Internal => "Safeparts could not complete the operation.",
+            Self::InvalidKdfParameters => "Invalid passphrase key-derivation costs.",
+            Self::ShareLocked => "A recovery share is locked with its own passphrase.",
+            Self::IncorrectSharePassphrase => "A share passphrase is incorrect.",
+            Self::InvalidPadding => "The secret does not fit the chosen padding.",
         })
     }
 }
@@ -86,8 +130,10 @@ pub struct Inspection {
     pub provided_count: u32,
     pub encrypted: bool,
     pub indexes: Vec<u8>,
+    pub locked_indexes: Vec<u8>,
     pub consistent: bool,
     pub ready: bool,
+    pub stealth: bool,
 }
 #[derive(Clone, Debug, uniffi::Record)]
 pub struct Recovery {
@@ -98,15 +144,44 @@ pub struct Recovery {
     pub encrypted: bool,
     pub indexes: Vec<u8>,
     pub set_id: String,
+    pub suspect_indexes: Vec<u8>,
 }
 
-fn core_encoding(value: ShareEncoding) -> Encoding {
+fn core_kdf(value: KdfProfile) -> Result<KdfParams, BridgeError> {
+    match value {
+        KdfProfile::Interactive => Ok(KdfParams::INTERACTIVE),
+        KdfProfile::Moderate => Ok(KdfParams::MODERATE),
+        KdfProfile::Paranoid => Ok(KdfParams::PARANOID),
+        KdfProfile::Custom {
+            memory_kib,
+            iterations,
+            parallelism,
+        } => KdfParams::new(memory_kib, iterations, parallelism).map_err(map_error),
+    }
+}
+fn core_cipher(value: ShareCipher) -> AeadAlgorithm {
     match value {
-        ShareEncoding::Auto => Encoding::Auto,
-        ShareEncoding::Base64url => Encoding::Base64url,
-        ShareEncoding::Base58check => Encoding::Base58check,
-        ShareEncoding::MnemoWords => Encoding::MnemoWords,
-        ShareEncoding::MnemoBip39 => Encoding::MnemoBip39,
+        ShareCipher::ChaCha20Poly1305 => AeadAlgorithm::ChaCha20Poly1305,
+        ShareCipher::XChaCha20Poly1305 => AeadAlgorithm::XChaCha20Poly1305,
+        ShareCipher::Aes256GcmSiv => AeadAlgorithm::Aes256GcmSiv,
+    }
+}
+fn core_padding(value: SharePadding) -> Padding {
+    match value {
+        SharePadding::PowerOfTwo => Padding::PowerOfTwo,
+        SharePadding::Fixed { size } => Padding::Fixed(size as usize),
+        SharePadding::Random { max } => Padding::Random(max as usize),
+    }
+}
+/// The share packet encoding; codex32 strings are not packets.
+fn core_encoding(value: ShareEncoding) -> Result<Encoding, BridgeError> {
+    match value {
+        ShareEncoding::Auto => Ok(Encoding::Auto),
+        ShareEncoding::Base64url => Ok(Encoding::Base64url),
+        ShareEncoding::Base58check => Ok(Encoding::Base58check),
+        ShareEncoding::MnemoWords => Ok(Encoding::MnemoWords),
+        ShareEncoding::MnemoBip39 => Ok(Encoding::MnemoBip39),
+        ShareEncoding::Codex32 => Err(BridgeError::InvalidEncoding),
     }
 }
 fn bridge_encoding(value: Encoding) -> ShareEncoding {
@@ -122,18 +197,33 @@ fn bridge_encoding(value: Encoding) -> ShareEncoding {
 fn map_error(error: CoreError) -> BridgeError {
     match error {
         CoreError::InvalidKAndN { .. } => BridgeError::InvalidParameters,
+        CoreError::InvalidKdfParams(_) => BridgeError::InvalidKdfParameters,
         CoreError::EmptyShareInput => BridgeError::EmptyInput,
-        CoreError::NotEnoughShares { .. } => BridgeError::InsufficientShares,
+        CoreError::NotEnoughShares { .. }
+        | CoreError::PolicyNotSatisfied { .. }
+        | CoreError::StealthNotRecovered { .. } => BridgeError::InsufficientShares,
         CoreError::DuplicateX { .. } => BridgeError::DuplicateShares,
-        CoreError::InconsistentMetadata | CoreError::CryptoParamsMismatch => {
-            BridgeError::MixedShares
+        CoreError::InconsistentMetadata
+        | CoreError::CryptoParamsMismatch
+        | CoreError::CommitmentsMismatch
+        | CoreError::EpochMismatch { .. }
+        | CoreError::HeaderMismatch(_) => BridgeError::MixedShares,
+        CoreError::PassphraseRequired | CoreError::NewPassphraseRequired => {
+            BridgeError::PassphraseRequired
         }
-        CoreError::PassphraseRequired => BridgeError::PassphraseRequired,
         CoreError::DecryptFailed => BridgeError::IncorrectPassphrase,
-        CoreError::IntegrityCheckFailed => BridgeError::IntegrityFailure,
+        CoreError::ShareLocked { .. } => BridgeError::ShareLocked,
+        CoreError::ShareUnlockFailed { .. } => BridgeError::IncorrectSharePassphrase,
+        CoreError::IntegrityCheckFailed
+        | CoreError::UncorrectableShares
+        | CoreError::ShareVerificationFailed { .. } => BridgeError::IntegrityFailure,
         CoreError::AutoEncodingForOutput => BridgeError::InvalidEncoding,
+        CoreError::InvalidPadding(_) => BridgeError::InvalidPadding,
         CoreError::InvalidPacket(_)
         | CoreError::Encoding(_)
+        | CoreError::Codex32(_)
+        | CoreError::MisspelledWords { .. }
+        | CoreError::InvalidCompression(_)
         | CoreError::CouldNotDetectEncoding
         | CoreError::UnknownEncoding(_) => BridgeError::MalformedShares,
         _ => BridgeError::Internal,
@@ -144,10 +234,34 @@ fn parse(
     selected: ShareEncoding,
 ) -> Result<(SensitivePackets, Encoding), BridgeError> {
     let guarded = Zeroizing::new(input);
-    let parsed = encoding::parse_share_packets_wrapped_mnemonics(&guarded, core_encoding(selected))
-        .map_err(map_error)?;
+    let parsed =
+        encoding::parse_share_packets_wrapped_mnemonics(&guarded, core_encoding(selected)?)
+            .map_err(map_error)?;
     Ok((SensitivePackets(parsed.packets), parsed.encoding))
 }
+/// The codex32 strings in `input` when `selected` is `Codex32` or detection
+/// finds them. They are not share packets, so they skip [`parse`].
+fn codex32_shares(
+    input: &str,
+    selected: ShareEncoding,
+) -> Result<Option<Vec<codex32::Share>>, BridgeError> {
+    let is_codex32 = match selected {
+        ShareEncoding::Codex32 => true,
+        ShareEncoding::Auto => {
+            !matches!(encoding::detect_encoding(input), Ok(Some(_))) && codex32::looks_like(input)
+        }
+        _ => false,
+    };
+    if !is_codex32 {
+        return Ok(None);
+    }
+    codex32::parse_shares(input).map(Some).map_err(map_error)
+}
+fn codex32_indexes(shares: &[codex32::Share]) -> Vec<u8> {
+    let mut indexes: Vec<_> = shares.iter().map(codex32::Share::index_value).collect();
+    indexes.sort_unstable();
+    indexes
+}
 fn set_id(packet: &SharePacket) -> String {
     packet.set_id.0.iter().map(|b| format!("{b:02x}")).collect()
 }
@@ -162,25 +276,63 @@ fn consistent(packets: &[SharePacket]) -> bool {
     })
 }
 
-#[uniffi::export]
+/// `kdf` sets the Argon2id costs for `passphrase`; `None` uses `Moderate`.
+/// `cipher` picks the AEAD; `None` uses ChaCha20-Poly1305.
+/// `padding` hides the secret's length; `None` adds no padding.
+#[uniffi::export(default(kdf = None, cipher = None, padding = None))]
+#[allow(clippy::too_many_arguments)]
 pub fn split_secret(
     secret: Vec<u8>,
     threshold: u8,
     share_count: u8,
     selected: ShareEncoding,
     passphrase: Option<String>,
+    kdf: Option<KdfProfile>,
+    cipher: Option<ShareCipher>,
+    padding: Option<SharePadding>,
 ) -> Result<Vec<EncodedShare>, BridgeError> {
     if matches!(selected, ShareEncoding::Auto) {
         return Err(BridgeError::InvalidEncoding);
     }
+    if matches!(selected, ShareEncoding::Codex32) {
+        if passphrase.is_some() || padding.is_some() {
+            return Err(BridgeError::InvalidParameters);
+        }
+        let secret = Zeroizing::new(secret);
+        let shares = codex32::split(
+            &secret,
+            threshold,
+            share_count,
+            &codex32::random_identifier(),
+        )
+        .map_err(map_error)?;
+        return Ok(shares
+            .iter()
+            .map(|share| EncodedShare {
+                text: share.to_string(),
+                index: share.index_value(),
+                share_count,
+                set_id: share.identifier(),
+            })
+            .collect());
+    }
+    let options = SplitOptions {
+        suite: CipherSuite {
+            kdf: kdf.map(core_kdf).transpose()?.unwrap_or_default(),
+            aead: cipher.map(core_cipher).unwrap_or_default(),
+        },
+        padding: padding.map(core_padding).unwrap_or_default(),
+        ..SplitOptions::default()
+    };
     let secret = Zeroizing::new(secret);
     let passphrase = passphrase.map(Zeroizing::new);
     let packets = SensitivePackets(
-        safeparts_core::split_secret(
+        safeparts_core::split_secret_with_options(
             &secret,
             threshold,
             share_count,
             passphrase.as_deref().map(|value| value.as_bytes()),
+            &options,
         )
         .map_err(map_error)?,
     );
@@ -188,7 +340,7 @@ pub fn split_secret(
         .iter()
         .map(|p| {
             Ok(EncodedShare {
-                text: encoding::encode_packet(p, core_encoding(selected)).map_err(map_error)?,
+                text: encoding::encode_packet(p, core_encoding(selected)?).map_err(map_error)?,
                 index: p.x,
                 share_count: p.n,
                 set_id: set_id(p),
@@ -197,59 +349,169 @@ pub fn split_secret(
         .collect()
 }
 
+/// Pick Argon2id costs that take about `target_ms` to derive on this device,
+/// using at most `max_memory_kib`. Blocks for a few times `target_ms`.
+#[uniffi::export]
+pub fn calibrate_kdf(target_ms: u32, max_memory_kib: u32) -> Result<KdfProfile, BridgeError> {
+    let kdf = crypto::calibrate(Duration::from_millis(target_ms.into()), max_memory_kib)
+        .map_err(map_error)?;
+    Ok(KdfProfile::Custom {
+        memory_kib: kdf.mem_cost_kib,
+        iterations: kdf.time_cost,
+        parallelism: kdf.parallelism,
+    })
+}
+
 #[uniffi::export]
 pub fn inspect_share_input(
     input: String,
     selected: ShareEncoding,
 ) -> Result<Inspection, BridgeError> {
-    let (packets, detected) = parse(input, selected)?;
+    let input = Zeroizing::new(input);
+    if let Some(shares) = codex32_shares(&input, selected)? {
+        // Codex32 strings do not record the share count.
+        let first = &shares[0];
+        let consistent = shares.iter().all(|share| {
+            share.threshold() == first.threshold() && share.identifier() == first.identifier()
+        });
+        let indexes = codex32_indexes(&shares);
+        let unique = indexes.windows(2).all(|pair| pair[0] != pair[1]);
+        let enough = shares.iter().any(codex32::Share::is_secret)
+            || shares.len() >= first.threshold() as usize;
+        return Ok(Inspection {
+            detected_encoding: ShareEncoding::Codex32,
+            threshold: first.threshold(),
+            share_count: 0,
+            provided_count: shares.len() as u32,
+            encrypted: false,
+            indexes,
+            locked_indexes: Vec::new(),
+            consistent,
+            ready: consistent && unique && enough,
+            stealth: false,
+        });
+    }
+    let (packets, detected) = parse(input.to_string(), selected)?;
     let first = packets.first().ok_or(BridgeError::EmptyInput)?;
     let consistent = consistent(&packets);
     let unique = packets.iter().map(|p| p.x).collect::<HashSet<_>>().len() == packets.len();
     let mut indexes: Vec<_> = packets.iter().map(|p| p.x).collect();
     indexes.sort_unstable();
+    let mut locked_indexes: Vec<_> = packets
+        .iter()
+        .filter(|p| p.is_locked())
+        .map(|p| p.x)
+        .collect();
+    locked_indexes.sort_unstable();
     Ok(Inspection {
         detected_encoding: bridge_encoding(detected),
         threshold: first.k,
         share_count: first.n,
         provided_count: packets.len() as u32,
-        encrypted: first.crypto_params.is_some(),
+        encrypted: first.is_encrypted(),
         indexes,
+        locked_indexes,
         consistent,
         ready: consistent && unique && packets.len() >= first.k as usize,
+        stealth: first.stealth,
     })
 }
 
-#[uniffi::export]
+/// Lock one share with its custodian's own passphrase. The locked share keeps
+/// the detected encoding when `selected` is `Auto`.
+#[uniffi::export(default(kdf = None, cipher = None))]
+pub fn lock_share(
+    share: String,
+    selected: ShareEncoding,
+    passphrase: String,
+    kdf: Option<KdfProfile>,
+    cipher: Option<ShareCipher>,
+) -> Result<String, BridgeError> {
+    let suite = CipherSuite {
+        kdf: kdf.map(core_kdf).transpose()?.unwrap_or_default(),
+        aead: cipher.map(core_cipher).unwrap_or_default(),
+    };
+    let passphrase = Zeroizing::new(passphrase);
+    let (packets, detected) = parse(share, selected)?;
+    let [packet] = &*packets else {
+        return Err(BridgeError::MalformedShares);
+    };
+    let locked = SensitivePackets(vec![
+        safeparts_core::lock_share(packet, passphrase.as_bytes(), suite).map_err(map_error)?,
+    ]);
+    encoding::encode_packet(&locked[0], detected).map_err(map_error)
+}
+
+/// `share_passphrases` maps a locked share's index to its own passphrase.
+/// Locked shares without an entry fail with `ShareLocked`;
+/// `Inspection::locked_indexes` lists which ones to ask for.
+#[uniffi::export(default(share_passphrases = None))]
 pub fn combine_share_input(
     input: String,
     selected: ShareEncoding,
     passphrase: Option<String>,
+    share_passphrases: Option<HashMap<u8, String>>,
 ) -> Result<Recovery, BridgeError> {
-    let (packets, detected) = parse(input, selected)?;
+    let input = Zeroizing::new(input);
+    if let Some(shares) = codex32_shares(&input, selected)? {
+        if passphrase.is_some_and(|passphrase| !passphrase.is_empty()) {
+            return Err(BridgeError::InvalidParameters);
+        }
+        let secret = codex32::combine(&shares).map_err(map_error)?;
+        return Ok(Recovery {
+            bytes: secret.to_vec(),
+            detected_encoding: ShareEncoding::Codex32,
+            threshold: shares[0].threshold(),
+            share_count: 0,
+            encrypted: false,
+            indexes: codex32_indexes(&shares),
+            set_id: shares[0].identifier(),
+            suspect_indexes: Vec::new(),
+        });
+    }
+    let (packets, detected) = parse(input.to_string(), selected)?;
+    let share_passphrases = share_passphrases.map(|map| {
+        map.into_iter()
+            .map(|(x, value)| (x, Zeroizing::new(value)))
+            .collect::<HashMap<_, _>>()
+    });
+    let packets = if packets.iter().any(SharePacket::is_locked) {
+        let unlocked = packets
+            .iter()
+            .map(|p| {
+                if !p.is_locked() {
+                    return Ok(p.clone());
+                }
+                let passphrase = share_passphrases
+                    .as_ref()
+                    .and_then(|map| map.get(&p.x))
+                    .ok_or(BridgeError::ShareLocked)?;
+                safeparts_core::unlock_share(p, passphrase.as_bytes()).map_err(map_error)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        SensitivePackets(unlocked)
+    } else {
+        packets
+    };
     let first = packets.first().ok_or(BridgeError::EmptyInput)?;
-    let metadata = (
-        first.k,
-        first.n,
-        first.crypto_params.is_some(),
-        set_id(first),
-    );
+    let metadata = (first.k, first.n, first.is_encrypted(), set_id(first));
     let mut indexes: Vec<_> = packets.iter().map(|p| p.x).collect();
     indexes.sort_unstable();
     let passphrase = passphrase.map(Zeroizing::new);
-    let bytes = safeparts_core::combine_shares(
+    let recovery = safeparts_core::combine_shares_robust(
         &packets,
         passphrase.as_deref().map(|value| value.as_bytes()),
     )
     .map_err(map_error)?;
     Ok(Recovery {
-        bytes,
+        bytes: recovery.secret,
         detected_encoding: bridge_encoding(detected),
         threshold: metadata.0,
         share_count: metadata.1,
         encrypted: metadata.2,
         indexes,
         set_id: metadata.3,
+        suspect_indexes: recovery.suspects,
     })
 }
 uniffi::setup_scaffolding!();
@@ -258,13 +520,22 @@ uniffi::setup_scaffolding!();
 mod tests {
     use super::*;
     fn joined(e: ShareEncoding, pass: Option<&str>) -> String {
-        split_secret(vec![0, 255, 1, 2], 2, 3, e, pass.map(str::to_owned))
-            .unwrap()
-            .into_iter()
-            .take(2)
-            .map(|s| s.text)
-            .collect::<Vec<_>>()
-            .join("\n\n")
+        split_secret(
+            vec![0, 255, 1, 2],
+            2,
+            3,
+            e,
+            pass.map(str::to_owned),
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .into_iter()
+        .take(2)
+        .map(|s| s.text)
+        .collect::<Vec<_>>()
+        .join("\n\n")
     }
     #[test]
     fn every_encoding_binary_round_trip_and_auto() {
@@ -275,7 +546,7 @@ mod tests {
             ShareEncoding::MnemoBip39,
         ] {
             let input = joined(e, None);
-            let result = combine_share_input(input, ShareEncoding::Auto, None).unwrap();
+            let result = combine_share_input(input, ShareEncoding::Auto, None, None).unwrap();
             assert_eq!(result.bytes, vec![0, 255, 1, 2]);
         }
     }
@@ -283,20 +554,136 @@ mod tests {
     fn passphrase_cases() {
         let input = joined(ShareEncoding::Base64url, Some("correct"));
         assert!(matches!(
-            combine_share_input(input.clone(), ShareEncoding::Auto, None),
+            combine_share_input(input.clone(), ShareEncoding::Auto, None, None),
             Err(BridgeError::PassphraseRequired)
         ));
         assert!(matches!(
-            combine_share_input(input.clone(), ShareEncoding::Auto, Some("wrong".into())),
+            combine_share_input(
+                input.clone(),
+                ShareEncoding::Auto,
+                Some("wrong".into()),
+                None
+            ),
             Err(BridgeError::IncorrectPassphrase)
         ));
         assert_eq!(
-            combine_share_input(input, ShareEncoding::Auto, Some("correct".into()))
+            combine_share_input(input, ShareEncoding::Auto, Some("correct".into()), None)
                 .unwrap()
                 .bytes,
             vec![0, 255, 1, 2]
         );
     }
+    #[test]
+    fn kdf_profiles_and_calibration_reach_split() {
+        let custom = KdfProfile::Custom {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let shares = split_secret(
+            vec![4, 2],
+            2,
+            3,
+            ShareEncoding::Base64url,
+            Some("pw".into()),
+            Some(custom),
+            Some(ShareCipher::Aes256GcmSiv),
+            None,
+        )
+        .unwrap();
+        let input = format!("{}\n{}", shares[0].text, shares[2].text);
+        let result =
+            combine_share_input(input, ShareEncoding::Auto, Some("pw".into()), None).unwrap();
+        assert_eq!(result.bytes, vec![4, 2]);
+
+        let invalid = KdfProfile::Custom {
+            memory_kib: 1,
+            iterations: 1,
+            parallelism: 1,
+        };
+        assert!(matches!(
+            split_secret(
+                vec![1],
+                2,
+                3,
+                ShareEncoding::Base64url,
+                None,
+                Some(invalid),
+                None,
+                None
+            ),
+            Err(BridgeError::InvalidKdfParameters)
+        ));
+
+        assert!(matches!(
+            calibrate_kdf(10, 16 * 1024),
+            Ok(KdfProfile::Custom { memory_kib, .. }) if memory_kib <= 16 * 1024
+        ));
+    }
+
+    #[test]
+    fn locked_share_needs_its_own_passphrase() {
+        let shares = split_secret(
+            vec![6, 6],
+            2,
+            3,
+            ShareEncoding::MnemoWords,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let fast = KdfProfile::Custom {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let locked = lock_share(
+            shares[1].text.clone(),
+            ShareEncoding::Auto,
+            "2468".into(),
+            Some(fast),
+            None,
+        )
+        .unwrap();
+        let input = format!("{}\n\n{locked}", shares[0].text);
+
+        let inspection = inspect_share_input(input.clone(), ShareEncoding::Auto).unwrap();
+        assert!(matches!(
+            inspection.detected_encoding,
+            ShareEncoding::MnemoWords
+        ));
+        assert_eq!(inspection.locked_indexes, vec![2]);
+        assert!(matches!(
+            combine_share_input(input.clone(), ShareEncoding::Auto, None, None),
+            Err(BridgeError::ShareLocked)
+        ));
+        let wrong = HashMap::from([(2, "1357".to_string())]);
+        assert!(matches!(
+            combine_share_input(input.clone(), ShareEncoding::Auto, None, Some(wrong)),
+            Err(BridgeError::IncorrectSharePassphrase)
+        ));
+        let right = HashMap::from([(2, "2468".to_string())]);
+        let result = combine_share_input(input, ShareEncoding::Auto, None, Some(right)).unwrap();
+        assert_eq!(result.bytes, vec![6, 6]);
+    }
+
+    #[test]
+    fn combine_reports_corrupted_surplus_share() {
+        let mut packets = safeparts_core::split_secret(&[7, 8, 9], 2, 4, None).unwrap();
+        packets[1].payload[0] ^= 0x10;
+        let input = packets
+            .iter()
+            .map(|p| encoding::encode_packet(p, Encoding::Base64url).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let result = combine_share_input(input, ShareEncoding::Auto, None, None).unwrap();
+        assert_eq!(result.bytes, vec![7, 8, 9]);
+        assert_eq!(result.suspect_indexes, vec![2]);
+    }
+
     #[test]
     fn packet_payload_guard_zeroizes_owned_payloads() {
         let mut packets =
@@ -314,9 +701,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn stealth_shares_inspect_as_hidden_and_combine_together() {
+        let options = SplitOptions {
+            stealth: true,
+            ..SplitOptions::default()
+        };
+        let packets =
+            safeparts_core::split_secret_with_options(b"s", 2, 3, None, &options).unwrap();
+        let texts: Vec<_> = packets
+            .iter()
+            .map(|packet| encoding::encode_packet(packet, Encoding::MnemoWords).unwrap())
+            .collect();
+
+        let inspection = inspect_share_input(texts[0].clone(), ShareEncoding::Auto).unwrap();
+        assert!(inspection.stealth);
+        assert_eq!((inspection.threshold, inspection.share_count), (0, 0));
+        assert!(matches!(
+            combine_share_input(texts[0].clone(), ShareEncoding::Auto, None, None),
+            Err(BridgeError::InsufficientShares)
+        ));
+        let all = combine_share_input(texts.join("\n\n"), ShareEncoding::Auto, None, None).unwrap();
+        assert_eq!(all.bytes, b"s");
+    }
+
     #[test]
     fn inspect_and_negative_inputs_are_sanitized() {
-        let shares = split_secret(vec![1], 2, 3, ShareEncoding::Base64url, None).unwrap();
+        let shares = split_secret(
+            vec![1],
+            2,
+            3,
+            ShareEncoding::Base64url,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
         let one = shares[0].text.clone();
         let one_inspection = inspect_share_input(one.clone(), ShareEncoding::Auto).unwrap();
         assert_eq!(one_inspection.threshold, 2);
@@ -329,20 +750,30 @@ mod tests {
             Err(BridgeError::EmptyInput)
         ));
         assert!(matches!(
-            combine_share_input(one.clone(), ShareEncoding::Auto, None),
+            combine_share_input(one.clone(), ShareEncoding::Auto, None, None),
             Err(BridgeError::InsufficientShares)
         ));
         let duplicate = format!("{one}\n{one}");
         assert!(matches!(
-            combine_share_input(duplicate, ShareEncoding::Auto, None),
+            combine_share_input(duplicate, ShareEncoding::Auto, None, None),
             Err(BridgeError::DuplicateShares)
         ));
-        let other = split_secret(vec![2], 2, 3, ShareEncoding::Base64url, None).unwrap();
+        let other = split_secret(
+            vec![2],
+            2,
+            3,
+            ShareEncoding::Base64url,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
         let mixed = format!("{one}\n{}", other[0].text);
         let inspection = inspect_share_input(mixed.clone(), ShareEncoding::Auto).unwrap();
         assert!(!inspection.consistent && !inspection.ready);
         assert!(matches!(
-            combine_share_input(mixed, ShareEncoding::Auto, None),
+            combine_share_input(mixed, ShareEncoding::Auto, None, None),
             Err(BridgeError::MixedShares)
         ));
         let sensitive = "SECRET-SHARE-TEXT";
@@ -1,6 +1,8 @@
 use safeparts_uniffi::{
-    BridgeError, ShareEncoding, combine_share_input, inspect_share_input, split_secret,
+    BridgeError, KdfProfile, ShareEncoding, SharePadding, combine_share_input, inspect_share_input,
+    lock_share, split_secret,
 };
+use std::collections::HashMap;
 
 const BINARY_SECRET: &[u8] = &[0, 255, 3, 128];
 
@@ -11,6 +13,9 @@ fn joined_shares(encoding: ShareEncoding, passphrase: Option<&str>) -> String {
         3,
         encoding,
         passphrase.map(str::to_owned),
+        None,
+        None,
+        None,
     )
     .expect("synthetic split should succeed")
     .into_iter()
@@ -37,7 +42,7 @@ fn public_native_api_round_trips_every_encoding_and_protection_mode() {
         assert!(inspection.ready);
         assert!(!inspection.encrypted);
         assert_eq!(
-            combine_share_input(plain_input, ShareEncoding::Auto, None)
+            combine_share_input(plain_input, ShareEncoding::Auto, None, None)
                 .expect("plain shares should recover")
                 .bytes,
             BINARY_SECRET
@@ -45,14 +50,15 @@ fn public_native_api_round_trips_every_encoding_and_protection_mode() {
 
         let protected_input = joined_shares(encoding, Some("correct"));
         assert!(matches!(
-            combine_share_input(protected_input.clone(), ShareEncoding::Auto, None),
+            combine_share_input(protected_input.clone(), ShareEncoding::Auto, None, None),
             Err(BridgeError::PassphraseRequired)
         ));
         assert!(matches!(
             combine_share_input(
                 protected_input.clone(),
                 ShareEncoding::Auto,
-                Some("wrong".to_owned())
+                Some("wrong".to_owned()),
+                None
             ),
             Err(BridgeError::IncorrectPassphrase)
         ));
@@ -60,7 +66,8 @@ fn public_native_api_round_trips_every_encoding_and_protection_mode() {
             combine_share_input(
                 protected_input,
                 ShareEncoding::Auto,
-                Some("correct".to_owned())
+                Some("correct".to_owned()),
+                None
             )
             .expect("protected shares should recover")
             .bytes,
@@ -68,3 +75,118 @@ fn public_native_api_round_trips_every_encoding_and_protection_mode() {
         );
     }
 }
+
+#[test]
+fn public_native_api_unlocks_locked_shares_with_their_own_passphrase() {
+    let shares = split_secret(
+        BINARY_SECRET.to_vec(),
+        2,
+        3,
+        ShareEncoding::Base58check,
+        Some("correct".to_owned()),
+        None,
+        None,
+        None,
+    )
+    .expect("synthetic split should succeed");
+    let locked = lock_share(
+        shares[0].text.clone(),
+        ShareEncoding::Auto,
+        "1234".to_owned(),
+        Some(KdfProfile::Interactive),
+        None,
+    )
+    .expect("synthetic share should lock");
+    let input = format!("{locked}\n{}", shares[2].text);
+
+    let inspection = inspect_share_input(input.clone(), ShareEncoding::Auto)
+        .expect("locked shares should inspect");
+    assert_eq!(inspection.locked_indexes, vec![1]);
+    assert!(matches!(
+        combine_share_input(
+            input.clone(),
+            ShareEncoding::Auto,
+            Some("correct".to_owned()),
+            None
+        ),
+        Err(BridgeError::ShareLocked)
+    ));
+    assert_eq!(
+        combine_share_input(
+            input,
+            ShareEncoding::Auto,
+            Some("correct".to_owned()),
+            Some(HashMap::from([(1, "1234".to_owned())]))
+        )
+        .expect("unlocked shares should recover")
+        .bytes,
+        BINARY_SECRET
+    );
+}
+
+#[test]
+fn public_native_api_pads_shares_to_hide_the_secret_length() {
+    let split = |secret: &[u8], padding| {
+        split_secret(
+            secret.to_vec(),
+            2,
+            3,
+            ShareEncoding::Base64url,
+            None,
+            None,
+            None,
+            Some(padding),
+        )
+    };
+    let short = split(BINARY_SECRET, SharePadding::PowerOfTwo).expect("padded split");
+    let long = split(&[7; 40], SharePadding::PowerOfTwo).expect("padded split");
+    assert_eq!(short[0].text.len(), long[0].text.len());
+
+    let input = format!("{}\n{}", short[0].text, short[2].text);
+    assert_eq!(
+        combine_share_input(input, ShareEncoding::Auto, None, None)
+            .expect("padded shares should recover")
+            .bytes,
+        BINARY_SECRET
+    );
+    assert!(matches!(
+        split(&[7; 40], SharePadding::Fixed { size: 16 }),
+        Err(BridgeError::InvalidPadding)
+    ));
+}
+
+#[test]
+fn public_native_api_splits_and_recovers_codex32_strings() {
+    let secret = vec![0x5a; 16];
+    let shares = split_secret(
+        secret.clone(),
+        2,
+        3,
+        ShareEncoding::Codex32,
+        None,
+        None,
+        None,
+        None,
+    )
+    .expect("codex32 split");
+    assert!(shares.iter().all(|share| share.text.starts_with("ms12")));
+    let input = format!("{}\n{}", shares[0].text, shares[2].text);
+
+    let inspection =
+        inspect_share_input(input.clone(), ShareEncoding::Auto).expect("codex32 inspects");
+    assert!(matches!(
+        inspection.detected_encoding,
+        ShareEncoding::Codex32
+    ));
+    assert_eq!(inspection.threshold, 2);
+    assert!(inspection.ready);
+
+    let recovery =
+        combine_share_input(input, ShareEncoding::Auto, None, None).expect("codex32 recovers");
+    assert_eq!(recovery.bytes, secret);
+    assert_eq!(recovery.set_id, shares[0].set_id);
+    assert!(matches!(
+        combine_share_input(shares[0].text.clone(), ShareEncoding::Codex32, None, None),
+        Err(BridgeError::InsufficientShares)
+    ));
+}
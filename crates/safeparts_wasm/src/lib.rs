@@ -1,8 +1,15 @@
 use std::fmt::Display;
+use std::time::Duration;
 
 use js_sys::{Array, Object, Reflect, Uint8Array};
+use safeparts_core::codex32;
+use safeparts_core::crypto::{self, AeadAlgorithm, CipherSuite, CryptoParams, KdfParams};
 use safeparts_core::encoding::{self, Encoding};
+use safeparts_core::mnemo_fix::WordCorrection;
 use safeparts_core::packet::SharePacket;
+use safeparts_core::padding::Padding;
+use safeparts_core::secrets_js;
+use safeparts_core::{CoreError, SplitOptions};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -11,17 +18,55 @@ pub fn share_threshold(share: &str, encoding: &str) -> Result<u8, JsValue> {
     Ok(packet.k)
 }
 
+/// Split `secret` into encoded shares.
+///
+/// `kdf` sets the Argon2id costs for `passphrase`: a profile name
+/// (`interactive`, `moderate`, `paranoid`), `"MEMORY_KIB,ITERATIONS[,LANES]"`,
+/// or the result of [`calibrate_kdf`]. It defaults to `moderate`. `cipher`
+/// picks the AEAD: `chacha20-poly1305` (default), `xchacha20-poly1305`, or
+/// `aes-256-gcm-siv`. `padding` hides the secret's length: `none` (default),
+/// `pow2`, `fixed:BYTES`, or `random:MAX_BYTES`.
+///
+/// `codex32` writes BIP-93 strings of the raw secret instead of share packets;
+/// it takes no passphrase or padding.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn split_secret(
     secret: &[u8],
     k: u8,
     n: u8,
     encoding: &str,
     passphrase: Option<String>,
+    kdf: Option<String>,
+    cipher: Option<String>,
+    padding: Option<String>,
 ) -> Result<Array, JsValue> {
+    if encoding == "codex32" {
+        if passphrase.is_some() || padding.is_some() {
+            return Err(js_error("codex32 strings take no passphrase or padding"));
+        }
+        let shares =
+            codex32::split(secret, k, n, &codex32::random_identifier()).map_err(js_error)?;
+        let out = Array::new();
+        for share in shares {
+            out.push(&JsValue::from_str(&share.to_string()));
+        }
+        return Ok(out);
+    }
+
     let passphrase_bytes = passphrase.as_deref().map(str::as_bytes);
+    let options = SplitOptions {
+        suite: CipherSuite {
+            kdf: parse_kdf(kdf.as_deref()).map_err(js_error)?,
+            aead: parse_cipher(cipher.as_deref()).map_err(js_error)?,
+        },
+        padding: parse_padding(padding.as_deref()).map_err(js_error)?,
+        ..SplitOptions::default()
+    };
 
-    let packets = safeparts_core::split_secret(secret, k, n, passphrase_bytes).map_err(js_error)?;
+    let packets =
+        safeparts_core::split_secret_with_options(secret, k, n, passphrase_bytes, &options)
+            .map_err(js_error)?;
 
     let out = Array::new();
     for packet in packets {
@@ -32,6 +77,25 @@ pub fn split_secret(
     Ok(out)
 }
 
+/// Pick Argon2id costs whose derivation takes about `target_ms` in this
+/// runtime, using at most `max_mem_kib`. Returns
+/// `"MEMORY_KIB,ITERATIONS,LANES"` for the `kdf` argument of [`split_secret`].
+#[wasm_bindgen]
+pub fn calibrate_kdf(target_ms: u32, max_mem_kib: u32) -> Result<String, JsValue> {
+    let target = Duration::from_millis(target_ms.into());
+    let kdf = crypto::calibrate_with(target, max_mem_kib, |kdf| {
+        let params = CryptoParams::random((*kdf).into());
+        // `Instant` is unavailable on wasm32-unknown-unknown.
+        let start = js_sys::Date::now();
+        crypto::encrypt_with_aad(&[], b"safeparts calibration", &params, &[])?;
+        let elapsed_ms = (js_sys::Date::now() - start).max(0.0);
+        Ok(Duration::from_secs_f64(elapsed_ms / 1000.0))
+    })
+    .map_err(js_error)?;
+
+    Ok(format_kdf(&kdf))
+}
+
 #[wasm_bindgen]
 pub fn combine_shares(
     shares: Array,
@@ -52,18 +116,106 @@ pub fn combine_shares(
     combine_packets(&packets, passphrase_bytes)
 }
 
+/// Combine pasted shares.
+///
+/// `share_passphrases` unlocks shares locked with [`lock_share`]: an object
+/// mapping share index to that share's own passphrase, such as
+/// `{ "2": "2468" }`. A locked share without an entry fails with
+/// `share N is locked`; [`inspect_share_input`] lists the locked indexes.
+/// Codex32 strings and secrets.js shares are combined as they are and take
+/// no passphrase.
 #[wasm_bindgen]
 pub fn combine_share_input(
     input: &str,
     encoding: &str,
     passphrase: Option<String>,
+    share_passphrases: Option<Object>,
 ) -> Result<Uint8Array, JsValue> {
     let passphrase_bytes = passphrase.as_deref().map(str::as_bytes);
+    if let Some(secret) = combine_raw_shares(input, encoding, passphrase_bytes).map_err(js_error)? {
+        return Ok(Uint8Array::from(secret.as_slice()));
+    }
     let encoding = Encoding::parse_name(encoding).map_err(js_error)?;
     let parsed =
         encoding::parse_share_packets_wrapped_mnemonics(input, encoding).map_err(js_error)?;
+    let packets = unlock_packets(&parsed.packets, |x| {
+        share_passphrase(share_passphrases.as_ref(), x)
+    })
+    .map_err(js_error)?;
 
-    combine_packets(&parsed.packets, passphrase_bytes)
+    combine_packets(&packets, passphrase_bytes)
+}
+
+/// Combine pasted shares, skipping corrupted ones when enough surplus shares
+/// are present. Returns `{ secret: Uint8Array, suspectIndexes: number[],
+/// suggestedFilename: string | null }`.
+/// `share_passphrases` works as in [`combine_share_input`].
+#[wasm_bindgen]
+pub fn combine_share_input_robust(
+    input: &str,
+    encoding: &str,
+    passphrase: Option<String>,
+    share_passphrases: Option<Object>,
+) -> Result<JsValue, JsValue> {
+    let passphrase_bytes = passphrase.as_deref().map(str::as_bytes);
+    let recovery = match combine_raw_shares(input, encoding, passphrase_bytes).map_err(js_error)? {
+        Some(secret) => safeparts_core::RobustRecovery {
+            secret,
+            suspects: Vec::new(),
+            metadata: None,
+        },
+        None => {
+            let encoding = Encoding::parse_name(encoding).map_err(js_error)?;
+            let parsed = encoding::parse_share_packets_wrapped_mnemonics(input, encoding)
+                .map_err(js_error)?;
+            let packets = unlock_packets(&parsed.packets, |x| {
+                share_passphrase(share_passphrases.as_ref(), x)
+            })
+            .map_err(js_error)?;
+            safeparts_core::combine_shares_robust(&packets, passphrase_bytes).map_err(js_error)?
+        }
+    };
+
+    let suspects = Array::new();
+    for x in recovery.suspects {
+        suspects.push(&JsValue::from_f64(x as f64));
+    }
+
+    let obj = Object::new();
+    Reflect::set(
+        &obj,
+        &JsValue::from_str("secret"),
+        &Uint8Array::from(recovery.secret.as_slice()),
+    )?;
+    Reflect::set(&obj, &JsValue::from_str("suspectIndexes"), &suspects)?;
+    let filename = recovery
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.safe_filename());
+    Reflect::set(
+        &obj,
+        &JsValue::from_str("suggestedFilename"),
+        &filename.map_or(JsValue::NULL, JsValue::from_str),
+    )?;
+
+    Ok(obj.into())
+}
+
+/// Lock one share with its custodian's own passphrase. The locked share keeps
+/// the input's encoding. `kdf` and `cipher` work as in [`split_secret`].
+#[wasm_bindgen]
+pub fn lock_share(
+    share: &str,
+    encoding: &str,
+    passphrase: &str,
+    kdf: Option<String>,
+    cipher: Option<String>,
+) -> Result<String, JsValue> {
+    let suite = CipherSuite {
+        kdf: parse_kdf(kdf.as_deref()).map_err(js_error)?,
+        aead: parse_cipher(cipher.as_deref()).map_err(js_error)?,
+    };
+    lock_encoded(share, encoding, passphrase, suite).map_err(js_error)
 }
 
 #[wasm_bindgen]
@@ -83,7 +235,160 @@ pub fn inspect_share_input(input: &str, encoding: &str) -> Result<JsValue, JsVal
         .first()
         .ok_or_else(|| JsValue::from_str("no shares provided"))?;
 
-    packet_info(first, parsed.encoding, parsed.packets.len())
+    let info = packet_info(first, parsed.encoding, parsed.packets.len())?;
+    let locked = Array::new();
+    for packet in parsed.packets.iter().filter(|packet| packet.is_locked()) {
+        locked.push(&JsValue::from_f64(packet.x as f64));
+    }
+    Reflect::set(&info, &JsValue::from_str("lockedIndexes"), &locked)?;
+    Ok(info)
+}
+
+/// Misspelled words in pasted mnemonic shares, or `null` when none are
+/// found.
+///
+/// Returns `{ share, corrections }` for the first share with words to fix,
+/// where each correction is `{ position, typed, candidates, verified }`.
+/// `share` and `position` count from 1, `candidates` is best first, and
+/// `verified` means the first candidate makes the share's checksum pass. Call
+/// it after [`combine_share_input`] fails to show the user what to fix.
+#[wasm_bindgen]
+pub fn word_corrections(input: &str, encoding: &str) -> Result<JsValue, JsValue> {
+    let encoding = Encoding::parse_name(encoding).map_err(js_error)?;
+    let Some((share, corrections)) = misspelled_words(input, encoding) else {
+        return Ok(JsValue::NULL);
+    };
+    let list = Array::new();
+    for correction in corrections {
+        let candidates = Array::new();
+        for candidate in &correction.candidates {
+            candidates.push(&JsValue::from_str(candidate));
+        }
+        let item = Object::new();
+        Reflect::set(
+            &item,
+            &JsValue::from_str("position"),
+            &JsValue::from_f64(correction.position as f64),
+        )?;
+        Reflect::set(
+            &item,
+            &JsValue::from_str("typed"),
+            &JsValue::from_str(&correction.typed),
+        )?;
+        Reflect::set(&item, &JsValue::from_str("candidates"), &candidates)?;
+        Reflect::set(
+            &item,
+            &JsValue::from_str("verified"),
+            &JsValue::from_bool(correction.verified),
+        )?;
+        list.push(&item);
+    }
+    let obj = Object::new();
+    Reflect::set(
+        &obj,
+        &JsValue::from_str("share"),
+        &JsValue::from_f64(share as f64),
+    )?;
+    Reflect::set(&obj, &JsValue::from_str("corrections"), &list)?;
+    Ok(obj.into())
+}
+
+fn misspelled_words(input: &str, encoding: Encoding) -> Option<(usize, Vec<WordCorrection>)> {
+    match encoding::parse_share_packets_wrapped_mnemonics(input, encoding) {
+        Err(CoreError::MisspelledWords { share, corrections }) => Some((share, corrections)),
+        _ => None,
+    }
+}
+
+fn share_passphrase(share_passphrases: Option<&Object>, x: u8) -> Option<String> {
+    let value = Reflect::get(share_passphrases?, &JsValue::from_str(&x.to_string())).ok()?;
+    value.as_string()
+}
+
+/// Unlock every locked packet with the passphrase `passphrase_for` returns
+/// for its index; a locked packet without one is an error.
+fn unlock_packets(
+    packets: &[SharePacket],
+    passphrase_for: impl Fn(u8) -> Option<String>,
+) -> Result<Vec<SharePacket>, String> {
+    packets
+        .iter()
+        .map(|packet| {
+            if !packet.is_locked() {
+                return Ok(packet.clone());
+            }
+            let passphrase = passphrase_for(packet.x)
+                .ok_or_else(|| CoreError::ShareLocked { x: packet.x }.to_string())?;
+            safeparts_core::unlock_share(packet, passphrase.as_bytes()).map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+fn lock_encoded(
+    share: &str,
+    encoding: &str,
+    passphrase: &str,
+    suite: CipherSuite,
+) -> Result<String, String> {
+    let encoding = Encoding::parse_name(encoding).map_err(|e| e.to_string())?;
+    let parsed = encoding::parse_share_packets_wrapped_mnemonics(share, encoding)
+        .map_err(|e| e.to_string())?;
+    let [packet] = parsed.packets.as_slice() else {
+        return Err(format!(
+            "expected one share packet, got {}",
+            parsed.packets.len()
+        ));
+    };
+    let locked = safeparts_core::lock_share(packet, passphrase.as_bytes(), suite)
+        .map_err(|e| e.to_string())?;
+    encoding::encode_packet(&locked, parsed.encoding).map_err(|e| e.to_string())
+}
+
+/// Recover the secret when `input` holds codex32 strings or secrets.js
+/// shares, chosen by `encoding` or detected; `None` for share packets.
+fn combine_raw_shares(
+    input: &str,
+    encoding: &str,
+    passphrase: Option<&[u8]>,
+) -> Result<Option<Vec<u8>>, String> {
+    let format = match encoding {
+        "auto" => detect_raw_format(input),
+        name => Some(name),
+    };
+    let secret = match format {
+        Some("codex32") => {
+            if passphrase.is_some() {
+                return Err("codex32 strings take no passphrase".to_string());
+            }
+            let shares = codex32::parse_shares(input).map_err(|e| e.to_string())?;
+            codex32::combine(&shares)
+        }
+        Some("secrets-js") => {
+            if passphrase.is_some() {
+                return Err("secrets.js shares take no passphrase".to_string());
+            }
+            let shares = secrets_js::parse_shares(input).map_err(|e| e.to_string())?;
+            secrets_js::combine(&shares)
+        }
+        _ => return Ok(None),
+    };
+    secret
+        .map(|secret| Some(secret.to_vec()))
+        .map_err(|e| e.to_string())
+}
+
+/// The name of the foreign share format of input no packet encoding reads.
+fn detect_raw_format(input: &str) -> Option<&'static str> {
+    if matches!(encoding::detect_encoding(input), Ok(Some(_))) {
+        return None;
+    }
+    if codex32::looks_like(input) {
+        Some("codex32")
+    } else if secrets_js::looks_like(input) {
+        Some("secrets-js")
+    } else {
+        None
+    }
 }
 
 fn combine_packets(
@@ -119,7 +424,18 @@ fn packet_info(
     Reflect::set(
         &obj,
         &JsValue::from_str("encrypted"),
-        &JsValue::from_bool(packet.crypto_params.is_some()),
+        &JsValue::from_bool(packet.is_encrypted()),
+    )?;
+    Reflect::set(
+        &obj,
+        &JsValue::from_str("locked"),
+        &JsValue::from_bool(packet.is_locked()),
+    )?;
+    // Stealth shares report k = n = 0; their threshold is not recorded.
+    Reflect::set(
+        &obj,
+        &JsValue::from_str("stealth"),
+        &JsValue::from_bool(packet.stealth),
     )?;
     Reflect::set(
         &obj,
@@ -144,6 +460,32 @@ fn encode_packet(packet: &SharePacket, encoding: &str) -> Result<String, String>
     encoding::encode_packet(packet, encoding).map_err(|e| e.to_string())
 }
 
+fn parse_kdf(kdf: Option<&str>) -> Result<KdfParams, String> {
+    match kdf {
+        None => Ok(KdfParams::default()),
+        Some(text) => text
+            .parse()
+            .map_err(|e: safeparts_core::CoreError| e.to_string()),
+    }
+}
+
+fn parse_cipher(cipher: Option<&str>) -> Result<AeadAlgorithm, String> {
+    match cipher {
+        None => Ok(AeadAlgorithm::default()),
+        Some(name) => AeadAlgorithm::parse_name(name).map_err(|e| e.to_string()),
+    }
+}
+
+fn parse_padding(padding: Option<&str>) -> Result<Padding, String> {
+    padding
+        .map_or(Ok(Padding::None), str::parse)
+        .map_err(|e: CoreError| e.to_string())
+}
+
+fn format_kdf(kdf: &KdfParams) -> String {
+    format!("{},{},{}", kdf.mem_cost_kib, kdf.time_cost, kdf.parallelism)
+}
+
 fn decode_packet(s: &str, encoding: &str) -> Result<SharePacket, String> {
     let encoding = Encoding::parse_name(encoding).map_err(|e| e.to_string())?;
     encoding::decode_packet(s, encoding).map_err(|e| e.to_string())
@@ -155,16 +497,112 @@ mod tests {
 
     #[test]
     fn unknown_encoding_is_error() {
-        let pkt = safeparts_core::packet::SharePacket {
-            set_id: safeparts_core::sss::SetId([0u8; 16]),
-            k: 2,
-            n: 3,
-            x: 1,
-            payload: vec![1, 2, 3],
-            crypto_params: None,
-        };
+        let pkt =
+            safeparts_core::packet::SharePacket::from_raw_share(safeparts_core::sss::RawShare {
+                set_id: safeparts_core::sss::SetId([0u8; 16]),
+                k: 2,
+                n: 3,
+                x: 1,
+                y: vec![1, 2, 3],
+            });
 
         let err = encode_packet(&pkt, "nope").unwrap_err();
         assert!(err.contains("unknown encoding"));
     }
+
+    #[test]
+    fn kdf_argument_accepts_profiles_and_calibrated_costs() {
+        assert_eq!(parse_kdf(None).unwrap(), KdfParams::MODERATE);
+        assert_eq!(parse_kdf(Some("paranoid")).unwrap(), KdfParams::PARANOID);
+
+        let custom = KdfParams::new(4096, 2, 1).unwrap();
+        assert_eq!(parse_kdf(Some(&format_kdf(&custom))).unwrap(), custom);
+        assert!(parse_kdf(Some("4,1,1")).is_err());
+    }
+
+    #[test]
+    fn share_passphrases_unlock_only_their_own_share() {
+        let shares = safeparts_core::split_secret(b"wasm", 2, 2, None).unwrap();
+        let encoded = encode_packet(&shares[0], "base64url").unwrap();
+        let suite = CipherSuite::from(KdfParams::new(1024, 1, 1).unwrap());
+        let locked = lock_encoded(&encoded, "auto", "pin", suite).unwrap();
+        let packets = vec![decode_packet(&locked, "auto").unwrap(), shares[1].clone()];
+
+        let missing = unlock_packets(&packets, |_| None).unwrap_err();
+        assert!(missing.contains("share 1 is locked"));
+        let wrong = unlock_packets(&packets, |_| Some("nope".to_string())).unwrap_err();
+        assert!(wrong.contains("wrong passphrase for share 1"));
+
+        let unlocked = unlock_packets(&packets, |x| (x == 1).then(|| "pin".to_string())).unwrap();
+        assert_eq!(unlocked, shares);
+    }
+
+    #[test]
+    fn codex32_input_is_combined_outside_the_packet_path() {
+        let input = "ms13casha320zyxwvutsrqpnmlkjhgfedca2a8d0zehn8a0t\n\
+                     ms13cashcacdefghjklmnpqrstuvwxyz023949xq35my48dr\n\
+                     ms13cashd0wsedstcdcts64cd7wvy4m90lm28w4ffupqs7rm";
+        let secret = combine_raw_shares(input, "auto", None).unwrap().unwrap();
+        assert_eq!(secret[..2], [0xff, 0xee]);
+        assert!(combine_raw_shares(input, "auto", Some(b"pw")).is_err());
+
+        let packet = safeparts_core::split_secret(b"wasm", 2, 2, None).unwrap();
+        let encoded = encode_packet(&packet[0], "base64url").unwrap();
+        assert_eq!(combine_raw_shares(&encoded, "auto", None).unwrap(), None);
+    }
+
+    #[test]
+    fn secrets_js_input_is_combined_outside_the_packet_path() {
+        let input = "8012461f89c3f0044f2614d95c6dc2b21f6\n\
+                     8028a192103f950465a05114f22dbd18be0\n\
+                     803ae78d99fc65003c51d7ca9816488cf62";
+        let secret = combine_raw_shares(input, "auto", None).unwrap().unwrap();
+        assert_eq!(secret, b"my secret");
+        assert!(combine_raw_shares(input, "secrets-js", Some(b"pw")).is_err());
+        assert!(combine_raw_shares("not a share", "secrets-js", None).is_err());
+    }
+
+    #[test]
+    fn misspelled_words_are_found_for_the_ui() {
+        let packet = safeparts_core::split_secret(b"wasm", 2, 2, None).unwrap();
+        let encoded = encode_packet(&packet[0], "mnemo-words").unwrap();
+        assert!(misspelled_words(&encoded, Encoding::Auto).is_none());
+
+        let mut words: Vec<&str> = encoded.split_whitespace().collect();
+        let expected = words[1];
+        let typo = format!("{expected}x");
+        words[1] = &typo;
+        let (share, corrections) = misspelled_words(&words.join(" "), Encoding::Auto).unwrap();
+        assert_eq!((share, corrections[0].position), (1, 2));
+        assert_eq!(corrections[0].candidates[0], expected);
+    }
+
+    #[test]
+    fn cipher_argument_accepts_labels() {
+        assert_eq!(parse_cipher(None).unwrap(), AeadAlgorithm::ChaCha20Poly1305);
+        assert_eq!(
+            parse_cipher(Some("aes-256-gcm-siv")).unwrap(),
+            AeadAlgorithm::Aes256GcmSiv
+        );
+        assert!(
+            parse_cipher(Some("des"))
+                .unwrap_err()
+                .contains("unknown cipher")
+        );
+    }
+
+    #[test]
+    fn padding_argument_accepts_modes() {
+        assert_eq!(parse_padding(None).unwrap(), Padding::None);
+        assert_eq!(parse_padding(Some("pow2")).unwrap(), Padding::PowerOfTwo);
+        assert_eq!(
+            parse_padding(Some("fixed:128")).unwrap(),
+            Padding::Fixed(128)
+        );
+        assert!(
+            parse_padding(Some("pad"))
+                .unwrap_err()
+                .contains("invalid padding")
+        );
+    }
 }
@@ -2,6 +2,8 @@ use std::collections::BTreeSet;
 
 use safeparts_core::CoreError;
 use safeparts_core::encoding::{self, Encoding};
+use safeparts_core::metadata::SecretMetadata;
+use safeparts_core::mnemo_fix::WordCorrection;
 use serde::Serialize;
 use zeroize::Zeroizing;
 
@@ -34,6 +36,31 @@ pub struct CombineResponse {
     text: Option<String>,
     encoding: String,
     share_count: usize,
+    metadata: Option<MetadataInfo>,
+    /// Stored filename when it is safe to offer in a save dialog.
+    suggested_filename: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataInfo {
+    filename: Option<String>,
+    content_type: Option<String>,
+    label: Option<String>,
+    created_at: Option<u64>,
+    safeparts_version: Option<String>,
+}
+
+impl From<SecretMetadata> for MetadataInfo {
+    fn from(metadata: SecretMetadata) -> Self {
+        Self {
+            filename: metadata.filename,
+            content_type: metadata.content_type,
+            label: metadata.label,
+            created_at: metadata.created_at,
+            safeparts_version: metadata.safeparts_version,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
@@ -50,6 +77,34 @@ pub struct ShareInspection {
     share_indexes: Vec<u8>,
 }
 
+/// Misspelled words of the first mnemonic share that has any.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordCorrections {
+    share: usize,
+    corrections: Vec<WordCorrectionInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordCorrectionInfo {
+    position: usize,
+    typed: String,
+    candidates: Vec<String>,
+    verified: bool,
+}
+
+impl From<WordCorrection> for WordCorrectionInfo {
+    fn from(correction: WordCorrection) -> Self {
+        Self {
+            position: correction.position,
+            typed: correction.typed,
+            candidates: correction.candidates,
+            verified: correction.verified,
+        }
+    }
+}
+
 #[cfg_attr(not(test), tauri::command)]
 fn supported_encodings_command() -> Vec<EncodingInfo> {
     vec![
@@ -134,10 +189,16 @@ fn combine_shares_command(
     let parsed = parse_input(&input, &encoding)?;
     let passphrase = zeroize_passphrase(passphrase);
     let passphrase_bytes = passphrase.as_deref().map(Vec::as_slice);
-    let secret = safeparts_core::combine_shares(&parsed.packets, passphrase_bytes)
+    let recovered = safeparts_core::combine_shares_with_metadata(&parsed.packets, passphrase_bytes)
         .map_err(|err| err.to_string())?;
+    let secret = recovered.secret;
     let byte_count = secret.len();
     let text = String::from_utf8(secret.clone()).ok();
+    let suggested_filename = recovered
+        .metadata
+        .as_ref()
+        .and_then(SecretMetadata::safe_filename)
+        .map(str::to_string);
 
     Ok(CombineResponse {
         secret,
@@ -146,6 +207,8 @@ fn combine_shares_command(
         text,
         encoding: parsed.encoding.label().to_string(),
         share_count: parsed.packets.len(),
+        metadata: recovered.metadata.map(MetadataInfo::from),
+        suggested_filename,
     })
 }
 
@@ -184,6 +247,21 @@ fn inspect_shares_command(input: String, encoding: String) -> Result<ShareInspec
     })
 }
 
+#[cfg_attr(not(test), tauri::command)]
+fn word_corrections_command(
+    input: String,
+    encoding: String,
+) -> Result<Option<WordCorrections>, String> {
+    let encoding = parse_encoding(&encoding)?;
+    match encoding::parse_share_packets_wrapped_mnemonics(&input, encoding) {
+        Err(CoreError::MisspelledWords { share, corrections }) => Ok(Some(WordCorrections {
+            share,
+            corrections: corrections.into_iter().map(Into::into).collect(),
+        })),
+        _ => Ok(None),
+    }
+}
+
 fn zeroize_passphrase(passphrase: Option<String>) -> Option<Zeroizing<Vec<u8>>> {
     passphrase.map(String::into_bytes).map(Zeroizing::new)
 }
@@ -200,7 +278,10 @@ fn sanitize_share_parse_error(err: CoreError) -> String {
         CoreError::CouldNotDetectEncoding => {
             "could not detect the share encoding; choose the encoding and try again".to_string()
         }
-        CoreError::Encoding(_) | CoreError::InvalidPacket(_) => {
+        // Misspelled words are listed by `word_corrections_command` instead.
+        CoreError::Encoding(_)
+        | CoreError::InvalidPacket(_)
+        | CoreError::MisspelledWords { .. } => {
             "recovery shares could not be decoded with the selected encoding".to_string()
         }
         other => other.to_string(),
@@ -228,6 +309,7 @@ pub fn run() {
             split_secret_command,
             combine_shares_command,
             inspect_shares_command,
+            word_corrections_command,
         ])
         .run(tauri::generate_context!())
         .expect("failed to run Safeparts desktop app");
@@ -304,6 +386,41 @@ mod tests {
         assert_eq!(combined.encoding, "base58check");
     }
 
+    #[test]
+    fn combine_suggests_the_stored_filename() {
+        let metadata = SecretMetadata {
+            filename: Some("recovery-codes.txt".to_string()),
+            label: Some("github".to_string()),
+            ..SecretMetadata::now()
+        };
+        let packets = safeparts_core::split_secret_with_metadata(
+            b"codes",
+            2,
+            3,
+            None,
+            Default::default(),
+            &metadata,
+        )
+        .unwrap();
+        let input = packets[..2]
+            .iter()
+            .map(|packet| encoding::encode_packet(packet, Encoding::Base64url).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let combined = combine_shares_command(input, "auto".to_string(), None).unwrap();
+
+        assert_eq!(combined.secret, b"codes");
+        assert_eq!(
+            combined.suggested_filename.as_deref(),
+            Some("recovery-codes.txt")
+        );
+        assert_eq!(
+            combined.metadata.and_then(|metadata| metadata.label),
+            Some("github".to_string())
+        );
+    }
+
     #[test]
     fn passphrase_protected_shares_combine_with_passphrase() {
         let split = split_secret_command(
@@ -419,4 +536,56 @@ mod tests {
             "recovery shares could not be decoded with the selected encoding"
         );
     }
+
+    #[test]
+    fn word_corrections_list_typos_without_echoing_them_in_errors() {
+        let split = split_secret_command(
+            b"desktop typo secret".to_vec(),
+            2,
+            3,
+            "mnemo-words".to_string(),
+            None,
+        )
+        .unwrap();
+        let mut words = split.shares[1]
+            .split_whitespace()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let swap_last_two = |word: &str| {
+            let mut letters = word.as_bytes().to_vec();
+            let len = letters.len();
+            letters.swap(len - 1, len - 2);
+            String::from_utf8(letters).unwrap()
+        };
+        // Misspell the first word whose last two letters swapped are no word.
+        let index = words
+            .iter()
+            .position(|word| safeparts_core::mnemo_fix::resolve(&swap_last_two(word)).is_none())
+            .unwrap();
+        let original = words[index].clone();
+        let typo = swap_last_two(&original);
+        words[index] = typo.clone();
+        let input = [split.shares[0].clone(), words.join(" ")].join("\n\n");
+
+        let err =
+            combine_shares_command(input.clone(), "mnemo-words".to_string(), None).unwrap_err();
+        assert!(!err.contains(&typo));
+
+        let found = word_corrections_command(input, "mnemo-words".to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.share, 2);
+        assert_eq!(found.corrections.len(), 1);
+        let correction = &found.corrections[0];
+        assert_eq!(correction.position, index + 1);
+        assert_eq!(correction.typed, typo);
+        assert_eq!(correction.candidates[0], original);
+        assert!(correction.verified);
+
+        let clean = join_share_input(&split.shares[..2], "mnemo-words");
+        assert_eq!(
+            word_corrections_command(clean, "mnemo-words".to_string()),
+            Ok(None)
+        );
+    }
 }
@@ -23,6 +23,16 @@ export interface CombineResponse {
   text: string | null;
   encoding: string;
   shareCount: number;
+  metadata: SecretMetadata | null;
+  suggestedFilename: string | null;
+}
+
+export interface SecretMetadata {
+  filename: string | null;
+  contentType: string | null;
+  label: string | null;
+  createdAt: number | null;
+  safepartsVersion: string | null;
 }
 
 export interface ShareInspection {
@@ -37,6 +47,18 @@ export interface ShareInspection {
   shareIndexes: number[];
 }
 
+export interface WordCorrections {
+  share: number;
+  corrections: WordCorrectionInfo[];
+}
+
+export interface WordCorrectionInfo {
+  position: number;
+  typed: string;
+  candidates: string[];
+  verified: boolean;
+}
+
 type SplitSecretArgs = {
   secret: Uint8Array;
   threshold: number;
@@ -77,3 +99,7 @@ export function combineShares(args: CombineSharesArgs): Promise<CombineResponse>
 export function inspectShares(args: InspectSharesArgs): Promise<ShareInspection> {
   return invoke<ShareInspection>("inspect_shares_command", args);
 }
+
+export function wordCorrections(args: InspectSharesArgs): Promise<WordCorrections | null> {
+  return invoke<WordCorrections | null>("word_corrections_command", args);
+}
@@ -23,6 +23,16 @@ type ShareBox = {
   value: string;
 };
 
+type WordCorrections = {
+  share: number;
+  corrections: {
+    position: number;
+    typed: string;
+    candidates: string[];
+    verified: boolean;
+  }[];
+};
+
 function MinusIcon() {
   return (
     <svg viewBox="0 0 24 24" fill="none" className="h-4 w-4" aria-hidden="true">
@@ -64,6 +74,19 @@ function toErrorMessage(err: unknown, strings: Strings): string {
   return message;
 }
 
+async function findWordCorrections(
+  input: string,
+  encoding: Encoding,
+): Promise<WordCorrections | null> {
+  try {
+    const wasm = await ensureWasm();
+    if (typeof wasm.word_corrections !== "function") return null;
+    return (await wasm.word_corrections(input, encoding)) ?? null;
+  } catch {
+    return null;
+  }
+}
+
 function parseSharesFromBox(text: string): string[] {
   return text
     .split(/\n\s*\n/g)
@@ -85,6 +108,24 @@ function createShareBox(): ShareBox {
   return { id: createId(), value: "" };
 }
 
+type RecoveredFile = {
+  bytes: Uint8Array;
+  contentType: string | null;
+  suggestedFilename: string | null;
+};
+
+function saveRecovered(file: RecoveredFile) {
+  const blob = new Blob([file.bytes], {
+    type: file.contentType ?? "application/octet-stream",
+  });
+  const url = URL.createObjectURL(blob);
+  const link = document.createElement("a");
+  link.href = url;
+  link.download = file.suggestedFilename ?? "recovered-secret";
+  link.click();
+  URL.revokeObjectURL(url);
+}
+
 export function CombineForm({ lang, strings }: CombineFormProps) {
   const [encoding, setEncoding] = useState<Encoding>("mnemo-words");
   const [passphrase, setPassphrase] = useState("");
@@ -95,7 +136,9 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
   const [invalidShareBoxIds, setInvalidShareBoxIds] = useState<string[]>([]);
   const [shareBoxFlashIds, setShareBoxFlashIds] = useState<string[]>([]);
   const [secret, setSecret] = useState("");
+  const [recovered, setRecovered] = useState<RecoveredFile | null>(null);
   const [error, setError] = useState<string | null>(null);
+  const [corrections, setCorrections] = useState<WordCorrections | null>(null);
   const [busy, setBusy] = useState(false);
 
   const [encodingFlash, setEncodingFlash] = useState(false);
@@ -261,12 +304,23 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
   async function onCombine() {
     setBusy(true);
     setError(null);
+    setCorrections(null);
     setSecret("");
+    setRecovered(null);
 
     try {
       const wasm = await ensureWasm();
+      const detailed =
+        typeof wasm.combine_share_input_detailed === "function"
+          ? await wasm.combine_share_input_detailed(
+              combinedShareInput,
+              encoding,
+              passphrase ? passphrase : undefined,
+            )
+          : null;
       const out =
-        typeof wasm.combine_share_input === "function"
+        detailed?.secret ??
+        (typeof wasm.combine_share_input === "function"
           ? await wasm.combine_share_input(
               combinedShareInput,
               encoding,
@@ -276,14 +330,24 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
               shares,
               encoding,
               passphrase ? passphrase : undefined,
-            );
+            ));
       const bytes = new Uint8Array(out);
+      setRecovered({
+        bytes,
+        contentType: detailed?.contentType ?? null,
+        suggestedFilename: detailed?.suggestedFilename ?? null,
+      });
       setSecret(new TextDecoder().decode(bytes));
       setInvalidShareBoxIds([]);
     } catch (e) {
       const rawMessage = rawErrorMessage(e);
-      const message = toErrorMessage(e, strings);
-      setError(message);
+      const found = await findWordCorrections(combinedShareInput, encoding);
+      setCorrections(found);
+      setError(
+        found
+          ? strings.errorMisspelledWords.replace("{share}", String(found.share))
+          : toErrorMessage(e, strings),
+      );
 
       const m = notEnoughSharesMatch(rawMessage);
       if (m) {
@@ -305,6 +369,17 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
 
   const canCombine = shares.length > 0;
 
+  function describeCandidates(
+    candidates: string[],
+    verified: boolean,
+  ): string {
+    if (candidates.length === 0) return strings.correctionNoMatch;
+    const list = `${strings.correctionDidYouMean} ${candidates.join(", ")}`;
+    return verified
+      ? `${list} (${candidates[0]} ${strings.correctionVerified})`
+      : list;
+  }
+
   return (
     <section className="glass p-4 sm:p-6">
       <div className="dir-row items-start justify-between gap-4">
@@ -475,6 +550,23 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
         {error ? (
           <div className="alert-error" role="alert" aria-live="assertive">
             {error}
+            {corrections ? (
+              <ul className="mt-2 space-y-1 text-xs">
+                {corrections.corrections.map((c) => (
+                  <li key={c.position} className="text-start">
+                    {strings.correctionWord.replace(
+                      "{position}",
+                      String(c.position),
+                    )}
+                    :{" "}
+                    <span dir="ltr" className="font-mono">
+                      {c.typed}
+                    </span>{" "}
+                    — {describeCandidates(c.candidates, c.verified)}
+                  </li>
+                ))}
+              </ul>
+            ) : null}
           </div>
         ) : null}
       </div>
@@ -491,12 +583,23 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
               </p>
             </div>
 
-            <CopyButton
-              value={secret}
-              copyLabel={strings.copy}
-              copiedLabel={strings.copied}
-              className="shrink-0"
-            />
+            <div className="dir-row shrink-0 items-center gap-2">
+              {recovered ? (
+                <button
+                  type="button"
+                  className="btn-secondary px-3 py-2 text-xs"
+                  onClick={() => saveRecovered(recovered)}
+                  title={recovered.suggestedFilename ?? undefined}
+                >
+                  {strings.save}
+                </button>
+              ) : null}
+              <CopyButton
+                value={secret}
+                copyLabel={strings.copy}
+                copiedLabel={strings.copied}
+              />
+            </div>
           </div>
 
           <div
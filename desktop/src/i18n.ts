@@ -63,6 +63,7 @@ export const STRINGS = {
     recoveredTitle: "Recovered secret",
     recoveredHint: "Handle carefully — this is sensitive.",
 
+    save: "Save",
     copy: "Copy",
     copied: "Copied",
     increment: "Increase",
@@ -75,6 +76,11 @@ export const STRINGS = {
     errorInvalidShare: "Invalid share",
     errorNotEnoughSharesOne: "Add 1 more share to recover this secret.",
     errorNotEnoughSharesMany: "Add {missing} more shares to recover this secret.",
+    errorMisspelledWords: "Some words in share {share} are not recognised.",
+    correctionWord: "Word {position}",
+    correctionDidYouMean: "did you mean",
+    correctionNoMatch: "no close match",
+    correctionVerified: "passes the checksum",
 
     keyboardShortcuts: "Keyboard shortcuts",
     shortcutClose: "Close",
@@ -146,6 +152,7 @@ export const STRINGS = {
     recoveredTitle: "السر المستعاد",
     recoveredHint: "تعامل بحذر — هذه بيانات حساسة.",
 
+    save: "حفظ",
     copy: "نسخ",
     copied: "تم النسخ",
     increment: "زيادة",
@@ -158,6 +165,11 @@ export const STRINGS = {
     errorInvalidShare: "حصة غير صالحة",
     errorNotEnoughSharesOne: "أضف حصة واحدة أخرى لاستعادة هذا السر.",
     errorNotEnoughSharesMany: "أضف {missing} حصص أخرى لاستعادة هذا السر.",
+    errorMisspelledWords: "بعض الكلمات في الحصة {share} غير معروفة.",
+    correctionWord: "الكلمة {position}",
+    correctionDidYouMean: "هل تقصد",
+    correctionNoMatch: "لا توجد كلمة قريبة",
+    correctionVerified: "يجتاز المجموع الاختباري",
 
     keyboardShortcuts: "اختصارات لوحة المفاتيح",
     shortcutClose: "إغلاق",
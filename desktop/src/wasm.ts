@@ -1,7 +1,19 @@
-import { combineShares, inspectShares, splitSecret } from "./commands";
+import {
+  combineShares,
+  inspectShares,
+  splitSecret,
+  wordCorrections,
+  type WordCorrections,
+} from "./commands";
 
 type Encoding = "base64url" | "mnemo-words" | "auto";
 
+type RecoveredSecret = {
+  secret: Uint8Array;
+  contentType: string | null;
+  suggestedFilename: string | null;
+};
+
 type ShareInspectionAdapter = {
   k: number;
   encoding?: string;
@@ -45,12 +57,25 @@ class DesktopSafepartsAdapter {
     encoding: Encoding,
     passphrase?: string,
   ): Promise<Uint8Array> {
+    const recovered = await this.combine_share_input_detailed(input, encoding, passphrase);
+    return recovered.secret;
+  }
+
+  async combine_share_input_detailed(
+    input: string,
+    encoding: Encoding,
+    passphrase?: string,
+  ): Promise<RecoveredSecret> {
     const response = await combineShares({
       input,
       encoding,
       passphrase,
     });
-    return new Uint8Array(response.secret);
+    return {
+      secret: new Uint8Array(response.secret),
+      contentType: response.metadata?.contentType ?? null,
+      suggestedFilename: response.suggestedFilename,
+    };
   }
 
   async combine_shares(
@@ -80,6 +105,10 @@ class DesktopSafepartsAdapter {
     const inspection = await this.inspect_share(share, encoding);
     return inspection.k;
   }
+
+  async word_corrections(input: string, encoding: Encoding): Promise<WordCorrections | null> {
+    return wordCorrections({ input, encoding });
+  }
 }
 
 export async function ensureWasm(): Promise<DesktopSafepartsAdapter> {
@@ -601,20 +601,24 @@ public struct Inspection: Equatable, Hashable {
     public var providedCount: UInt32
     public var encrypted: Bool
     public var indexes: Data
+    public var lockedIndexes: Data
     public var consistent: Bool
     public var ready: Bool
+    public var stealth: Bool
 
     // Default memberwise initializers are never public by default, so we
     // declare one manually.
-    public init(detectedEncoding: ShareEncoding, threshold: UInt8, shareCount: UInt8, providedCount: UInt32, encrypted: Bool, indexes: Data, consistent: Bool, ready: Bool) {
+    public init(detectedEncoding: ShareEncoding, threshold: UInt8, shareCount: UInt8, providedCount: UInt32, encrypted: Bool, indexes: Data, lockedIndexes: Data, consistent: Bool, ready: Bool, stealth: Bool) {
         self.detectedEncoding = detectedEncoding
         self.threshold = threshold
         self.shareCount = shareCount
         self.providedCount = providedCount
         self.encrypted = encrypted
         self.indexes = indexes
+        self.lockedIndexes = lockedIndexes
         self.consistent = consistent
         self.ready = ready
+        self.stealth = stealth
     }
 
 
@@ -639,8 +643,10 @@ public struct FfiConverterTypeInspection: FfiConverterRustBuffer {
                 providedCount: FfiConverterUInt32.read(from: &buf),
                 encrypted: FfiConverterBool.read(from: &buf),
                 indexes: FfiConverterData.read(from: &buf),
+                lockedIndexes: FfiConverterData.read(from: &buf),
                 consistent: FfiConverterBool.read(from: &buf),
-                ready: FfiConverterBool.read(from: &buf)
+                ready: FfiConverterBool.read(from: &buf),
+                stealth: FfiConverterBool.read(from: &buf)
         )
     }
 
@@ -651,8 +657,10 @@ public struct FfiConverterTypeInspection: FfiConverterRustBuffer {
         FfiConverterUInt32.write(value.providedCount, into: &buf)
         FfiConverterBool.write(value.encrypted, into: &buf)
         FfiConverterData.write(value.indexes, into: &buf)
+        FfiConverterData.write(value.lockedIndexes, into: &buf)
         FfiConverterBool.write(value.consistent, into: &buf)
         FfiConverterBool.write(value.ready, into: &buf)
+        FfiConverterBool.write(value.stealth, into: &buf)
     }
 }
 
@@ -680,10 +688,11 @@ public struct Recovery: Equatable, Hashable {
     public var encrypted: Bool
     public var indexes: Data
     public var setId: String
+    public var suspectIndexes: Data
 
     // Default memberwise initializers are never public by default, so we
     // declare one manually.
-    public init(bytes: Data, detectedEncoding: ShareEncoding, threshold: UInt8, shareCount: UInt8, encrypted: Bool, indexes: Data, setId: String) {
+    public init(bytes: Data, detectedEncoding: ShareEncoding, threshold: UInt8, shareCount: UInt8, encrypted: Bool, indexes: Data, setId: String, suspectIndexes: Data) {
         self.bytes = bytes
         self.detectedEncoding = detectedEncoding
         self.threshold = threshold
@@ -691,6 +700,7 @@ public struct Recovery: Equatable, Hashable {
         self.encrypted = encrypted
         self.indexes = indexes
         self.setId = setId
+        self.suspectIndexes = suspectIndexes
     }
 
 
@@ -715,7 +725,8 @@ public struct FfiConverterTypeRecovery: FfiConverterRustBuffer {
                 shareCount: FfiConverterUInt8.read(from: &buf),
                 encrypted: FfiConverterBool.read(from: &buf),
                 indexes: FfiConverterData.read(from: &buf),
-                setId: FfiConverterString.read(from: &buf)
+                setId: FfiConverterString.read(from: &buf),
+                suspectIndexes: FfiConverterData.read(from: &buf)
         )
     }
 
@@ -727,6 +738,7 @@ public struct FfiConverterTypeRecovery: FfiConverterRustBuffer {
         FfiConverterBool.write(value.encrypted, into: &buf)
         FfiConverterData.write(value.indexes, into: &buf)
         FfiConverterString.write(value.setId, into: &buf)
+        FfiConverterData.write(value.suspectIndexes, into: &buf)
     }
 }
 
@@ -761,6 +773,10 @@ public enum BridgeError: Swift.Error, Equatable, Hashable, Foundation.LocalizedE
     case IncorrectPassphrase
     case IntegrityFailure
     case Internal
+    case InvalidKdfParameters
+    case ShareLocked
+    case IncorrectSharePassphrase
+    case InvalidPadding
 
 
 
@@ -801,6 +817,10 @@ public struct FfiConverterTypeBridgeError: FfiConverterRustBuffer {
         case 9: return .IncorrectPassphrase
         case 10: return .IntegrityFailure
         case 11: return .Internal
+        case 12: return .InvalidKdfParameters
+        case 13: return .ShareLocked
+        case 14: return .IncorrectSharePassphrase
+        case 15: return .InvalidPadding
 
          default: throw UniffiInternalError.unexpectedEnumCase
         }
@@ -856,6 +876,22 @@ public struct FfiConverterTypeBridgeError: FfiConverterRustBuffer {
         case .Internal:
             writeInt(&buf, Int32(11))
 
+
+        case .InvalidKdfParameters:
+            writeInt(&buf, Int32(12))
+
+
+        case .ShareLocked:
+            writeInt(&buf, Int32(13))
+
+
+        case .IncorrectSharePassphrase:
+            writeInt(&buf, Int32(14))
+
+
+        case .InvalidPadding:
+            writeInt(&buf, Int32(15))
+
         }
     }
 }
@@ -875,6 +911,172 @@ public func FfiConverterTypeBridgeError_lower(_ value: BridgeError) -> RustBuffe
     return FfiConverterTypeBridgeError.lower(value)
 }
 
+// Note that we don't yet support `indirect` for enums.
+// See https://github.com/mozilla/uniffi-rs/issues/396 for further discussion.
+/**
+ * Argon2id costs for passphrase-protected splits.
+ */
+
+public enum KdfProfile: Equatable, Hashable {
+
+    case interactive
+    case moderate
+    case paranoid
+    case custom(memoryKib: UInt32, iterations: UInt32, parallelism: UInt32
+    )
+
+
+
+
+
+}
+
+#if compiler(>=6)
+extension KdfProfile: Sendable {}
+#endif
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeKdfProfile: FfiConverterRustBuffer {
+    typealias SwiftType = KdfProfile
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> KdfProfile {
+        let variant: Int32 = try readInt(&buf)
+        switch variant {
+
+        case 1: return .interactive
+
+        case 2: return .moderate
+
+        case 3: return .paranoid
+
+        case 4: return .custom(memoryKib: try FfiConverterUInt32.read(from: &buf), iterations: try FfiConverterUInt32.read(from: &buf), parallelism: try FfiConverterUInt32.read(from: &buf)
+        )
+
+        default: throw UniffiInternalError.unexpectedEnumCase
+        }
+    }
+
+    public static func write(_ value: KdfProfile, into buf: inout [UInt8]) {
+        switch value {
+
+
+        case .interactive:
+            writeInt(&buf, Int32(1))
+
+
+        case .moderate:
+            writeInt(&buf, Int32(2))
+
+
+        case .paranoid:
+            writeInt(&buf, Int32(3))
+
+
+        case let .custom(memoryKib,iterations,parallelism):
+            writeInt(&buf, Int32(4))
+            FfiConverterUInt32.write(memoryKib, into: &buf)
+            FfiConverterUInt32.write(iterations, into: &buf)
+            FfiConverterUInt32.write(parallelism, into: &buf)
+
+        }
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeKdfProfile_lift(_ buf: RustBuffer) throws -> KdfProfile {
+    return try FfiConverterTypeKdfProfile.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeKdfProfile_lower(_ value: KdfProfile) -> RustBuffer {
+    return FfiConverterTypeKdfProfile.lower(value)
+}
+
+
+// Note that we don't yet support `indirect` for enums.
+// See https://github.com/mozilla/uniffi-rs/issues/396 for further discussion.
+/**
+ * AEAD for passphrase-protected splits.
+ */
+
+public enum ShareCipher: Equatable, Hashable {
+
+    case chaCha20Poly1305
+    case xChaCha20Poly1305
+    case aes256GcmSiv
+
+
+
+
+
+}
+
+#if compiler(>=6)
+extension ShareCipher: Sendable {}
+#endif
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeShareCipher: FfiConverterRustBuffer {
+    typealias SwiftType = ShareCipher
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ShareCipher {
+        let variant: Int32 = try readInt(&buf)
+        switch variant {
+
+        case 1: return .chaCha20Poly1305
+
+        case 2: return .xChaCha20Poly1305
+
+        case 3: return .aes256GcmSiv
+
+        default: throw UniffiInternalError.unexpectedEnumCase
+        }
+    }
+
+    public static func write(_ value: ShareCipher, into buf: inout [UInt8]) {
+        switch value {
+
+
+        case .chaCha20Poly1305:
+            writeInt(&buf, Int32(1))
+
+
+        case .xChaCha20Poly1305:
+            writeInt(&buf, Int32(2))
+
+
+        case .aes256GcmSiv:
+            writeInt(&buf, Int32(3))
+
+        }
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeShareCipher_lift(_ buf: RustBuffer) throws -> ShareCipher {
+    return try FfiConverterTypeShareCipher.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeShareCipher_lower(_ value: ShareCipher) -> RustBuffer {
+    return FfiConverterTypeShareCipher.lower(value)
+}
+
+
 // Note that we don't yet support `indirect` for enums.
 // See https://github.com/mozilla/uniffi-rs/issues/396 for further discussion.
 
@@ -885,6 +1087,7 @@ public enum ShareEncoding: Equatable, Hashable {
     case base58check
     case mnemoWords
     case mnemoBip39
+    case codex32
 
 
 
@@ -916,6 +1119,8 @@ public struct FfiConverterTypeShareEncoding: FfiConverterRustBuffer {
 
         case 5: return .mnemoBip39
 
+        case 6: return .codex32
+
         default: throw UniffiInternalError.unexpectedEnumCase
         }
     }
@@ -943,6 +1148,10 @@ public struct FfiConverterTypeShareEncoding: FfiConverterRustBuffer {
         case .mnemoBip39:
             writeInt(&buf, Int32(5))
 
+
+        case .codex32:
+            writeInt(&buf, Int32(6))
+
         }
     }
 }
@@ -963,6 +1172,89 @@ public func FfiConverterTypeShareEncoding_lower(_ value: ShareEncoding) -> RustB
 }
 
 
+// Note that we don't yet support `indirect` for enums.
+// See https://github.com/mozilla/uniffi-rs/issues/396 for further discussion.
+/**
+ * Length-hiding padding added before splitting.
+ */
+
+public enum SharePadding: Equatable, Hashable {
+
+    case powerOfTwo
+    case fixed(size: UInt32
+    )
+    case random(max: UInt32
+    )
+
+
+
+
+
+}
+
+#if compiler(>=6)
+extension SharePadding: Sendable {}
+#endif
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeSharePadding: FfiConverterRustBuffer {
+    typealias SwiftType = SharePadding
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SharePadding {
+        let variant: Int32 = try readInt(&buf)
+        switch variant {
+
+        case 1: return .powerOfTwo
+
+        case 2: return .fixed(size: try FfiConverterUInt32.read(from: &buf)
+        )
+
+        case 3: return .random(max: try FfiConverterUInt32.read(from: &buf)
+        )
+
+        default: throw UniffiInternalError.unexpectedEnumCase
+        }
+    }
+
+    public static func write(_ value: SharePadding, into buf: inout [UInt8]) {
+        switch value {
+
+
+        case .powerOfTwo:
+            writeInt(&buf, Int32(1))
+
+
+        case let .fixed(size):
+            writeInt(&buf, Int32(2))
+            FfiConverterUInt32.write(size, into: &buf)
+
+
+        case let .random(max):
+            writeInt(&buf, Int32(3))
+            FfiConverterUInt32.write(max, into: &buf)
+
+        }
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeSharePadding_lift(_ buf: RustBuffer) throws -> SharePadding {
+    return try FfiConverterTypeSharePadding.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeSharePadding_lower(_ value: SharePadding) -> RustBuffer {
+    return FfiConverterTypeSharePadding.lower(value)
+}
+
+
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
@@ -987,6 +1279,102 @@ fileprivate struct FfiConverterOptionString: FfiConverterRustBuffer {
     }
 }
 
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterOptionTypeKdfProfile: FfiConverterRustBuffer {
+    typealias SwiftType = KdfProfile?
+
+    public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
+        guard let value = value else {
+            writeInt(&buf, Int8(0))
+            return
+        }
+        writeInt(&buf, Int8(1))
+        FfiConverterTypeKdfProfile.write(value, into: &buf)
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        switch try readInt(&buf) as Int8 {
+        case 0: return nil
+        case 1: return try FfiConverterTypeKdfProfile.read(from: &buf)
+        default: throw UniffiInternalError.unexpectedOptionalTag
+        }
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterOptionTypeShareCipher: FfiConverterRustBuffer {
+    typealias SwiftType = ShareCipher?
+
+    public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
+        guard let value = value else {
+            writeInt(&buf, Int8(0))
+            return
+        }
+        writeInt(&buf, Int8(1))
+        FfiConverterTypeShareCipher.write(value, into: &buf)
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        switch try readInt(&buf) as Int8 {
+        case 0: return nil
+        case 1: return try FfiConverterTypeShareCipher.read(from: &buf)
+        default: throw UniffiInternalError.unexpectedOptionalTag
+        }
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterOptionTypeSharePadding: FfiConverterRustBuffer {
+    typealias SwiftType = SharePadding?
+
+    public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
+        guard let value = value else {
+            writeInt(&buf, Int8(0))
+            return
+        }
+        writeInt(&buf, Int8(1))
+        FfiConverterTypeSharePadding.write(value, into: &buf)
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        switch try readInt(&buf) as Int8 {
+        case 0: return nil
+        case 1: return try FfiConverterTypeSharePadding.read(from: &buf)
+        default: throw UniffiInternalError.unexpectedOptionalTag
+        }
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterOptionDictionaryUInt8String: FfiConverterRustBuffer {
+    typealias SwiftType = [UInt8: String]?
+
+    public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
+        guard let value = value else {
+            writeInt(&buf, Int8(0))
+            return
+        }
+        writeInt(&buf, Int8(1))
+        FfiConverterDictionaryUInt8String.write(value, into: &buf)
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        switch try readInt(&buf) as Int8 {
+        case 0: return nil
+        case 1: return try FfiConverterDictionaryUInt8String.read(from: &buf)
+        default: throw UniffiInternalError.unexpectedOptionalTag
+        }
+    }
+}
+
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
@@ -1011,12 +1399,56 @@ fileprivate struct FfiConverterSequenceTypeEncodedShare: FfiConverterRustBuffer
         return seq
     }
 }
-public func combineShareInput(input: String, selected: ShareEncoding, passphrase: String?)throws  -> Recovery  {
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterDictionaryUInt8String: FfiConverterRustBuffer {
+    public static func write(_ value: [UInt8: String], into buf: inout [UInt8]) {
+        let len = Int32(value.count)
+        writeInt(&buf, len)
+        for (key, value) in value {
+            FfiConverterUInt8.write(key, into: &buf)
+            FfiConverterString.write(value, into: &buf)
+        }
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> [UInt8: String] {
+        let len: Int32 = try readInt(&buf)
+        var dict = [UInt8: String]()
+        dict.reserveCapacity(Int(len))
+        for _ in 0..<len {
+            let key = try FfiConverterUInt8.read(from: &buf)
+            let value = try FfiConverterString.read(from: &buf)
+            dict[key] = value
+        }
+        return dict
+    }
+}
+/**
+ * Pick Argon2id costs that take about `target_ms` to derive on this device,
+ * using at most `max_memory_kib`. Blocks for a few times `target_ms`.
+ */
+public func calibrateKdf(targetMs: UInt32, maxMemoryKib: UInt32)throws  -> KdfProfile  {
+    return try  FfiConverterTypeKdfProfile_lift(try rustCallWithError(FfiConverterTypeBridgeError_lift) {
+    uniffi_safeparts_uniffi_fn_func_calibrate_kdf(
+        FfiConverterUInt32.lower(targetMs),
+        FfiConverterUInt32.lower(maxMemoryKib),$0
+    )
+})
+}
+/**
+ * `share_passphrases` maps a locked share's index to its own passphrase.
+ * Locked shares without an entry fail with `ShareLocked`;
+ * `Inspection::locked_indexes` lists which ones to ask for.
+ */
+public func combineShareInput(input: String, selected: ShareEncoding, passphrase: String?, sharePassphrases: [UInt8: String]? = nil)throws  -> Recovery  {
     return try  FfiConverterTypeRecovery_lift(try rustCallWithError(FfiConverterTypeBridgeError_lift) {
     uniffi_safeparts_uniffi_fn_func_combine_share_input(
         FfiConverterString.lower(input),
         FfiConverterTypeShareEncoding_lower(selected),
-        FfiConverterOptionString.lower(passphrase),$0
+        FfiConverterOptionString.lower(passphrase),
+        FfiConverterOptionDictionaryUInt8String.lower(sharePassphrases),$0
     )
 })
 }
@@ -1028,14 +1460,37 @@ public func inspectShareInput(input: String, selected: ShareEncoding)throws  ->
     )
 })
 }
-public func splitSecret(secret: Data, threshold: UInt8, shareCount: UInt8, selected: ShareEncoding, passphrase: String?)throws  -> [EncodedShare]  {
+/**
+ * Lock one share with its custodian's own passphrase. The locked share keeps
+ * the detected encoding when `selected` is `Auto`.
+ */
+public func lockShare(share: String, selected: ShareEncoding, passphrase: String, kdf: KdfProfile? = nil, cipher: ShareCipher? = nil)throws  -> String  {
+    return try  FfiConverterString.lift(try rustCallWithError(FfiConverterTypeBridgeError_lift) {
+    uniffi_safeparts_uniffi_fn_func_lock_share(
+        FfiConverterString.lower(share),
+        FfiConverterTypeShareEncoding_lower(selected),
+        FfiConverterString.lower(passphrase),
+        FfiConverterOptionTypeKdfProfile.lower(kdf),
+        FfiConverterOptionTypeShareCipher.lower(cipher),$0
+    )
+})
+}
+/**
+ * `kdf` sets the Argon2id costs for `passphrase`; `None` uses `Moderate`.
+ * `cipher` picks the AEAD; `None` uses ChaCha20-Poly1305.
+ * `padding` hides the secret's length; `None` adds no padding.
+ */
+public func splitSecret(secret: Data, threshold: UInt8, shareCount: UInt8, selected: ShareEncoding, passphrase: String?, kdf: KdfProfile? = nil, cipher: ShareCipher? = nil, padding: SharePadding? = nil)throws  -> [EncodedShare]  {
     return try  FfiConverterSequenceTypeEncodedShare.lift(try rustCallWithError(FfiConverterTypeBridgeError_lift) {
     uniffi_safeparts_uniffi_fn_func_split_secret(
         FfiConverterData.lower(secret),
         FfiConverterUInt8.lower(threshold),
         FfiConverterUInt8.lower(shareCount),
         FfiConverterTypeShareEncoding_lower(selected),
-        FfiConverterOptionString.lower(passphrase),$0
+        FfiConverterOptionString.lower(passphrase),
+        FfiConverterOptionTypeKdfProfile.lower(kdf),
+        FfiConverterOptionTypeShareCipher.lower(cipher),
+        FfiConverterOptionTypeSharePadding.lower(padding),$0
     )
 })
 }
@@ -1055,13 +1510,19 @@ private let initializationResult: InitializationResult = {
     if bindings_contract_version != scaffolding_contract_version {
         return InitializationResult.contractVersionMismatch
     }
-    if (uniffi_safeparts_uniffi_checksum_func_combine_share_input() != 62916) {
+    if (uniffi_safeparts_uniffi_checksum_func_calibrate_kdf() != 61470) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_safeparts_uniffi_checksum_func_combine_share_input() != 54206) {
         return InitializationResult.apiChecksumMismatch
     }
     if (uniffi_safeparts_uniffi_checksum_func_inspect_share_input() != 2438) {
         return InitializationResult.apiChecksumMismatch
     }
-    if (uniffi_safeparts_uniffi_checksum_func_split_secret() != 21893) {
+    if (uniffi_safeparts_uniffi_checksum_func_lock_share() != 21911) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_safeparts_uniffi_checksum_func_split_secret() != 3959) {
         return InitializationResult.apiChecksumMismatch
     }
 
@@ -242,10 +242,15 @@ typedef struct UniffiForeignFutureResultVoid {
 typedef void (*UniffiForeignFutureCompleteVoid)(uint64_t, UniffiForeignFutureResultVoid
     );
 
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_CALIBRATE_KDF
+#define UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_CALIBRATE_KDF
+RustBuffer uniffi_safeparts_uniffi_fn_func_calibrate_kdf(uint32_t target_ms, uint32_t max_memory_kib, RustCallStatus *_Nonnull out_status
+);
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_COMBINE_SHARE_INPUT
 #define UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_COMBINE_SHARE_INPUT
-RustBuffer uniffi_safeparts_uniffi_fn_func_combine_share_input(RustBuffer input, RustBuffer selected, RustBuffer passphrase, RustCallStatus *_Nonnull out_status
+RustBuffer uniffi_safeparts_uniffi_fn_func_combine_share_input(RustBuffer input, RustBuffer selected, RustBuffer passphrase, RustBuffer share_passphrases, RustCallStatus *_Nonnull out_status
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_INSPECT_SHARE_INPUT
@@ -253,9 +258,14 @@ RustBuffer uniffi_safeparts_uniffi_fn_func_combine_share_input(RustBuffer input,
 RustBuffer uniffi_safeparts_uniffi_fn_func_inspect_share_input(RustBuffer input, RustBuffer selected, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_LOCK_SHARE
+#define UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_LOCK_SHARE
+RustBuffer uniffi_safeparts_uniffi_fn_func_lock_share(RustBuffer share, RustBuffer selected, RustBuffer passphrase, RustBuffer kdf, RustBuffer cipher, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_SPLIT_SECRET
 #define UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_FN_FUNC_SPLIT_SECRET
-RustBuffer uniffi_safeparts_uniffi_fn_func_split_secret(RustBuffer secret, uint8_t threshold, uint8_t share_count, RustBuffer selected, RustBuffer passphrase, RustCallStatus *_Nonnull out_status
+RustBuffer uniffi_safeparts_uniffi_fn_func_split_secret(RustBuffer secret, uint8_t threshold, uint8_t share_count, RustBuffer selected, RustBuffer passphrase, RustBuffer kdf, RustBuffer cipher, RustBuffer padding, RustCallStatus *_Nonnull out_status
 );
 #endif
 #ifndef UNIFFI_FFIDEF_FFI_SAFEPARTS_UNIFFI_RUSTBUFFER_ALLOC
@@ -516,6 +526,12 @@ void ffi_safeparts_uniffi_rust_future_free_void(uint64_t handle
 #ifndef UNIFFI_FFIDEF_FFI_SAFEPARTS_UNIFFI_RUST_FUTURE_COMPLETE_VOID
 #define UNIFFI_FFIDEF_FFI_SAFEPARTS_UNIFFI_RUST_FUTURE_COMPLETE_VOID
 void ffi_safeparts_uniffi_rust_future_complete_void(uint64_t handle, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_CHECKSUM_FUNC_CALIBRATE_KDF
+#define UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_CHECKSUM_FUNC_CALIBRATE_KDF
+uint16_t uniffi_safeparts_uniffi_checksum_func_calibrate_kdf(void
+
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_CHECKSUM_FUNC_COMBINE_SHARE_INPUT
@@ -528,6 +544,12 @@ uint16_t uniffi_safeparts_uniffi_checksum_func_combine_share_input(void
 #define UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_CHECKSUM_FUNC_INSPECT_SHARE_INPUT
 uint16_t uniffi_safeparts_uniffi_checksum_func_inspect_share_input(void
 
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_CHECKSUM_FUNC_LOCK_SHARE
+#define UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_CHECKSUM_FUNC_LOCK_SHARE
+uint16_t uniffi_safeparts_uniffi_checksum_func_lock_share(void
+
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_SAFEPARTS_UNIFFI_CHECKSUM_FUNC_SPLIT_SECRET
@@ -471,6 +471,8 @@ public final class AppModel: ObservableObject {
         switch error {
         case .InvalidParameters:
             return "Choose a threshold no greater than the share count."
+        case .InvalidKdfParameters:
+            return "Choose valid passphrase key-derivation costs."
         case .InvalidEncoding:
             return "Choose a concrete share encoding before splitting."
         case .EmptyInput:
@@ -489,6 +491,12 @@ public final class AppModel: ObservableObject {
             return "The passphrase is incorrect, or the encrypted data was changed."
         case .IntegrityFailure:
             return "The recovery shares failed their integrity check."
+        case .ShareLocked:
+            return "A recovery share is locked with its custodian’s own passphrase."
+        case .IncorrectSharePassphrase:
+            return "A share passphrase is incorrect."
+        case .InvalidPadding:
+            return "The secret is too long for the chosen padding size."
         case .Internal:
             return "Safeparts couldn’t complete the operation."
         }
@@ -411,7 +411,11 @@ struct RecoverView: View {
                 HStack {
                     Text("Recovery shares")
                     Spacer()
-                    if model.inspection?.ready == true {
+                    if model.inspection?.stealth == true {
+                        Text("Stealth share, metadata hidden")
+                            .font(.caption)
+                            .foregroundStyle(.secondary)
+                    } else if model.inspection?.ready == true {
                         Label("Ready", systemImage: "checkmark.circle.fill")
                             .font(.caption.weight(.medium))
                             .foregroundStyle(.green)
@@ -631,6 +635,7 @@ extension ShareEncoding {
         case .base58check: "Checked letters"
         case .mnemoWords: "Words"
         case .mnemoBip39: "BIP-39 words"
+        case .codex32: "Codex32"
         }
     }
 
@@ -641,6 +646,7 @@ extension ShareEncoding {
         case .base58check: "Compact text with typo detection"
         case .mnemoWords: "Easy-to-write mnemonic words"
         case .mnemoBip39: "Familiar BIP-39 vocabulary"
+        case .codex32: "BIP-93 strings you can check by hand"
         }
     }
 
@@ -651,6 +657,7 @@ extension ShareEncoding {
         case .base58check: "checkmark.seal"
         case .mnemoWords: "text.book.closed"
         case .mnemoBip39: "list.bullet.rectangle"
+        case .codex32: "checkmark.rectangle"
         }
     }
 }
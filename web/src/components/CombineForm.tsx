@@ -23,6 +23,16 @@ type ShareBox = {
   value: string;
 };
 
+type WordCorrections = {
+  share: number;
+  corrections: {
+    position: number;
+    typed: string;
+    candidates: string[];
+    verified: boolean;
+  }[];
+};
+
 function MinusIcon() {
   return (
     <svg viewBox="0 0 24 24" fill="none" className="h-4 w-4" aria-hidden="true">
@@ -64,6 +74,19 @@ function toErrorMessage(err: unknown, strings: Strings): string {
   return message;
 }
 
+async function findWordCorrections(
+  input: string,
+  encoding: Encoding,
+): Promise<WordCorrections | null> {
+  try {
+    const wasm = await ensureWasm();
+    if (typeof wasm.word_corrections !== "function") return null;
+    return wasm.word_corrections(input, encoding) ?? null;
+  } catch {
+    return null;
+  }
+}
+
 function parseSharesFromBox(text: string): string[] {
   return text
     .split(/\n\s*\n/g)
@@ -96,6 +119,7 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
   const [shareBoxFlashIds, setShareBoxFlashIds] = useState<string[]>([]);
   const [secret, setSecret] = useState("");
   const [error, setError] = useState<string | null>(null);
+  const [corrections, setCorrections] = useState<WordCorrections | null>(null);
   const [busy, setBusy] = useState(false);
 
   const [encodingFlash, setEncodingFlash] = useState(false);
@@ -261,6 +285,7 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
   async function onCombine() {
     setBusy(true);
     setError(null);
+    setCorrections(null);
     setSecret("");
 
     try {
@@ -282,8 +307,13 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
       setInvalidShareBoxIds([]);
     } catch (e) {
       const rawMessage = rawErrorMessage(e);
-      const message = toErrorMessage(e, strings);
-      setError(message);
+      const found = await findWordCorrections(combinedShareInput, encoding);
+      setCorrections(found);
+      setError(
+        found
+          ? strings.errorMisspelledWords.replace("{share}", String(found.share))
+          : toErrorMessage(e, strings),
+      );
 
       const m = notEnoughSharesMatch(rawMessage);
       if (m) {
@@ -305,6 +335,17 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
 
   const canCombine = shares.length > 0;
 
+  function describeCandidates(
+    candidates: string[],
+    verified: boolean,
+  ): string {
+    if (candidates.length === 0) return strings.correctionNoMatch;
+    const list = `${strings.correctionDidYouMean} ${candidates.join(", ")}`;
+    return verified
+      ? `${list} (${candidates[0]} ${strings.correctionVerified})`
+      : list;
+  }
+
   return (
     <section className="glass p-4 sm:p-6">
       <div className="dir-row items-start justify-between gap-4">
@@ -475,6 +516,23 @@ export function CombineForm({ lang, strings }: CombineFormProps) {
         {error ? (
           <div className="alert-error" role="alert" aria-live="assertive">
             {error}
+            {corrections ? (
+              <ul className="mt-2 space-y-1 text-xs">
+                {corrections.corrections.map((c) => (
+                  <li key={c.position} className="text-start">
+                    {strings.correctionWord.replace(
+                      "{position}",
+                      String(c.position),
+                    )}
+                    :{" "}
+                    <span dir="ltr" className="font-mono">
+                      {c.typed}
+                    </span>{" "}
+                    — {describeCandidates(c.candidates, c.verified)}
+                  </li>
+                ))}
+              </ul>
+            ) : null}
           </div>
         ) : null}
       </div>